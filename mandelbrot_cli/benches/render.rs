@@ -0,0 +1,129 @@
+// Benchmarks ////////////////////////////////////////////////////////
+//            ///////////////////////////////////////////////////////
+// Time of the inner loop, on points inside the set, which iterate up to
+// `max_iters`, and outside of it, which escape early, of a full HD frame
+// of the default view and of its coloring, with each backend, so that
+// changes to the render paths, eg, SIMD, the interior checks or the
+// scheduling of the threads, can be compared before and after with
+// `cargo bench -p mandelbrot_cli`. A name given after `--` only runs
+// the benchmarks containing it, eg, `cargo bench -- frame`.
+//
+// Each benchmark runs for about `MEASURE` after a warm-up, and prints
+// the median and the fastest of its samples.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use mandelbrot_cli::{
+    color_schemes::ColorSchemes, get_rgb_buf, mandel, mandel_single, ComputeBackend, Domain,
+    MandelConfig, Precision, Resolution,
+};
+
+const WARM_UP: Duration = Duration::from_millis(300);
+const MEASURE: Duration = Duration::from_secs(2);
+const MAX_SAMPLES: usize = 100;
+
+/// Run `f` repeatedly and print the time per run, if `name` contains
+/// the filter
+fn bench<T>(filter: &Option<String>, name: &str, mut f: impl FnMut() -> T) {
+    if filter
+        .as_ref()
+        .is_some_and(|filter| !name.contains(filter.as_str()))
+    {
+        return;
+    }
+    let start = Instant::now();
+    while start.elapsed() < WARM_UP {
+        black_box(f());
+    }
+    let mut samples = Vec::new();
+    let start = Instant::now();
+    while samples.len() < MAX_SAMPLES && (samples.len() < 5 || start.elapsed() < MEASURE) {
+        let run = Instant::now();
+        black_box(f());
+        samples.push(run.elapsed());
+    }
+    samples.sort();
+    let ms = |d: Duration| d.as_secs_f64() * 1e3;
+    println!(
+        "{name:<32} median {:>9.3} ms, fastest {:>9.3} ms, {} samples",
+        ms(samples[samples.len() / 2]),
+        ms(samples[0]),
+        samples.len()
+    );
+}
+
+fn view(xdomain: (f64, f64), ydomain: (f64, f64), x: usize, y: usize) -> MandelConfig {
+    MandelConfig {
+        xdomain: Domain {
+            start: xdomain.0,
+            end: xdomain.1,
+        },
+        ydomain: Domain {
+            start: ydomain.0,
+            end: ydomain.1,
+        },
+        resolution: Resolution { x, y },
+        max_iters: 256,
+        ..Default::default()
+    }
+}
+
+fn main() {
+    // `cargo bench` passes `--bench`
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with("--"));
+    let backends = [
+        ("scalar", ComputeBackend::Scalar, Precision::F64),
+        ("simd", ComputeBackend::Simd, Precision::F64),
+        ("simd-f32", ComputeBackend::Simd, Precision::F32),
+    ];
+
+    // inner loop, on a single thread: all the points of the main
+    // cardioid iterate to `max_iters`, the ones far out escape after a
+    // few iterations
+    let inside = view((-0.3, 0.1), (-0.2, 0.2), 128, 128);
+    let outside = view((0.5, 1.0), (0.5, 1.0), 128, 128);
+    for (name, backend, precision) in backends {
+        for (region, cfg) in [("inside", inside), ("outside", outside)] {
+            let cfg = MandelConfig {
+                backend,
+                precision,
+                ..cfg
+            };
+            bench(&filter, &format!("worker/{region}/{name}"), || {
+                mandel_single(cfg).unwrap()
+            });
+        }
+    }
+    let interior_check = MandelConfig {
+        interior_check: true,
+        ..inside
+    };
+    bench(&filter, "worker/inside/interior-check", || {
+        mandel_single(interior_check).unwrap()
+    });
+
+    // full frames, on all the threads
+    let mut frame = MandelConfig::default();
+    frame.domains().resize(Resolution { x: 1920, y: 1080 });
+    for (name, backend, precision) in backends {
+        let cfg = MandelConfig {
+            backend,
+            precision,
+            ..frame
+        };
+        bench(&filter, &format!("frame/1920x1080/{name}"), || {
+            mandel(cfg).unwrap()
+        });
+    }
+
+    // coloring the full frame
+    let iters = mandel(frame).unwrap();
+    let mut color_schemes = ColorSchemes::new();
+    bench(&filter, "color/1920x1080", || {
+        get_rgb_buf(&iters, frame.max_iters, &color_schemes).unwrap()
+    });
+    color_schemes.toggle_equalize();
+    bench(&filter, "color/1920x1080/equalized", || {
+        get_rgb_buf(&iters, frame.max_iters, &color_schemes).unwrap()
+    });
+}
@@ -0,0 +1,61 @@
+// Adaptive anti-aliasing ////////////////////////////////////////////
+//                        ///////////////////////////////////////////
+// Supersampling only the pixels on the edges, see `AaMode::Adaptive`.
+//
+// The image is rendered with one sample per pixel first. Pixels whose
+// neighbours differ by more than `tolerance` iterations are then
+// rendered again with `max_samples` by `max_samples` samples. The other
+// pixels repeat their one sample, so that the grid has the same layout
+// as with `AaMode::Full` and is colored the same way.
+use rayon::prelude::*;
+
+use crate::render_job::Tile;
+use crate::{mandel_unchecked, AaMode, IterationGrid, MandelConfig};
+
+/// Return `true` if pixel `(x, y)` differs from any of its neighbours by
+/// more than `tolerance` iterations
+fn is_edge(grid: &IterationGrid, x: usize, y: usize, tolerance: f64) -> bool {
+    let smooth = grid.get(x, y).smooth;
+    let (x0, x1) = (x.saturating_sub(1), (x + 1).min(grid.width - 1));
+    let (y0, y1) = (y.saturating_sub(1), (y + 1).min(grid.height - 1));
+    (y0..=y1).any(|j| (x0..=x1).any(|i| (grid.get(i, j).smooth - smooth).abs() > tolerance))
+}
+
+pub(crate) fn mandel_adaptive(cfg: MandelConfig, tolerance: f64, max_samples: usize) -> IterationGrid {
+    let n = max_samples;
+    let mut first = cfg;
+    first.aa = AaMode::Off;
+    let coarse = mandel_unchecked(first);
+
+    // repeat the one sample of each pixel
+    let mut grid = IterationGrid::for_config(&cfg);
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            grid.set_pixel(x, y, coarse.get_pixel(x / n, y / n));
+        }
+    }
+
+    // supersample the edges, each pixel being an `n` by `n` tile of
+    // the supersampled config
+    let supersampled = cfg.supersampled();
+    let edges: Vec<_> = (0..coarse.height)
+        .flat_map(|y| (0..coarse.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| is_edge(&coarse, x, y, tolerance))
+        .collect();
+    let samples: Vec<_> = edges
+        .par_iter()
+        .map(|&(x, y)| {
+            let tile = Tile {
+                x: x * n,
+                y: y * n,
+                width: n,
+                height: n,
+            };
+            mandel_unchecked(tile.config(&supersampled))
+        })
+        .collect();
+    for (&(x, y), pixel) in edges.iter().zip(&samples) {
+        grid.paste(x, y, pixel);
+    }
+    grid
+}
@@ -0,0 +1,177 @@
+// Animations ////////////////////////////////////////////////////////
+//            ///////////////////////////////////////////////////////
+// Animated GIF and PNG (APNG) files, written a frame at a time, eg, the
+// colors of a render cycled through the palette, which only colors the
+// iterations again, see `save_palette_cycle()`, or the frames of a short
+// `ZoomSequence`. The format is given by the extension of the file.
+//
+// GIF frames are reduced to 256 colors each, so that smooth gradients
+// band. APNG keeps the colors exactly, and shows its first frame in the
+// viewers that do not animate it.
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::Duration;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+use crate::color_schemes::ColorSchemes;
+use crate::{get_rgb_buf, IterationGrid, MandelError};
+
+/// Speed of the color reduction of GIF frames, from 1, the best, to 30,
+/// the fastest
+const GIF_SPEED: i32 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnimationOptions {
+    /// Time each frame is shown
+    pub delay: Duration,
+    /// Number of times the animation is played, 0 for forever
+    pub loops: u16,
+}
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(40),
+            loops: 0,
+        }
+    }
+}
+
+enum Encoder {
+    Gif(GifEncoder<BufWriter<File>>),
+    Apng(png::Writer<BufWriter<File>>),
+}
+
+/// Animation being written to a file, see `AnimationWriter::create()`
+pub struct AnimationWriter {
+    encoder: Encoder,
+    width: u32,
+    height: u32,
+    delay: Duration,
+}
+
+impl AnimationWriter {
+    /// Start an animation of `frames` frames of `width` by `height`
+    /// pixels, a GIF file if `path` ends in `.gif`, or else an APNG file
+    /// if it ends in `.png` or `.apng`
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        width: usize,
+        height: usize,
+        frames: usize,
+        options: AnimationOptions,
+    ) -> Result<Self, MandelError> {
+        let path = path.as_ref();
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase());
+        let (width, height) = (width as u32, height as u32);
+        let file = || -> Result<_, MandelError> { Ok(BufWriter::new(File::create(path)?)) };
+        let encoder = match ext.as_deref() {
+            Some("gif") => {
+                let mut encoder = GifEncoder::new_with_speed(file()?, GIF_SPEED);
+                // GIFs without the repeat extension play once, and its
+                // count is of the repeats after the first play
+                match options.loops {
+                    0 => encoder.set_repeat(Repeat::Infinite)?,
+                    1 => (),
+                    loops => encoder.set_repeat(Repeat::Finite(loops - 1))?,
+                }
+                Encoder::Gif(encoder)
+            }
+            Some("png" | "apng") => {
+                let mut encoder = png::Encoder::new(file()?, width, height);
+                encoder.set_color(png::ColorType::Rgb);
+                encoder.set_depth(png::BitDepth::Eight);
+                let frames = frames.max(1) as u32;
+                encoder
+                    .set_animated(frames, options.loops as u32)
+                    .and_then(|()| encoder.set_frame_delay(delay_ms(options.delay), 1000))
+                    .map_err(std::io::Error::from)?;
+                Encoder::Apng(encoder.write_header().map_err(std::io::Error::from)?)
+            }
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "animations are saved as GIF or PNG files",
+                )
+                .into())
+            }
+        };
+        Ok(Self {
+            encoder,
+            width,
+            height,
+            delay: options.delay,
+        })
+    }
+
+    /// Add a frame, its RGB bytes row-major from the top-left corner,
+    /// eg, of `get_rgb_buf()`
+    pub fn add_frame(&mut self, rgb: &[u8]) -> Result<(), MandelError> {
+        if rgb.len() != 3 * (self.width * self.height) as usize {
+            return Err(MandelError::InvalidGrid(
+                "frame does not match the animation size",
+            ));
+        }
+        match &mut self.encoder {
+            Encoder::Gif(encoder) => {
+                let rgba = rgb
+                    .chunks_exact(3)
+                    .flat_map(|c| [c[0], c[1], c[2], 255])
+                    .collect();
+                let image = RgbaImage::from_raw(self.width, self.height, rgba)
+                    .expect("buffer matches the size");
+                let delay = Delay::from_numer_denom_ms(delay_ms(self.delay) as u32, 1);
+                encoder.encode_frame(Frame::from_parts(image, 0, 0, delay))?;
+            }
+            Encoder::Apng(writer) => {
+                writer.write_image_data(rgb).map_err(std::io::Error::from)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Finish the file. APNG files must have all the frames given to
+    /// `create()`.
+    pub fn finish(self) -> Result<(), MandelError> {
+        match self.encoder {
+            // the trailer is written when the encoder is dropped
+            Encoder::Gif(encoder) => drop(encoder),
+            Encoder::Apng(writer) => writer.finish().map_err(std::io::Error::from)?,
+        }
+        Ok(())
+    }
+}
+
+/// Delay in milliseconds, at most about a minute
+fn delay_ms(delay: Duration) -> u16 {
+    delay.as_millis().min(u16::MAX as u128) as u16
+}
+
+/// Save an animation of `frames` frames cycling the colors of `iters`
+/// once through the palette, from its current offset, see
+/// `ColorSchemes::set_offset()`. The iterations are only colored again,
+/// not recalculated.
+pub fn save_palette_cycle<P: AsRef<Path>>(
+    iters: &IterationGrid,
+    max_iters: usize,
+    color_schemes: &mut ColorSchemes,
+    frames: usize,
+    options: AnimationOptions,
+    path: P,
+) -> Result<(), MandelError> {
+    let (width, height) = iters.image_size();
+    let frames = frames.max(1);
+    let mut writer = AnimationWriter::create(path, width, height, frames, options)?;
+    let start = color_schemes.offset();
+    let colored = (0..frames).try_for_each(|i| {
+        color_schemes.set_offset(start + i as f64 / frames as f64);
+        writer.add_frame(&get_rgb_buf(iters, max_iters, color_schemes)?)
+    });
+    color_schemes.set_offset(start);
+    colored?;
+    writer.finish()
+}
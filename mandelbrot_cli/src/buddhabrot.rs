@@ -0,0 +1,282 @@
+// Buddhabrot ////////////////////////////////////////////////////////
+//            ///////////////////////////////////////////////////////
+// Density of the orbits of `z = z^2 + c`, instead of the escape time
+// of each pixel. Random points `c` are sampled in the disk `|c| < 2`,
+// and each point `z` of the orbits escaping to infinity adds a hit to
+// the pixel it falls on, which draws the Buddhabrot. The orbits that
+// do not escape draw the anti-Buddhabrot instead.
+//
+// Each channel of the image counts only the orbits escaping within its
+// range of iterations, eg, red for the long ones and blue for the short
+// ones, which is the Nebulabrot. The counts are mapped to colors with an
+// exposure and a gamma, see `Density::to_rgb()`.
+//
+// The samples are split in chunks, each one with its own random seed
+// from `seed`, so that the image is the same for any number of threads.
+// The chunks run in batches, after which the hits so far can be saved
+// to a `Checkpoint`, and a render resumed from it skips the chunks done.
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::ops::ControlFlow;
+
+use crate::checkpoint::Checkpoint;
+use crate::{in_main_bulbs, pool, MandelConfig, MandelError};
+
+/// Samples per chunk, see `Buddhabrot::render()`
+const CHUNK: usize = 100_000;
+
+/// Iterations an orbit escapes within, inclusive, to count in a channel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IterRange {
+    pub min: usize,
+    pub max: usize,
+}
+impl std::str::FromStr for IterRange {
+    type Err = String;
+    /// Parse `min-max`, eg, `20-500`, or `max` for `0-max`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<usize>()
+                .map_err(|e| format!("invalid iteration range \"{s}\" - {e}"))
+        };
+        let (min, max) = match s.split_once('-') {
+            Some((min, max)) => (parse(min)?, parse(max)?),
+            None => (0, parse(s)?),
+        };
+        if min > max {
+            return Err(format!("invalid iteration range \"{s}\", {min} > {max}"));
+        }
+        Ok(IterRange { min, max })
+    }
+}
+
+/// Buddhabrot of `samples` random points, in the view of `view`, ie, its
+/// domains, rotation and resolution, and its `threshold`
+#[derive(Clone, Copy, Debug)]
+pub struct Buddhabrot {
+    pub view: MandelConfig,
+    pub samples: usize,
+    /// Ranges of the red, green and blue channels, all the same for a
+    /// plain Buddhabrot
+    pub channels: [IterRange; 3],
+    /// Count the orbits that do not escape, within the largest `max` of
+    /// the channels, instead
+    pub anti: bool,
+    pub seed: u64,
+}
+
+/// Hits of each pixel of the image, per channel, row-major from the
+/// top-left corner
+pub struct Density {
+    pub width: usize,
+    pub height: usize,
+    pub counts: Vec<[u32; 3]>,
+}
+
+impl Buddhabrot {
+    /// Plain Buddhabrot, all channels counting the orbits escaping
+    /// within `view.max_iters`
+    pub fn new(view: MandelConfig, samples: usize) -> Self {
+        let range = IterRange {
+            min: 0,
+            max: view.max_iters,
+        };
+        Self {
+            view,
+            samples,
+            channels: [range; 3],
+            anti: false,
+            seed: 0,
+        }
+    }
+
+    /// Sample the orbits in parallel, calling `progress` with the number
+    /// of samples done by each chunk as it completes. Return an error if
+    /// the view is not valid.
+    pub fn render<F>(&self, progress: F) -> Result<Density, MandelError>
+    where
+        F: Fn(usize) + Sync,
+    {
+        self.render_resumable(&mut self.checkpoint(), progress, |_| ControlFlow::Continue(()))
+    }
+
+    /// Return an empty checkpoint of the render, see `render_resumable()`.
+    /// Its key is the settings, but for the threads, which do not change
+    /// the image.
+    pub fn checkpoint(&self) -> Checkpoint {
+        let settings = Buddhabrot {
+            view: MandelConfig {
+                threads: None,
+                ..self.view
+            },
+            ..*self
+        };
+        Checkpoint::new(format!("{settings:?}"), self.samples.div_ceil(CHUNK))
+    }
+
+    /// Return the number of samples of the chunks done in `checkpoint`
+    pub fn samples_done(&self, checkpoint: &Checkpoint) -> usize {
+        (0..checkpoint.units())
+            .filter(|&chunk| checkpoint.is_done(chunk))
+            .map(|chunk| CHUNK.min(self.samples.saturating_sub(chunk * CHUNK)))
+            .sum()
+    }
+
+    /// Same as `render()`, adding the hits of the chunks not done in
+    /// `checkpoint` to its data, and calling `save` with it after each
+    /// batch of chunks. The chunks left are skipped once `save` breaks,
+    /// eg, on Ctrl-C, and the density returned is the one of the chunks
+    /// done. Return an error if the checkpoint is not of this render.
+    pub fn render_resumable<F, S>(
+        &self,
+        checkpoint: &mut Checkpoint,
+        progress: F,
+        mut save: S,
+    ) -> Result<Density, MandelError>
+    where
+        F: Fn(usize) + Sync,
+        S: FnMut(&Checkpoint) -> ControlFlow<()>,
+    {
+        self.view.validate()?;
+        let empty = self.checkpoint();
+        checkpoint.check(&empty.key, empty.units())?;
+        let (width, height) = (self.view.resolution.x, self.view.resolution.y);
+        if checkpoint.data.is_empty() {
+            checkpoint.data = vec![0; 3 * width * height];
+        } else if checkpoint.data.len() != 3 * width * height {
+            return Err(MandelError::InvalidCheckpoint("the hits do not match the view"));
+        }
+        let chunks: Vec<usize> = (0..checkpoint.units())
+            .filter(|&chunk| !checkpoint.is_done(chunk))
+            .collect();
+        // a few chunks per thread, so that they finish together
+        let batch = 4 * pool::num_threads(&self.view);
+        for batch in chunks.chunks(batch) {
+            let counts = pool::install(&self.view, || {
+                batch
+                    .par_iter()
+                    .fold(
+                        || vec![[0u32; 3]; width * height],
+                        |mut counts, &chunk| {
+                            let samples = CHUNK.min(self.samples - chunk * CHUNK);
+                            self.sample(chunk, samples, &mut counts);
+                            progress(samples);
+                            counts
+                        },
+                    )
+                    .reduce_with(|mut a, b| {
+                        for (a, b) in a.iter_mut().zip(&b) {
+                            for ch in 0..3 {
+                                a[ch] = a[ch].saturating_add(b[ch]);
+                            }
+                        }
+                        a
+                    })
+            });
+            if let Some(counts) = counts {
+                for (total, count) in checkpoint.data.iter_mut().zip(counts.iter().flatten()) {
+                    *total = total.saturating_add(*count);
+                }
+            }
+            batch.iter().for_each(|&chunk| checkpoint.mark_done(chunk));
+            if save(checkpoint).is_break() {
+                break;
+            }
+        }
+        Ok(Density {
+            width,
+            height,
+            counts: checkpoint
+                .data
+                .chunks_exact(3)
+                .map(|c| [c[0], c[1], c[2]])
+                .collect(),
+        })
+    }
+
+    /// Add the hits of `samples` points of chunk `chunk` to `counts`
+    fn sample(&self, chunk: usize, samples: usize, counts: &mut [[u32; 3]]) {
+        let view = &self.view;
+        let (width, height) = (view.resolution.x, view.resolution.y);
+        let (xdomain, ydomain) = view.absolute_domains();
+        let (x0, y0) = (xdomain.start, ydomain.start);
+        let scale_x = (width - 1) as f64 / (xdomain.end - x0);
+        let scale_y = (height - 1) as f64 / (ydomain.end - y0);
+        let max_iters = self.channels.iter().map(|r| r.max).max().unwrap_or(0);
+
+        let mut rng = SmallRng::seed_from_u64(self.seed.wrapping_add(chunk as u64));
+        let mut orbit = Vec::with_capacity(max_iters);
+        for _ in 0..samples {
+            let c = (rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0));
+            if c.0 * c.0 + c.1 * c.1 > 4.0 {
+                continue;
+            }
+            // the orbits of the main bulbs never escape
+            if !self.anti && in_main_bulbs(c.0, c.1) {
+                continue;
+            }
+            orbit.clear();
+            let (mut x, mut y) = (0.0, 0.0);
+            let mut escaped = false;
+            for _ in 0..max_iters {
+                (x, y) = (x * x - y * y + c.0, 2.0 * x * y + c.1);
+                if x * x + y * y > view.threshold {
+                    escaped = true;
+                    break;
+                }
+                orbit.push((x, y));
+            }
+            if escaped == self.anti {
+                continue;
+            }
+            let n = orbit.len() + 1;
+            let channels = self
+                .channels
+                .map(|r| self.anti || (r.min..=r.max).contains(&n));
+            if !channels.contains(&true) {
+                continue;
+            }
+            for &(x, y) in &orbit {
+                let (x, y) = view.to_domains(x, y);
+                let px = ((x - x0) * scale_x).round();
+                let py = ((y - y0) * scale_y).round();
+                if px < 0.0 || py < 0.0 || px >= width as f64 || py >= height as f64 {
+                    continue;
+                }
+                // row 0 of the image is at `ydomain.end`
+                let i = (height - 1 - py as usize) * width + px as usize;
+                for ch in 0..3 {
+                    if channels[ch] {
+                        counts[i][ch] = counts[i][ch].saturating_add(1);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Density {
+    /// Return the RGB bytes of the image. The counts of each channel are
+    /// divided by their maximum, scaled by `exposure` and clipped to 1,
+    /// and then raised to `1 / gamma`, so a higher gamma brings out the
+    /// faint orbits.
+    pub fn to_rgb(&self, exposure: f64, gamma: f64) -> Vec<u8> {
+        let mut max = [1u32; 3];
+        for count in &self.counts {
+            for ch in 0..3 {
+                max[ch] = max[ch].max(count[ch]);
+            }
+        }
+        self.counts
+            .iter()
+            .flat_map(|count| {
+                (0..3).map(move |ch| {
+                    let v = (count[ch] as f64 / max[ch] as f64 * exposure).min(1.0);
+                    (v.powf(1.0 / gamma) * 255.0).round() as u8
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,148 @@
+// Tile cache ////////////////////////////////////////////////////////
+//            ///////////////////////////////////////////////////////
+// Memoized tiles of the image, so that views already rendered, eg,
+// when zooming out and back in, or going back to the previous
+// `max_iters`, are not calculated again.
+//
+// The image is split in tiles as in `RenderJob` and each tile is looked
+// up by its config. The domains are compared within a fraction of a
+// pixel, since zooming in and out does not give back exactly the same
+// floats. The least recently used tiles are dropped when the cache is
+// full.
+use rayon::prelude::*;
+
+use crate::render_job::{tiles, Tile};
+use crate::{mandel_unchecked, pixel_step, pool, IterationGrid, MandelConfig, MandelError};
+
+struct Entry {
+    cfg: MandelConfig,
+    grid: IterationGrid,
+    last_used: u64,
+}
+
+/// Return `true` if `a` and `b` render the same pixels
+fn same_tile(a: &MandelConfig, b: &MandelConfig) -> bool {
+    let mut b_at_a = *b;
+    (b_at_a.xdomain, b_at_a.ydomain) = (a.xdomain, a.ydomain);
+    if b_at_a != *a {
+        return false;
+    }
+    let tolerance = 1e-3 * pixel_step(a).0;
+    [
+        (a.xdomain.start, b.xdomain.start),
+        (a.xdomain.end, b.xdomain.end),
+        (a.ydomain.start, b.ydomain.start),
+        (a.ydomain.end, b.ydomain.end),
+    ]
+    .iter()
+    .all(|(u, v)| (u - v).abs() <= tolerance)
+}
+
+/// LRU cache of up to `capacity` tiles of `tile_size` by `tile_size`
+/// pixels
+pub struct TileCache {
+    tile_size: usize,
+    capacity: usize,
+    entries: Vec<Entry>,
+    clock: u64,
+    hits: usize,
+    misses: usize,
+}
+impl TileCache {
+    pub fn new(tile_size: usize, capacity: usize) -> Self {
+        Self {
+            tile_size,
+            capacity,
+            entries: Vec::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Same as `mandel()`, calculating only the tiles not in the cache
+    pub fn mandel(&mut self, cfg: MandelConfig) -> Result<IterationGrid, MandelError> {
+        let (mut grid, missing) = self.lookup(&cfg)?;
+        let rendered: Vec<_> = pool::install(&cfg, || {
+            missing
+                .into_par_iter()
+                .map(|tile| {
+                    let tile_cfg = tile.config(&cfg);
+                    (tile, tile_cfg, mandel_unchecked(tile_cfg))
+                })
+                .collect()
+        });
+        for (tile, tile_cfg, tile_grid) in rendered {
+            grid.paste(tile.x, tile.y, &tile_grid);
+            self.insert(tile_cfg, tile_grid);
+        }
+        Ok(grid)
+    }
+
+    /// Return a grid of `cfg` with the tiles found in the cache, and the
+    /// tiles missing from it, eg, to render them with a `RenderJob`
+    pub fn lookup(&mut self, cfg: &MandelConfig) -> Result<(IterationGrid, Vec<Tile>), MandelError> {
+        cfg.validate()?;
+        let mut grid = IterationGrid::for_config(cfg);
+        let mut missing = Vec::new();
+        for tile in tiles(cfg.resolution, self.tile_size) {
+            let tile_cfg = tile.config(cfg);
+            self.clock += 1;
+            match self.entries.iter_mut().find(|e| same_tile(&e.cfg, &tile_cfg)) {
+                Some(entry) => {
+                    entry.last_used = self.clock;
+                    grid.paste(tile.x, tile.y, &entry.grid);
+                    self.hits += 1;
+                }
+                None => missing.push(tile),
+            }
+        }
+        self.misses += missing.len();
+        Ok((grid, missing))
+    }
+
+    /// Store the `tiles` of `grid`, rendered for `cfg`, see `lookup()`
+    pub fn insert_tiles(&mut self, cfg: &MandelConfig, grid: &IterationGrid, tiles: &[Tile]) {
+        for tile in tiles {
+            let tile_grid = grid.crop(tile.x, tile.y, tile.width, tile.height);
+            self.insert(tile.config(cfg), tile_grid);
+        }
+    }
+
+    fn insert(&mut self, cfg: MandelConfig, grid: IterationGrid) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            let oldest = (0..self.entries.len())
+                .min_by_key(|&i| self.entries[i].last_used)
+                .expect("the cache is not empty");
+            self.entries.swap_remove(oldest);
+        }
+        self.clock += 1;
+        self.entries.push(Entry {
+            cfg,
+            grid,
+            last_used: self.clock,
+        });
+    }
+
+    /// Return the number of tiles in the cache
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Return the number of tiles found in and missing from the cache
+    /// so far
+    pub fn stats(&self) -> (usize, usize) {
+        (self.hits, self.misses)
+    }
+}
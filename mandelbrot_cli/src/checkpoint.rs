@@ -0,0 +1,155 @@
+// Checkpoints ///////////////////////////////////////////////////////
+//             //////////////////////////////////////////////////////
+// State of a long render split in units of work, eg, the tiles of a
+// poster or the chunks of samples of a Buddhabrot, saved to a file from
+// time to time, so that the render can resume from it after a crash or
+// an interruption instead of starting over. It records which units are
+// done, the data accumulated so far, eg, the hits of the Buddhabrot, and
+// a key describing the render, eg, its settings, to check that it is
+// resumed with the same ones.
+//
+// The file is binary, little-endian: a magic number, the key, a bitmap
+// of the units done and the data. It is written to a temporary file
+// first and then renamed, so that a crash while saving leaves the last
+// checkpoint intact.
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::MandelError;
+
+/// Start of checkpoint files, with the version of the format
+const MAGIC: &[u8; 8] = b"MANDCKP1";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    /// Description of the render, to check that a resume is of the same
+    /// render, see `check()`
+    pub key: String,
+    /// Data accumulated by the units done, if any
+    pub data: Vec<u32>,
+    done: Vec<bool>,
+}
+
+impl Checkpoint {
+    /// Checkpoint of a render of `units` units of work, none done yet
+    pub fn new(key: impl Into<String>, units: usize) -> Self {
+        Self {
+            key: key.into(),
+            data: Vec::new(),
+            done: vec![false; units],
+        }
+    }
+
+    /// Return the number of units of work of the render
+    pub fn units(&self) -> usize {
+        self.done.len()
+    }
+
+    /// Return the number of units done
+    pub fn units_done(&self) -> usize {
+        self.done.iter().filter(|&&done| done).count()
+    }
+
+    pub fn is_done(&self, unit: usize) -> bool {
+        self.done.get(unit).copied().unwrap_or(false)
+    }
+
+    /// Return whether each unit is done, by index
+    pub fn done(&self) -> &[bool] {
+        &self.done
+    }
+
+    pub fn mark_done(&mut self, unit: usize) {
+        self.done[unit] = true;
+    }
+
+    /// Return an error if the checkpoint is not of a render with `key`
+    /// and `units` units, eg, one resumed with other settings
+    pub fn check(&self, key: &str, units: usize) -> Result<(), MandelError> {
+        if self.key != key || self.units() != units {
+            return Err(MandelError::InvalidCheckpoint("it is of another render"));
+        }
+        Ok(())
+    }
+
+    /// Save the checkpoint to `path`, replacing the last one only once
+    /// written
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MandelError> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let mut file = BufWriter::new(File::create(&tmp)?);
+        file.write_all(MAGIC)?;
+        write_u64(&mut file, self.key.len())?;
+        file.write_all(self.key.as_bytes())?;
+        write_u64(&mut file, self.done.len())?;
+        let bitmap: Vec<u8> = self
+            .done
+            .chunks(8)
+            .map(|bits| {
+                bits.iter()
+                    .rev()
+                    .fold(0, |byte, &bit| byte << 1 | bit as u8)
+            })
+            .collect();
+        file.write_all(&bitmap)?;
+        write_u64(&mut file, self.data.len())?;
+        for value in &self.data {
+            file.write_all(&value.to_le_bytes())?;
+        }
+        file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MandelError> {
+        let mut file = BufReader::new(File::open(path)?);
+        let mut magic = [0; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(MandelError::InvalidCheckpoint(
+                "it is not a checkpoint file",
+            ));
+        }
+        let len = read_u64(&mut file)?;
+        let key = String::from_utf8(read_bytes(&mut file, len)?)
+            .map_err(|_| MandelError::InvalidCheckpoint("the key is not UTF-8"))?;
+        let units = read_u64(&mut file)?;
+        let bitmap = read_bytes(&mut file, units.div_ceil(8))?;
+        let done = (0..units)
+            .map(|i| bitmap[i / 8] >> (i % 8) & 1 == 1)
+            .collect();
+        let len = read_u64(&mut file)?;
+        let bytes = len
+            .checked_mul(4)
+            .ok_or(MandelError::InvalidCheckpoint("a length is too large"))?;
+        let data = read_bytes(&mut file, bytes)?
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        Ok(Self { key, data, done })
+    }
+}
+
+fn write_u64(w: &mut impl Write, n: usize) -> std::io::Result<()> {
+    w.write_all(&(n as u64).to_le_bytes())
+}
+
+fn read_u64(r: &mut impl Read) -> Result<usize, MandelError> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes)?;
+    usize::try_from(u64::from_le_bytes(bytes))
+        .map_err(|_| MandelError::InvalidCheckpoint("a length is too large"))
+}
+
+/// Read `n` bytes, as they come, so that a corrupt length fails at the
+/// end of the file instead of allocating it
+fn read_bytes(r: &mut impl Read, n: usize) -> Result<Vec<u8>, MandelError> {
+    let mut bytes = Vec::new();
+    r.take(n as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != n {
+        return Err(MandelError::InvalidCheckpoint("the file is truncated"));
+    }
+    Ok(bytes)
+}
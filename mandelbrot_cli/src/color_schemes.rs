@@ -2,15 +2,69 @@
 //               ///////////////////////////////
 // Color schemes must be implemented as structs that implement
 // the `MandelRGB` trait, ie, they must have a function that
-// take 2 `usize` parameters, `c` and `max_iters`, and return a
+// take 2 parameters, `c` and `max_iters`, and return a
 // 3-tuple of type `u8` with the RGB values of a color.
-pub trait MandelRGB {
-    fn rgb(&self, c: usize, max_iters: usize) -> (u8, u8, u8);
+//
+// This module is the single source of the color schemes for both the
+// CLI and the GUI. Apps can add their own with `ColorSchemes::register`.
+//
+// `c` is a float so that the smooth (fractional) escape value can
+// be used to render continuous gradients. Points inside the set have
+// `c >= max_iters`.
+//
+// Frames are colored through a `ColorLut` of the current scheme, ie, its
+// colors at each iteration count, computed once per scheme and
+// `max_iters`, so that neither the scheme nor the offset and scale of
+// the colors cost a call per pixel.
+use serde::Deserialize;
+use std::error::Error;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::IterationGrid;
+
+/// `Send + Sync` so that frames can be colored in parallel
+pub trait MandelRGB: Send + Sync {
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8);
+    /// Color a row of `smooth` values into `rgb`, of the same length.
+    /// Compiled for each scheme, so that only the call of the row is
+    /// dynamic, not the call of `rgb()` of each pixel.
+    fn rgb_row(&self, smooth: &[f64], max_iters: usize, rgb: &mut [(u8, u8, u8)]) {
+        for (&c, rgb) in smooth.iter().zip(rgb) {
+            *rgb = self.rgb(c, max_iters);
+        }
+    }
+    /// Name shown to the user, eg, in the GUI
+    fn name(&self) -> &str {
+        "custom"
+    }
+}
+
+/// Color schemes for orbit traps, see `crate::OrbitTrap`. `distance`
+/// is the minimum distance of the orbit of the pixel to the trap.
+pub trait TrapRGB: Send + Sync {
+    fn rgb(&self, distance: f64) -> (u8, u8, u8);
+}
+
+/// Color schemes for the stripe average coloring, see
+/// `crate::MandelConfig::stripes`. `stripe` is the average of the orbit
+/// of the pixel, from 0.0 to 1.0.
+pub trait StripeRGB: Send + Sync {
+    fn rgb(&self, stripe: f64) -> (u8, u8, u8);
 }
 
 pub struct ColorSchemes {
     color_schemes: Vec<Box<dyn MandelRGB>>,
     index_current: usize,
+    equalize: bool,
+    offset: f64,
+    scale: f64,
+    trap_scheme: Box<dyn TrapRGB>,
+    stripe_scheme: Box<dyn StripeRGB>,
+    distance_shading: DistanceShaded,
+    relief: Option<ReliefShaded>,
+    // lookup table of the scheme at the index, see `lut()`
+    lut: Mutex<Option<(usize, Arc<ColorLut>)>>,
 }
 impl ColorSchemes {
     pub fn new() -> Self {
@@ -22,103 +76,674 @@ impl ColorSchemes {
                 Box::new(Weirdy {}),
                 Box::new(GreyeyDark {}),
                 Box::new(GreyeyLight {}),
-		Box::new(Hulky {}),
-		Box::new(Wiky {}),
-		
+                Box::new(Hulky {}),
+                Box::new(Wiky {}),
             ],
             index_current: 0,
+            equalize: false,
+            offset: 0.0,
+            scale: 1.0,
+            trap_scheme: Box::new(TrapGlow::default()),
+            stripe_scheme: Box::new(StripeFlame::default()),
+            distance_shading: DistanceShaded::default(),
+            relief: None,
+            lut: Mutex::new(None),
         }
     }
-    pub fn get(&self) -> &Box<dyn MandelRGB> {
-        &self.color_schemes[self.index_current]
+    pub fn get(&self) -> &dyn MandelRGB {
+        self.color_schemes[self.index_current].as_ref()
+    }
+    /// Return the lookup table of the current color scheme up to
+    /// `max_iters`. It is kept until the scheme or `max_iters` change,
+    /// eg, while the colors are cycled.
+    pub fn lut(&self, max_iters: usize) -> Arc<ColorLut> {
+        let mut lut = self.lut.lock().expect("no panic while locked");
+        match &*lut {
+            Some((index, table))
+                if *index == self.index_current && table.max_iters() == max_iters =>
+            {
+                table.clone()
+            }
+            _ => {
+                let table = Arc::new(ColorLut::new(self.get(), max_iters));
+                *lut = Some((self.index_current, table.clone()));
+                table
+            }
+        }
+    }
+    /// Return the current color scheme to color `iters`, ie, its lookup
+    /// table shifted by the offset and scale and wrapped in
+    /// `HistogramEqualized` if equalisation is on.
+    pub fn get_for(&self, iters: &IterationGrid, max_iters: usize) -> Box<dyn MandelRGB + '_> {
+        let scheme = Shifted {
+            scheme: self.lut(max_iters),
+            offset: self.offset,
+            scale: self.scale,
+        };
+        if self.equalize {
+            Box::new(HistogramEqualized::new(scheme, iters, max_iters))
+        } else {
+            Box::new(scheme)
+        }
+    }
+    /// Return the offset of the colors, see `Shifted`
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+    /// Shift the colors by `offset`, eg, increasing over time to cycle
+    /// them, see `Shifted`
+    pub fn set_offset(&mut self, offset: f64) -> &mut Self {
+        self.offset = offset.rem_euclid(1.0);
+        self
+    }
+    /// Return the scale of the colors, see `Shifted`
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+    /// Repeat the colors `scale` times along the iteration counts, see
+    /// `Shifted`. Scales that are not positive are ignored.
+    pub fn set_scale(&mut self, scale: f64) -> &mut Self {
+        if scale > 0.0 && scale.is_finite() {
+            self.scale = scale;
+        }
+        self
+    }
+    /// Return the color scheme used for grids with orbit trap data
+    pub fn get_trap(&self) -> &dyn TrapRGB {
+        self.trap_scheme.as_ref()
+    }
+    pub fn set_trap(&mut self, scheme: Box<dyn TrapRGB>) -> &mut Self {
+        self.trap_scheme = scheme;
+        self
+    }
+    /// Return the color scheme used for grids with stripe averages
+    pub fn get_stripe(&self) -> &dyn StripeRGB {
+        self.stripe_scheme.as_ref()
+    }
+    pub fn set_stripe(&mut self, scheme: Box<dyn StripeRGB>) -> &mut Self {
+        self.stripe_scheme = scheme;
+        self
+    }
+    /// Return the shading of grids with distance estimates
+    pub fn get_distance(&self) -> &DistanceShaded {
+        &self.distance_shading
+    }
+    pub fn set_distance(&mut self, shading: DistanceShaded) -> &mut Self {
+        self.distance_shading = shading;
+        self
+    }
+    /// Return the relief shading of all grids, if on
+    pub fn get_relief(&self) -> Option<&ReliefShaded> {
+        self.relief.as_ref()
+    }
+    pub fn set_relief(&mut self, shading: Option<ReliefShaded>) -> &mut Self {
+        self.relief = shading;
+        self
+    }
+    pub fn is_equalized(&self) -> bool {
+        self.equalize
+    }
+    pub fn toggle_equalize(&mut self) -> &mut Self {
+        self.equalize = !self.equalize;
+        self
+    }
+    /// Register a color scheme, eg, defined by the app, in addition
+    /// to the built-in ones. Return its index, see `set()`.
+    pub fn register(&mut self, scheme: Box<dyn MandelRGB>) -> usize {
+        self.color_schemes.push(scheme);
+        self.color_schemes.len() - 1
+    }
+    /// Return the number of color schemes
+    pub fn len(&self) -> usize {
+        self.color_schemes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.color_schemes.is_empty()
+    }
+    /// Return the name of the current color scheme
+    pub fn name(&self) -> &str {
+        self.get().name()
+    }
+    /// Return the index of the current color scheme
+    pub fn index(&self) -> usize {
+        self.index_current
+    }
+    /// Make the color scheme at `index` the current one
+    pub fn set(&mut self, index: usize) -> &mut Self {
+        assert!(index < self.color_schemes.len(), "no color scheme {index}");
+        self.index_current = index;
+        self
+    }
+    /// Make the first color scheme called `name`, ignoring case, the
+    /// current one. Return `None`, leaving the current one, if there's
+    /// no such scheme, see `names()`.
+    pub fn set_by_name(&mut self, name: &str) -> Option<&mut Self> {
+        let index = self
+            .names()
+            .position(|other| other.eq_ignore_ascii_case(name))?;
+        Some(self.set(index))
+    }
+    /// Return the names of the color schemes, in index order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.color_schemes.iter().map(|scheme| scheme.name())
     }
     // Returning `self` allow chained-calls: `.next().next()...`
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> &mut Self {
         if self.index_current == self.color_schemes.len() - 1 {
             self.index_current = 0;
         } else {
             self.index_current += 1;
         }
-	self
+        self
+    }
+    /// The color scheme before the current one, the opposite of `next()`
+    pub fn prev(&mut self) -> &mut Self {
+        if self.index_current == 0 {
+            self.index_current = self.color_schemes.len() - 1;
+        } else {
+            self.index_current -= 1;
+        }
+        self
+    }
+}
+impl Default for ColorSchemes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: MandelRGB + ?Sized> MandelRGB for &T {
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        (**self).rgb(c, max_iters)
+    }
+    fn rgb_row(&self, smooth: &[f64], max_iters: usize, rgb: &mut [(u8, u8, u8)]) {
+        (**self).rgb_row(smooth, max_iters, rgb)
+    }
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
+impl<T: MandelRGB + ?Sized> MandelRGB for Arc<T> {
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        (**self).rgb(c, max_iters)
+    }
+    fn rgb_row(&self, smooth: &[f64], max_iters: usize, rgb: &mut [(u8, u8, u8)]) {
+        (**self).rgb_row(smooth, max_iters, rgb)
+    }
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
+/// Colors of a scheme at each iteration count from 0 to `max_iters`,
+/// the last one the color inside the set. The smooth counts in between
+/// are interpolated linearly, except in the last interval before
+/// `max_iters`, which keeps the color of its start.
+pub struct ColorLut {
+    colors: Vec<(u8, u8, u8)>,
+    name: String,
+}
+impl ColorLut {
+    pub fn new(scheme: &dyn MandelRGB, max_iters: usize) -> Self {
+        let counts: Vec<_> = (0..=max_iters).map(|k| k as f64).collect();
+        let mut colors = vec![(0, 0, 0); max_iters + 1];
+        scheme.rgb_row(&counts, max_iters, &mut colors);
+        Self {
+            colors,
+            name: scheme.name().to_string(),
+        }
+    }
+    pub fn max_iters(&self) -> usize {
+        self.colors.len() - 1
+    }
+}
+impl MandelRGB for ColorLut {
+    /// `max_iters` is that of the table
+    fn rgb(&self, c: f64, _max_iters: usize) -> (u8, u8, u8) {
+        let m = self.max_iters();
+        if c >= m as f64 || c.is_nan() {
+            return self.colors[m];
+        }
+        let k = c.max(0.0) as usize;
+        if k + 1 >= m {
+            return self.colors[k];
+        }
+        let f = c - k as f64;
+        let (a, b) = (self.colors[k], self.colors[k + 1]);
+        let lerp = |a: u8, b: u8| (a as f64 + f * (b as f64 - a as f64)).round() as u8;
+        (lerp(a.0, b.0), lerp(a.1, b.1), lerp(a.2, b.2))
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Histogram equalisation of any color scheme
+///
+/// Maps the iteration counts of one frame by their cumulative
+/// distribution instead of `c / max_iters`, so that the colors of the
+/// scheme are spread evenly among the pixels, and low contrast regions
+/// get the full palette range.
+pub struct HistogramEqualized<S: MandelRGB> {
+    scheme: S,
+    // `cdf[k]` is the fraction of escaped pixels with less than `k`
+    // iterations, for `k` in `0..=max_iters`
+    cdf: Vec<f64>,
+}
+impl<S: MandelRGB> HistogramEqualized<S> {
+    pub fn new(scheme: S, iters: &IterationGrid, max_iters: usize) -> Self {
+        let mut histogram = vec![0usize; max_iters + 1];
+        for &c in &iters.data {
+            histogram[(c as usize).min(max_iters)] += 1;
+        }
+        let escaped: usize = histogram[..max_iters].iter().sum();
+        let mut cdf = Vec::with_capacity(max_iters + 1);
+        let mut cumulative = 0;
+        for count in &histogram[..max_iters] {
+            cdf.push(cumulative as f64 / escaped.max(1) as f64);
+            cumulative += count;
+        }
+        cdf.push(1.0);
+        Self { scheme, cdf }
+    }
+
+    /// Return the iteration count `c` is colored as by the scheme
+    fn equalize(&self, c: f64, max_iters: usize) -> f64 {
+        if c < max_iters as f64 {
+            // interpolate the cumulative distribution at the smooth `c`
+            let k = (c as usize).min(max_iters - 1);
+            let f = c - k as f64;
+            let q = self.cdf[k] + f * (self.cdf[k + 1] - self.cdf[k]);
+            q * (max_iters - 1) as f64
+        } else {
+            c
+        }
+    }
+}
+impl<S: MandelRGB> MandelRGB for HistogramEqualized<S> {
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        self.scheme.rgb(self.equalize(c, max_iters), max_iters)
+    }
+    fn rgb_row(&self, smooth: &[f64], max_iters: usize, rgb: &mut [(u8, u8, u8)]) {
+        let equalized: Vec<_> = smooth.iter().map(|&c| self.equalize(c, max_iters)).collect();
+        self.scheme.rgb_row(&equalized, max_iters, rgb)
+    }
+    fn name(&self) -> &str {
+        self.scheme.name()
+    }
+}
+
+/// Any color scheme stretched along the iteration counts by `scale`, eg,
+/// repeated twice from 0 to `max_iters` with `2.0`, and shifted by
+/// `offset`, a fraction of `max_iters`, wrapping around. The points
+/// inside the set keep their color.
+pub struct Shifted<S: MandelRGB> {
+    pub scheme: S,
+    pub offset: f64,
+    pub scale: f64,
+}
+impl<S: MandelRGB> Shifted<S> {
+    /// Return the iteration count `c` is colored as by the scheme
+    fn shift(&self, c: f64, max_iters: usize) -> f64 {
+        let m = max_iters as f64;
+        if c < m && (self.offset != 0.0 || self.scale != 1.0) {
+            (c * self.scale + self.offset * m).rem_euclid(m)
+        } else {
+            c
+        }
+    }
+}
+impl<S: MandelRGB> MandelRGB for Shifted<S> {
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        self.scheme.rgb(self.shift(c, max_iters), max_iters)
+    }
+    fn rgb_row(&self, smooth: &[f64], max_iters: usize, rgb: &mut [(u8, u8, u8)]) {
+        if self.offset == 0.0 && self.scale == 1.0 {
+            return self.scheme.rgb_row(smooth, max_iters, rgb);
+        }
+        let shifted: Vec<_> = smooth.iter().map(|&c| self.shift(c, max_iters)).collect();
+        self.scheme.rgb_row(&shifted, max_iters, rgb)
+    }
+    fn name(&self) -> &str {
+        self.scheme.name()
+    }
+}
+
+/// Color gradient linearly interpolated between color stops
+///
+/// Palettes can be loaded from a TOML file with a list of stops, where
+/// `pos` goes from 0.0 (`c = 0`) to 1.0 (`c = max_iters`), and an
+/// optional color for the points inside the set:
+///
+/// ```toml
+/// inside = [0, 0, 0]
+///
+/// [[stops]]
+/// pos = 0.0
+/// rgb = [0, 7, 100]
+///
+/// [[stops]]
+/// pos = 1.0
+/// rgb = [255, 170, 0]
+/// ```
+///
+/// or from an image, in which case the pixels of its first row are
+/// the stops, evenly spaced.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Palette {
+    stops: Vec<Stop>,
+    #[serde(default)]
+    inside: [u8; 3],
+}
+#[derive(Clone, Copy, Debug, Deserialize)]
+struct Stop {
+    pos: f64,
+    rgb: [u8; 3],
+}
+impl Palette {
+    /// Create a palette of evenly spaced colors
+    pub fn new(colors: &[[u8; 3]], inside: [u8; 3]) -> Self {
+        let n = colors.len().saturating_sub(1).max(1) as f64;
+        let stops = colors
+            .iter()
+            .enumerate()
+            .map(|(i, &rgb)| Stop {
+                pos: i as f64 / n,
+                rgb,
+            })
+            .collect();
+        Self { stops, inside }
+    }
+
+    /// Load a palette from a `.toml` file, or from the first row of an
+    /// image in any other format
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let mut palette: Self = if path.extension().is_some_and(|e| e == "toml") {
+            toml::from_str(&std::fs::read_to_string(path)?)?
+        } else {
+            Self::from_image(path)?
+        };
+        if palette.stops.is_empty() {
+            return Err(format!("no color stops in {}", path.display()).into());
+        }
+        palette.stops.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        Ok(palette)
+    }
+
+    /// Palette of the first row of pixels of the image at `path`
+    #[cfg(feature = "images")]
+    fn from_image(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let img = image::open(path)?.into_rgb8();
+        let colors: Vec<_> = (0..img.width()).map(|x| img.get_pixel(x, 0).0).collect();
+        Ok(Self::new(&colors, [0, 0, 0]))
+    }
+    #[cfg(not(feature = "images"))]
+    fn from_image(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Err(format!("{} - image palettes need the `images` feature", path.display()).into())
+    }
+
+    /// Return the color at `q`, from 0.0 to 1.0
+    pub fn color_at(&self, q: f64) -> [u8; 3] {
+        // first stop after `q`, colors before the first and after the
+        // last stop are flat
+        let i = self.stops.partition_point(|s| s.pos <= q);
+        if i == 0 {
+            self.stops[0].rgb
+        } else if i == self.stops.len() {
+            self.stops[i - 1].rgb
+        } else {
+            let (s0, s1) = (self.stops[i - 1], self.stops[i]);
+            let f = (q - s0.pos) / (s1.pos - s0.pos);
+            let mix = |a: u8, b: u8| (a as f64 + f * (b as f64 - a as f64)).round() as u8;
+            [
+                mix(s0.rgb[0], s1.rgb[0]),
+                mix(s0.rgb[1], s1.rgb[1]),
+                mix(s0.rgb[2], s1.rgb[2]),
+            ]
+        }
+    }
+}
+impl MandelRGB for Palette {
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        let [r, g, b] = if c < max_iters as f64 {
+            self.color_at(c / max_iters as f64)
+        } else {
+            self.inside
+        };
+        (r, g, b)
+    }
+    fn name(&self) -> &str {
+        "palette"
+    }
+}
+
+/// Orbit trap coloring, brighter the closer the orbit gets to the trap
+///
+/// `exp(-distance / scale)` goes from 0.0 far from the trap to 1.0 on
+/// the trap, and picks the color from `palette`.
+pub struct TrapGlow {
+    pub palette: Palette,
+    pub scale: f64,
+}
+impl Default for TrapGlow {
+    fn default() -> Self {
+        Self {
+            palette: Palette::new(
+                &[
+                    [0, 2, 0],
+                    [0, 7, 100],
+                    [32, 107, 203],
+                    [237, 255, 255],
+                    [255, 170, 0],
+                ],
+                [0, 0, 0],
+            ),
+            scale: 0.25,
+        }
+    }
+}
+impl TrapRGB for TrapGlow {
+    fn rgb(&self, distance: f64) -> (u8, u8, u8) {
+        let [r, g, b] = self.palette.color_at((-distance / self.scale).exp());
+        (r, g, b)
+    }
+}
+
+/// Stripe average coloring, the flame-like bands that follow the
+/// filaments of the set
+///
+/// The stripe average goes from 0.0 to 1.0 and picks the color from
+/// `palette`, stretched by `contrast` around 0.5, since the averages of
+/// long orbits stay close to it.
+pub struct StripeFlame {
+    pub palette: Palette,
+    pub contrast: f64,
+}
+impl Default for StripeFlame {
+    fn default() -> Self {
+        Self {
+            palette: Palette::new(
+                &[
+                    [10, 0, 20],
+                    [120, 10, 10],
+                    [230, 90, 0],
+                    [255, 200, 40],
+                    [255, 250, 220],
+                ],
+                [0, 0, 0],
+            ),
+            contrast: 2.0,
+        }
+    }
+}
+impl StripeRGB for StripeFlame {
+    fn rgb(&self, stripe: f64) -> (u8, u8, u8) {
+        let [r, g, b] = self.palette.color_at(0.5 + (stripe - 0.5) * self.contrast);
+        (r, g, b)
+    }
+}
+
+/// Distance estimation shading
+///
+/// Darkens the color of the pixels closer than `width` pixels to the
+/// set, which draws its boundary crisp and thin at any zoom, where
+/// the escape time alone washes out.
+#[derive(Clone, Copy, Debug)]
+pub struct DistanceShaded {
+    pub width: f64,
+}
+impl Default for DistanceShaded {
+    fn default() -> Self {
+        Self { width: 2.0 }
+    }
+}
+impl DistanceShaded {
+    /// Shade `rgb` of a pixel at `distance` pixels from the set
+    pub fn shade(&self, (r, g, b): (u8, u8, u8), distance: f64) -> (u8, u8, u8) {
+        let k = (distance / self.width).clamp(0.0, 1.0).sqrt();
+        let dim = |v: u8| (v as f64 * k) as u8;
+        (dim(r), dim(g), dim(b))
+    }
+}
+
+/// Relief shading, lighting the smooth iteration count as the height of
+/// a surface
+///
+/// The light comes from `azimuth` radians counter-clockwise from the x
+/// axis, `elevation` radians above the image. The surface is lit with
+/// Lambert diffuse light over `ambient` light, plus Blinn-Phong
+/// highlights of strength `specular`, and `depth` scales its height,
+/// steeper with larger values.
+#[derive(Clone, Copy, Debug)]
+pub struct ReliefShaded {
+    pub azimuth: f64,
+    pub elevation: f64,
+    pub depth: f64,
+    pub ambient: f64,
+    pub specular: f64,
+}
+impl Default for ReliefShaded {
+    fn default() -> Self {
+        Self {
+            azimuth: 135f64.to_radians(),
+            elevation: 45f64.to_radians(),
+            depth: 1.0,
+            ambient: 0.3,
+            specular: 0.3,
+        }
+    }
+}
+impl ReliefShaded {
+    /// Shininess of the highlights, ie, the exponent of Blinn-Phong
+    const SHININESS: i32 = 16;
+
+    /// Shade `rgb` of a pixel where the slope of the smooth iteration
+    /// count is `(dx, dy)` iterations per pixel
+    pub fn shade(&self, (r, g, b): (u8, u8, u8), (dx, dy): (f64, f64)) -> (u8, u8, u8) {
+        let normalize = |[x, y, z]: [f64; 3]| {
+            let norm = (x * x + y * y + z * z).sqrt();
+            [x / norm, y / norm, z / norm]
+        };
+        let dot = |a: [f64; 3], b: [f64; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+        let normal = normalize([-self.depth * dx, -self.depth * dy, 1.0]);
+        let (sin_el, cos_el) = self.elevation.sin_cos();
+        let (sin_az, cos_az) = self.azimuth.sin_cos();
+        let light = [cos_el * cos_az, cos_el * sin_az, sin_el];
+        // halfway between the light and the viewer, straight above
+        let half = normalize([light[0], light[1], light[2] + 1.0]);
+        let diffuse = dot(normal, light).max(0.0);
+        let highlight = self.specular * dot(normal, half).max(0.0).powi(Self::SHININESS);
+        let k = self.ambient + (1.0 - self.ambient) * diffuse;
+        let lit = |v: u8| (v as f64 * k + 255.0 * highlight).min(255.0) as u8;
+        (lit(r), lit(g), lit(b))
     }
 }
 
 struct Wiky {}
 impl MandelRGB for Wiky {
-    fn rgb(&self, c: usize, max_iters: usize) -> (u8, u8, u8) {
-        if c < max_iters {
-            let q = (c as f64) / (max_iters as f64);
-	    if q < 0.16 { ( 0, 7, 100) }
-	    else if q < 0.42 { (32, 107, 203) }
-	    else if q < 0.64 { (237, 255, 255) }
-	    else if q < 0.86 { (255, 170, 0) }
-	    else { (0, 2, 0) }
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        if c < max_iters as f64 {
+            let q = c / (max_iters as f64);
+            if q < 0.16 {
+                (0, 7, 100)
+            } else if q < 0.42 {
+                (32, 107, 203)
+            } else if q < 0.64 {
+                (237, 255, 255)
+            } else if q < 0.86 {
+                (255, 170, 0)
+            } else {
+                (0, 2, 0)
+            }
         } else {
             (0, 0, 0)
         }
     }
+    fn name(&self) -> &str {
+        "wiky"
+    }
 }
 
 struct Hulky {}
 impl MandelRGB for Hulky {
-    fn rgb(&self, c: usize, max_iters: usize) -> (u8, u8, u8) {
-        if c < max_iters {
-            let q = (c as f64) / (max_iters as f64);
-	    if q > 0.5 {
-		(
-                    (255.0 * q) as u8,
-                    255 as u8,
-                    (255.0 * q) as u8,
-		)
-	    } else {
-		(
-		    0 as u8,
-		    (255.0 * q) as u8,
-		    0 as u8,
-		)
-	    }
-		    
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        if c < max_iters as f64 {
+            let q = c / (max_iters as f64);
+            if q > 0.5 {
+                ((255.0 * q) as u8, 255, (255.0 * q) as u8)
+            } else {
+                (0, (255.0 * q) as u8, 0)
+            }
         } else {
             (0, 0, 0)
         }
     }
+    fn name(&self) -> &str {
+        "hulky"
+    }
 }
 
 struct Bluey {}
 impl MandelRGB for Bluey {
-    fn rgb(&self, c: usize, max_iters: usize) -> (u8, u8, u8) {
-        if c < max_iters {
-            let c = c as f64;
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        if c < max_iters as f64 {
             (
                 (255.0 * c / max_iters as f64) as u8,
                 (255.0 * c / (c + 8.0)) as u8,
-                255 as u8,
+                255,
             )
         } else {
             (0, 0, 0)
         }
     }
+    fn name(&self) -> &str {
+        "bluey"
+    }
 }
 struct Greeny {}
 impl MandelRGB for Greeny {
-    fn rgb(&self, c: usize, max_iters: usize) -> (u8, u8, u8) {
-        if c < max_iters {
-            let c = c as f64;
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        if c < max_iters as f64 {
             (
                 (255.0 * c / max_iters as f64) as u8,
-                255 as u8,
+                255,
                 (255.0 * c / (c + 8.0)) as u8,
             )
         } else {
             (0, 0, 0)
         }
     }
+    fn name(&self) -> &str {
+        "greeny"
+    }
 }
 struct Purply {}
 impl MandelRGB for Purply {
-    fn rgb(&self, c: usize, max_iters: usize) -> (u8, u8, u8) {
-        if c < max_iters {
-            let c = c as f64;
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        if c < max_iters as f64 {
             let m = max_iters as f64;
             (
                 (255.0 * c / m) as u8,
@@ -129,12 +754,14 @@ impl MandelRGB for Purply {
             (0, 0, 0)
         }
     }
+    fn name(&self) -> &str {
+        "purply"
+    }
 }
 struct Weirdy {}
 impl MandelRGB for Weirdy {
-    fn rgb(&self, c: usize, max_iters: usize) -> (u8, u8, u8) {
-        if c < max_iters {
-            let c = c as f64;
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        if c < max_iters as f64 {
             let m = max_iters as f64;
             (
                 (255.0 * (2.0 * c / m) - 1.0).abs() as u8,
@@ -145,12 +772,14 @@ impl MandelRGB for Weirdy {
             (0, 0, 0)
         }
     }
+    fn name(&self) -> &str {
+        "weirdy"
+    }
 }
 struct GreyeyLight {}
 impl MandelRGB for GreyeyLight {
-    fn rgb(&self, c: usize, max_iters: usize) -> (u8, u8, u8) {
-        if c < max_iters {
-            let c = c as f64;
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        if c < max_iters as f64 {
             let m = max_iters as f64;
             (
                 (255.0 * (2.0 * c / m - 1.0).abs()) as u8,
@@ -161,12 +790,14 @@ impl MandelRGB for GreyeyLight {
             (255, 255, 255)
         }
     }
+    fn name(&self) -> &str {
+        "greyeylight"
+    }
 }
 struct GreyeyDark {}
 impl MandelRGB for GreyeyDark {
-    fn rgb(&self, c: usize, max_iters: usize) -> (u8, u8, u8) {
-        if c < max_iters {
-            let c = c as f64;
+    fn rgb(&self, c: f64, max_iters: usize) -> (u8, u8, u8) {
+        if c < max_iters as f64 {
             let m = max_iters as f64;
             (
                 (255.0 * c / m) as u8,
@@ -177,4 +808,7 @@ impl MandelRGB for GreyeyDark {
             (0, 0, 0)
         }
     }
+    fn name(&self) -> &str {
+        "greyeydark"
+    }
 }
@@ -0,0 +1,201 @@
+// Contact sheet ///////////////////////////////////////////////////////
+//               //////////////////////////////////////////////////////
+// Julia sets of a grid of `c` over a rectangle of the plane, `cx` along
+// the columns and `cy` along the rows, the largest `cy` at the top as in
+// the images, composed into one image to compare them at a glance.
+//
+// Each cell is rendered with `mandel()` from the view of `cell`, of
+// which only the kind is replaced, and is labelled with its `c` in a
+// small bitmap font, so that no font file is needed.
+use crate::{
+    get_rgb_buf, mandel, ColorSchemes, Domain, FractalKind, MandelConfig, MandelError, Resolution,
+};
+
+/// Pixels between the cells and around them
+const GAP: usize = 2;
+/// Color of the gaps
+const BACKGROUND: (u8, u8, u8) = (32, 32, 32);
+
+/// Julia sets of `columns` x `rows` values of `c`, from `cx.start` to
+/// `cx.end` and `cy.start` to `cy.end`, ends included, each in the view
+/// of `cell`
+#[derive(Clone, Debug)]
+pub struct ContactSheet {
+    pub cell: MandelConfig,
+    pub cx: Domain,
+    pub cy: Domain,
+    pub columns: usize,
+    pub rows: usize,
+    /// Label each cell with its `c`
+    pub labels: bool,
+}
+
+impl ContactSheet {
+    /// Sheet of small cells showing the whole Julia sets, at 256
+    /// iterations
+    pub fn new(cx: Domain, cy: Domain, columns: usize, rows: usize) -> Self {
+        let mut cell = MandelConfig {
+            kind: FractalKind::Julia { cx: 0.0, cy: 0.0 },
+            resolution: Resolution { x: 256, y: 160 },
+            max_iters: 256,
+            ..Default::default()
+        };
+        (cell.xdomain, cell.ydomain) = cell.kind.default_domains();
+        cell.domains().fit_to_aspect();
+        Self {
+            cell,
+            cx,
+            cy,
+            columns,
+            rows,
+            labels: true,
+        }
+    }
+
+    /// Return the `c` of the cell at `column`, `row`, from the top-left
+    /// one, the centre of the rectangle along a single column or row
+    pub fn c(&self, column: usize, row: usize) -> (f64, f64) {
+        // exact at both ends
+        let at = |d: Domain, i: usize, n: usize| {
+            let t = if n > 1 {
+                i as f64 / (n - 1) as f64
+            } else {
+                0.5
+            };
+            d.start * (1.0 - t) + d.end * t
+        };
+        // row 0 is at `cy.end`
+        let cy = Domain {
+            start: self.cy.end,
+            end: self.cy.start,
+        };
+        (at(self.cx, column, self.columns), at(cy, row, self.rows))
+    }
+
+    /// Return the view of the cell at `column`, `row`
+    pub fn config(&self, column: usize, row: usize) -> MandelConfig {
+        let (cx, cy) = self.c(column, row);
+        MandelConfig {
+            kind: FractalKind::Julia { cx, cy },
+            ..self.cell
+        }
+    }
+
+    /// Return the width and height of the sheet, in pixels
+    pub fn size(&self) -> (usize, usize) {
+        let Resolution { x, y } = self.cell.resolution;
+        (self.columns * (x + GAP) + GAP, self.rows * (y + GAP) + GAP)
+    }
+
+    /// Render the sheet to RGB, row-major from the top-left corner,
+    /// calling `done` after each cell. Return an error if the view of
+    /// the cells is not valid.
+    pub fn render(
+        &self,
+        color_schemes: &ColorSchemes,
+        mut done: impl FnMut(),
+    ) -> Result<Vec<u8>, MandelError> {
+        self.cell.validate()?;
+        let (width, height) = self.size();
+        let Resolution {
+            x: cell_width,
+            y: cell_height,
+        } = self.cell.resolution;
+        let (r, g, b) = BACKGROUND;
+        let mut sheet = [r, g, b].repeat(width * height);
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let cfg = self.config(column, row);
+                let rgb = get_rgb_buf(&mandel(cfg)?, cfg.max_iters, color_schemes)?;
+                let (left, top) = (
+                    GAP + column * (cell_width + GAP),
+                    GAP + row * (cell_height + GAP),
+                );
+                for (y, line) in rgb.chunks_exact(3 * cell_width).enumerate() {
+                    let start = 3 * ((top + y) * width + left);
+                    sheet[start..start + line.len()].copy_from_slice(line);
+                }
+                if self.labels {
+                    let (cx, cy) = self.c(column, row);
+                    let label = Label {
+                        text: format!("{cx:+.3} {cy:+.3}i"),
+                        left,
+                        bottom: top + cell_height,
+                        max_width: cell_width,
+                    };
+                    label.draw(&mut sheet, width);
+                }
+                done();
+            }
+        }
+        Ok(sheet)
+    }
+}
+
+/// Text at the bottom-left corner of a cell, white on black, scaled up
+/// if it fits
+struct Label {
+    text: String,
+    left: usize,
+    bottom: usize,
+    max_width: usize,
+}
+
+impl Label {
+    /// Draw the label into `sheet`, `width` pixels wide, or nothing if
+    /// it does not fit
+    fn draw(&self, sheet: &mut [u8], width: usize) {
+        let chars = self.text.chars().count();
+        // text at `scale`, and a margin of `scale` around it
+        let size = |scale: usize| ((4 * chars + 1) * scale, 7 * scale);
+        let Some(scale) = [2, 1].into_iter().find(|&s| {
+            let (w, h) = size(s);
+            w <= self.max_width && h <= self.bottom
+        }) else {
+            return;
+        };
+        let (box_width, box_height) = size(scale);
+        let top = self.bottom - box_height;
+        let mut fill = |x: usize, y: usize, value: u8| {
+            let i = 3 * ((top + y) * width + self.left + x);
+            sheet[i..i + 3].fill(value);
+        };
+        for y in 0..box_height {
+            for x in 0..box_width {
+                fill(x, y, 0);
+            }
+        }
+        for (n, c) in self.text.chars().enumerate() {
+            for (y, bits) in glyph(c).into_iter().enumerate() {
+                for x in (0..3).filter(|x| bits & (0b100 >> x) != 0) {
+                    let (x, y) = ((1 + 4 * n + x) * scale, (1 + y) * scale);
+                    for (dx, dy) in (0..scale).flat_map(|dx| (0..scale).map(move |dy| (dx, dy))) {
+                        fill(x + dx, y + dy, 255);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Rows of the 3x5 glyph of `c`, from the top, the leftmost pixel in the
+/// highest of the 3 bits. Blank for the characters without a glyph.
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        'i' => [0b010, 0b000, 0b010, 0b010, 0b010],
+        _ => [0; 5],
+    }
+}
@@ -0,0 +1,275 @@
+// Domain management /////////////////////////////////////////////////
+//                   ////////////////////////////////////////////////
+// Operations on the x and y domains of a view that keep the pixels
+// square, ie, the aspect ratio of the domains equal to the one of the
+// resolution, so that the set is not stretched, eg, when zooming into
+// a rectangle or resizing the window.
+//
+// The domains go from the centre of the first pixel to the centre of
+// the last one, so a pixel is `width / (resolution - 1)` wide.
+//
+// A view can also be given by its centre and zoom factor, as `View`,
+// the way deep zoom locations are usually published.
+//
+// Deep views keep their centre in `origin`, in high precision, and
+// their domains around zero, see `Origin`. The operations setting the
+// domains move it there, or back to the domains of shallow views, while
+// zooming about a point and panning only do once `f64` barely resolves
+// the pixels around the centre, so that the points of the domains stay
+// put, eg, under the mouse.
+//
+// `ScreenMap` maps the positions in a window showing the view to the
+// points of the domains, and back, eg, to zoom about the mouse.
+use crate::origin::{Coordinate, Origin, DEEP_STEP};
+use crate::{Domain, MandelConfig, Resolution};
+
+/// Views zoomed or panned are recentered on their origin once the
+/// rounding of their centre gets to this fraction of a pixel
+const ROUNDING: f64 = 1.0 / 256.0;
+
+/// View given by its centre and zoom factor instead of its domains, see
+/// `MandelConfig::view()` and `DomainManager::set_view()`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct View {
+    /// Centre of the view in the complex plane
+    pub center_x: f64,
+    pub center_y: f64,
+    /// Zoom factor, the width of the default x domain of the fractal
+    /// over the width of the x domain, eg, 1e9
+    pub scale: f64,
+    /// Angle of the view in radians, counter-clockwise, see `rotation`
+    /// in `MandelConfig`
+    pub rotation: f64,
+}
+
+/// Aspect ratio preserving operations on the domains of a config, see
+/// `MandelConfig::domains()`
+pub struct DomainManager<'a> {
+    cfg: &'a mut MandelConfig,
+}
+impl<'a> DomainManager<'a> {
+    pub fn new(cfg: &'a mut MandelConfig) -> Self {
+        Self { cfg }
+    }
+
+    /// Return the centre of the view
+    pub fn center(&self) -> (f64, f64) {
+        let (x, y) = (self.cfg.xdomain, self.cfg.ydomain);
+        ((x.start + x.end) / 2.0, (y.start + y.end) / 2.0)
+    }
+
+    /// Return the width of a pixel along x and y
+    fn steps(&self) -> (f64, f64) {
+        let cfg = &self.cfg;
+        (
+            (cfg.xdomain.end - cfg.xdomain.start) / (cfg.resolution.x - 1) as f64,
+            (cfg.ydomain.end - cfg.ydomain.start) / (cfg.resolution.y - 1) as f64,
+        )
+    }
+
+    /// Set the domains around `(cx, cy)` with square pixels of `step`
+    fn set(&mut self, (cx, cy): (f64, f64), step: f64) -> &mut Self {
+        let half = |res: usize| step * (res - 1) as f64 / 2.0;
+        let (hx, hy) = (half(self.cfg.resolution.x), half(self.cfg.resolution.y));
+        // around the origin first, so that the domains of deep views
+        // keep their width
+        self.cfg.origin = self.cfg.origin.shifted(cx, cy);
+        self.cfg.xdomain = Domain {
+            start: -hx,
+            end: hx,
+        };
+        self.cfg.ydomain = Domain {
+            start: -hy,
+            end: hy,
+        };
+        self.recenter()
+    }
+
+    /// Move the domains by `(dx, dy)`
+    fn shift(&mut self, dx: f64, dy: f64) {
+        self.cfg.xdomain.start += dx;
+        self.cfg.xdomain.end += dx;
+        self.cfg.ydomain.start += dy;
+        self.cfg.ydomain.end += dy;
+    }
+
+    /// Move the centre of the domains to `origin` if the view is deep,
+    /// or else `origin` back to the domains, see `Origin`. The view is the
+    /// same, but not the points of its domains.
+    pub fn recenter(&mut self) -> &mut Self {
+        let (cx, cy) = self.center();
+        let (stepx, stepy) = self.steps();
+        let (ox, oy) = self.cfg.origin.to_f64();
+        let magnitude = (ox + cx).abs().max((oy + cy).abs()).max(1.0);
+        if stepx.abs().min(stepy.abs()) < DEEP_STEP * magnitude {
+            if (cx, cy) != (0.0, 0.0) {
+                self.cfg.origin = self.cfg.origin.shifted(cx, cy);
+                self.shift(-cx, -cy);
+            }
+        } else if !self.cfg.origin.is_zero() {
+            self.cfg.origin = Origin::default();
+            self.shift(ox, oy);
+        }
+        self
+    }
+
+    /// `recenter()` if the domains lose precision, see `ROUNDING`
+    fn follow(&mut self) -> &mut Self {
+        let (cx, cy) = self.center();
+        let (stepx, stepy) = self.steps();
+        if cx.abs().max(cy.abs()) * f64::EPSILON > ROUNDING * stepx.abs().min(stepy.abs()) {
+            self.recenter();
+        }
+        self
+    }
+
+    /// Set the domains to `xdomain` and `ydomain` of the plane, eg, the
+    /// default ones, as they are
+    pub fn set_domains(&mut self, xdomain: Domain, ydomain: Domain) -> &mut Self {
+        self.cfg.origin = Origin::default();
+        (self.cfg.xdomain, self.cfg.ydomain) = (xdomain, ydomain);
+        self
+    }
+
+    /// Centre the view on `(cx, cy)`, with an x domain `width` wide
+    pub fn set_center(&mut self, cx: f64, cy: f64, width: f64) -> &mut Self {
+        let step = width / (self.cfg.resolution.x - 1) as f64;
+        self.set((cx, cy), step)
+    }
+
+    /// Set the domains and the rotation to `view`, with square pixels
+    pub fn set_view(&mut self, view: &View) -> &mut Self {
+        let (default_x, _) = self.cfg.kind.default_domains();
+        let width = (default_x.end - default_x.start) / view.scale;
+        self.cfg.rotation = view.rotation;
+        self.cfg.origin = Origin::default();
+        let (cx, cy) = self.cfg.to_domains(view.center_x, view.center_y);
+        self.set_center(cx, cy, width)
+    }
+
+    /// Centre the view on `(x, y)` of the plane, in high precision, eg,
+    /// a deep zoom location with more digits than `f64` holds, keeping
+    /// the width and the rotation
+    pub fn center_on(&mut self, x: Coordinate, y: Coordinate) -> &mut Self {
+        let width = self.cfg.xdomain.end - self.cfg.xdomain.start;
+        self.cfg.origin = Origin::from_plane(&x, &y, self.cfg.rotation);
+        self.set_center(0.0, 0.0, width)
+    }
+
+    /// Rotate the view by `angle` radians, counter-clockwise, around its
+    /// centre
+    pub fn rotate(&mut self, angle: f64) -> &mut Self {
+        let (x, y) = self.cfg.center();
+        self.cfg.rotation = (self.cfg.rotation + angle).rem_euclid(std::f64::consts::TAU);
+        self.center_on(x, y)
+    }
+
+    /// Widen the x or the y domain, around the centre, so that the
+    /// pixels are square. The view still shows all of the domains.
+    pub fn fit_to_aspect(&mut self) -> &mut Self {
+        let (stepx, stepy) = self.steps();
+        self.set(self.center(), stepx.max(stepy))
+    }
+
+    /// Change the resolution, keeping the centre and the x domain, eg,
+    /// when the window is resized
+    pub fn resize(&mut self, resolution: Resolution) -> &mut Self {
+        let width = self.cfg.xdomain.end - self.cfg.xdomain.start;
+        let center = self.center();
+        self.cfg.resolution = resolution;
+        self.set_center(center.0, center.1, width)
+    }
+
+    /// Scale the domains by `factor` around `(x, y)`, which stays at the
+    /// same pixel. Zooms in with `factor < 1`.
+    pub fn zoom_about_point(&mut self, x: f64, y: f64, factor: f64) -> &mut Self {
+        let scale = |d: Domain, c: f64| Domain {
+            start: c + (d.start - c) * factor,
+            end: c + (d.end - c) * factor,
+        };
+        self.cfg.xdomain = scale(self.cfg.xdomain, x);
+        self.cfg.ydomain = scale(self.cfg.ydomain, y);
+        self.follow()
+    }
+
+    /// Zoom to show the rectangle between `(x0, y0)` and `(x1, y1)`,
+    /// centred, with square pixels, so the view may be wider or taller
+    pub fn zoom_to_rect(&mut self, x0: f64, y0: f64, x1: f64, y1: f64) -> &mut Self {
+        let stepx = (x1 - x0).abs() / (self.cfg.resolution.x - 1) as f64;
+        let stepy = (y1 - y0).abs() / (self.cfg.resolution.y - 1) as f64;
+        self.set(((x0 + x1) / 2.0, (y0 + y1) / 2.0), stepx.max(stepy))
+    }
+
+    /// Pan the domains by `(dx, dy)`, rounded to whole pixels, so that
+    /// `mandel_incremental()` reuses the pixels of the previous view
+    pub fn pan(&mut self, dx: f64, dy: f64) -> &mut Self {
+        let (stepx, stepy) = self.steps();
+        let (dx, dy) = ((dx / stepx).round() * stepx, (dy / stepy).round() * stepy);
+        self.shift(dx, dy);
+        self.follow()
+    }
+
+    /// Pan by fractions of the width and height of the domains, eg,
+    /// `(0.25, 0.0)` moves the view a quarter of its width to the right
+    pub fn pan_by_fraction(&mut self, fx: f64, fy: f64) -> &mut Self {
+        let width = self.cfg.xdomain.end - self.cfg.xdomain.start;
+        let height = self.cfg.ydomain.end - self.cfg.ydomain.start;
+        self.pan(fx * width, fy * height)
+    }
+}
+
+/// Map between the positions in a window showing a view and the points
+/// of its domains, see `MandelConfig::screen_map()`.
+///
+/// Positions are in logical points from the centre of the window, y up,
+/// eg, the mouse positions of `nannou`. On HiDPI displays, a point is
+/// `scale_factor` physical pixels, and the image is drawn `resolution /
+/// scale_factor` points wide and high, centred, so that each pixel of
+/// the image is a pixel of the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenMap {
+    pub xdomain: Domain,
+    pub ydomain: Domain,
+    pub resolution: Resolution,
+    /// Physical pixels per logical point, eg, 2.0 on HiDPI displays
+    pub scale_factor: f64,
+}
+impl ScreenMap {
+    /// Return the size of the image in logical points
+    pub fn image_size(&self) -> (f64, f64) {
+        (
+            self.resolution.x as f64 / self.scale_factor,
+            self.resolution.y as f64 / self.scale_factor,
+        )
+    }
+
+    /// Return the width of a pixel along x and y
+    fn steps(&self) -> (f64, f64) {
+        (
+            (self.xdomain.end - self.xdomain.start) / (self.resolution.x - 1) as f64,
+            (self.ydomain.end - self.ydomain.start) / (self.resolution.y - 1) as f64,
+        )
+    }
+
+    /// Return the point of the domains at `position` in the window
+    pub fn to_domains(&self, [px, py]: [f64; 2]) -> (f64, f64) {
+        // physical pixels from the bottom-left corner of the image, the
+        // domains go from the centre of the first pixel
+        let sx = px * self.scale_factor + self.resolution.x as f64 / 2.0 - 0.5;
+        let sy = py * self.scale_factor + self.resolution.y as f64 / 2.0 - 0.5;
+        let (stepx, stepy) = self.steps();
+        (self.xdomain.start + sx * stepx, self.ydomain.start + sy * stepy)
+    }
+
+    /// Return the position in the window of the point `(x, y)` of the
+    /// domains, the inverse of `to_domains()`
+    pub fn to_window(&self, (x, y): (f64, f64)) -> [f64; 2] {
+        let (stepx, stepy) = self.steps();
+        let sx = (x - self.xdomain.start) / stepx;
+        let sy = (y - self.ydomain.start) / stepy;
+        [
+            (sx + 0.5 - self.resolution.x as f64 / 2.0) / self.scale_factor,
+            (sy + 0.5 - self.resolution.y as f64 / 2.0) / self.scale_factor,
+        ]
+    }
+}
@@ -0,0 +1,105 @@
+// Iteration data export /////////////////////////////////////////////
+//                       ////////////////////////////////////////////
+// Writing the escape data of an `IterationGrid` itself, rather than an
+// image, to post-process it elsewhere, eg, in Python with Numpy.
+//
+// All formats go row by row from the top-left corner of the image, like
+// `get_raw_buf()`, with every subpixel sample of anti-aliased grids.
+use std::io::{self, Read, Write};
+
+use crate::IterationGrid;
+
+/// Magic bytes of `write_binary()`, "Mandelbrot Iteration Grid"
+const BINARY_MAGIC: &[u8; 4] = b"MBIG";
+const BINARY_VERSION: u32 = 1;
+
+impl IterationGrid {
+    /// Return the indices of the pixels, from the top-left corner
+    fn indices_from_top(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.height)
+            .rev()
+            .flat_map(move |y| (0..self.width).map(move |x| y * self.width + x))
+    }
+
+    /// Write the normalised iteration counts, see `Escape::smooth`, as a
+    /// Numpy `.npy` array of `f64` of shape `(height, width)`
+    pub fn write_npy<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let mut header = format!(
+            "{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}",
+            self.height, self.width
+        );
+        // the magic, version and header length take 10 bytes, and the
+        // header is padded with spaces and a newline to 64 bytes
+        let len = 10 + header.len() + 1;
+        header.push_str(&" ".repeat(len.next_multiple_of(64) - len));
+        header.push('\n');
+        w.write_all(b"\x93NUMPY\x01\x00")?;
+        w.write_all(&(header.len() as u16).to_le_bytes())?;
+        w.write_all(header.as_bytes())?;
+        for i in self.indices_from_top() {
+            w.write_all(&self.smooth[i].to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Write one line per pixel, `row,col,iters,smooth`, after a header
+    pub fn write_csv<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "row,col,iters,smooth")?;
+        for (k, i) in self.indices_from_top().enumerate() {
+            let (row, col) = (k / self.width, k % self.width);
+            writeln!(w, "{row},{col},{},{}", self.data[i], self.smooth[i])?;
+        }
+        Ok(())
+    }
+
+    /// Write the compact binary format: the magic `MBIG`, then the
+    /// version, `width`, `height` and `max_iters` as `u32`, then the
+    /// iteration counts as `u32` and the normalised ones as `f64`, all
+    /// little endian. See `read_binary()`.
+    pub fn write_binary<W: Write>(&self, max_iters: usize, mut w: W) -> io::Result<()> {
+        w.write_all(BINARY_MAGIC)?;
+        for v in [BINARY_VERSION, self.width as u32, self.height as u32, max_iters as u32] {
+            w.write_all(&v.to_le_bytes())?;
+        }
+        for i in self.indices_from_top() {
+            w.write_all(&self.data[i].to_le_bytes())?;
+        }
+        for i in self.indices_from_top() {
+            w.write_all(&self.smooth[i].to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Read the format of `write_binary()`. Return the grid and
+    /// `max_iters`.
+    pub fn read_binary<R: Read>(mut r: R) -> io::Result<(IterationGrid, usize)> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != BINARY_MAGIC {
+            return Err(invalid("not an iteration grid"));
+        }
+        let read_u32 = |r: &mut R| -> io::Result<u32> {
+            let mut b = [0; 4];
+            r.read_exact(&mut b)?;
+            Ok(u32::from_le_bytes(b))
+        };
+        if read_u32(&mut r)? != BINARY_VERSION {
+            return Err(invalid("unsupported version"));
+        }
+        let (width, height) = (read_u32(&mut r)? as usize, read_u32(&mut r)? as usize);
+        let max_iters = read_u32(&mut r)? as usize;
+
+        let mut grid = IterationGrid::new(width, height);
+        let indices: Vec<_> = grid.indices_from_top().collect();
+        for &i in &indices {
+            grid.data[i] = read_u32(&mut r)?;
+        }
+        for &i in &indices {
+            let mut b = [0; 8];
+            r.read_exact(&mut b)?;
+            grid.smooth[i] = f64::from_le_bytes(b);
+        }
+        Ok((grid, max_iters))
+    }
+}
@@ -0,0 +1,76 @@
+// Errors ////////////////////////////////////////////////////////////
+//        ///////////////////////////////////////////////////////////
+// Errors of the public functions of the library, eg, an invalid
+// `MandelConfig` or a file that could not be saved, so that the apps
+// can report them instead of crashing.
+use std::fmt;
+
+use crate::{Domain, Resolution};
+
+#[derive(Debug)]
+pub enum MandelError {
+    /// The resolution needs at least 2 by 2 pixels
+    InvalidResolution(Resolution),
+    /// The domain along `axis`, 'x' or 'y', is empty, inverted or not finite
+    DegenerateDomain { axis: char, domain: Domain },
+    /// The bailout value must be above 1
+    InvalidThreshold(f64),
+    /// `max_iters` must be at least 1
+    InvalidMaxIters(usize),
+    /// The buffers of an `IterationGrid` do not match its size
+    InvalidGrid(&'static str),
+    /// The checkpoint can not be resumed, see `Checkpoint`
+    InvalidCheckpoint(&'static str),
+    /// The text is not a link of a view, see `ViewLink`
+    InvalidLink(&'static str),
+    Io(std::io::Error),
+    #[cfg(feature = "images")]
+    Image(image::ImageError),
+}
+
+impl fmt::Display for MandelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidResolution(res) => {
+                write!(f, "resolution {}x{} is below 2x2 pixels", res.x, res.y)
+            }
+            Self::DegenerateDomain { axis, domain } => write!(
+                f,
+                "{axis} domain ({}, {}) is empty, inverted or not finite",
+                domain.start, domain.end
+            ),
+            Self::InvalidThreshold(t) => write!(f, "threshold {t} is not above 1"),
+            Self::InvalidMaxIters(n) => write!(f, "max_iters {n} is below 1"),
+            Self::InvalidGrid(reason) => write!(f, "invalid iteration grid, {reason}"),
+            Self::InvalidCheckpoint(reason) => write!(f, "invalid checkpoint, {reason}"),
+            Self::InvalidLink(reason) => write!(f, "invalid link, {reason}"),
+            Self::Io(e) => write!(f, "{e}"),
+            #[cfg(feature = "images")]
+            Self::Image(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for MandelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            #[cfg(feature = "images")]
+            Self::Image(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for MandelError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[cfg(feature = "images")]
+impl From<image::ImageError> for MandelError {
+    fn from(e: image::ImageError) -> Self {
+        Self::Image(e)
+    }
+}
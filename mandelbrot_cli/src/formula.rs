@@ -0,0 +1,162 @@
+// Formulas //////////////////////////////////////////////////////////
+//          /////////////////////////////////////////////////////////
+// Escape-time fractals that only differ from the Mandelbrot set by the
+// iteration `z = f(z) + c`. Each formula is a zero-sized type that
+// implements `Step`, and the workers are generic over it, so they are
+// monomorphized for each formula and there is no branch per iteration.
+//
+// Powers other than 2, `z^n + c` (Multibrot), replace the squaring by
+// `Power::pow`, with the folds of the formula applied before and after.
+use crate::scalar::Scalar;
+use crate::simd::Lanes;
+
+pub(crate) trait Step: Send + Sync {
+    /// Return the next `z` from `z = (x, y)`, with `xx = x^2` and
+    /// `yy = y^2` already computed for the escape test
+    fn step<T: Scalar>(x: T, y: T, xx: T, yy: T, cx: &T, cy: &T, two: &T) -> (T, T);
+    /// Same as `step()` for the lanes of `V` at once
+    fn step_simd<V: Lanes>(x: V, y: V, xx: V, yy: V, cx: V, cy: V) -> (V, V);
+    /// Applied to `z` before raising it to a power other than 2
+    #[inline]
+    fn fold_pre<T: Scalar>(x: T, y: T) -> (T, T) {
+        (x, y)
+    }
+    /// Applied to `z^n` before adding `c`
+    #[inline]
+    fn fold_post<T: Scalar>(x: T, y: T) -> (T, T) {
+        (x, y)
+    }
+}
+
+/// Exponent of `z^n + c`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Power {
+    power: f64,
+    // `Some` for positive integer powers, which are computed exactly
+    integer: Option<u32>,
+}
+impl Power {
+    pub(crate) fn new(power: f64) -> Self {
+        let integer = (power.fract() == 0.0 && power >= 1.0 && power <= u32::MAX as f64)
+            .then_some(power as u32);
+        Self { power, integer }
+    }
+
+    /// Return `true` for the positive integer powers
+    pub(crate) fn is_integer(&self) -> bool {
+        self.integer.is_some()
+    }
+
+    /// Return `z^n`. Integer powers are exact in `T`, non-integer
+    /// powers are computed in polar form in `f64`.
+    pub(crate) fn pow<T: Scalar>(&self, x: T, y: T, precision: usize) -> (T, T) {
+        match self.integer {
+            Some(n) => {
+                // exponentiation by squaring
+                let mul = |(a, b): (T, T), (c, d): (T, T)| {
+                    (
+                        a.clone() * c.clone() - b.clone() * d.clone(),
+                        a * d + b * c,
+                    )
+                };
+                let (mut base, mut n) = ((x, y), n);
+                let mut acc: Option<(T, T)> = None;
+                loop {
+                    if n & 1 == 1 {
+                        acc = Some(match acc {
+                            None => base.clone(),
+                            Some(acc) => mul(acc, base.clone()),
+                        });
+                    }
+                    n >>= 1;
+                    if n == 0 {
+                        break;
+                    }
+                    base = mul(base.clone(), base);
+                }
+                acc.expect("power is at least 1")
+            }
+            None => {
+                let (x, y) = (x.to_f64(), y.to_f64());
+                let r = x.hypot(y).powf(self.power);
+                let theta = y.atan2(x) * self.power;
+                (
+                    T::from_f64(r * theta.cos(), precision),
+                    T::from_f64(r * theta.sin(), precision),
+                )
+            }
+        }
+    }
+
+    /// Return `n z^(n - 1)`, the derivative of `z^n`, in `f64`
+    pub(crate) fn derivative(&self, x: f64, y: f64) -> (f64, f64) {
+        let n = self.power;
+        let r = x.hypot(y).powf(n - 1.0);
+        let theta = y.atan2(x) * (n - 1.0);
+        (n * r * theta.cos(), n * r * theta.sin())
+    }
+}
+
+/// `z^2 + c`
+pub(crate) struct Mandelbrot;
+impl Step for Mandelbrot {
+    #[inline]
+    fn step<T: Scalar>(x: T, y: T, xx: T, yy: T, cx: &T, cy: &T, two: &T) -> (T, T) {
+        (xx - yy + cx.clone(), two.clone() * x * y + cy.clone())
+    }
+    #[inline]
+    fn step_simd<V: Lanes>(x: V, y: V, xx: V, yy: V, cx: V, cy: V) -> (V, V) {
+        (xx - yy + cx, V::splat(2.0) * x * y + cy)
+    }
+}
+
+/// `(|x| + i|y|)^2 + c`
+pub(crate) struct BurningShip;
+impl Step for BurningShip {
+    #[inline]
+    fn step<T: Scalar>(x: T, y: T, xx: T, yy: T, cx: &T, cy: &T, two: &T) -> (T, T) {
+        (xx - yy + cx.clone(), two.clone() * (x * y).abs() + cy.clone())
+    }
+    #[inline]
+    fn step_simd<V: Lanes>(x: V, y: V, xx: V, yy: V, cx: V, cy: V) -> (V, V) {
+        (xx - yy + cx, V::splat(2.0) * (x * y).abs() + cy)
+    }
+    #[inline]
+    fn fold_pre<T: Scalar>(x: T, y: T) -> (T, T) {
+        (x.abs(), y.abs())
+    }
+}
+
+/// `conj(z)^2 + c`
+pub(crate) struct Tricorn;
+impl Step for Tricorn {
+    #[inline]
+    fn step<T: Scalar>(x: T, y: T, xx: T, yy: T, cx: &T, cy: &T, two: &T) -> (T, T) {
+        (xx - yy + cx.clone(), cy.clone() - two.clone() * x * y)
+    }
+    #[inline]
+    fn step_simd<V: Lanes>(x: V, y: V, xx: V, yy: V, cx: V, cy: V) -> (V, V) {
+        (xx - yy + cx, cy - V::splat(2.0) * x * y)
+    }
+    #[inline]
+    fn fold_post<T: Scalar>(x: T, y: T) -> (T, T) {
+        (x, -y)
+    }
+}
+
+/// `|Re(z^2)| + i Im(z^2) + c`
+pub(crate) struct Celtic;
+impl Step for Celtic {
+    #[inline]
+    fn step<T: Scalar>(x: T, y: T, xx: T, yy: T, cx: &T, cy: &T, two: &T) -> (T, T) {
+        ((xx - yy).abs() + cx.clone(), two.clone() * x * y + cy.clone())
+    }
+    #[inline]
+    fn step_simd<V: Lanes>(x: V, y: V, xx: V, yy: V, cx: V, cy: V) -> (V, V) {
+        ((xx - yy).abs() + cx, V::splat(2.0) * x * y + cy)
+    }
+    #[inline]
+    fn fold_post<T: Scalar>(x: T, y: T) -> (T, T) {
+        (x.abs(), y)
+    }
+}
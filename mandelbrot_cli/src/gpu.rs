@@ -0,0 +1,242 @@
+// GPU ///////////////////////////////////////////////////////////////
+//     //////////////////////////////////////////////////////////////
+// Compute shader backend, one GPU invocation per pixel. WGSL has no
+// portable `f64`, so the shader iterates in `f32`, which is only good
+// for shallow zooms. `mandel_gpu()` returns `None` when `f32` is not
+// enough or when there is no GPU, and the caller falls back to the CPU.
+//
+// The device and pipeline are created on the first call and reused.
+use std::borrow::Cow;
+use std::sync::{mpsc, OnceLock};
+use wgpu::util::DeviceExt;
+
+use crate::{f32_is_enough, Escape, Formula, FractalKind, IterationGrid, MandelConfig};
+
+const SHADER: &str = r#"
+struct Params {
+    x0: f32, y0: f32, dx: f32, dy: f32,
+    cx: f32, cy: f32, threshold: f32,
+    width: u32, height: u32, max_iters: u32, julia: u32, formula: u32,
+}
+struct Escape {
+    iters: u32,
+    norm: f32,
+}
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> escapes: array<Escape>;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.width || id.y >= params.height) {
+        return;
+    }
+    let p = vec2<f32>(params.x0 + params.dx * f32(id.x), params.y0 + params.dy * f32(id.y));
+    // `z = z^2 + c`
+    var z = vec2<f32>(0.0, 0.0);
+    var c = p;
+    if (params.julia != 0u) {
+        z = p;
+        c = vec2<f32>(params.cx, params.cy);
+    }
+    var n = 0u;
+    var norm = 0.0;
+    loop {
+        norm = dot(z, z);
+        if (norm > params.threshold || n >= params.max_iters) {
+            break;
+        }
+        // the formula is uniform, so this does not diverge
+        switch params.formula {
+            // Burning Ship
+            case 1u: { z = vec2<f32>(z.x * z.x - z.y * z.y, 2.0 * abs(z.x * z.y)) + c; }
+            // Tricorn
+            case 2u: { z = vec2<f32>(z.x * z.x - z.y * z.y, -2.0 * z.x * z.y) + c; }
+            // Celtic
+            case 3u: { z = vec2<f32>(abs(z.x * z.x - z.y * z.y), 2.0 * z.x * z.y) + c; }
+            default: { z = vec2<f32>(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c; }
+        }
+        n = n + 1u;
+    }
+    escapes[id.y * params.width + id.x] = Escape(n, norm);
+}
+"#;
+const WORKGROUP_SIZE: u32 = 16;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    x0: f32,
+    y0: f32,
+    dx: f32,
+    dy: f32,
+    cx: f32,
+    cy: f32,
+    threshold: f32,
+    width: u32,
+    height: u32,
+    max_iters: u32,
+    julia: u32,
+    formula: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuEscape {
+    iters: u32,
+    norm: f32,
+}
+
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+impl Gpu {
+    fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }))?;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("mandel"),
+                features: wgpu::Features::empty(),
+                limits: adapter.limits(),
+            },
+            None,
+        ))
+        .ok()?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("mandel"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER)),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("mandel"),
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+        Some(Self {
+            device,
+            queue,
+            pipeline,
+        })
+    }
+
+    fn render(&self, params: &Params) -> Vec<GpuEscape> {
+        let npixels = (params.width * params.height) as usize;
+        let size = (npixels * std::mem::size_of::<GpuEscape>()) as wgpu::BufferAddress;
+        let uniform = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: bytemuck::bytes_of(params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let escapes = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("escapes"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("staging"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("mandel"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: escapes.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass =
+                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(
+                params.width.div_ceil(WORKGROUP_SIZE),
+                params.height.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+        encoder.copy_buffer_to_buffer(&escapes, 0, &staging, 0, size);
+        self.queue.submit(Some(encoder.finish()));
+
+        // read back the results
+        let slice = staging.slice(..);
+        let (tx, rx) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| tx.send(result).unwrap());
+        self.device.poll(wgpu::Maintain::Wait);
+        rx.recv()
+            .unwrap()
+            .expect("failed to read back the GPU buffer");
+        let result = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        staging.unmap();
+        result
+    }
+}
+
+static GPU: OnceLock<Option<Gpu>> = OnceLock::new();
+
+/// Calculate the set on the GPU. Return `None` if there is no GPU or
+/// if `f32` is not precise enough for the current zoom.
+pub(crate) fn mandel_gpu(cfg: MandelConfig) -> Option<IterationGrid> {
+    if !f32_is_enough(&cfg) {
+        return None;
+    }
+    let gpu = GPU.get_or_init(Gpu::new).as_ref()?;
+    let size = cfg.resolution.x * cfg.resolution.y * std::mem::size_of::<GpuEscape>();
+    if size as u64 > gpu.device.limits().max_storage_buffer_binding_size as u64 {
+        return None;
+    }
+
+    let (cx, cy, julia) = match cfg.kind {
+        FractalKind::Mandelbrot => (0.0, 0.0, 0),
+        FractalKind::Julia { cx, cy } => (cx as f32, cy as f32, 1),
+    };
+    let (width, height) = (cfg.resolution.x, cfg.resolution.y);
+    let (ox, oy) = cfg.origin.to_f64();
+    let params = Params {
+        x0: (ox + cfg.xdomain.start) as f32,
+        y0: (oy + cfg.ydomain.start) as f32,
+        dx: ((cfg.xdomain.end - cfg.xdomain.start) / (width - 1) as f64) as f32,
+        dy: ((cfg.ydomain.end - cfg.ydomain.start) / (height - 1) as f64) as f32,
+        cx,
+        cy,
+        threshold: cfg.threshold as f32,
+        width: width as u32,
+        height: height as u32,
+        max_iters: cfg.max_iters as u32,
+        julia,
+        formula: match cfg.formula {
+            Formula::Mandelbrot => 0,
+            Formula::BurningShip => 1,
+            Formula::Tricorn => 2,
+            Formula::Celtic => 3,
+        },
+    };
+
+    let mut grid = IterationGrid::new(width, height);
+    for (i, e) in gpu.render(&params).iter().enumerate() {
+        let escape = Escape::new(e.iters as usize, e.norm as f64, cfg.max_iters, cfg.threshold);
+        grid.set(i % width, i / width, escape);
+    }
+    Some(grid)
+}
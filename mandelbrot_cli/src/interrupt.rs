@@ -0,0 +1,44 @@
+// Interrupt /////////////////////////////////////////////////////////
+//           ////////////////////////////////////////////////////////
+// Ctrl-C handling of the CLI. The first SIGINT only sets a flag, polled
+// by the renders between tiles, bands of rows, frames or batches of
+// samples: they stop dispatching new ones, let the ones in progress
+// finish, save what is done, with the missing pixels black, and exit
+// with `EXIT_INTERRUPTED`. A second SIGINT exits at once, eg, if a
+// tile takes too long.
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Exit status of an interrupted render, 128 + SIGINT like the shells
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Install the handler of SIGINT, warning if it fails, as the renders
+/// still work, they just can not be interrupted gracefully
+pub fn install() {
+    let handler = || {
+        if INTERRUPTED.swap(true, Ordering::Relaxed) {
+            // only async-signal-safe calls here, no destructors
+            unsafe { libc::_exit(EXIT_INTERRUPTED) };
+        }
+    };
+    // Safety: the handler only touches an atomic and calls `_exit()`,
+    // which are async-signal-safe
+    if let Err(e) = unsafe { signal_hook_registry::register(libc::SIGINT, handler) } {
+        log::warn!("Ctrl-C handler not installed - {e}");
+    }
+}
+
+/// Return `true` once Ctrl-C was pressed
+pub fn requested() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// Break once Ctrl-C was pressed, to stop the renders taking callbacks
+pub fn check() -> ControlFlow<()> {
+    match requested() {
+        true => ControlFlow::Break(()),
+        false => ControlFlow::Continue(()),
+    }
+}
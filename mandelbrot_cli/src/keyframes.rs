@@ -0,0 +1,160 @@
+// Keyframes /////////////////////////////////////////////////////////
+//           ////////////////////////////////////////////////////////
+// Animation script: a list of keyframes, each a view with its palette,
+// eg, recorded while exploring in the GUI, and the frames between them
+// interpolated to render a video. The zoom is interpolated in log
+// scale, so that its speed is constant, and the centre moves to the
+// next keyframe in proportion to the width of the view, like in a
+// `ZoomSequence`, with the easing of each keyframe. `max_iters` and the
+// scale of the palette are interpolated geometrically, and the offset
+// of the palette and the rotation linearly.
+//
+// The script is saved as TOML, to be edited by hand, eg, to change the
+// number of frames between two keyframes.
+use std::error::Error;
+use std::ops::ControlFlow;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::color_schemes::ColorSchemes;
+use crate::domain::View;
+use crate::zoom::{render_frames, Easing};
+use crate::{IterationGrid, MandelConfig, MandelError, Resolution};
+
+/// Color scheme, by name, and the offset and scale of its palette, see
+/// `ColorSchemes::set_offset()` and `ColorSchemes::set_scale()`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PaletteSettings {
+    pub scheme: String,
+    pub offset: f64,
+    pub scale: f64,
+}
+impl PaletteSettings {
+    /// The palette of the current scheme of `color_schemes`
+    pub fn new(color_schemes: &ColorSchemes) -> Self {
+        Self {
+            scheme: color_schemes.name().to_string(),
+            offset: color_schemes.offset(),
+            scale: color_schemes.scale(),
+        }
+    }
+
+    /// Color with this palette. An unknown scheme is ignored.
+    pub fn apply(&self, color_schemes: &mut ColorSchemes) {
+        if color_schemes.set_by_name(&self.scheme).is_none() {
+            log::warn!("unknown color scheme \"{}\"", self.scheme);
+        }
+        color_schemes.set_offset(self.offset).set_scale(self.scale);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// View, `max_iters`, fractal... The resolution is the one of the
+    /// script.
+    pub cfg: MandelConfig,
+    pub palette: PaletteSettings,
+    /// Frames from the previous keyframe to this one, ignored for the
+    /// first one
+    pub frames: usize,
+    /// Speed profile from the previous keyframe to this one
+    pub easing: Easing,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AnimationScript {
+    /// Resolution of the frames
+    pub resolution: Resolution,
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl AnimationScript {
+    /// Return the number of frames of the animation, from the first
+    /// keyframe to the last one, both included
+    pub fn frames(&self) -> usize {
+        match self.keyframes.split_first() {
+            Some((_, rest)) => 1 + rest.iter().map(|k| k.frames.max(1)).sum::<usize>(),
+            None => 0,
+        }
+    }
+
+    /// Return the config and the palette of frame `i`, in `0..frames()`
+    pub fn frame(&self, i: usize) -> (MandelConfig, PaletteSettings) {
+        let mut start = 0;
+        for pair in self.keyframes.windows(2) {
+            let frames = pair[1].frames.max(1);
+            if i < start + frames {
+                let t = pair[1].easing.apply((i - start) as f64 / frames as f64);
+                return self.interpolate(&pair[0], &pair[1], t);
+            }
+            start += frames;
+        }
+        let last = self.keyframes.last().expect("the script has keyframes");
+        self.interpolate(last, last, 0.0)
+    }
+
+    /// Return the configs of all frames
+    pub fn configs(&self) -> Vec<MandelConfig> {
+        (0..self.frames()).map(|i| self.frame(i).0).collect()
+    }
+
+    /// Return the frame at `t` in `[0, 1]` from keyframe `a` to `b`
+    fn interpolate(&self, a: &Keyframe, b: &Keyframe, t: f64) -> (MandelConfig, PaletteSettings) {
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        let geometric = |a: f64, b: f64| a * (b / a).powf(t);
+        let (va, vb) = (a.cfg.view(), b.cfg.view());
+        let scale = geometric(va.scale, vb.scale);
+        // 1.0 at `a`, 0.0 at `b`, proportional to the width
+        let offset = if va.scale == vb.scale {
+            1.0 - t
+        } else {
+            (1.0 / scale - 1.0 / vb.scale) / (1.0 / va.scale - 1.0 / vb.scale)
+        };
+        let view = View {
+            center_x: vb.center_x + (va.center_x - vb.center_x) * offset,
+            center_y: vb.center_y + (va.center_y - vb.center_y) * offset,
+            scale,
+            rotation: lerp(va.rotation, vb.rotation),
+        };
+        let mut cfg = MandelConfig {
+            resolution: self.resolution,
+            max_iters: geometric(a.cfg.max_iters as f64, b.cfg.max_iters as f64).round() as usize,
+            ..a.cfg
+        };
+        cfg.domains().set_view(&view);
+        cfg.update_max_iters();
+        let palette = PaletteSettings {
+            scheme: a.palette.scheme.clone(),
+            offset: lerp(a.palette.offset, b.palette.offset),
+            scale: geometric(a.palette.scale, b.palette.scale),
+        };
+        (cfg, palette)
+    }
+
+    /// Render the frames in parallel, calling `callback` with the index
+    /// and the grid of each frame as it completes, in any order, like
+    /// `ZoomSequence::render()`
+    pub fn render<F>(&self, callback: F) -> Result<(), MandelError>
+    where
+        F: Fn(usize, IterationGrid) -> ControlFlow<()> + Sync,
+    {
+        render_frames(self.configs(), callback)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("the script is valid TOML")
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(s)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::from_toml(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_toml())
+    }
+}
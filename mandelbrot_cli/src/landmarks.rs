@@ -0,0 +1,121 @@
+// Landmarks /////////////////////////////////////////////////////////
+//           ////////////////////////////////////////////////////////
+// Built-in gallery of notable locations of the Mandelbrot set, eg, the
+// valleys between the bulbs, Misiurewicz points, where the set is a
+// dendrite, and minibrots, the small copies of the set. Each one comes
+// with the width of the view and the `max_iters` to show it well.
+use crate::{FractalKind, MandelConfig};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Landmark {
+    /// Short name, eg, `--landmark seahorse`
+    pub name: &'static str,
+    pub title: &'static str,
+    pub center: (f64, f64),
+    /// Width of the x domain
+    pub width: f64,
+    pub max_iters: usize,
+}
+
+pub const LANDMARKS: [Landmark; 10] = [
+    Landmark {
+        name: "full",
+        title: "The whole set",
+        center: (-0.75, 0.0),
+        width: 3.5,
+        max_iters: 128,
+    },
+    Landmark {
+        name: "seahorse",
+        title: "Seahorse Valley",
+        center: (-0.743643887037151, 0.13182590420533),
+        width: 0.01,
+        max_iters: 1000,
+    },
+    Landmark {
+        name: "seahorse-spiral",
+        title: "Spiral of a seahorse",
+        center: (-0.7453, 0.1127),
+        width: 6.5e-4,
+        max_iters: 2000,
+    },
+    Landmark {
+        name: "elephant",
+        title: "Elephant Valley",
+        center: (0.275, 0.006),
+        width: 0.02,
+        max_iters: 1000,
+    },
+    Landmark {
+        name: "scepter",
+        title: "Scepter Valley",
+        center: (-1.37, 0.0),
+        width: 0.03,
+        max_iters: 1000,
+    },
+    Landmark {
+        name: "misiurewicz-i",
+        title: "Misiurewicz point c = i, tip of a dendrite",
+        center: (0.0, 1.0),
+        width: 0.01,
+        max_iters: 1000,
+    },
+    Landmark {
+        name: "antenna-tip",
+        title: "Misiurewicz point c = -2, tip of the antenna",
+        center: (-2.0, 0.0),
+        width: 0.01,
+        max_iters: 500,
+    },
+    Landmark {
+        name: "misiurewicz-m23",
+        title: "Misiurewicz point M23,2",
+        center: (-0.77568377, 0.13646737),
+        width: 1e-5,
+        max_iters: 2000,
+    },
+    Landmark {
+        name: "minibrot-3",
+        title: "Period-3 minibrot on the antenna",
+        center: (-1.7548776662466927, 0.0),
+        width: 0.04,
+        max_iters: 500,
+    },
+    Landmark {
+        name: "minibrot",
+        title: "Deep minibrot near the antenna",
+        center: (-1.7687788, 0.0017389),
+        width: 2e-5,
+        max_iters: 4000,
+    },
+];
+
+impl Landmark {
+    /// Set the view of `cfg` to the landmark, with its `max_iters`,
+    /// keeping the resolution and the other settings
+    pub fn apply(&self, cfg: &mut MandelConfig) {
+        cfg.kind = FractalKind::Mandelbrot;
+        cfg.rotation = 0.0;
+        // the width first, which `center_on()` keeps
+        cfg.domains()
+            .set_center(0.0, 0.0, self.width)
+            .center_on(self.center.0.into(), self.center.1.into());
+        cfg.max_iters = self.max_iters;
+    }
+}
+
+impl std::str::FromStr for Landmark {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match LANDMARKS.iter().find(|l| l.name == s) {
+            Some(landmark) => Ok(*landmark),
+            None => {
+                let names: Vec<_> = LANDMARKS.iter().map(|l| l.name).collect();
+                Err(format!(
+                    "unknown landmark \"{s}\", one of {}",
+                    names.join(", ")
+                ))
+            }
+        }
+    }
+}
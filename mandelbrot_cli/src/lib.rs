@@ -1,47 +1,439 @@
 use std::fmt::Debug;
-use std::sync::{Arc, Mutex};
-use threadpool::ThreadPool;
-//use std::time::SystemTime;
-extern crate num_cpus;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+#[cfg(feature = "parallel")]
+use std::sync::Arc;
+use wide::{f32x8, f64x4};
 
+#[cfg(feature = "parallel")]
+mod aa;
+#[cfg(feature = "images")]
+pub mod animation;
+#[cfg(feature = "parallel")]
+pub mod buddhabrot;
+#[cfg(feature = "parallel")]
+pub mod cache;
+pub mod checkpoint;
 pub mod color_schemes;
 use color_schemes::ColorSchemes;
+#[cfg(feature = "parallel")]
+pub mod contact_sheet;
+pub mod domain;
+use domain::{DomainManager, ScreenMap, View};
+mod dump;
+mod error;
+pub use error::MandelError;
+pub mod scalar;
+use scalar::{BigFloat, Scalar};
+mod formula;
+use formula::{Power, Step};
+#[cfg(feature = "gpu")]
+mod gpu;
+#[cfg(feature = "parallel")]
+pub mod keyframes;
+pub mod landmarks;
+pub mod link;
+#[cfg(feature = "parallel")]
+pub mod lyapunov;
+#[cfg(feature = "parallel")]
+mod mariani;
+#[cfg(feature = "images")]
+pub mod metadata;
+#[cfg(feature = "images")]
+use metadata::ImageMetadata;
+pub mod origin;
+use origin::{Coordinate, Origin};
+#[cfg(feature = "parallel")]
+mod perturbation;
+#[cfg(feature = "parallel")]
+mod pool;
+#[cfg(feature = "parallel")]
+pub use pool::{num_threads, THREADS_ENV};
+#[cfg(feature = "parallel")]
+pub mod render_job;
+#[cfg(feature = "parallel")]
+mod renderer;
+#[cfg(feature = "parallel")]
+pub use renderer::Renderer;
+#[cfg(feature = "parallel")]
+use renderer::AxisCache;
+#[cfg(feature = "parallel")]
+use render_job::Tile;
+mod simd;
+mod span;
+use span::span;
+mod stats;
+pub use stats::RenderStats;
+#[cfg(feature = "parallel")]
+pub mod tune;
+#[cfg(feature = "parallel")]
+pub mod zoom;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Resolution {
     pub x: usize,
     pub y: usize,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Domain {
     pub start: f64,
     pub end: f64,
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Which fractal to iterate.
+///
+/// `Mandelbrot` iterates `z = z^2 + c` from `z = 0` with `c` being the
+/// pixel, whereas `Julia` starts from `z` being the pixel and keeps `c`
+/// constant.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FractalKind {
+    #[default]
+    Mandelbrot,
+    Julia { cx: f64, cy: f64 },
+}
+impl FractalKind {
+    /// Return the x and y domains that show the whole fractal
+    pub fn default_domains(&self) -> (Domain, Domain) {
+        match self {
+            FractalKind::Mandelbrot => (
+                Domain {
+                    start: -2.5,
+                    end: 1.0,
+                },
+                Domain {
+                    start: -1.0,
+                    end: 1.0,
+                },
+            ),
+            FractalKind::Julia { .. } => (
+                Domain {
+                    start: -2.0,
+                    end: 2.0,
+                },
+                Domain {
+                    start: -1.25,
+                    end: 1.25,
+                },
+            ),
+        }
+    }
+}
+
+/// Iteration formula `z = f(z) + c`, with `z = x + iy`.
+///
+/// `Mandelbrot` is `z^2`, `BurningShip` is `(|x| + i|y|)^2`, `Tricorn`
+/// (or Mandelbar) is `conj(z)^2` and `Celtic` is `|Re(z^2)| + i Im(z^2)`.
+/// Any of them gives a Julia set too, see `FractalKind`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Formula {
+    #[default]
+    Mandelbrot,
+    BurningShip,
+    Tricorn,
+    Celtic,
+}
+impl std::str::FromStr for Formula {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mandelbrot" => Ok(Formula::Mandelbrot),
+            "burning-ship" => Ok(Formula::BurningShip),
+            "tricorn" => Ok(Formula::Tricorn),
+            "celtic" => Ok(Formula::Celtic),
+            _ => Err(format!("unknown formula \"{s}\"")),
+        }
+    }
+}
+
+/// Algorithm used to iterate the pixels.
+///
+/// `Direct` iterates every pixel on its own. `Perturbation` iterates one
+/// reference orbit in high precision and only the small difference of
+/// each pixel to it in `f64`, which is much faster for deep zooms.
+/// `MarianiSilver` iterates only the borders of rectangles of the image
+/// that are not all inside the set, or in one band of iterations, which
+/// is much faster for views with large areas of the set. It runs the
+/// scalar iteration, and the direct one for auxiliary data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Engine {
+    #[default]
+    Direct,
+    Perturbation,
+    /// Rectangle subdivision, see `mariani`
+    MarianiSilver,
+}
+impl std::str::FromStr for Engine {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "direct" => Ok(Engine::Direct),
+            "perturbation" => Ok(Engine::Perturbation),
+            "mariani-silver" => Ok(Engine::MarianiSilver),
+            _ => Err(format!("unknown engine \"{s}\"")),
+        }
+    }
+}
+
+/// Where and how the iteration runs.
+///
+/// `Scalar` iterates one pixel at a time, `Simd` iterates 4 pixels of a
+/// row at once with vector instructions. `Gpu` runs a compute shader in
+/// `f32`, and falls back to `Simd` at zooms beyond `f32` or if there is
+/// no GPU. Arbitrary precision and perturbation always run scalar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ComputeBackend {
+    #[default]
+    Scalar,
+    Simd,
+    #[cfg(feature = "gpu")]
+    Gpu,
+}
+impl std::str::FromStr for ComputeBackend {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scalar" => Ok(ComputeBackend::Scalar),
+            "simd" => Ok(ComputeBackend::Simd),
+            #[cfg(feature = "gpu")]
+            "gpu" => Ok(ComputeBackend::Gpu),
+            _ => Err(format!("unknown compute backend \"{s}\"")),
+        }
+    }
+}
+
+/// Floating point type the direct iteration runs in.
+///
+/// `F32` iterates 8 pixels at once with the SIMD backend, twice as many
+/// as `F64`, and is fine at shallow zooms, with some noise near the
+/// boundary of the set at high `max_iters`. `Arbitrary` is needed when
+/// the distance between two pixels gets below the resolution of `f64`.
+/// `Auto` picks the fastest one that resolves the pixels, see
+/// `select_precision()`. Perturbation and the GPU ignore it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Precision {
+    #[default]
+    Auto,
+    F32,
+    F64,
+    Arbitrary,
+}
+impl std::str::FromStr for Precision {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Precision::Auto),
+            "f32" => Ok(Precision::F32),
+            "f64" => Ok(Precision::F64),
+            "arbitrary" => Ok(Precision::Arbitrary),
+            _ => Err(format!("unknown precision \"{s}\"")),
+        }
+    }
+}
+
+/// Orbit trap, a shape to which the minimum distance of the orbit of
+/// each pixel is recorded, see `PixelData::trap`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrbitTrap {
+    Point { x: f64, y: f64 },
+    /// Line through `(x, y)` at `angle` radians from the x axis
+    Line { x: f64, y: f64, angle: f64 },
+    Circle { x: f64, y: f64, r: f64 },
+}
+impl OrbitTrap {
+    /// Return the distance of `z = (zx, zy)` to the trap
+    pub fn distance(&self, zx: f64, zy: f64) -> f64 {
+        match *self {
+            OrbitTrap::Point { x, y } => (zx - x).hypot(zy - y),
+            OrbitTrap::Line { x, y, angle } => {
+                ((zy - y) * angle.cos() - (zx - x) * angle.sin()).abs()
+            }
+            OrbitTrap::Circle { x, y, r } => ((zx - x).hypot(zy - y) - r).abs(),
+        }
+    }
+}
+impl std::str::FromStr for OrbitTrap {
+    type Err = String;
+    /// Parse `point`, `line` or `circle`, centred at the origin. The
+    /// line is the x axis and the circle has radius 1.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "point" => Ok(OrbitTrap::Point { x: 0.0, y: 0.0 }),
+            "line" => Ok(OrbitTrap::Line {
+                x: 0.0,
+                y: 0.0,
+                angle: 0.0,
+            }),
+            "circle" => Ok(OrbitTrap::Circle {
+                x: 0.0,
+                y: 0.0,
+                r: 1.0,
+            }),
+            _ => Err(format!("unknown orbit trap \"{s}\"")),
+        }
+    }
+}
+
+/// Anti-aliasing of the image, see `MandelConfig::aa`
+///
+/// `Full` renders `samples` by `samples` subpixel samples per pixel and
+/// averages their colors. `Adaptive` renders one sample per pixel first,
+/// and then up to `max_samples` by `max_samples` samples only for the
+/// pixels whose neighbours differ by more than `tolerance` iterations,
+/// ie, on the edges, which is much faster than `Full`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AaMode {
+    #[default]
+    Off,
+    Full { samples: usize },
+    Adaptive { tolerance: f64, max_samples: usize },
+}
+impl AaMode {
+    /// Return the subpixel samples per pixel along each axis, at most
+    pub fn samples(&self) -> usize {
+        match *self {
+            AaMode::Off => 1,
+            AaMode::Full { samples } => samples.max(1),
+            AaMode::Adaptive { max_samples, .. } => max_samples.max(1),
+        }
+    }
+}
+
+/// Policy setting `max_iters` from the depth of the view, see
+/// `MandelConfig::update_max_iters()`
+///
+/// Deeper views need more iterations to show their details, so
+/// `max_iters` grows by `per_decade` for every factor of 10 the x domain
+/// is narrower than the default one, from `floor` and up to `ceiling`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoIters {
+    pub floor: usize,
+    pub ceiling: usize,
+    pub per_decade: usize,
+}
+impl Default for AutoIters {
+    fn default() -> Self {
+        Self {
+            floor: 128,
+            ceiling: 20000,
+            per_decade: 200,
+        }
+    }
+}
+impl AutoIters {
+    /// Return `max_iters` for the view of `cfg`
+    pub fn max_iters(&self, cfg: &MandelConfig) -> usize {
+        let decades = cfg.view().scale.log10().max(0.0);
+        let iters = self.floor as f64 + self.per_decade as f64 * decades;
+        (iters as usize).clamp(self.floor, self.ceiling.max(self.floor))
+    }
+}
+
+/// Parameters of one render
+///
+/// Saved to and loaded from TOML with `to_toml()` and `from_toml()`.
+/// Fields missing from the TOML take their default value.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MandelConfig {
     pub xdomain: Domain,
     pub ydomain: Domain,
+    /// Point of the domains, in high precision, their `(0, 0)` is at,
+    /// zero but for deep views, see `Origin`. Set by `domains()`.
+    #[serde(skip_serializing_if = "Origin::is_zero")]
+    pub origin: Origin,
+    /// Angle in radians, counter-clockwise, of the frame of the domains
+    /// around the origin, ie, the pixel at `(x, y)` of the domains shows
+    /// the point `to_plane(x, y)`. Panning and zooming the domains work
+    /// the same with any rotation, see `DomainManager::rotate()`.
+    /// Rotated views do not run on the GPU nor with perturbation, which
+    /// fall back to the SIMD and the direct iteration.
+    pub rotation: f64,
     pub resolution: Resolution,
+    /// Bailout value of `|z|^2`, above 1. The smooth iteration count is
+    /// more accurate with large values, eg, `65536.0`, at the cost of a
+    /// few more iterations per pixel.
     pub threshold: f64,
     pub max_iters: usize,
+    pub kind: FractalKind,
+    pub formula: Formula,
+    /// Exponent `n` of `z^n + c`, 2 for the classic formulas. Powers
+    /// other than 2 only run in the scalar direct iteration, and
+    /// non-integer powers are iterated in `f64` even at deep zooms.
+    pub power: f64,
+    pub engine: Engine,
+    pub backend: ComputeBackend,
+    pub precision: Precision,
+    /// Record the distance of the orbits to this trap. Only the scalar
+    /// `Direct` iteration collects it, so it overrides `engine` and
+    /// `backend`.
+    pub trap: Option<OrbitTrap>,
+    /// Record the exterior distance estimate of each pixel. Collected
+    /// like `trap`. The estimate assumes `Formula::Mandelbrot`.
+    pub distance: bool,
+    /// Record `|z|` and `arg(z)` at the last iteration of each pixel,
+    /// eg, for exterior angle or binary decomposition coloring.
+    /// Collected like `trap`.
+    pub final_z: bool,
+    /// Record the stripe average of the orbits, with this density, ie,
+    /// stripes per turn of `z`, eg, `5.0`. Collected like `trap`, and
+    /// smoother with a large `threshold`.
+    pub stripes: Option<f64>,
+    /// Record `z` at the last iteration of each pixel, in `f64`, so that
+    /// the pixels reaching `max_iters` can continue their iteration when
+    /// it increases, see `mandel_deeper()`, instead of starting again.
+    /// Takes 16 bytes more per pixel. Collected like `trap`, and not
+    /// continued with `distance`, `stripes` or arbitrary precision.
+    pub resumable: bool,
+    pub aa: AaMode,
+    /// Detect points inside the set early, with the analytic check of
+    /// the main cardioid and period-2 bulb, and by periodicity of the
+    /// orbits. Only the scalar and SIMD direct iterations check.
+    pub interior_check: bool,
+    /// Set `max_iters` from the depth of the view, see `AutoIters`.
+    /// Applied by `update_max_iters()`, eg, after zooming.
+    pub auto_iters: Option<AutoIters>,
+    /// Number of threads of the parallel renders, `None` or 0 for the
+    /// `MANDELBROT_THREADS` environment variable or else one per core.
+    /// The thread pools are kept for the next renders.
+    pub threads: Option<usize>,
 }
 
 impl Default for MandelConfig {
     fn default() -> Self {
+        let kind = FractalKind::default();
+        let (xdomain, ydomain) = kind.default_domains();
         Self {
-            xdomain: Domain {
-                start: -2.5,
-                end: 1.0,
-            },
-            ydomain: Domain {
-                start: -1.0,
-                end: 1.0,
-            },
+            xdomain,
+            ydomain,
+            origin: Origin::default(),
+            rotation: 0.0,
             resolution: Resolution { x: 1920, y: 1080 },
             threshold: 4.0,
             max_iters: 128,
+            kind,
+            formula: Formula::default(),
+            power: 2.0,
+            engine: Engine::default(),
+            backend: ComputeBackend::default(),
+            precision: Precision::default(),
+            trap: None,
+            distance: false,
+            final_z: false,
+            stripes: None,
+            resumable: false,
+            aa: AaMode::Off,
+            interior_check: false,
+            auto_iters: None,
+            threads: None,
         }
     }
 }
@@ -49,6 +441,611 @@ impl MandelConfig {
     pub fn new() -> Self {
         Self { ..Self::default() }
     }
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("the config is valid TOML")
+    }
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(s)
+    }
+    /// Return the operations on the domains that keep the pixels square,
+    /// eg, `cfg.domains().zoom_about_point(x, y, 0.5)`
+    pub fn domains(&mut self) -> DomainManager<'_> {
+        DomainManager::new(self)
+    }
+    /// Return the centre and zoom factor of the view, see `View`
+    pub fn view(&self) -> View {
+        let (default_x, _) = self.kind.default_domains();
+        let (center_x, center_y) = self.to_plane(
+            (self.xdomain.start + self.xdomain.end) / 2.0,
+            (self.ydomain.start + self.ydomain.end) / 2.0,
+        );
+        View {
+            center_x,
+            center_y,
+            scale: (default_x.end - default_x.start) / (self.xdomain.end - self.xdomain.start),
+            rotation: self.rotation,
+        }
+    }
+    /// Return the domains from the origin, ie, points of the plane if not
+    /// rotated, rounded to `f64`, eg, to show them
+    pub fn absolute_domains(&self) -> (Domain, Domain) {
+        let (ox, oy) = self.origin.to_f64();
+        let moved = |d: Domain, offset: f64| Domain {
+            start: offset + d.start,
+            end: offset + d.end,
+        };
+        (moved(self.xdomain, ox), moved(self.ydomain, oy))
+    }
+    /// Return the centre of the view in high precision, see `view()`
+    pub fn center(&self) -> (Coordinate, Coordinate) {
+        let center = (
+            (self.xdomain.start + self.xdomain.end) / 2.0,
+            (self.ydomain.start + self.ydomain.end) / 2.0,
+        );
+        self.origin.to_plane(center, self.rotation)
+    }
+    /// Return the point of the complex plane at `(x, y)` of the domains,
+    /// see `origin` and `rotation`
+    pub fn to_plane(&self, x: f64, y: f64) -> (f64, f64) {
+        let (ox, oy) = self.origin.to_f64();
+        let (x, y) = (ox + x, oy + y);
+        if self.rotation == 0.0 {
+            return (x, y);
+        }
+        let (sin, cos) = self.rotation.sin_cos();
+        (x * cos - y * sin, x * sin + y * cos)
+    }
+    /// Return the map between the positions in a window showing the view,
+    /// with `scale_factor` physical pixels per point, and the domains
+    pub fn screen_map(&self, scale_factor: f64) -> ScreenMap {
+        ScreenMap {
+            xdomain: self.xdomain,
+            ydomain: self.ydomain,
+            resolution: self.resolution,
+            scale_factor,
+        }
+    }
+    /// Return the point of the domains at `(x, y)` of the complex plane,
+    /// the inverse of `to_plane()`
+    pub fn to_domains(&self, x: f64, y: f64) -> (f64, f64) {
+        let (x, y) = match self.rotation {
+            0.0 => (x, y),
+            rotation => {
+                let (sin, cos) = rotation.sin_cos();
+                (x * cos + y * sin, y * cos - x * sin)
+            }
+        };
+        let (ox, oy) = self.origin.to_f64();
+        (x - ox, y - oy)
+    }
+    /// Set `max_iters` by `auto_iters`, if any, for the depth of the view
+    pub fn update_max_iters(&mut self) {
+        if let Some(auto) = self.auto_iters {
+            self.max_iters = auto.max_iters(self);
+        }
+    }
+    /// Return `true` if any auxiliary data is collected, see `PixelData`
+    pub fn collects_aux(&self) -> bool {
+        self.trap.is_some() || self.distance || self.final_z || self.stripes.is_some() || self.resumable
+    }
+    /// Check that the config can be rendered: at least 2 by 2 pixels,
+    /// finite and non-empty domains, from start to a greater end, a
+    /// threshold above 1 and at least 1 iteration
+    pub fn validate(&self) -> Result<(), MandelError> {
+        if self.resolution.x < 2 || self.resolution.y < 2 {
+            return Err(MandelError::InvalidResolution(self.resolution));
+        }
+        for (axis, domain) in [('x', self.xdomain), ('y', self.ydomain)] {
+            let width = domain.end - domain.start;
+            if !width.is_finite() || width <= 0.0 {
+                return Err(MandelError::DegenerateDomain { axis, domain });
+            }
+        }
+        if self.threshold.is_nan() || self.threshold <= 1.0 {
+            return Err(MandelError::InvalidThreshold(self.threshold));
+        }
+        if self.max_iters == 0 {
+            return Err(MandelError::InvalidMaxIters(self.max_iters));
+        }
+        Ok(())
+    }
+    /// Return the config with one pixel per subpixel sample of this
+    /// one, see `AaMode::samples()`. The samples are at the centres of
+    /// the `n` by `n` subpixels.
+    pub fn supersampled(&self) -> MandelConfig {
+        let n = self.aa.samples();
+        let mut cfg = *self;
+        let supersample = |domain: Domain, res: usize| {
+            let step = (domain.end - domain.start) / (res - 1) as f64;
+            let start = domain.start + step * (0.5 / n as f64 - 0.5);
+            Domain {
+                start,
+                end: start + step / n as f64 * (res * n - 1) as f64,
+            }
+        };
+        cfg.xdomain = supersample(self.xdomain, self.resolution.x);
+        cfg.ydomain = supersample(self.ydomain, self.resolution.y);
+        cfg.resolution = Resolution {
+            x: self.resolution.x * n,
+            y: self.resolution.y * n,
+        };
+        cfg.aa = AaMode::Off;
+        cfg
+    }
+}
+
+/// Escape data of one pixel
+///
+/// `iters` is the integer number of iterations before divergence,
+/// `smooth` is the normalised (fractional) iteration count, used to
+/// render continuous color gradients without banding. Points that did
+/// not diverge have `iters == max_iters` and `smooth == max_iters`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Escape {
+    pub iters: usize,
+    pub smooth: f64,
+}
+impl Escape {
+    /// Escape data after `iters` iterations, with `norm` being `|z|^2`
+    /// at the last iteration and `threshold` the bailout of `|z|^2`.
+    pub fn new(iters: usize, norm: f64, max_iters: usize, threshold: f64) -> Self {
+        Self::with_power(iters, norm, max_iters, threshold, 2.0)
+    }
+    /// Same as `new()`, for `z^power + c`
+    pub fn with_power(
+        iters: usize,
+        norm: f64,
+        max_iters: usize,
+        threshold: f64,
+        power: f64,
+    ) -> Self {
+        // Normalised iteration count with log-log smoothing, for the
+        // bailout radius `R`:
+        //
+        //    mu = c + 1 - log_n(ln|z| / ln R)
+        //
+        // which goes from `c` to `c + 1` as `|z|` goes from `R^n` down
+        // to `R`, so the gradients are continuous and do not shift with
+        // the threshold. Clamped so that escaped points never reach
+        // `max_iters`, which is reserved for points inside the set.
+        let smooth = if iters < max_iters {
+            let ratio = norm.ln() / threshold.ln();
+            let mu = iters as f64 + 1.0 - ratio.ln() / power.ln();
+            mu.clamp(0.0, (max_iters - 1) as f64)
+        } else {
+            max_iters as f64
+        };
+        Self { iters, smooth }
+    }
+}
+
+/// Escape data of one pixel plus the auxiliary data collected during
+/// its iteration, depending on `MandelConfig`
+#[derive(Clone, Copy, Debug)]
+pub struct PixelData {
+    pub escape: Escape,
+    /// Minimum distance of the orbit to `cfg.trap`
+    pub trap: f64,
+    /// Estimate of the distance to the set, in pixels, if `cfg.distance`.
+    /// Zero for points inside the set.
+    pub distance: f64,
+    /// `|z|` at the last iteration, if `cfg.final_z`
+    pub z_abs: f64,
+    /// `arg(z)` at the last iteration, in `-pi..=pi`, if `cfg.final_z`
+    pub z_arg: f64,
+    /// Stripe average of the orbit, from 0.0 to 1.0, if `cfg.stripes`
+    pub stripe: f64,
+    /// `z` at the last iteration, if `cfg.resumable`
+    pub zx: f64,
+    pub zy: f64,
+}
+impl Default for PixelData {
+    fn default() -> Self {
+        Self {
+            escape: Escape::default(),
+            trap: f64::INFINITY,
+            distance: 0.0,
+            z_abs: 0.0,
+            z_arg: 0.0,
+            stripe: 0.0,
+            zx: 0.0,
+            zy: 0.0,
+        }
+    }
+}
+impl PixelData {
+    /// Return the auxiliary data, in the order of the buffers of
+    /// `IterationGrid`
+    fn aux(&self) -> [f64; 7] {
+        [
+            self.trap,
+            self.distance,
+            self.z_abs,
+            self.z_arg,
+            self.stripe,
+            self.zx,
+            self.zy,
+        ]
+    }
+}
+
+/// Escape data of the whole image
+///
+/// Stored row-major in flat buffers, ie, pixel `(x, y)` is at index
+/// `y * width + x`. Row `0` is at `ydomain.start`.
+///
+/// The buffers of auxiliary data, see `PixelData`, are empty unless
+/// `MandelConfig` asks for them.
+///
+/// With anti-aliasing, see `AaMode`, the grid stores every subpixel
+/// sample, ie, `width` and `height` are `samples` times the size of the
+/// image, see `image_size()`.
+#[derive(Clone, Debug)]
+pub struct IterationGrid {
+    pub width: usize,
+    pub height: usize,
+    /// Subpixel samples per pixel along each axis
+    pub samples: usize,
+    /// Number of iterations, see `Escape::iters`
+    pub data: Vec<u32>,
+    /// Normalised iteration count, see `Escape::smooth`
+    pub smooth: Vec<f64>,
+    /// Orbit trap distance, see `PixelData::trap`
+    pub trap: Vec<f64>,
+    /// Distance estimate, see `PixelData::distance`
+    pub distance: Vec<f64>,
+    /// Final `|z|`, see `PixelData::z_abs`
+    pub z_abs: Vec<f64>,
+    /// Final `arg(z)`, see `PixelData::z_arg`
+    pub z_arg: Vec<f64>,
+    /// Stripe average, see `PixelData::stripe`
+    pub stripe: Vec<f64>,
+    /// Final `z`, see `PixelData::zx`
+    pub zx: Vec<f64>,
+    pub zy: Vec<f64>,
+}
+impl IterationGrid {
+    /// Grid of `width` by `height` pixels, initialised with zeros
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            samples: 1,
+            data: vec![0; width * height],
+            smooth: vec![0.0; width * height],
+            trap: Vec::new(),
+            distance: Vec::new(),
+            z_abs: Vec::new(),
+            z_arg: Vec::new(),
+            stripe: Vec::new(),
+            zx: Vec::new(),
+            zy: Vec::new(),
+        }
+    }
+    /// Grid for the resolution of `cfg`, with the auxiliary data it
+    /// asks for
+    pub fn for_config(cfg: &MandelConfig) -> Self {
+        let samples = cfg.aa.samples();
+        let mut grid = Self::new(cfg.resolution.x * samples, cfg.resolution.y * samples);
+        grid.samples = samples;
+        if cfg.trap.is_some() {
+            grid.trap = vec![f64::INFINITY; grid.width * grid.height];
+        }
+        if cfg.distance {
+            grid.distance = vec![0.0; grid.width * grid.height];
+        }
+        if cfg.final_z {
+            grid.z_abs = vec![0.0; grid.width * grid.height];
+            grid.z_arg = vec![0.0; grid.width * grid.height];
+        }
+        if cfg.stripes.is_some() {
+            grid.stripe = vec![0.0; grid.width * grid.height];
+        }
+        if cfg.resumable {
+            grid.zx = vec![0.0; grid.width * grid.height];
+            grid.zy = vec![0.0; grid.width * grid.height];
+        }
+        grid
+    }
+    /// Check that the buffers match the size of the grid
+    pub fn validate(&self) -> Result<(), MandelError> {
+        let n = self.width * self.height;
+        if n == 0 {
+            return Err(MandelError::InvalidGrid("no pixels"));
+        }
+        if self.samples == 0
+            || !self.width.is_multiple_of(self.samples)
+            || !self.height.is_multiple_of(self.samples)
+        {
+            return Err(MandelError::InvalidGrid("size is not a multiple of the samples"));
+        }
+        if self.data.len() != n || self.smooth.len() != n {
+            return Err(MandelError::InvalidGrid("buffers do not match the size"));
+        }
+        if self.aux().iter().any(|v| !v.is_empty() && v.len() != n) {
+            return Err(MandelError::InvalidGrid("auxiliary buffers do not match the size"));
+        }
+        Ok(())
+    }
+    /// Return the size of the image in pixels
+    pub fn image_size(&self) -> (usize, usize) {
+        (self.width / self.samples, self.height / self.samples)
+    }
+    pub fn get(&self, x: usize, y: usize) -> Escape {
+        let i = y * self.width + x;
+        Escape {
+            iters: self.data[i] as usize,
+            smooth: self.smooth[i],
+        }
+    }
+    pub fn set(&mut self, x: usize, y: usize, escape: Escape) {
+        let i = y * self.width + x;
+        self.data[i] = escape.iters as u32;
+        self.smooth[i] = escape.smooth;
+    }
+    pub fn get_pixel(&self, x: usize, y: usize) -> PixelData {
+        let i = y * self.width + x;
+        PixelData {
+            escape: self.get(x, y),
+            trap: self.trap.get(i).copied().unwrap_or(f64::INFINITY),
+            distance: self.distance.get(i).copied().unwrap_or(0.0),
+            z_abs: self.z_abs.get(i).copied().unwrap_or(0.0),
+            z_arg: self.z_arg.get(i).copied().unwrap_or(0.0),
+            stripe: self.stripe.get(i).copied().unwrap_or(0.0),
+            zx: self.zx.get(i).copied().unwrap_or(0.0),
+            zy: self.zy.get(i).copied().unwrap_or(0.0),
+        }
+    }
+    /// Set the escape data and the auxiliary data the grid stores
+    pub fn set_pixel(&mut self, x: usize, y: usize, pixel: PixelData) {
+        let i = y * self.width + x;
+        self.set(x, y, pixel.escape);
+        for (v, value) in self.aux_mut().into_iter().zip(pixel.aux()) {
+            if !v.is_empty() {
+                v[i] = value;
+            }
+        }
+    }
+    /// Copy `other` into this grid, with its top-left corner at pixel
+    /// `(x, y)` of the image
+    pub fn paste(&mut self, x: usize, y: usize, other: &IterationGrid) {
+        let (x, y) = (x * self.samples, y * self.samples);
+        for row in 0..other.height {
+            let dst = (y + row) * self.width + x;
+            let src = row * other.width;
+            self.data[dst..dst + other.width].copy_from_slice(&other.data[src..src + other.width]);
+            self.smooth[dst..dst + other.width]
+                .copy_from_slice(&other.smooth[src..src + other.width]);
+            for (v, o) in self.aux_mut().into_iter().zip(other.aux()) {
+                if !v.is_empty() && !o.is_empty() {
+                    v[dst..dst + other.width].copy_from_slice(&o[src..src + other.width]);
+                }
+            }
+        }
+    }
+    /// Return a copy of the `width` by `height` pixels of the image with
+    /// the top-left corner at pixel `(x, y)`, the reverse of `paste()`
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> IterationGrid {
+        let n = self.samples;
+        let (x, y, width, height) = (x * n, y * n, width * n, height * n);
+        // the auxiliary buffers may be empty, see `rows_mut()`
+        let copy = |v: &[_]| -> Vec<_> {
+            if v.is_empty() {
+                return Vec::new();
+            }
+            (y..y + height)
+                .flat_map(|row| &v[row * self.width + x..row * self.width + x + width])
+                .copied()
+                .collect()
+        };
+        IterationGrid {
+            width,
+            height,
+            samples: n,
+            data: (y..y + height)
+                .flat_map(|row| &self.data[row * self.width + x..row * self.width + x + width])
+                .copied()
+                .collect(),
+            smooth: copy(&self.smooth),
+            trap: copy(&self.trap),
+            distance: copy(&self.distance),
+            z_abs: copy(&self.z_abs),
+            z_arg: copy(&self.z_arg),
+            stripe: copy(&self.stripe),
+            zx: copy(&self.zx),
+            zy: copy(&self.zy),
+        }
+    }
+    /// Copy the row `src` of every buffer to the row `dst`, its mirror
+    /// image across the real axis
+    pub(crate) fn mirror_row(&mut self, src: usize, dst: usize) {
+        let (width, range) = (self.width, src * self.width..(src + 1) * self.width);
+        self.data.copy_within(range.clone(), dst * width);
+        self.smooth.copy_within(range.clone(), dst * width);
+        for aux in self.aux_mut() {
+            if !aux.is_empty() {
+                aux.copy_within(range.clone(), dst * width);
+            }
+        }
+        // the final `z` is mirrored too, and so the sine of its
+        // argument in the stripe average
+        for arg in self.z_arg.iter_mut().skip(dst * width).take(width) {
+            *arg = -*arg;
+        }
+        for zy in self.zy.iter_mut().skip(dst * width).take(width) {
+            *zy = -*zy;
+        }
+        for stripe in self.stripe.iter_mut().skip(dst * width).take(width) {
+            *stripe = 1.0 - *stripe;
+        }
+    }
+    /// Return the auxiliary buffers, in the order of `PixelData::aux()`
+    fn aux(&self) -> [&Vec<f64>; 7] {
+        [
+            &self.trap,
+            &self.distance,
+            &self.z_abs,
+            &self.z_arg,
+            &self.stripe,
+            &self.zx,
+            &self.zy,
+        ]
+    }
+    fn aux_mut(&mut self) -> [&mut Vec<f64>; 7] {
+        [
+            &mut self.trap,
+            &mut self.distance,
+            &mut self.z_abs,
+            &mut self.z_arg,
+            &mut self.stripe,
+            &mut self.zx,
+            &mut self.zy,
+        ]
+    }
+    /// Return the rows of the grid, eg, to be processed in parallel
+    pub(crate) fn rows_mut(&mut self) -> Vec<RowMut<'_>> {
+        // the auxiliary buffers may be empty, in which case their rows
+        // are empty too
+        fn aux_rows(v: &mut [f64], width: usize, height: usize) -> Vec<&mut [f64]> {
+            let mut rows: Vec<_> = v.chunks_mut(width).collect();
+            rows.resize_with(height, Default::default);
+            rows
+        }
+        let (width, height) = (self.width, self.height);
+        // not `aux_mut()`, which borrows the whole grid
+        let mut aux = [
+            &mut self.trap,
+            &mut self.distance,
+            &mut self.z_abs,
+            &mut self.z_arg,
+            &mut self.stripe,
+            &mut self.zx,
+            &mut self.zy,
+        ]
+        .map(|v| aux_rows(v, width, height).into_iter());
+        self.data
+            .chunks_mut(width)
+            .zip(self.smooth.chunks_mut(width))
+            .map(|(iters, smooth)| RowMut {
+                iters,
+                smooth,
+                aux: aux.each_mut().map(|rows| rows.next().expect("one row per row")),
+            })
+            .collect()
+    }
+}
+
+impl Default for IterationGrid {
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+/// Mutable view of one row of an `IterationGrid`
+pub(crate) struct RowMut<'a> {
+    iters: &'a mut [u32],
+    smooth: &'a mut [f64],
+    // in the order of `PixelData::aux()`, empty if the grid does not
+    // store them
+    aux: [&'a mut [f64]; 7],
+}
+impl RowMut<'_> {
+    /// Return the number of pixels of the row
+    pub(crate) fn len(&self) -> usize {
+        self.iters.len()
+    }
+    /// Return the pixel `x` as a row of 1 pixel
+    pub(crate) fn pixel_mut(&mut self, x: usize) -> RowMut<'_> {
+        fn aux<'a>(v: &'a mut &mut [f64], x: usize) -> &'a mut [f64] {
+            if v.is_empty() {
+                &mut []
+            } else {
+                &mut v[x..x + 1]
+            }
+        }
+        RowMut {
+            iters: &mut self.iters[x..x + 1],
+            smooth: &mut self.smooth[x..x + 1],
+            aux: self.aux.each_mut().map(|v| aux(v, x)),
+        }
+    }
+    pub(crate) fn set(&mut self, x: usize, escape: Escape) {
+        self.iters[x] = escape.iters as u32;
+        self.smooth[x] = escape.smooth;
+    }
+    /// Return the iterations, the last `z` and the trap distance of the
+    /// pixel `x`, if the grid stores `z` and the pixel was iterated, see
+    /// `MandelConfig::resumable`
+    pub(crate) fn resume_from(&self, x: usize) -> Option<(usize, (f64, f64), f64)> {
+        let [trap, .., zx, zy] = &self.aux;
+        let trap = trap.get(x).copied().unwrap_or(f64::INFINITY);
+        (!zx.is_empty() && self.iters[x] > 0).then(|| (self.iters[x] as usize, (zx[x], zy[x]), trap))
+    }
+    /// Set the escape data and the auxiliary data the grid stores
+    pub(crate) fn set_pixel(&mut self, x: usize, pixel: PixelData) {
+        self.set(x, pixel.escape);
+        for (v, value) in self.aux.iter_mut().zip(pixel.aux()) {
+            if !v.is_empty() {
+                v[x] = value;
+            }
+        }
+    }
+}
+
+/// Return `true` if `c = (x, y)` is in the main cardioid or in the
+/// period-2 bulb of the Mandelbrot set, ie, inside the set
+pub(crate) fn in_main_bulbs(x: f64, y: f64) -> bool {
+    let yy = y * y;
+    let q = (x - 0.25) * (x - 0.25) + yy;
+    q * (q + x - 0.25) <= 0.25 * yy || (x + 1.0) * (x + 1.0) + yy <= 0.0625
+}
+
+/// Return `true` if `in_main_bulbs()` applies to `cfg`. Not with orbit
+/// traps, which color the inside of the set too.
+pub(crate) fn checks_main_bulbs(cfg: &MandelConfig) -> bool {
+    cfg.interior_check
+        && cfg.trap.is_none()
+        && cfg.kind == FractalKind::Mandelbrot
+        && cfg.formula == Formula::Mandelbrot
+        && cfg.power == 2.0
+}
+
+/// Return the distance below which an orbit coming back to a point it
+/// visited is taken as periodic, ie, inside the set. A fraction of the
+/// pixel size, since the orbits of pixels just outside the set get
+/// close to periodic.
+pub(crate) fn periodicity_epsilon(cfg: &MandelConfig) -> f64 {
+    (pixel_step(cfg).0 * 1e-3).min(1e-10)
+}
+
+/// Running stripe average of an orbit, see `MandelConfig::stripes`
+#[derive(Default)]
+struct StripeAverage {
+    sum: f64,
+    // the sum without the last term
+    last: f64,
+    n: usize,
+}
+impl StripeAverage {
+    /// Add the term of `z = (x, y)`, in `0.0..=1.0`
+    fn add(&mut self, density: f64, x: f64, y: f64) {
+        self.last = self.sum;
+        self.sum += 0.5 * (density * y.atan2(x)).sin() + 0.5;
+        self.n += 1;
+    }
+    /// Return the average, interpolated between the one without and
+    /// with the last term by the fractional iteration count `frac`, so
+    /// it is continuous across the bands of the escape time
+    fn value(&self, frac: f64) -> f64 {
+        match self.n {
+            0 => 0.5,
+            1 => self.sum,
+            n => {
+                let (avg, prev) = (self.sum / n as f64, self.last / (n - 1) as f64);
+                prev + frac.clamp(0.0, 1.0) * (avg - prev)
+            }
+        }
+    }
 }
 
 /// Process one horizontal row of the domain
@@ -62,144 +1059,1078 @@ impl MandelConfig {
 //           / .... ---> ..... | res
 //   thread1 | .... ---> ..... |
 //           \ .... ---> ..... v
+//
+// `SQUARE` is `true` for `cfg.power == 2`, which takes the faster
+// `F::step`.
+fn mandel_worker<T: Scalar, F: Step, const SQUARE: bool>(
+    mut row: RowMut,
+    y0: &T,
+    xdomain: &[T],
+    cfg: &MandelConfig,
+    precision: usize,
+) {
+    let (max_iters, threshold) = (cfg.max_iters, cfg.threshold);
+    let zero = T::from_f64(0.0, precision);
+    let two = T::from_f64(2.0, precision);
+    let power = Power::new(cfg.power);
+    // pixel size, to get the distance estimate in pixels
+    let step = pixel_step(cfg).0;
+    let julia_c = match cfg.kind {
+        FractalKind::Mandelbrot => None,
+        FractalKind::Julia { cx, cy } => {
+            Some((T::from_f64(cx, precision), T::from_f64(cy, precision)))
+        }
+    };
+    let main_bulbs = checks_main_bulbs(cfg);
+    let epsilon = periodicity_epsilon(cfg);
+    // the orbits are continued exactly from a `z` in `f64`, and only
+    // with the data kept of the iterations before
+    let resumes = cfg.resumable
+        && !cfg.distance
+        && cfg.stripes.is_none()
+        && precision <= f64::MANTISSA_DIGITS as usize;
+    debug_assert_eq!(xdomain.len(), row.len());
+    for (px, x0) in xdomain.iter().enumerate() {
+        if main_bulbs && in_main_bulbs(x0.to_f64(), y0.to_f64()) {
+            let pixel = PixelData {
+                escape: Escape::new(max_iters, 0.0, max_iters, threshold),
+                ..Default::default()
+            };
+            row.set_pixel(px, pixel);
+            continue;
+        }
+        // `(x1, y1)` is `z` and `(cx, cy)` is `c` in `z = z^2 + c`
+        let (mut x1, mut y1, cx, cy) = match &julia_c {
+            None => (zero.clone(), zero.clone(), x0.clone(), y0.clone()),
+            Some((cx, cy)) => (x0.clone(), y0.clone(), cx.clone(), cy.clone()),
+        };
+        let mut c = 0;
+        let mut pixel = PixelData::default();
+        // a pixel iterated before up to a lower `max_iters`, without
+        // escaping, continues from its last `z`
+        let resumed = row.resume_from(px).filter(|&(iters, (zx, zy), _)| {
+            resumes && iters < max_iters && zx * zx + zy * zy <= threshold
+        });
+        if let Some((iters, (zx, zy), trap)) = resumed {
+            (x1, y1) = (T::from_f64(zx, precision), T::from_f64(zy, precision));
+            (c, pixel.trap) = (iters, trap);
+        }
+        // derivative `dz/dc`, or `dz/dz0` for the Julia set
+        let (mut dx, mut dy) = if julia_c.is_some() { (1.0, 0.0) } else { (0.0, 0.0) };
+        // periodicity check (Brent), `z` is saved at iterations 1, 2,
+        // 4, 8, ... and compared to the following ones
+        let (mut saved, mut checkpoint) = ((f64::NAN, f64::NAN), (c + 1).next_power_of_two());
+        let mut stripes = StripeAverage::default();
+        // |z|^2 at the last iteration
+        let norm = loop {
+            let (xx, yy) = (x1.clone() * x1.clone(), y1.clone() * y1.clone());
+            let norm = (xx.clone() + yy.clone()).to_f64();
+            if norm > threshold || c >= max_iters {
+                break norm;
+            }
+            if cfg.distance {
+                // `dz' = n z^(n-1) dz + 1`, without the `+ 1` for the
+                // Julia set
+                let (zx, zy) = (x1.to_f64(), y1.to_f64());
+                let (ax, ay) = if SQUARE {
+                    (2.0 * zx, 2.0 * zy)
+                } else {
+                    power.derivative(zx, zy)
+                };
+                let dxtmp = ax * dx - ay * dy + if julia_c.is_some() { 0.0 } else { 1.0 };
+                dy = ax * dy + ay * dx;
+                dx = dxtmp;
+            }
+            (x1, y1) = if SQUARE {
+                F::step(x1, y1, xx, yy, &cx, &cy, &two)
+            } else {
+                let (x, y) = F::fold_pre(x1, y1);
+                let (x, y) = power.pow(x, y, precision);
+                let (x, y) = F::fold_post(x, y);
+                (x + cx.clone(), y + cy.clone())
+            };
+            c += 1;
+            if let Some(trap) = &cfg.trap {
+                pixel.trap = pixel.trap.min(trap.distance(x1.to_f64(), y1.to_f64()));
+            }
+            // the first `z` is `c`, which would only add the angle of the
+            // pixel
+            if let Some(density) = cfg.stripes.filter(|_| c > 1) {
+                stripes.add(density, x1.to_f64(), y1.to_f64());
+            }
+            if cfg.interior_check {
+                let z = (x1.to_f64(), y1.to_f64());
+                if (z.0 - saved.0).abs() < epsilon && (z.1 - saved.1).abs() < epsilon {
+                    c = max_iters;
+                    break norm;
+                }
+                if c == checkpoint {
+                    saved = z;
+                    checkpoint *= 2;
+                }
+            }
+        };
+        pixel.escape = Escape::with_power(c, norm, max_iters, threshold, cfg.power);
+        if cfg.distance && c < max_iters {
+            // `|z| ln|z| / |dz|`
+            let z = norm.sqrt();
+            pixel.distance = z * z.ln() / dx.hypot(dy) / step;
+        }
+        if cfg.final_z {
+            let (zx, zy) = (x1.to_f64(), y1.to_f64());
+            (pixel.z_abs, pixel.z_arg) = (zx.hypot(zy), zy.atan2(zx));
+        }
+        if cfg.stripes.is_some() {
+            pixel.stripe = stripes.value(pixel.escape.smooth - c as f64);
+        }
+        if cfg.resumable {
+            (pixel.zx, pixel.zy) = (x1.to_f64(), y1.to_f64());
+        }
+        row.set_pixel(px, pixel);
+    }
+}
+
+/// Return `mandel_worker` for `formula` and `power`
+fn scalar_worker<T: Scalar>(formula: Formula, power: f64) -> Worker<T> {
+    fn pick<T: Scalar, F: Step>(power: f64) -> Worker<T> {
+        if power == 2.0 {
+            mandel_worker::<T, F, true>
+        } else {
+            mandel_worker::<T, F, false>
+        }
+    }
+    match formula {
+        Formula::Mandelbrot => pick::<T, formula::Mandelbrot>(power),
+        Formula::BurningShip => pick::<T, formula::BurningShip>(power),
+        Formula::Tricorn => pick::<T, formula::Tricorn>(power),
+        Formula::Celtic => pick::<T, formula::Celtic>(power),
+    }
+}
 
-fn mandel_worker(
-    iters_row: &mut Vec<usize>,
-    y0: f64,
-    xdomain: &Vec<f64>,
-    xres: usize,
+/// Return the distance between two pixels, and the magnitude of the
+/// coordinates of the domain, from the origin, at least `1.0`.
+pub(crate) fn pixel_step(cfg: &MandelConfig) -> (f64, f64) {
+    let stepx = (cfg.xdomain.end - cfg.xdomain.start).abs() / (cfg.resolution.x - 1) as f64;
+    let stepy = (cfg.ydomain.end - cfg.ydomain.start).abs() / (cfg.resolution.y - 1) as f64;
+    let (ox, oy) = cfg.origin.to_f64();
+    let magnitude = [
+        ox + cfg.xdomain.start,
+        ox + cfg.xdomain.end,
+        oy + cfg.ydomain.start,
+        oy + cfg.ydomain.end,
+    ]
+    .iter()
+    .fold(1.0_f64, |m, v| m.max(v.abs()));
+    (stepx.min(stepy), magnitude)
+}
+
+/// Return the distance between two pixels in the complex plane
+pub fn pixel_size(cfg: &MandelConfig) -> f64 {
+    pixel_step(cfg).0
+}
+
+/// Return the number of bits of precision required to resolve the
+/// distance between two pixels, or `None` if `f64` is good enough.
+pub fn required_precision(cfg: &MandelConfig) -> Option<usize> {
+    let (step, magnitude) = pixel_step(cfg);
+    // A few ulps per pixel are needed since rounding errors grow
+    // along the iterations
+    if step > 4.0 * magnitude * f64::EPSILON {
+        None
+    } else {
+        // bits to resolve one pixel plus guard bits for error growth
+        Some((magnitude / step).log2().ceil() as usize + 32)
+    }
+}
+
+/// Return `true` if `f32` resolves the distance between two pixels
+pub(crate) fn f32_is_enough(cfg: &MandelConfig) -> bool {
+    let (step, magnitude) = pixel_step(cfg);
+    step > 4.0 * magnitude * f32::EPSILON as f64
+}
+
+/// Return the precision the direct iteration of `cfg` runs in, never
+/// `Precision::Auto`. For `Auto`, the lowest of `f32`, `f64` and
+/// arbitrary precision that resolves the distance between two pixels,
+/// with `f32` only on the SIMD backend, the only one it speeds up.
+pub fn select_precision(cfg: &MandelConfig) -> Precision {
+    // see `mandel()`
+    let simd = cfg.backend == ComputeBackend::Simd && !cfg.collects_aux() && cfg.power == 2.0;
+    match cfg.precision {
+        Precision::Auto if simd && f32_is_enough(cfg) => Precision::F32,
+        Precision::Auto if required_precision(cfg).is_none() => Precision::F64,
+        Precision::Auto => Precision::Arbitrary,
+        precision => precision,
+    }
+}
+
+/// Return the number of bits of the arbitrary-precision floats for
+/// `cfg`, see `required_precision()`, with the guard bits above `f64`
+/// even if it is good enough
+fn arbitrary_bits(cfg: &MandelConfig) -> usize {
+    required_precision(cfg).unwrap_or(f64::MANTISSA_DIGITS as usize + 32)
+}
+
+/// Return the orbit of `z = z^2 + c` from `z = 0`, ie, the points `z`
+/// goes through, until `|z|^2 > threshold` or for `max_iters`
+/// iterations. The last point is the one that escaped, if any.
+pub fn orbit(c: (f64, f64), max_iters: usize, threshold: f64) -> Vec<(f64, f64)> {
+    orbit_from((0.0, 0.0), c, max_iters, threshold)
+}
+
+/// Same as `orbit()` from `z = z0`, eg, for the Julia set of `c`
+pub fn orbit_from(
+    z0: (f64, f64),
+    c: (f64, f64),
     max_iters: usize,
     threshold: f64,
+) -> Vec<(f64, f64)> {
+    let (mut x, mut y) = z0;
+    let mut points = vec![z0];
+    for _ in 0..max_iters {
+        if x * x + y * y > threshold {
+            break;
+        }
+        (x, y) = (x * x - y * y + c.0, 2.0 * x * y + c.1);
+        points.push((x, y));
+    }
+    points
+}
+
+/// Return `true` if the view of `cfg` is symmetric across the real
+/// axis, ie, the orbit of the conjugate of a point is the conjugate of
+/// its orbit, and so escapes the same
+fn is_conjugate_symmetric(cfg: &MandelConfig) -> bool {
+    let kind = match cfg.kind {
+        FractalKind::Mandelbrot => true,
+        FractalKind::Julia { cy, .. } => cy == 0.0,
+    };
+    let trap = match cfg.trap {
+        None => true,
+        Some(OrbitTrap::Point { y, .. } | OrbitTrap::Circle { y, .. }) => y == 0.0,
+        Some(OrbitTrap::Line { y, angle, .. }) => y == 0.0 && angle == 0.0,
+    };
+    // non-integer powers go through `atan2()`, whose branch cut is on
+    // the negative real axis
+    kind && trap
+        && cfg.formula != Formula::BurningShip
+        && Power::new(cfg.power).is_integer()
+        && cfg.rotation == 0.0
+}
+
+/// Return, for each row at the points `ydomain` of `cfg`, the row it is
+/// the mirror image of across the real axis, if any, ie, whose point is
+/// the opposite of its own within a millionth of a pixel
+fn mirrored_rows<T: Scalar>(cfg: &MandelConfig, ydomain: &[T]) -> Vec<Option<usize>> {
+    let n = ydomain.len();
+    let mut mirrors = vec![None; n];
+    if n < 2 || !is_conjugate_symmetric(cfg) {
+        return mirrors;
+    }
+    // row `i` is at `start + i * step`, and the opposite point at row
+    // `-2 * start / step - i`
+    let Domain { start, end } = cfg.ydomain;
+    let step = (end - start) / (n - 1) as f64;
+    let sum = -2.0 * (cfg.origin.y.to_f64() + start) / step;
+    // the mirror of a row far off the real axis is off the image
+    if !sum.is_finite() || sum.abs() > 2.0 * n as f64 || (sum - sum.round()).abs() > 1e-6 {
+        return mirrors;
+    }
+    let sum = sum.round() as isize;
+    for i in 0..n {
+        let j = sum - i as isize;
+        if j > i as isize && j < n as isize {
+            let gap = (ydomain[j as usize].clone() + ydomain[i].clone()).to_f64();
+            if gap.abs() <= 1e-6 * step.abs() {
+                mirrors[j as usize] = Some(i);
+            }
+        }
+    }
+    mirrors
+}
+
+/// Discretise `domain`, from `origin`, into `n` equally spaced points
+pub(crate) fn linspace<T: Scalar>(origin: Coordinate, domain: Domain, n: usize, precision: usize) -> Vec<T> {
+    let origin = origin.to_scalar::<T>(precision);
+    let start = origin.clone() + T::from_f64(domain.start, precision);
+    let end = origin + T::from_f64(domain.end, precision);
+    let step = (end - start.clone()) / T::from_f64((n - 1) as f64, precision);
+    (0..n)
+        .map(|i| start.clone() + step.clone() * T::from_f64(i as f64, precision))
+        .collect()
+}
+
+/// Calculate the Mandelbrot set, or the Julia set depending on
+/// `cfg.kind`.
+///
+/// Iterates in the precision given by `select_precision()`, unless
+/// `cfg.engine` asks for perturbation. The rows mirroring others across
+/// the real axis, eg, half of the rows of views centred on it, are
+/// copied instead of iterated.
+///
+/// Return an error if `cfg` is not valid, see `MandelConfig::validate()`.
+#[cfg(feature = "parallel")]
+pub fn mandel(cfg: MandelConfig) -> Result<IterationGrid, MandelError> {
+    cfg.validate()?;
+    Ok(mandel_unchecked(cfg))
+}
+
+/// Same as `mandel()`, for configs known to be valid, eg, the tiles of a
+/// valid config
+#[cfg(feature = "parallel")]
+pub(crate) fn mandel_unchecked(cfg: MandelConfig) -> IterationGrid {
+    pool::install(&cfg, || render_unchecked(cfg, None))
+}
+
+/// `mandel_unchecked()` in the current thread pool, with the axes of
+/// the domains from `axes`, if any
+#[cfg(feature = "parallel")]
+pub(crate) fn render_unchecked(cfg: MandelConfig, axes: Option<&AxisCache>) -> IterationGrid {
+    render_timed(cfg, axes).0
+}
+
+/// Same as `render_unchecked()`, also returning the time of the setup
+/// of the domains and of the iteration, and the iterations
+#[cfg(feature = "parallel")]
+pub(crate) fn render_timed(cfg: MandelConfig, axes: Option<&AxisCache>) -> (IterationGrid, RenderStats) {
+    let _span = span!(log::Level::Trace, "render {}x{}", cfg.resolution.x, cfg.resolution.y);
+    let started = std::time::Instant::now();
+    let (grid, setup) = render_stages(cfg, axes);
+    let stats = RenderStats {
+        setup,
+        iterate: started.elapsed().saturating_sub(setup),
+        iterations: grid.data.iter().map(|&n| n as u64).sum(),
+        ..Default::default()
+    };
+    log::trace!(
+        "{}x{} rendered - setup {:?}, iteration {:?}, {:.1} Mit/s",
+        cfg.resolution.x,
+        cfg.resolution.y,
+        stats.setup,
+        stats.iterate,
+        stats.iterations_per_second() / 1e6
+    );
+    (grid, stats)
+}
+
+/// Render `cfg` with the engine and backend it asks for. Return the grid
+/// and the time of the setup of the domains, zero for the engines which
+/// do not set them up first.
+#[cfg(feature = "parallel")]
+fn render_stages(cfg: MandelConfig, axes: Option<&AxisCache>) -> (IterationGrid, std::time::Duration) {
+    let untimed = |grid| (grid, std::time::Duration::ZERO);
+    match cfg.aa {
+        AaMode::Full { samples } if samples > 1 => {
+            let mut grid = mandel_unchecked(cfg.supersampled());
+            grid.samples = samples;
+            return untimed(grid);
+        }
+        AaMode::Adaptive { tolerance, max_samples } if max_samples > 1 => {
+            return untimed(aa::mandel_adaptive(cfg, tolerance, max_samples));
+        }
+        _ => (),
+    }
+    // Auxiliary data and powers other than 2 are only implemented in
+    // the scalar direct iteration
+    let scalar_only = cfg.collects_aux() || cfg.power != 2.0;
+    let backend = match cfg.backend {
+        _ if scalar_only => ComputeBackend::Scalar,
+        // nor rotations on the GPU
+        #[cfg(feature = "gpu")]
+        ComputeBackend::Gpu if cfg.rotation != 0.0 => ComputeBackend::Simd,
+        backend => backend,
+    };
+    // Perturbation is only implemented for `z^2 + c`, without rotation
+    if cfg.engine == Engine::Perturbation
+        && cfg.formula == Formula::Mandelbrot
+        && !scalar_only
+        && cfg.rotation == 0.0
+    {
+        return untimed(perturbation::mandel_perturbation(cfg));
+    }
+    // nor can rectangle subdivision fill in the auxiliary data
+    if cfg.engine == Engine::MarianiSilver && !cfg.collects_aux() {
+        return untimed(mariani::mandel_mariani_silver(cfg));
+    }
+    let (f32_bits, f64_bits) = (f32::MANTISSA_DIGITS as usize, f64::MANTISSA_DIGITS as usize);
+    let (formula, power) = (cfg.formula, cfg.power);
+    let precision = select_precision(&cfg);
+    log::trace!("{:?} iteration, {backend:?} backend, {precision:?} precision", cfg.engine);
+    match (precision, backend) {
+        (Precision::F32, ComputeBackend::Scalar) => {
+            mandel_generic(cfg, f32_bits, scalar_worker::<f32>(formula, power), axes)
+        }
+        (Precision::F32, ComputeBackend::Simd) => {
+            mandel_generic(cfg, f32_bits, simd::simd_worker::<f32x8>(formula), axes)
+        }
+        (Precision::F64, ComputeBackend::Scalar) => {
+            mandel_generic(cfg, f64_bits, scalar_worker::<f64>(formula, power), axes)
+        }
+        (Precision::F64, ComputeBackend::Simd) => {
+            mandel_generic(cfg, f64_bits, simd::simd_worker::<f64x4>(formula), axes)
+        }
+        #[cfg(feature = "gpu")]
+        (Precision::F32 | Precision::F64, ComputeBackend::Gpu) => {
+            match gpu::mandel_gpu(cfg) {
+                Some(grid) => untimed(grid),
+                None => {
+                    log::debug!("no GPU for the view, rendering with SIMD");
+                    mandel_generic(cfg, f64_bits, simd::simd_worker::<f64x4>(formula), axes)
+                }
+            }
+        }
+        _ => {
+            let worker = scalar_worker::<BigFloat>(formula, power);
+            mandel_generic(cfg, arbitrary_bits(&cfg), worker, axes)
+        }
+    }
+}
+
+/// Render `cfg` progressively, at 1/8, 1/4, 1/2 and then the full
+/// resolution, calling `callback` with each grid and the divisor of
+/// the resolution it was rendered at. The coarse grids cover the same
+/// domain with fewer pixels, so they can be upscaled as a preview, and
+/// are not anti-aliased.
+///
+/// Return the full resolution grid.
+#[cfg(feature = "parallel")]
+pub fn mandel_progressive<F>(cfg: MandelConfig, mut callback: F) -> Result<IterationGrid, MandelError>
+where
+    F: FnMut(&IterationGrid, usize),
+{
+    cfg.validate()?;
+    for divisor in [8, 4, 2] {
+        let mut coarse = cfg;
+        coarse.aa = AaMode::Off;
+        coarse.resolution.x = (cfg.resolution.x / divisor).max(2);
+        coarse.resolution.y = (cfg.resolution.y / divisor).max(2);
+        callback(&mandel_unchecked(coarse), divisor);
+    }
+    let grid = mandel_unchecked(cfg);
+    callback(&grid, 1);
+    Ok(grid)
+}
+
+/// Render `cfg` at `resolution` with low latency, eg, a small preview
+/// updated live as the mouse moves. Iterates in `f64`, with the SIMD
+/// backend for `z^2 + c`, without anti-aliasing nor auxiliary data,
+/// whatever `cfg` asks for, so deep zooms come out blurred.
+#[cfg(feature = "parallel")]
+pub fn mandel_preview(cfg: MandelConfig, resolution: Resolution) -> Result<IterationGrid, MandelError> {
+    let preview = preview_config(cfg, resolution);
+    preview.validate()?;
+    Ok(pool::install(&preview, || render_preview(preview, None)))
+}
+
+/// Return the config of the preview of `cfg`, see `mandel_preview()`
+#[cfg(feature = "parallel")]
+pub(crate) fn preview_config(cfg: MandelConfig, resolution: Resolution) -> MandelConfig {
+    MandelConfig {
+        resolution,
+        aa: AaMode::Off,
+        trap: None,
+        distance: false,
+        final_z: false,
+        stripes: None,
+        resumable: false,
+        ..cfg
+    }
+}
+
+/// `mandel_preview()` of a valid `preview_config()`, in the current
+/// thread pool
+#[cfg(feature = "parallel")]
+pub(crate) fn render_preview(preview: MandelConfig, axes: Option<&AxisCache>) -> IterationGrid {
+    let worker = if preview.power == 2.0 {
+        simd::simd_worker::<f64x4>(preview.formula)
+    } else {
+        scalar_worker::<f64>(preview.formula, preview.power)
+    };
+    mandel_generic(preview, f64::MANTISSA_DIGITS as usize, worker, axes).0
+}
+
+/// Calculate the set in horizontal bands of about `band_height` rows,
+/// calling `callback` with the index of the first row of each band and
+/// its grid. The bands go from the top of the image down, ie, from
+/// `ydomain.end`, so that a caller can encode the image incrementally,
+/// eg, with `get_rgb_buf()` of each band. Only one band is held in
+/// memory at a time, for images too large for `mandel()`. The bands
+/// left are not rendered once `callback` breaks, eg, on Ctrl-C.
+#[cfg(feature = "parallel")]
+pub fn mandel_streaming<F>(cfg: MandelConfig, band_height: usize, mut callback: F) -> Result<(), MandelError>
+where
+    F: FnMut(usize, &IterationGrid) -> std::ops::ControlFlow<()>,
+{
+    cfg.validate()?;
+    for (y, height) in render_job::split(cfg.resolution.y, band_height).into_iter().rev() {
+        let band = Tile {
+            x: 0,
+            y,
+            width: cfg.resolution.x,
+            height,
+        };
+        if callback(y, &mandel_unchecked(band.config(&cfg))).is_break() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Return the shift of `cfg` from `prev`, in whole pixels, if `cfg` is
+/// `prev` panned by a whole number of pixels
+pub fn pixel_shift(prev: &MandelConfig, cfg: &MandelConfig) -> Option<(isize, isize)> {
+    let mut panned = *prev;
+    (panned.xdomain, panned.ydomain, panned.origin) = (cfg.xdomain, cfg.ydomain, cfg.origin);
+    if panned != *cfg {
+        return None;
+    }
+    // the domains of `cfg` from the origin of `prev`
+    let (ox, oy) = cfg.origin.offset_from(&prev.origin);
+    let moved = |d: Domain, offset: f64| Domain {
+        start: d.start + offset,
+        end: d.end + offset,
+    };
+    let shift = |prev: Domain, domain: Domain, res: usize| {
+        let step = (prev.end - prev.start) / (res - 1) as f64;
+        let new_step = (domain.end - domain.start) / (res - 1) as f64;
+        let shift = (domain.start - prev.start) / step;
+        let whole = (new_step - step).abs() <= 1e-9 * step.abs() && (shift - shift.round()).abs() < 1e-3;
+        whole.then_some(shift.round() as isize)
+    };
+    Some((
+        shift(prev.xdomain, moved(cfg.xdomain, ox), cfg.resolution.x)?,
+        shift(prev.ydomain, moved(cfg.ydomain, oy), cfg.resolution.y)?,
+    ))
+}
+
+/// Calculate the set for `cfg`, reusing the grid `prev` of `prev_cfg`.
+///
+/// If `cfg` is `prev_cfg` panned by a whole number of pixels, the pixels
+/// of the overlap are copied from `prev` and only the strips exposed by
+/// the pan are calculated. Otherwise this is the same as `mandel()`.
+#[cfg(feature = "parallel")]
+pub fn mandel_incremental(
+    prev: &IterationGrid,
+    prev_cfg: &MandelConfig,
+    cfg: MandelConfig,
+) -> Result<IterationGrid, MandelError> {
+    cfg.validate()?;
+    let (w, h) = (cfg.resolution.x, cfg.resolution.y);
+    // `prev` must be a render of `prev_cfg` to copy its pixels
+    let matches = prev.validate().is_ok()
+        && prev.image_size() == (prev_cfg.resolution.x, prev_cfg.resolution.y)
+        && prev.samples == prev_cfg.aa.samples();
+    let (sx, sy) = match pixel_shift(prev_cfg, &cfg) {
+        Some((sx, sy)) if matches && sx.unsigned_abs() < w && sy.unsigned_abs() < h => (sx, sy),
+        _ => return Ok(mandel_unchecked(cfg)),
+    };
+    let mut grid = IterationGrid::for_config(&cfg);
+
+    // pixel `(x, y)` of `grid` is pixel `(x + sx, y + sy)` of `prev`
+    let (ow, oh) = (w - sx.unsigned_abs(), h - sy.unsigned_abs());
+    let overlap = prev.crop(sx.max(0) as usize, sy.max(0) as usize, ow, oh);
+    grid.paste((-sx).max(0) as usize, (-sy).max(0) as usize, &overlap);
+
+    // the exposed strips, along the left or right and the top or bottom
+    // edges. A tile needs at least 2 pixels, see `Tile::config()`.
+    let strip = |shift: isize, size: usize| {
+        let len = shift.unsigned_abs().max(2).min(size);
+        let start = if shift > 0 { size - len } else { 0 };
+        (start, len)
+    };
+    let mut tiles = Vec::new();
+    if sx != 0 {
+        let (x, width) = strip(sx, w);
+        tiles.push(Tile {
+            x,
+            y: 0,
+            width,
+            height: h,
+        });
+    }
+    if sy != 0 {
+        let (y, height) = strip(sy, h);
+        tiles.push(Tile {
+            x: 0,
+            y,
+            width: w,
+            height,
+        });
+    }
+    for tile in tiles {
+        grid.paste(tile.x, tile.y, &mandel_unchecked(tile.config(&cfg)));
+    }
+    Ok(grid)
+}
+
+/// Return `true` if `cfg` is `prev` with a higher `max_iters`, see
+/// `mandel_deeper()`
+pub fn is_deeper(prev: &MandelConfig, cfg: &MandelConfig) -> bool {
+    let mut deeper = *prev;
+    (deeper.max_iters, deeper.auto_iters) = (cfg.max_iters, cfg.auto_iters);
+    deeper == *cfg && cfg.max_iters > prev.max_iters
+}
+
+/// Calculate the set for `cfg`, reusing the grid `prev` of `prev_cfg`.
+///
+/// If `cfg` is `prev_cfg` with a higher `max_iters`, only the samples
+/// which reached the cap of `prev_cfg`, or escaped at its last
+/// iteration, are iterated again, from the start, or else from their
+/// last `z` if `cfg.resumable`. The others escaped
+/// before and are copied from `prev`. Otherwise, and with adaptive
+/// anti-aliasing, this is the same as `mandel()`.
+#[cfg(feature = "parallel")]
+pub fn mandel_deeper(
+    prev: &IterationGrid,
+    prev_cfg: &MandelConfig,
+    cfg: MandelConfig,
+) -> Result<IterationGrid, MandelError> {
+    cfg.validate()?;
+    // `prev` must be a render of `prev_cfg`, a valid one, to copy its
+    // samples
+    let matches = prev.validate().is_ok()
+        && prev_cfg.validate().is_ok()
+        && prev.image_size() == (prev_cfg.resolution.x, prev_cfg.resolution.y)
+        && prev.samples == prev_cfg.aa.samples();
+    let adaptive = matches!(cfg.aa, AaMode::Adaptive { max_samples, .. } if max_samples > 1);
+    if !matches || adaptive || !is_deeper(prev_cfg, &cfg) {
+        return Ok(mandel_unchecked(cfg));
+    }
+    let mut grid = prev.clone();
+    // the smooth count of the samples escaping at the last iteration is
+    // clamped below the cap, see `Escape::with_power()`
+    let last = prev_cfg.max_iters as u32 - 1;
+    let samples = if cfg.aa.samples() > 1 { cfg.supersampled() } else { cfg };
+    let (f32_bits, f64_bits) = (f32::MANTISSA_DIGITS as usize, f64::MANTISSA_DIGITS as usize);
+    let (formula, power) = (cfg.formula, cfg.power);
+    pool::install(&cfg, || match select_precision(&samples) {
+        Precision::F32 => {
+            iterate_again(&mut grid, &samples, last, f32_bits, scalar_worker::<f32>(formula, power))
+        }
+        Precision::F64 => {
+            iterate_again(&mut grid, &samples, last, f64_bits, scalar_worker::<f64>(formula, power))
+        }
+        _ => {
+            let worker = scalar_worker::<BigFloat>(formula, power);
+            iterate_again(&mut grid, &samples, last, arbitrary_bits(&samples), worker)
+        }
+    });
+    Ok(grid)
+}
+
+/// Iterate the samples of `grid`, rendered for `cfg` without
+/// anti-aliasing, with at least `from` iterations again with `worker`,
+/// one at a time, in the current thread pool
+#[cfg(feature = "parallel")]
+fn iterate_again<T: Scalar + 'static>(
+    grid: &mut IterationGrid,
+    cfg: &MandelConfig,
+    from: u32,
+    precision: usize,
+    worker: Worker<T>,
 ) {
-    for i in 0..xres - 1 {
-        let x0 = xdomain[i];
-        let mut x1 = 0.0;
-        let mut y1 = 0.0;
-        let mut c = 0;
-        while x1 * x1 + y1 * y1 <= threshold && c < max_iters {
-            let xtmp = x1 * x1 - y1 * y1 + x0;
-            y1 = 2.0 * x1 * y1 + y0;
-            x1 = xtmp;
-            c += 1;
+    let xdomain = linspace::<T>(cfg.origin.x, cfg.xdomain, cfg.resolution.x, precision);
+    let ydomain = linspace::<T>(cfg.origin.y, cfg.ydomain, cfg.resolution.y, precision);
+    // the rows mirroring others are copied after, as by `mandel()`
+    let mirrors = mirrored_rows(cfg, &ydomain);
+    let rows: Vec<_> = grid
+        .rows_mut()
+        .into_iter()
+        .zip(ydomain.iter())
+        .zip(&mirrors)
+        .filter(|((row, _), mirror)| mirror.is_none() && row.iters.iter().any(|&n| n >= from))
+        .map(|(row, _)| row)
+        .collect();
+    pool::for_each_block(rows, pool::block_rows(), |(mut row, y0)| {
+        for (px, x0) in xdomain.iter().enumerate() {
+            if row.iters[px] < from {
+                continue;
+            }
+            let x0 = std::slice::from_ref(x0);
+            match cfg.rotation {
+                0.0 => worker(row.pixel_mut(px), y0, x0, cfg, precision),
+                _ => rotated_row(worker, row.pixel_mut(px), y0, x0, cfg, precision),
+            }
         }
-        // Pushing instead of indexing, since the matrix is not
-        // initialised with zeros, but rather just allocated by size.
-        //iters_row[i] = c;
-        iters_row.push(c);
+    });
+    for (y, mirror) in mirrors.into_iter().enumerate() {
+        if let Some(src) = mirror {
+            grid.mirror_row(src, y);
+        }
+    }
+}
+
+/// Signature of the functions processing one row of the domain. The
+/// worker writes each pixel of the row once, one per value of `xdomain`,
+/// which has exactly as many values as the row has pixels.
+pub(crate) type Worker<T> = fn(RowMut, &T, &[T], &MandelConfig, usize);
+
+/// Process one row of a rotated view with `worker`. The points of the
+/// row are not on a horizontal line of the plane, see `rotation` in
+/// `MandelConfig`, so the worker gets them one at a time.
+fn rotated_row<T: Scalar>(
+    worker: Worker<T>,
+    mut row: RowMut,
+    y0: &T,
+    xdomain: &[T],
+    cfg: &MandelConfig,
+    precision: usize,
+) {
+    let (sin, cos) = cfg.rotation.sin_cos();
+    let (sin, cos) = (T::from_f64(sin, precision), T::from_f64(cos, precision));
+    for (px, x0) in xdomain.iter().enumerate() {
+        let x = x0.clone() * cos.clone() - y0.clone() * sin.clone();
+        let y = x0.clone() * sin.clone() + y0.clone() * cos.clone();
+        worker(row.pixel_mut(px), &y, std::slice::from_ref(&x), cfg, precision);
     }
 }
 
-pub fn mandel(cfg: MandelConfig) -> Vec<Vec<usize>> {
-    //let t0 = SystemTime::now();
+/// Iterate `cfg` with `worker` in the current thread pool. Return the
+/// grid and the time taken to set up the points of the domains.
+#[cfg(feature = "parallel")]
+fn mandel_generic<T: Scalar + 'static>(
+    cfg: MandelConfig,
+    precision: usize,
+    worker: Worker<T>,
+    axes: Option<&AxisCache>,
+) -> (IterationGrid, std::time::Duration) {
+    let t0 = std::time::Instant::now();
 
     // The domain is chunked along y, meaning that each thread will
     // process along x - horizontally
 
-    // fill the x- and y-domain vectors
-    let mut xdomain = vec![];
-    {
-        let step = (cfg.xdomain.end - cfg.xdomain.start) / (cfg.resolution.x - 1) as f64;
-        let start = cfg.xdomain.start;
+    // fill the x- and y-domain vectors, or take them from the cache
+    let axis = |origin: Coordinate, domain: Domain, n: usize| match axes {
+        Some(axes) => axes.get::<T>(origin, domain, n, precision),
+        None => Arc::new(linspace::<T>(origin, domain, n, precision)),
+    };
+    let (xdomain, ydomain) = {
+        let _span = span!(log::Level::Trace, "domains");
+        (
+            axis(cfg.origin.x, cfg.xdomain, cfg.resolution.x),
+            axis(cfg.origin.y, cfg.ydomain, cfg.resolution.y),
+        )
+    };
+    let setup = t0.elapsed();
 
-        for i in 0..cfg.resolution.x {
-            xdomain.push(start + step * i as f64)
+    // The threads take blocks of a few rows at a time, so that the
+    // slow rows through the set do not leave one thread finishing last
+    let mut grid = IterationGrid::for_config(&cfg);
+    debug_assert_eq!((xdomain.len(), ydomain.len()), (grid.width, grid.height));
+    // the rows mirroring others across the real axis are copied after
+    let mirrors = mirrored_rows(&cfg, &ydomain);
+    let rows: Vec<_> = grid
+        .rows_mut()
+        .into_iter()
+        .zip(ydomain.iter())
+        .zip(&mirrors)
+        .filter_map(|(row, mirror)| mirror.is_none().then_some(row))
+        .collect();
+    let span = span!(log::Level::Trace, "workers");
+    pool::for_each_block(rows, pool::block_rows(), |(row, y0)| match cfg.rotation {
+        0.0 => worker(row, y0, &xdomain, &cfg, precision),
+        _ => rotated_row(worker, row, y0, &xdomain, &cfg, precision),
+    });
+    drop(span);
+    for (y, mirror) in mirrors.into_iter().enumerate() {
+        if let Some(src) = mirror {
+            grid.mirror_row(src, y);
         }
     }
-    let xdomain = Arc::new(Vec::from_iter(xdomain));
 
-    let mut ydomain = vec![];
-    {
-        let step = (cfg.ydomain.end - cfg.ydomain.start) / (cfg.resolution.y - 1) as f64;
-        let start = cfg.ydomain.start;
-
-        for i in 0..cfg.resolution.y {
-            ydomain.push(start + step * i as f64)
-        }
-    }
-    let ydomain = Arc::new(Vec::from_iter(ydomain));
-
-    // Divide y-resolution to run in parallel
-    let cpus = 4 * num_cpus::get();
-    let pool = ThreadPool::new(cpus);
-
-    // Matrix with number of Mandelbrot iterations:
-    //
-    //    iters[pixel_y][pixel_x]
-    //
-    // Must wrap each vector item in an `Arc<Mutex>` since the rows will
-    // be updated in parallel by multiple threads. So the type of `iters`
-    // is `Vec<Arc<Mutex<Vec<usize>>>>` since multiple threads
-    //
-    let mut iters = vec![];
-    for _ in 0..cfg.resolution.y {
-        // Here instead of initialising with zero, I'm just allocating
-        // the capacity. Will need to change the workers too to `push`
-        // instead of assining by indes.
-        //let row = Arc::new(Mutex::new(vec![0; cfg.resolution.x]));
-        let row = Arc::new(Mutex::new(Vec::with_capacity(cfg.resolution.x)));
-        iters.push(row);
-    }
-
-    //let t1 = t0.elapsed().unwrap().as_millis();
-    //println!("Initialised all arrays - eta {} ms", t1);
-
-	// sends jobs to the threadpool. each job processes one row
-	for py in 0..cfg.resolution.y {
-		
-	    let ydomain = Arc::clone(&ydomain);
-            let xdomain = Arc::clone(&xdomain);
-	    let row = Arc::clone(&iters[py]);
-		
-	    pool.execute(move || {
-		mandel_worker(
-		    &mut row.lock().unwrap(),
-		    ydomain[py],
-		    &xdomain,
-		    cfg.resolution.x,
-		    cfg.max_iters,
-		    cfg.threshold,
-		);
-	    });
-	}
-    pool.join();
-
-    //let t2 = t0.elapsed().unwrap().as_millis() - t1;
-    //println!("All threads done - et {t2} ms");
-
-    // converting here from:
-    //     &Vec<Arc<Mutex<Vec<usize>>>>
-    // to
-    //     &Vec<Vec<usize>>
-    //
-    // https://stackoverflow.com/questions/78768409/fill-a-matrix-in-
-    // parallel-how-to-convert-vecarcmutexvec-to-vecvec
-    let mut ret = vec![];
-    for row in iters {
-        ret.push(Mutex::into_inner(Arc::into_inner(row).unwrap()).unwrap());
-    }
-
-    //let t3 = t0.elapsed().unwrap().as_millis() - t1 - t2;
-    //println!("Conversion done - eta {} ms", t3);
-
-    ret
+    (grid, setup)
+}
+
+/// Single-threaded version of `mandel()`, without rayon, eg, for WASM.
+///
+/// Only the direct iteration runs, on the CPU, and `AaMode::Adaptive`
+/// renders all the samples like `AaMode::Full`.
+pub fn mandel_single(cfg: MandelConfig) -> Result<IterationGrid, MandelError> {
+    cfg.validate()?;
+    if cfg.aa.samples() > 1 {
+        let mut grid = mandel_single(cfg.supersampled())?;
+        grid.samples = cfg.aa.samples();
+        return Ok(grid);
+    }
+    fn rows<T: Scalar>(cfg: MandelConfig, precision: usize, worker: Worker<T>) -> IterationGrid {
+        let span = span!(log::Level::Trace, "domains");
+        let xdomain = linspace::<T>(cfg.origin.x, cfg.xdomain, cfg.resolution.x, precision);
+        let ydomain = linspace::<T>(cfg.origin.y, cfg.ydomain, cfg.resolution.y, precision);
+        drop(span);
+        let mut grid = IterationGrid::for_config(&cfg);
+        debug_assert_eq!((xdomain.len(), ydomain.len()), (grid.width, grid.height));
+        let mirrors = mirrored_rows(&cfg, &ydomain);
+        let _span = span!(log::Level::Trace, "workers");
+        for ((row, y0), mirror) in grid.rows_mut().into_iter().zip(&ydomain).zip(&mirrors) {
+            if mirror.is_some() {
+                continue;
+            }
+            match cfg.rotation {
+                0.0 => worker(row, y0, &xdomain, &cfg, precision),
+                _ => rotated_row(worker, row, y0, &xdomain, &cfg, precision),
+            }
+        }
+        for (y, mirror) in mirrors.into_iter().enumerate() {
+            if let Some(src) = mirror {
+                grid.mirror_row(src, y);
+            }
+        }
+        grid
+    }
+    // see `mandel()`
+    let scalar_only = cfg.collects_aux() || cfg.power != 2.0;
+    let (f32_bits, f64_bits) = (f32::MANTISSA_DIGITS as usize, f64::MANTISSA_DIGITS as usize);
+    let (formula, power) = (cfg.formula, cfg.power);
+    let scalar = scalar_only || cfg.backend == ComputeBackend::Scalar;
+    Ok(match select_precision(&cfg) {
+        Precision::F32 if scalar => rows(cfg, f32_bits, scalar_worker::<f32>(formula, power)),
+        Precision::F32 => rows(cfg, f32_bits, simd::simd_worker::<f32x8>(formula)),
+        Precision::F64 if scalar => rows(cfg, f64_bits, scalar_worker::<f64>(formula, power)),
+        Precision::F64 => rows(cfg, f64_bits, simd::simd_worker::<f64x4>(formula)),
+        _ => rows(cfg, arbitrary_bits(&cfg), scalar_worker::<BigFloat>(formula, power)),
+    })
+}
+
+/// Return the slope of the smooth iteration count at sample `i` of
+/// `iters`, along x and y, in iterations per sample. The neighbours
+/// inside the set or outside the grid are left out.
+fn smooth_slope(iters: &IterationGrid, i: usize, max_iters: usize) -> (f64, f64) {
+    let (x, y) = (i % iters.width, i / iters.width);
+    let escaped = |x: usize, y: usize| {
+        let j = y * iters.width + x;
+        (iters.data[j] < max_iters as u32).then_some(iters.smooth[j])
+    };
+    let diff = |prev: Option<f64>, next: Option<f64>| match (prev, next) {
+        (Some(a), Some(b)) => (b - a) / 2.0,
+        (Some(a), None) => iters.smooth[i] - a,
+        (None, Some(b)) => b - iters.smooth[i],
+        (None, None) => 0.0,
+    };
+    let left = x.checked_sub(1).and_then(|x| escaped(x, y));
+    let right = (x + 1 < iters.width).then(|| escaped(x + 1, y)).flatten();
+    let below = y.checked_sub(1).and_then(|y| escaped(x, y));
+    let above = (y + 1 < iters.height).then(|| escaped(x, y + 1)).flatten();
+    (diff(left, right), diff(below, above))
 }
 
 /// Return a buffer with the image of the mandelbrot set
+#[cfg(feature = "images")]
 pub fn get_image_buf(
-    iters: &Vec<Vec<usize>>,
+    iters: &IterationGrid,
     max_iters: usize,
-    color_schemes: ColorSchemes,
-) -> image::ImageBuffer<image::Rgb<u8>, Vec<u8>> {
-    let resy = iters.len() as u32;
-    let resx = iters[0].len() as u32;
-
-    let mut imgbuf = image::ImageBuffer::new(resx, resy);
-    for (x, y, pixel) in imgbuf.enumerate_pixels_mut() {
-        // imgbuf is indexed top-left to bottom-right,
-        // hence the y-index must be reversed:
-        let c = iters[(resy - y - 1) as usize][x as usize];
-        let (r, g, b) = color_schemes.get().rgb(c, max_iters);
-        *pixel = image::Rgb([r, g, b]);
-    }
-    imgbuf
+    color_schemes: &ColorSchemes,
+) -> Result<image::ImageBuffer<image::Rgb<u8>, Vec<u8>>, MandelError> {
+    let (resx, resy) = iters.image_size();
+    let buf = get_rgb_buf(iters, max_iters, color_schemes)?;
+    Ok(image::ImageBuffer::from_raw(resx as u32, resy as u32, buf).expect("buffer matches the grid size"))
+}
+
+/// Color `iters` with the current color scheme. Return the RGB bytes,
+/// row-major from the top-left corner of the image. This is independent
+/// of the version of the `image` crate, eg, the one in `nannou`.
+///
+/// The colors of the subpixel samples are averaged, see
+/// `AaMode`. The rows are colored in parallel, with the `parallel`
+/// feature.
+pub fn get_rgb_buf(
+    iters: &IterationGrid,
+    max_iters: usize,
+    color_schemes: &ColorSchemes,
+) -> Result<Vec<u8>, MandelError> {
+    iters.validate()?;
+    if max_iters == 0 {
+        return Err(MandelError::InvalidMaxIters(max_iters));
+    }
+    let (resx, resy) = iters.image_size();
+    let n = iters.samples;
+    let scheme = color_schemes.get_for(iters, max_iters);
+    let escaped = |i: usize| iters.data[i] < max_iters as u32;
+    // colors `rgb` of the row of samples starting at sample `start`
+    let color_samples = |start: usize, rgb: &mut [(u8, u8, u8)]| {
+        // grids with orbit trap data are colored by the trap distance,
+        // and grids with stripe averages by the stripes outside the set
+        if !iters.trap.is_empty() {
+            let trap = color_schemes.get_trap();
+            for (rgb, &distance) in rgb.iter_mut().zip(&iters.trap[start..]) {
+                *rgb = trap.rgb(distance);
+            }
+        } else {
+            scheme.rgb_row(&iters.smooth[start..start + rgb.len()], max_iters, rgb);
+            if !iters.stripe.is_empty() {
+                let stripe = color_schemes.get_stripe();
+                for (i, rgb) in (start..).zip(rgb.iter_mut()) {
+                    if escaped(i) {
+                        *rgb = stripe.rgb(iters.stripe[i]);
+                    }
+                }
+            }
+        }
+        if !iters.distance.is_empty() {
+            let shading = color_schemes.get_distance();
+            for (i, rgb) in (start..).zip(rgb.iter_mut()) {
+                if escaped(i) {
+                    // the distance is in samples, shade it in pixels
+                    *rgb = shading.shade(*rgb, iters.distance[i] / n as f64);
+                }
+            }
+        }
+        if let Some(relief) = color_schemes.get_relief() {
+            for (i, rgb) in (start..).zip(rgb.iter_mut()) {
+                if escaped(i) {
+                    // the slope is in samples too
+                    let (dx, dy) = smooth_slope(iters, i, max_iters);
+                    *rgb = relief.shade(*rgb, (dx * n as f64, dy * n as f64));
+                }
+            }
+        }
+    };
+    // the image is indexed top-left to bottom-right, hence the y-index
+    // of the grid is reversed
+    let color_row = |(row, out): (usize, &mut [u8])| {
+        let y = resy - 1 - row;
+        let mut samples = vec![(0, 0, 0); iters.width];
+        let mut sums = vec![[0_u32; 3]; resx];
+        for sy in 0..n {
+            color_samples((y * n + sy) * iters.width, &mut samples);
+            for (sum, pixel) in sums.iter_mut().zip(samples.chunks_exact(n)) {
+                for &(r, g, b) in pixel {
+                    sum[0] += r as u32;
+                    sum[1] += g as u32;
+                    sum[2] += b as u32;
+                }
+            }
+        }
+        let count = (n * n) as u32;
+        for (out, sum) in out.chunks_exact_mut(3).zip(sums) {
+            out.copy_from_slice(&sum.map(|c| ((c + count / 2) / count) as u8));
+        }
+    };
+    let mut buf = vec![0; 3 * resx * resy];
+    let rows: Vec<_> = buf.chunks_mut(3 * resx).enumerate().collect();
+    #[cfg(feature = "parallel")]
+    pool::for_each_block(rows, pool::block_rows(), color_row);
+    #[cfg(not(feature = "parallel"))]
+    rows.into_iter().for_each(color_row);
+    Ok(buf)
+}
+
+/// Return the normalised iteration count of each pixel, `smooth /
+/// max_iters`, from 0.0 up to 1.0 inside the set. Row-major from the
+/// top-left corner of the image, like `get_rgb_buf()`. The subpixel
+/// samples are averaged.
+pub fn get_raw_buf(iters: &IterationGrid, max_iters: usize) -> Result<Vec<f32>, MandelError> {
+    iters.validate()?;
+    if max_iters == 0 {
+        return Err(MandelError::InvalidMaxIters(max_iters));
+    }
+    let (resx, resy) = iters.image_size();
+    let n = iters.samples;
+    let mut buf = Vec::with_capacity(resx * resy);
+    for y in (0..resy).rev() {
+        for x in 0..resx {
+            let mut sum = 0.0;
+            for sy in 0..n {
+                let i = (y * n + sy) * iters.width + x * n;
+                sum += iters.smooth[i..i + n].iter().sum::<f64>();
+            }
+            buf.push((sum / (n * n) as f64 / max_iters as f64) as f32);
+        }
+    }
+    Ok(buf)
+}
+
+/// Save the image to `path`, with the format given by its extension.
+/// OpenEXR files (`.exr`) store the raw values, see `save_raw()`.
+#[cfg(feature = "images")]
+pub fn save_image<P: AsRef<Path>>(
+    iters: &IterationGrid,
+    max_iters: usize,
+    color_schemes: &ColorSchemes,
+    path: P,
+) -> Result<(), MandelError> {
+    let path = path.as_ref();
+    let _span = span!(log::Level::Debug, "save \"{}\"", path.display());
+    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("exr")) {
+        save_raw(iters, max_iters, path)?;
+    } else {
+        let span = span!(log::Level::Debug, "color");
+        let image = get_image_buf(iters, max_iters, color_schemes)?;
+        drop(span);
+        let _span = span!(log::Level::Debug, "encode");
+        image.save(path)?;
+    }
+    Ok(())
+}
+
+/// Same as `save_image()`, with the view of the image in the text chunks
+/// of PNG files, see `ImageMetadata::from_file()` to read it back.
+/// Other formats are saved without it.
+#[cfg(feature = "images")]
+pub fn save_image_with_metadata<P: AsRef<Path>>(
+    iters: &IterationGrid,
+    metadata: &ImageMetadata,
+    color_schemes: &ColorSchemes,
+    path: P,
+) -> Result<(), MandelError> {
+    let path = path.as_ref();
+    let max_iters = metadata.cfg.max_iters;
+    if !path.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")) {
+        return save_image(iters, max_iters, color_schemes, path);
+    }
+    let _span = span!(log::Level::Debug, "save \"{}\"", path.display());
+    let span = span!(log::Level::Debug, "color");
+    let buf = get_rgb_buf(iters, max_iters, color_schemes)?;
+    drop(span);
+    let _span = span!(log::Level::Debug, "encode");
+    let (width, height) = iters.image_size();
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    metadata.add_to_png(&mut encoder)?;
+    let mut writer = encoder.write_header().map_err(std::io::Error::from)?;
+    writer.write_image_data(&buf).map_err(std::io::Error::from)?;
+    writer.finish().map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+/// Save the normalised iteration counts, see `get_raw_buf()`, for
+/// external post-processing. OpenEXR files (`.exr`) store them as
+/// 32-bit floats, other formats as 16-bit greyscale, eg, PNG or TIFF.
+#[cfg(feature = "images")]
+pub fn save_raw<P: AsRef<Path>>(iters: &IterationGrid, max_iters: usize, path: P) -> Result<(), MandelError> {
+    let path = path.as_ref();
+    let (resx, resy) = iters.image_size();
+    let (resx, resy) = (resx as u32, resy as u32);
+    let raw = get_raw_buf(iters, max_iters)?;
+    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("exr")) {
+        // EXR only takes RGB(A), so the value goes to all channels
+        let rgb = raw.iter().flat_map(|&v| [v, v, v]).collect();
+        image::Rgb32FImage::from_raw(resx, resy, rgb)
+            .expect("buffer matches the grid size")
+            .save(path)?;
+    } else {
+        let gray = raw.iter().map(|&v| (v * u16::MAX as f32).round() as u16).collect();
+        image::ImageBuffer::<image::Luma<u16>, Vec<u16>>::from_raw(resx, resy, gray)
+            .expect("buffer matches the grid size")
+            .save(path)?;
+    }
+    Ok(())
+}
+
+/// Save the escape data of `iters` to `path`, with the format given by
+/// its extension, `.npy` or `.csv`, or else the binary format of
+/// `IterationGrid::write_binary()`
+pub fn dump_iters<P: AsRef<Path>>(iters: &IterationGrid, max_iters: usize, path: P) -> Result<(), MandelError> {
+    iters.validate()?;
+    let path = path.as_ref();
+    let mut w = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let ext = path.extension().map(|e| e.to_ascii_lowercase());
+    match ext.as_ref().and_then(|e| e.to_str()) {
+        Some("npy") => iters.write_npy(&mut w)?,
+        Some("csv") => iters.write_csv(&mut w)?,
+        _ => iters.write_binary(max_iters, &mut w)?,
+    }
+    std::io::Write::flush(&mut w)?;
+    Ok(())
 }
@@ -0,0 +1,123 @@
+// Links /////////////////////////////////////////////////////////////
+//       ////////////////////////////////////////////////////////////
+// Compact text of a view, to share it in a message, eg,
+// `mandel://-0.743643887/0.131825904/1e6/1000/wiky`: the centre, the
+// zoom factor, `max_iters` and the color scheme, and the rotation in
+// degrees, if any, after them. The other settings, eg, the fractal or
+// the resolution, are those of the config the link is applied to. The
+// centre has the digits of deep zooms, see `Coordinate`.
+use std::fmt;
+use std::str::FromStr;
+
+use crate::color_schemes::ColorSchemes;
+use crate::domain::View;
+use crate::origin::Coordinate;
+use crate::{MandelConfig, MandelError};
+
+/// Start of the links
+pub const PREFIX: &str = "mandel://";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewLink {
+    pub center: (Coordinate, Coordinate),
+    /// Zoom factor from the default domains, see `View`
+    pub zoom: f64,
+    pub max_iters: usize,
+    /// Name of the color scheme
+    pub scheme: String,
+    /// Rotation in degrees, counter-clockwise
+    pub rotation: f64,
+}
+
+impl ViewLink {
+    /// Return the link of the view of `cfg` colored with `scheme`
+    pub fn new(cfg: &MandelConfig, scheme: &str) -> Self {
+        let view = cfg.view();
+        Self {
+            center: cfg.center(),
+            zoom: view.scale,
+            max_iters: cfg.max_iters,
+            scheme: scheme.to_string(),
+            rotation: view.rotation.to_degrees(),
+        }
+    }
+
+    /// Set the view and `max_iters` of `cfg` to those of the link,
+    /// keeping its resolution and other settings
+    pub fn apply(&self, cfg: &mut MandelConfig) {
+        let (x, y) = self.center;
+        let view = View {
+            center_x: x.to_f64(),
+            center_y: y.to_f64(),
+            scale: self.zoom,
+            rotation: self.rotation.to_radians(),
+        };
+        cfg.domains().set_view(&view).center_on(x, y);
+        cfg.max_iters = self.max_iters;
+        cfg.auto_iters = None;
+    }
+
+    /// Set the color scheme of the link, return `false` if there is no
+    /// scheme of its name
+    pub fn apply_scheme(&self, color_schemes: &mut ColorSchemes) -> bool {
+        color_schemes.set_by_name(&self.scheme).is_some()
+    }
+}
+
+impl fmt::Display for ViewLink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (x, y) = self.center;
+        write!(
+            f,
+            "{PREFIX}{x}/{y}/{:e}/{}/{}",
+            self.zoom, self.max_iters, self.scheme
+        )?;
+        if self.rotation != 0.0 {
+            write!(f, "/{}", self.rotation)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ViewLink {
+    type Err = MandelError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = MandelError::InvalidLink;
+        let fields = s
+            .trim()
+            .strip_prefix(PREFIX)
+            .ok_or(invalid("it does not start with mandel://"))?;
+        let fields: Vec<_> = fields.trim_end_matches('/').split('/').collect();
+        let [x, y, zoom, max_iters, scheme, rest @ ..] = fields.as_slice() else {
+            return Err(invalid("expected x/y/zoom/iters/scheme"));
+        };
+        let number = |s: &str| s.parse::<f64>().ok().filter(|v| v.is_finite());
+        let coordinate = |s: &str| s.parse::<Coordinate>().ok();
+        let center = coordinate(x)
+            .zip(coordinate(y))
+            .ok_or(invalid("the centre is not a number"))?;
+        let zoom = number(zoom)
+            .filter(|&zoom| zoom > 0.0)
+            .ok_or(invalid("the zoom is not a positive number"))?;
+        let max_iters = max_iters
+            .parse()
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or(invalid("max_iters is not a positive integer"))?;
+        if scheme.is_empty() {
+            return Err(invalid("the color scheme is empty"));
+        }
+        let rotation = match rest {
+            [] => 0.0,
+            [rotation] => number(rotation).ok_or(invalid("the rotation is not a number"))?,
+            _ => return Err(invalid("too many fields")),
+        };
+        Ok(Self {
+            center,
+            zoom,
+            max_iters,
+            scheme: scheme.to_string(),
+            rotation,
+        })
+    }
+}
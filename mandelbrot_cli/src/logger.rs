@@ -0,0 +1,98 @@
+// Logger ////////////////////////////////////////////////////////////
+//        ///////////////////////////////////////////////////////////
+// Prints the log messages of the library and of the CLI to stderr, eg,
+// the time of each render and of the encoding of the images at the
+// `debug` level, and of each tile at the `trace` level. `--log-level`
+// sets the lowest level printed, and the `MANDELBROT_LOG` environment
+// variable refines it per module, like `RUST_LOG`, with a comma
+// separated list of `level` or `module=level`, eg,
+// `MANDELBROT_LOG=warn,mandelbrot_cli::render_job=debug`. The spans of
+// the stages of the renders, see `span.rs` of the library, are records
+// like the others.
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Environment variable with the levels of the modules
+pub const LOG_ENV: &str = "MANDELBROT_LOG";
+
+struct Logger {
+    /// Level of the modules without a directive
+    default: LevelFilter,
+    /// Modules and their levels
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl Logger {
+    /// Return the level of `target`, the one of its most specific
+    /// module in the directives
+    fn level(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .filter(|(module, _)| {
+                target
+                    .strip_prefix(module.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!(
+                "{:<5} {}: {}",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Parse a level, eg, `debug`, in any case, see `--log-level`
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level
+        .parse()
+        .map_err(|_| format!("unknown level \"{level}\""))
+}
+
+/// Parse the directives of `LOG_ENV` over the `default` level
+fn parse(filter: &str, default: LevelFilter) -> Result<Logger, String> {
+    let mut logger = Logger {
+        default,
+        directives: Vec::new(),
+    };
+    for directive in filter.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+        match directive.split_once('=') {
+            Some((module, level)) => {
+                let level = parse_level(level)?;
+                logger.directives.push((module.to_string(), level));
+            }
+            None => logger.default = parse_level(directive)?,
+        }
+    }
+    Ok(logger)
+}
+
+/// Print the messages up to `level`, or to the levels of `LOG_ENV`
+pub fn init(level: LevelFilter) {
+    let filter = std::env::var(LOG_ENV).unwrap_or_default();
+    let logger = parse(&filter, level).unwrap_or_else(|e| {
+        eprintln!("Error in {LOG_ENV} \"{filter}\" - {e}");
+        std::process::exit(1);
+    });
+    let max_level = logger
+        .directives
+        .iter()
+        .map(|(_, level)| *level)
+        .fold(logger.default, Ord::max);
+    log::set_max_level(max_level);
+    log::set_logger(Box::leak(Box::new(logger))).expect("the logger is set once");
+}
@@ -0,0 +1,136 @@
+// Lyapunov fractal //////////////////////////////////////////////////
+//                  /////////////////////////////////////////////////
+// Lyapunov exponent of the logistic map `x = r x (1 - x)`, where `r`
+// alternates between the two coordinates of the pixel, `a` along x and
+// `b` along y, following a sequence of `A`s and `B`s, eg, `AB` or
+// `AABAB` (Markus-Lyapunov fractal).
+//
+// The exponent is the average of `ln |r (1 - 2 x)|` over the
+// iterations, after a few to warm up. Negative exponents are stable,
+// drawn in yellow, and positive ones chaotic, drawn in blue, see
+// `Exponents::to_rgb()`.
+//
+// The view, ie, the domains, resolution and rotation, and the number of
+// iterations, `max_iters`, are those of a `MandelConfig`, and the rows
+// are rendered in parallel as in `mandel()`.
+use rayon::prelude::*;
+
+use crate::{pool, Domain, MandelConfig, MandelError};
+
+/// Sequence of the coordinate, `a` or `b`, used as `r` at each
+/// iteration, repeated. `true` is `b`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sequence(Vec<bool>);
+impl std::str::FromStr for Sequence {
+    type Err = String;
+    /// Parse a sequence of `A`s and `B`s, eg, `AABAB`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let seq = s
+            .chars()
+            .map(|c| match c.to_ascii_uppercase() {
+                'A' => Ok(false),
+                'B' => Ok(true),
+                _ => Err(format!("invalid sequence \"{s}\", only A and B are allowed")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if seq.is_empty() {
+            return Err("empty sequence".to_string());
+        }
+        Ok(Sequence(seq))
+    }
+}
+
+/// Lyapunov fractal of `sequence` in the view of `view`, `warmup`
+/// iterations and then `view.max_iters` to average the exponent over
+#[derive(Clone, Debug)]
+pub struct Lyapunov {
+    pub view: MandelConfig,
+    pub sequence: Sequence,
+    pub warmup: usize,
+}
+
+/// Lyapunov exponent of each pixel of the image, row-major from the
+/// top-left corner
+pub struct Exponents {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<f64>,
+}
+
+impl Lyapunov {
+    /// Return an error if the view is not valid
+    pub fn render(&self) -> Result<Exponents, MandelError> {
+        let view = &self.view;
+        view.validate()?;
+        let (width, height) = (view.resolution.x, view.resolution.y);
+        let step = |d: Domain, n: usize| (d.end - d.start) / (n - 1) as f64;
+        let (stepx, stepy) = (step(view.xdomain, width), step(view.ydomain, height));
+        let mut values = vec![0.0; width * height];
+        pool::install(view, || {
+            values
+                .par_chunks_mut(width)
+                .enumerate()
+                .for_each(|(row, values)| {
+                    // row 0 of the image is at `ydomain.end`
+                    let y = view.ydomain.end - row as f64 * stepy;
+                    for (px, value) in values.iter_mut().enumerate() {
+                        let x = view.xdomain.start + px as f64 * stepx;
+                        let (a, b) = view.to_plane(x, y);
+                        *value = self.exponent(a, b);
+                    }
+                })
+        });
+        Ok(Exponents {
+            width,
+            height,
+            values,
+        })
+    }
+
+    /// Return the Lyapunov exponent at `(a, b)`
+    fn exponent(&self, a: f64, b: f64) -> f64 {
+        let seq = &self.sequence.0;
+        let r = |n: usize| if seq[n % seq.len()] { b } else { a };
+        let mut x = 0.5;
+        for n in 0..self.warmup {
+            x = r(n) * x * (1.0 - x);
+        }
+        let iters = self.view.max_iters;
+        let mut sum = 0.0;
+        for n in self.warmup..self.warmup + iters {
+            let r = r(n);
+            x = r * x * (1.0 - x);
+            sum += (r * (1.0 - 2.0 * x)).abs().ln();
+        }
+        // -inf at superstable points, where `ln 0`
+        sum / iters as f64
+    }
+}
+
+impl Exponents {
+    /// Return the RGB bytes of the image. Negative exponents go from
+    /// black at 0 to yellow at the lowest one, and positive exponents
+    /// from black to blue at the highest one. The infinite exponents of
+    /// the superstable points are the most stable.
+    pub fn to_rgb(&self) -> Vec<u8> {
+        let finite = self.values.iter().copied().filter(|v| v.is_finite());
+        let min = finite.clone().fold(0.0, f64::min);
+        let max = finite.fold(0.0, f64::max);
+        self.values
+            .iter()
+            .flat_map(|&v| {
+                if v.is_nan() {
+                    return [0, 0, 0];
+                }
+                if v <= 0.0 {
+                    let t = if min < 0.0 { (v / min).min(1.0) } else { 0.0 };
+                    let t = t.sqrt();
+                    [(255.0 * t) as u8, (215.0 * t) as u8, (40.0 * t) as u8]
+                } else {
+                    let t = if max > 0.0 { (v / max).min(1.0) } else { 0.0 };
+                    [(30.0 * t) as u8, (60.0 * t) as u8, (200.0 * t) as u8]
+                }
+            })
+            .collect()
+    }
+}
@@ -1,99 +1,1856 @@
-use std::env;
-use std::fmt::Debug;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Cursor, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
 use std::process;
-use std::str::FromStr;
-use std::time::SystemTime;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
 
 use mandelbrot_cli::{
-    mandel,
+    dump_iters,
     get_image_buf,
+    get_rgb_buf,
+    mandel_streaming,
+    num_threads,
+    pixel_size,
+    IterationGrid,
+    MandelError,
+    required_precision,
+    select_precision,
+    save_image,
+    save_image_with_metadata,
+    save_raw,
+    AaMode,
+    ComputeBackend,
     Domain,
+    Engine,
+    Formula,
+    FractalKind,
     MandelConfig,
+    OrbitTrap,
+    Precision,
+    Renderer,
+    RenderStats,
     Resolution,
-    color_schemes::ColorSchemes
+    animation::{save_palette_cycle, AnimationOptions, AnimationWriter},
+    buddhabrot::{Buddhabrot, IterRange},
+    checkpoint::Checkpoint,
+    color_schemes::{ColorSchemes, Palette, ReliefShaded},
+    contact_sheet::ContactSheet,
+    landmarks::Landmark,
+    link::ViewLink,
+    lyapunov::{Lyapunov, Sequence},
+    metadata::ImageMetadata,
+    origin::Coordinate,
+    render_job::{render_tiles_skipping, tiles, RenderJob, Tile},
+    tune::Tuning,
+    keyframes::{AnimationScript, PaletteSettings},
+    zoom::{Easing, ZoomSequence},
 };
 
-fn help() {
-    eprintln!("Use:");
-    eprintln!(
-        "  {} x0 x1 y0 y1 max_iters resx resy fname",
-        env::args().collect::<Vec<_>>()[0]
-    );
-    eprintln!("Typical call:");
-    eprintln!(
-        "  {} -2.5 1.0 -1.0 1.0 128 1920 1080 fractal.png",
-        env::args().collect::<Vec<_>>()[0]
-    );
+mod interrupt;
+mod logger;
+mod progress;
+use progress::Progress;
+mod repl;
+mod script;
+mod video;
+use video::VideoWriter;
+
+/// Render the Mandelbrot set and friends to image files
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Lowest level of the messages logged to stderr: off, error, warn,
+    /// info, debug or trace, refined per module by the `MANDELBROT_LOG`
+    /// environment variable, eg, `MANDELBROT_LOG=mandelbrot_cli=trace`
+    #[arg(long, value_name = "LEVEL", value_parser = logger::parse_level, default_value = "info",
+          global = true)]
+    log_level: log::LevelFilter,
 }
 
-// Parse a string into a value, eg, `"2.5" => 2.5`.
-// Exit process in case of parse error.
-fn arg_parse<T: FromStr>(arg: &str, name: &str) -> T
-where
-    <T as FromStr>::Err: Debug,
-{
-    match arg.parse() {
-        Ok(val) => val,
-        Err(e) => {
-            eprintln!("Error parsing {name} - {e:?}, got \"{arg}\"");
-            help();
+#[derive(Subcommand)]
+enum Command {
+    /// Render one image
+    Render {
+        #[command(flatten)]
+        view: ViewArgs,
+        #[command(flatten)]
+        color: ColorArgs,
+        /// Output file, the format is given by the extension
+        #[arg(short, long, default_value = "fractal.png")]
+        output: PathBuf,
+        /// Save the normalised iteration counts instead of colors
+        #[arg(long)]
+        raw: bool,
+        /// Render and save the PNG in bands of rows, for images too large
+        /// to fit in memory
+        #[arg(long)]
+        stream: bool,
+        /// Save the image as tiles of SIZE by SIZE pixels, named after the
+        /// output, eg, `fractal_tile_000_001.png` for row 0 and column 1
+        #[arg(long, value_name = "SIZE")]
+        tile_size: Option<usize>,
+        /// Also stitch the tiles into the output
+        #[arg(long, requires = "tile_size")]
+        stitch: bool,
+        #[command(flatten)]
+        checkpoint: CheckpointArgs,
+        /// Also save the escape data, as `.npy`, `.csv` or else binary
+        #[arg(long, value_name = "FILE")]
+        dump_iters: Option<PathBuf>,
+        /// Also save the view to a JSON file next to the output, eg,
+        /// `fractal.json`, as well as in the PNG text chunks
+        #[arg(long)]
+        sidecar: bool,
+        /// Do not print the progress to stderr
+        #[arg(short, long)]
+        quiet: bool,
+        /// Also print the time of each stage of the render, see
+        /// `RenderStats`
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Render the frames of a zoom from the view into a point
+    ZoomSequence {
+        #[command(flatten)]
+        view: ViewArgs,
+        #[command(flatten)]
+        color: ColorArgs,
+        /// x coordinate of the point to zoom into, with as many digits
+        /// as the last frame needs
+        #[arg(long, allow_hyphen_values = true)]
+        cx: Coordinate,
+        /// y coordinate of the point to zoom into
+        #[arg(long, allow_hyphen_values = true)]
+        cy: Coordinate,
+        /// Width of the x domain of the last frame
+        #[arg(long)]
+        end_width: f64,
+        /// Number of frames
+        #[arg(long, default_value_t = 100)]
+        frames: usize,
+        /// Zoom speed profile: linear, ease-in, ease-out or ease-in-out
+        #[arg(long, default_value = "linear")]
+        easing: Easing,
+        /// Directory of the frames, named `frame_00000.png` onwards
+        #[arg(short, long, default_value = "frames")]
+        output_dir: PathBuf,
+        /// Encode the frames to the video FILE with ffmpeg instead, eg,
+        /// `zoom.mp4`, the codec is given by the extension
+        #[arg(long, value_name = "FILE")]
+        video: Option<PathBuf>,
+        /// Frames per second of the video
+        #[arg(long, default_value_t = 30.0, requires = "video")]
+        fps: f64,
+        /// Quality of the video, from 0, lossless, to 51, lower is better
+        #[arg(long, default_value_t = 18, value_parser = clap::value_parser!(u8).range(0..=51),
+              requires = "video")]
+        crf: u8,
+        /// Do not print the progress to stderr
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Save an animated GIF or PNG cycling the palette over the view,
+    /// without rendering it again, or zooming into a point
+    Animate {
+        #[command(flatten)]
+        view: ViewArgs,
+        #[command(flatten)]
+        color: ColorArgs,
+        /// Zoom into `x,y` instead of cycling the palette
+        #[arg(long, value_name = "X,Y", value_parser = parse_complex, allow_hyphen_values = true,
+              requires = "end_width")]
+        zoom_into: Option<(f64, f64)>,
+        /// Width of the x domain of the last frame of the zoom
+        #[arg(long, requires = "zoom_into")]
+        end_width: Option<f64>,
+        /// Zoom speed profile: linear, ease-in, ease-out or ease-in-out
+        #[arg(long, default_value = "linear")]
+        easing: Easing,
+        /// Number of frames
+        #[arg(long, default_value_t = 60)]
+        frames: usize,
+        /// Time each frame is shown, in milliseconds
+        #[arg(long, value_name = "MS", default_value_t = 40)]
+        delay: u64,
+        /// Number of times the animation is played, 0 for forever
+        #[arg(long, default_value_t = 0)]
+        loops: u16,
+        /// Output file, an animated GIF if it ends in `.gif`, or else an
+        /// animated PNG if it ends in `.png` or `.apng`
+        #[arg(short, long, default_value = "animation.gif")]
+        output: PathBuf,
+        /// Do not print the progress to stderr
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Render the frames of an animation script, interpolated between
+    /// its keyframes, eg, recorded in the GUI
+    Keyframes {
+        /// Animation script, a TOML file of keyframes
+        script: PathBuf,
+        /// Image width in pixels [default: the one of the script]
+        #[arg(long)]
+        width: Option<usize>,
+        /// Image height in pixels [default: the one of the script]
+        #[arg(long)]
+        height: Option<usize>,
+        /// Directory of the frames, named `frame_00000.png` onwards
+        #[arg(short, long, default_value = "frames")]
+        output_dir: PathBuf,
+        /// Encode the frames to the video FILE with ffmpeg instead, eg,
+        /// `zoom.mp4`, the codec is given by the extension
+        #[arg(long, value_name = "FILE")]
+        video: Option<PathBuf>,
+        /// Frames per second of the video
+        #[arg(long, default_value_t = 30.0, requires = "video")]
+        fps: f64,
+        /// Quality of the video, from 0, lossless, to 51, lower is better
+        #[arg(long, default_value_t = 18, value_parser = clap::value_parser!(u8).range(0..=51),
+              requires = "video")]
+        crf: u8,
+        /// Do not print the progress to stderr
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Time the rendering of standard locations, with the settings of
+    /// the view, eg, resolution and backend
+    Bench {
+        #[command(flatten)]
+        view: ViewArgs,
+        /// Number of runs
+        #[arg(long, default_value_t = 5)]
+        runs: usize,
+        /// Time the view itself instead of the standard locations
+        #[arg(long)]
+        view_only: bool,
+        /// Also save the results as JSON, for regression tracking
+        #[arg(long)]
+        json: Option<PathBuf>,
+    },
+    /// Render the density of the escaping orbits, the Buddhabrot
+    Buddhabrot {
+        #[command(flatten)]
+        view: ViewArgs,
+        /// Number of random points sampled
+        #[arg(long, default_value_t = 10_000_000)]
+        samples: usize,
+        /// Iterations the orbits of the red, green and blue channels
+        /// escape within, eg, `0-5000,0-500,0-50` for the Nebulabrot
+        /// [default: 0-max_iters for all]
+        #[arg(long, value_delimiter = ',', value_name = "RANGES")]
+        channels: Option<Vec<IterRange>>,
+        /// Draw the orbits that do not escape, the anti-Buddhabrot
+        #[arg(long)]
+        anti: bool,
+        /// Scale of the densities before clipping, above 1 saturates the
+        /// densest areas
+        #[arg(long, default_value_t = 1.0)]
+        exposure: f64,
+        /// Gamma of the densities, above 1 brings out the faint orbits
+        #[arg(long, default_value_t = 2.0)]
+        gamma: f64,
+        /// Seed of the random points
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Output image
+        #[arg(short, long, default_value = "buddhabrot.png")]
+        output: PathBuf,
+        #[command(flatten)]
+        checkpoint: CheckpointArgs,
+        /// Do not print the progress to stderr
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Render the Lyapunov fractal of the logistic map, with `a` along x
+    /// and `b` along y, in (2, 4) by default, averaging the exponent
+    /// over `--max-iters` iterations
+    Lyapunov {
+        #[command(flatten)]
+        view: ViewArgs,
+        /// Sequence of `a` and `b`, eg, `AABAB`
+        #[arg(long, default_value = "AB")]
+        sequence: Sequence,
+        /// Iterations before averaging the exponent
+        #[arg(long, default_value_t = 100)]
+        warmup: usize,
+        /// Output image
+        #[arg(short, long, default_value = "lyapunov.png")]
+        output: PathBuf,
+    },
+    /// Render the Julia sets of a grid of `c`, `--columns` from `--cx`
+    /// START,END and `--rows` from `--cy` START,END, into one image,
+    /// each labelled with its `c`
+    JuliaSheet {
+        /// Range of the real part of `c`, along the columns
+        #[arg(long, value_name = "START,END", value_parser = parse_complex,
+              allow_hyphen_values = true, default_value = "-1.0,0.5")]
+        cx: (f64, f64),
+        /// Range of the imaginary part of `c`, along the rows, from the
+        /// bottom
+        #[arg(long, value_name = "START,END", value_parser = parse_complex,
+              allow_hyphen_values = true, default_value = "0.0,1.0")]
+        cy: (f64, f64),
+        /// Number of columns
+        #[arg(long, value_parser = clap::value_parser!(u16).range(1..), default_value_t = 6)]
+        columns: u16,
+        /// Number of rows
+        #[arg(long, value_parser = clap::value_parser!(u16).range(1..), default_value_t = 4)]
+        rows: u16,
+        /// View of each cell, `--width` and `--height` its size [default:
+        /// the whole Julia set, 256x160, 256 iterations]
+        #[command(flatten)]
+        view: ViewArgs,
+        #[command(flatten)]
+        color: ColorArgs,
+        /// Do not label the cells with their `c`
+        #[arg(long)]
+        no_labels: bool,
+        /// Output image
+        #[arg(short, long, default_value = "julia_sheet.png")]
+        output: PathBuf,
+        /// Do not print the progress to stderr
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Explore from the view with commands read from stdin, eg, `zoom
+    /// 2`, `iters 512` or `render out.png`, see `help` in it
+    Repl {
+        #[command(flatten)]
+        view: ViewArgs,
+        #[command(flatten)]
+        color: ColorArgs,
+    },
+    /// Run a script of batch renders, eg, a sweep of parameters, on the
+    /// view, see `script.rs`
+    Script {
+        /// Script file, eg, `sweep.rhai`
+        script: PathBuf,
+        #[command(flatten)]
+        view: ViewArgs,
+        #[command(flatten)]
+        color: ColorArgs,
+    },
+    /// Print information about the view, eg, zoom and precision
+    Info {
+        #[command(flatten)]
+        view: ViewArgs,
+    },
+    /// Find the number of threads and of rows per block with the highest
+    /// throughput on this machine, and save them for the next renders,
+    /// of the CLI and of the GUI
+    Tune {
+        /// Number of runs of each setting
+        #[arg(long, default_value_t = 3)]
+        runs: usize,
+        /// File the tuning is saved to [default: `MANDELBROT_TUNING`, or
+        /// `mandelbrot/tuning.json` in the cache directory]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Fields of `MandelConfig`, the defaults are those of `--config`, if
+/// given, or else of `MandelConfig::default()`
+#[derive(Args)]
+struct ViewArgs {
+    /// Load the view from a TOML file, or from a PNG or JSON file saved
+    /// with it, overridden by the other flags
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Start of the x domain [default: depends on the fractal]
+    #[arg(long, allow_hyphen_values = true)]
+    x0: Option<f64>,
+    /// End of the x domain
+    #[arg(long, allow_hyphen_values = true)]
+    x1: Option<f64>,
+    /// Start of the y domain
+    #[arg(long, allow_hyphen_values = true)]
+    y0: Option<f64>,
+    /// End of the y domain
+    #[arg(long, allow_hyphen_values = true)]
+    y1: Option<f64>,
+    /// Centre of the view, instead of the domains, eg, `--center
+    /// -0.743643 0.131825`, with as many digits as deep zooms need
+    /// [default: centre of the domains]
+    #[arg(long, num_args = 2, value_names = ["X", "Y"], allow_negative_numbers = true,
+          conflicts_with_all = ["x0", "x1", "y0", "y1"])]
+    center: Option<Vec<Coordinate>>,
+    /// Zoom factor from the default domains, instead of the domains, eg,
+    /// `--zoom 1e9` [default: zoom of the domains]
+    #[arg(long, conflicts_with_all = ["x0", "x1", "y0", "y1"])]
+    zoom: Option<f64>,
+    /// Notable location, with its `max_iters`, eg, `seahorse`, see
+    /// `mandelbrot_cli::landmarks`
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["x0", "x1", "y0", "y1", "center", "julia"])]
+    landmark: Option<Landmark>,
+    /// Link of a view, `mandel://x/y/zoom/iters/scheme`, eg, from the
+    /// HUD of the GUI, with its `max_iters` and color scheme
+    #[arg(long, value_name = "URL",
+          conflicts_with_all = ["x0", "x1", "y0", "y1", "center", "zoom", "landmark"])]
+    link: Option<ViewLink>,
+    /// Rotation of the view around its centre, in degrees,
+    /// counter-clockwise [default: 0]
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true)]
+    rotation: Option<f64>,
+    /// Image width in pixels [default: 1920]
+    #[arg(long)]
+    width: Option<usize>,
+    /// Image height in pixels [default: 1080]
+    #[arg(long)]
+    height: Option<usize>,
+    /// Maximum number of iterations for divergence [default: 128]
+    #[arg(short, long)]
+    max_iters: Option<usize>,
+    /// Set the maximum number of iterations from the zoom of the view
+    #[arg(long, conflicts_with = "max_iters")]
+    auto_iters: bool,
+    /// Lowest maximum number of iterations of `--auto-iters`
+    /// [default: 128]
+    #[arg(long, value_name = "N")]
+    iters_floor: Option<usize>,
+    /// Highest maximum number of iterations of `--auto-iters`
+    /// [default: 20000]
+    #[arg(long, value_name = "N")]
+    iters_ceiling: Option<usize>,
+    /// Bailout value of |z|^2, eg, 65536 for smoother color gradients
+    /// [default: 4]
+    #[arg(long)]
+    threshold: Option<f64>,
+    /// Render the Julia set of `c`, given as `cx,cy`
+    #[arg(long, value_parser = parse_complex, allow_hyphen_values = true)]
+    julia: Option<(f64, f64)>,
+    /// Iteration formula: mandelbrot, burning-ship, tricorn or celtic
+    /// [default: mandelbrot]
+    #[arg(long)]
+    formula: Option<Formula>,
+    /// Exponent n of z^n + c [default: 2]
+    #[arg(long)]
+    power: Option<f64>,
+    /// Iteration engine: direct, perturbation or mariani-silver
+    /// [default: direct]
+    #[arg(long)]
+    engine: Option<Engine>,
+    /// Compute backend: scalar, simd or gpu [default: scalar]
+    #[arg(long)]
+    backend: Option<ComputeBackend>,
+    /// Iteration precision: auto, f32, f64 or arbitrary. auto picks the
+    /// fastest one that resolves the pixels [default: auto]
+    #[arg(long)]
+    precision: Option<Precision>,
+    /// Number of render threads, also given by the MANDELBROT_THREADS
+    /// environment variable [default: one per core]
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Orbit trap coloring: point, line or circle
+    #[arg(long)]
+    trap: Option<OrbitTrap>,
+    /// Distance estimation shading
+    #[arg(long)]
+    distance: bool,
+    /// Stripe average coloring, with DENSITY stripes per turn of the
+    /// orbits, eg 5. Smoother with a large --threshold
+    #[arg(long, value_name = "DENSITY")]
+    stripes: Option<f64>,
+    /// Anti-aliasing, N by N samples per pixel [default: 1]
+    #[arg(long, value_name = "N")]
+    aa: Option<usize>,
+    /// Adaptive anti-aliasing, only of the pixels whose neighbours
+    /// differ by more than TOLERANCE iterations, with up to `--aa`
+    /// samples [default: 3]
+    #[arg(long, value_name = "TOLERANCE")]
+    aa_adaptive: Option<f64>,
+    /// Detect points inside the set early, faster for views with large
+    /// areas of the set
+    #[arg(long)]
+    interior_check: bool,
+}
+impl ViewArgs {
+    /// Return `true` if no flag gives the view, ie, its domains are the
+    /// default ones
+    fn is_default(&self) -> bool {
+        let domains_given = [self.x0, self.x1, self.y0, self.y1]
+            .iter()
+            .any(Option::is_some);
+        self.config.is_none()
+            && !domains_given
+            && self.center.is_none()
+            && self.zoom.is_none()
+            && self.landmark.is_none()
+    }
+
+    fn config(&self) -> MandelConfig {
+        let mut cfg = match &self.config {
+            Some(path) => {
+                let loaded = if path.extension().is_some_and(|e| e == "toml") {
+                    std::fs::read_to_string(path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|s| MandelConfig::from_toml(&s).map_err(|e| e.to_string()))
+                } else {
+                    ImageMetadata::from_file(path).map(|m| m.cfg).map_err(|e| e.to_string())
+                };
+                match loaded {
+                    Ok(cfg) => cfg,
+                    Err(e) => {
+                        eprintln!("Error loading config \"{}\" - {e}", path.display());
+                        process::exit(1);
+                    }
+                }
+            }
+            None => MandelConfig::default(),
+        };
+        if let Some((cx, cy)) = self.julia {
+            cfg.kind = FractalKind::Julia { cx, cy };
+            // the domains default to those of the Julia set
+            let (xdomain, ydomain) = cfg.kind.default_domains();
+            cfg.domains().set_domains(xdomain, ydomain);
+        }
+        let domains_given = [self.x0, self.x1, self.y0, self.y1]
+            .iter()
+            .any(Option::is_some);
+        if domains_given {
+            // the domains given are points of the plane
+            let (xdomain, ydomain) = cfg.absolute_domains();
+            cfg.domains().set_domains(xdomain, ydomain);
+        }
+        let set = |field: &mut f64, value: Option<f64>| {
+            if let Some(v) = value {
+                *field = v;
+            }
+        };
+        set(&mut cfg.xdomain.start, self.x0);
+        set(&mut cfg.xdomain.end, self.x1);
+        set(&mut cfg.ydomain.start, self.y0);
+        set(&mut cfg.ydomain.end, self.y1);
+        set(&mut cfg.threshold, self.threshold);
+        set(&mut cfg.power, self.power);
+        cfg.resolution.x = self.width.unwrap_or(cfg.resolution.x);
+        cfg.resolution.y = self.height.unwrap_or(cfg.resolution.y);
+        if self.config.is_none() && !domains_given {
+            // default domains, widened to the aspect of the image
+            cfg.domains().fit_to_aspect();
+        }
+        if let Some(landmark) = &self.landmark {
+            landmark.apply(&mut cfg);
+        }
+        if let Some(link) = &self.link {
+            link.apply(&mut cfg);
+        }
+        if self.center.is_some() || self.zoom.is_some() || self.rotation.is_some() {
+            // the centre in high precision, for deep zooms
+            let (x, y) = match &self.center {
+                Some(center) => (center[0], center[1]),
+                None => cfg.center(),
+            };
+            let mut view = cfg.view();
+            view.scale = self.zoom.unwrap_or(view.scale);
+            view.rotation = self.rotation.map_or(view.rotation, f64::to_radians);
+            cfg.domains().set_view(&view).center_on(x, y);
+        }
+        cfg.max_iters = self.max_iters.unwrap_or(cfg.max_iters);
+        if self.max_iters.is_some() {
+            // overrides the `auto_iters` of `--config`
+            cfg.auto_iters = None;
+        }
+        if self.auto_iters || self.iters_floor.is_some() || self.iters_ceiling.is_some() {
+            let mut auto = cfg.auto_iters.unwrap_or_default();
+            auto.floor = self.iters_floor.unwrap_or(auto.floor);
+            auto.ceiling = self.iters_ceiling.unwrap_or(auto.ceiling);
+            cfg.auto_iters = Some(auto);
+        }
+        cfg.update_max_iters();
+        cfg.formula = self.formula.unwrap_or(cfg.formula);
+        cfg.engine = self.engine.unwrap_or(cfg.engine);
+        cfg.backend = self.backend.unwrap_or(cfg.backend);
+        cfg.precision = self.precision.unwrap_or(cfg.precision);
+        cfg.threads = self.threads.or(cfg.threads);
+        cfg.trap = self.trap.or(cfg.trap);
+        cfg.distance |= self.distance;
+        cfg.stripes = self.stripes.or(cfg.stripes);
+        cfg.interior_check |= self.interior_check;
+        cfg.aa = match (self.aa, self.aa_adaptive) {
+            (samples, Some(tolerance)) => AaMode::Adaptive {
+                tolerance,
+                max_samples: samples.unwrap_or(3),
+            },
+            (Some(samples), None) => AaMode::Full { samples },
+            (None, None) => cfg.aa,
+        };
+        if let Err(e) = cfg.validate() {
+            eprintln!("Error: invalid view - {e}");
             process::exit(1);
         }
+        cfg
     }
 }
 
+#[derive(Args, Debug)]
+struct ColorArgs {
+    /// Built-in color scheme, by name or index: bluey, greeny, purply,
+    /// weirdy, greyeydark, greyeylight, hulky or wiky [default: wiky, or
+    /// the one of `--link`]
+    #[arg(long, value_name = "NAME")]
+    color_scheme: Option<String>,
+    /// Custom palette, TOML or gradient image, instead of the color scheme
+    #[arg(long)]
+    palette: Option<PathBuf>,
+    /// Histogram equalisation of the colors
+    #[arg(long)]
+    equalize: bool,
+    /// Shift the colors along the iteration counts, by a fraction of
+    /// the maximum number of iterations [default: 0]
+    #[arg(long, value_name = "FRACTION", allow_negative_numbers = true)]
+    palette_offset: Option<f64>,
+    /// Repeat the colors SCALE times along the iteration counts, eg, 2
+    /// for a palette twice as dense [default: 1]
+    #[arg(long, value_name = "SCALE")]
+    palette_scale: Option<f64>,
+    /// Relief shading, lit from AZIMUTH,ELEVATION in degrees, from the
+    /// x axis and above the image [default: 135,45]
+    #[arg(long, value_name = "AZIMUTH,ELEVATION", value_parser = parse_complex,
+          num_args = 0..=1, default_missing_value = "135,45")]
+    relief: Option<(f64, f64)>,
+    /// Height of the relief, steeper with larger values [default: 1]
+    #[arg(long, requires = "relief")]
+    depth: Option<f64>,
+}
+impl ColorArgs {
+    fn color_schemes(&self) -> ColorSchemes {
+        let mut color_schemes = ColorSchemes::new();
+        let name = self.color_scheme.as_deref().unwrap_or(DEFAULT_COLOR_SCHEME);
+        let found = match name.parse::<usize>() {
+            Ok(index) if index < color_schemes.len() => {
+                color_schemes.set(index);
+                true
+            }
+            Ok(_) => false,
+            Err(_) => color_schemes.set_by_name(name).is_some(),
+        };
+        if !found {
+            let names: Vec<_> = color_schemes.names().collect();
+            eprintln!(
+                "Error: no color scheme \"{}\", there are {}",
+                name,
+                names.join(", ")
+            );
+            process::exit(1);
+        }
+        if let Some(path) = &self.palette {
+            match Palette::from_file(path) {
+                Ok(palette) => {
+                    let index = color_schemes.register(Box::new(palette));
+                    color_schemes.set(index);
+                }
+                Err(e) => {
+                    eprintln!("Error loading palette \"{}\" - {e}", path.display());
+                    process::exit(1);
+                }
+            }
+        }
+        if self.equalize {
+            color_schemes.toggle_equalize();
+        }
+        if let Some(scale) = self.palette_scale {
+            if !(scale > 0.0 && scale.is_finite()) {
+                eprintln!("Error: --palette-scale {scale} is not positive");
+                process::exit(1);
+            }
+            color_schemes.set_scale(scale);
+        }
+        color_schemes.set_offset(self.palette_offset.unwrap_or(0.0));
+        if let Some((azimuth, elevation)) = self.relief {
+            let default = ReliefShaded::default();
+            color_schemes.set_relief(Some(ReliefShaded {
+                azimuth: azimuth.to_radians(),
+                elevation: elevation.to_radians(),
+                depth: self.depth.unwrap_or(default.depth),
+                ..default
+            }));
+        }
+        color_schemes
+    }
+}
+
+#[derive(Args)]
+struct CheckpointArgs {
+    /// Save the progress to FILE from time to time, to resume the render
+    /// with `--resume` if it is interrupted. It is removed once the render
+    /// completes.
+    #[arg(long, value_name = "FILE")]
+    checkpoint: Option<PathBuf>,
+    /// Resume the render from the checkpoint FILE, with the same flags,
+    /// and keep saving the progress to it
+    #[arg(long, value_name = "FILE", conflicts_with = "checkpoint")]
+    resume: Option<PathBuf>,
+}
+impl CheckpointArgs {
+    /// Return the file the progress is saved to, if any
+    fn path(&self) -> Option<&Path> {
+        self.resume.as_deref().or(self.checkpoint.as_deref())
+    }
+
+    /// Return the checkpoint of `--resume`, if it is of the render of
+    /// `empty`, or else `empty`
+    fn start(&self, empty: Checkpoint) -> Checkpoint {
+        let Some(path) = &self.resume else {
+            return empty;
+        };
+        let loaded = Checkpoint::load(path).and_then(|checkpoint| {
+            checkpoint.check(&empty.key, empty.units())?;
+            Ok(checkpoint)
+        });
+        match loaded {
+            Ok(checkpoint) => {
+                println!(
+                    "==> resuming from \"{}\", {} of {} done",
+                    path.display(),
+                    checkpoint.units_done(),
+                    checkpoint.units()
+                );
+                checkpoint
+            }
+            Err(e) => {
+                eprintln!("Error resuming \"{}\" - {e}", path.display());
+                process::exit(1);
+            }
+        }
+    }
+
+    /// Save `checkpoint`, if asked to, warning if it fails, as the
+    /// render can still complete
+    fn save(&self, checkpoint: &Checkpoint) {
+        if let Some(path) = self.path() {
+            if let Err(e) = checkpoint.save(path) {
+                log::warn!("checkpoint not saved to \"{}\" - {e}", path.display());
+            }
+        }
+    }
+
+    /// Remove the checkpoint of a completed render
+    fn finish(&self) {
+        if let Some(path) = self.path() {
+            if let Err(e) = std::fs::remove_file(path) {
+                log::warn!("checkpoint \"{}\" not removed - {e}", path.display());
+            }
+        }
+    }
+}
+
+/// Color scheme of the renders, unless `--color-scheme` or `--link` are
+/// given
+const DEFAULT_COLOR_SCHEME: &str = "wiky";
+
+/// Time between the checkpoints of the Buddhabrot, whose hits take a
+/// while to save for large images. The tiles of posters are saved as
+/// they complete.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Parse a complex number `x,y`, or any pair of numbers
+fn parse_complex(s: &str) -> Result<(f64, f64), String> {
+    let (x, y) = s
+        .split_once(',')
+        .ok_or_else(|| format!("expected `x,y`, got \"{s}\""))?;
+    let parse = |v: &str| v.trim().parse::<f64>().map_err(|e| e.to_string());
+    Ok((parse(x)?, parse(y)?))
+}
+
 fn main() {
+    let mut cli = Cli::parse();
+    logger::init(cli.log_level);
+    if !matches!(cli.command, Command::Tune { .. }) {
+        Tuning::load_cached();
+    }
+    // the renders that save what is done on Ctrl-C
+    if matches!(
+        cli.command,
+        Command::Render { .. }
+            | Command::ZoomSequence { .. }
+            | Command::Keyframes { .. }
+            | Command::Buddhabrot { .. }
+    ) {
+        interrupt::install();
+    }
+    // the color scheme of `--link`, unless `--color-scheme` is given
+    if let Command::Render { view, color, .. }
+    | Command::ZoomSequence { view, color, .. }
+    | Command::Animate { view, color, .. }
+    | Command::Repl { view, color }
+    | Command::Script { view, color, .. } = &mut cli.command
+    {
+        if let (Some(link), None) = (&view.link, &color.color_scheme) {
+            color.color_scheme = Some(link.scheme.clone());
+        }
+    }
+    match cli.command {
+        Command::Render {
+            view,
+            color,
+            output,
+            raw,
+            stream,
+            tile_size,
+            stitch,
+            checkpoint,
+            dump_iters,
+            sidecar,
+            quiet,
+            verbose,
+        } => {
+            match tile_size {
+                Some(tile_size) => render_poster(&view, &color, &output, tile_size, stitch, &checkpoint, quiet),
+                None if checkpoint.path().is_some() => {
+                    eprintln!("Error: --checkpoint and --resume need --tile-size");
+                    process::exit(1);
+                }
+                None => render(&view, &color, &output, raw, stream, dump_iters.as_deref(), quiet, verbose),
+            }
+            if sidecar {
+                let path = output.with_extension("json");
+                let metadata = ImageMetadata::new(&view.config(), &color.color_schemes());
+                if let Err(e) = metadata.save_json(&path) {
+                    eprintln!("Error saving \"{}\" - {e}", path.display());
+                    process::exit(1);
+                }
+            }
+        }
+        Command::ZoomSequence {
+            view,
+            color,
+            cx,
+            cy,
+            end_width,
+            frames,
+            easing,
+            output_dir,
+            video,
+            fps,
+            crf,
+            quiet,
+        } => {
+            let sequence = ZoomSequence {
+                start: view.config(),
+                center: (cx, cy),
+                end_width,
+                frames,
+                easing,
+            };
+            match video {
+                Some(video) => zoom_video(&sequence, &color, &video, fps, crf, quiet),
+                None => zoom_sequence(&sequence, &color, &output_dir, quiet),
+            }
+        }
+        Command::Animate {
+            view,
+            color,
+            zoom_into,
+            end_width,
+            easing,
+            frames,
+            delay,
+            loops,
+            output,
+            quiet,
+        } => {
+            let options = AnimationOptions {
+                delay: Duration::from_millis(delay),
+                loops,
+            };
+            let cfg = view.config();
+            match zoom_into.zip(end_width) {
+                Some(((cx, cy), end_width)) => {
+                    let sequence = ZoomSequence {
+                        start: cfg,
+                        center: (cx.into(), cy.into()),
+                        end_width,
+                        frames,
+                        easing,
+                    };
+                    animate_zoom(&sequence, &color, options, &output, quiet)
+                }
+                None => animate_palette(cfg, &color, frames, options, &output, quiet),
+            }
+        }
+        Command::Keyframes {
+            script,
+            width,
+            height,
+            output_dir,
+            video,
+            fps,
+            crf,
+            quiet,
+        } => {
+            let mut script = AnimationScript::load(&script).unwrap_or_else(|e| {
+                eprintln!("Error loading \"{}\" - {e}", script.display());
+                process::exit(1);
+            });
+            script.resolution.x = width.unwrap_or(script.resolution.x);
+            script.resolution.y = height.unwrap_or(script.resolution.y);
+            match video {
+                Some(video) => keyframes_video(&script, &video, fps, crf, quiet),
+                None => keyframes(&script, &output_dir, quiet),
+            }
+        }
+        Command::Bench {
+            view,
+            runs,
+            view_only,
+            json,
+        } => bench(&view, runs, view_only, json.as_deref()),
+        Command::Buddhabrot {
+            view,
+            samples,
+            channels,
+            anti,
+            exposure,
+            gamma,
+            seed,
+            output,
+            checkpoint,
+            quiet,
+        } => {
+            let mut buddhabrot = Buddhabrot::new(view.config(), samples);
+            if let Some(channels) = channels {
+                buddhabrot.channels = channels.try_into().unwrap_or_else(|_| {
+                    eprintln!("Error: --channels needs 3 ranges, red, green and blue");
+                    process::exit(1);
+                });
+            }
+            buddhabrot.anti = anti;
+            buddhabrot.seed = seed;
+            render_buddhabrot(&buddhabrot, exposure, gamma, &output, &checkpoint, quiet)
+        }
+        Command::Lyapunov {
+            view,
+            sequence,
+            warmup,
+            output,
+        } => {
+            let mut cfg = view.config();
+            if view.is_default() {
+                let domain = Domain {
+                    start: 2.0,
+                    end: 4.0,
+                };
+                (cfg.xdomain, cfg.ydomain) = (domain, domain);
+                cfg.domains().fit_to_aspect();
+            }
+            let lyapunov = Lyapunov {
+                view: cfg,
+                sequence,
+                warmup,
+            };
+            render_lyapunov(&lyapunov, &output)
+        }
+        Command::JuliaSheet {
+            cx,
+            cy,
+            columns,
+            rows,
+            view,
+            color,
+            no_labels,
+            output,
+            quiet,
+        } => {
+            let domain = |(start, end)| Domain { start, end };
+            let mut sheet = ContactSheet::new(domain(cx), domain(cy), columns.into(), rows.into());
+            // the flags not given keep the whole Julia sets in small cells
+            let mut cell = view.config();
+            let view_of = cell.view();
+            cell.resolution.x = view.width.map_or(sheet.cell.resolution.x, |_| cell.resolution.x);
+            cell.resolution.y = view.height.map_or(sheet.cell.resolution.y, |_| cell.resolution.y);
+            if view.is_default() {
+                (cell.xdomain, cell.ydomain) = sheet.cell.kind.default_domains();
+                cell.domains().fit_to_aspect();
+            } else {
+                cell.domains().set_view(&view_of);
+            }
+            if cell.auto_iters.is_none() && view.max_iters.is_none() {
+                cell.max_iters = sheet.cell.max_iters;
+            }
+            sheet.cell = cell;
+            sheet.labels = !no_labels;
+            render_julia_sheet(&sheet, &color, &output, quiet)
+        }
+        Command::Repl { view, color } => {
+            if !repl::run(view.config(), color.color_schemes()) {
+                process::exit(1);
+            }
+        }
+        Command::Script {
+            script,
+            view,
+            color,
+        } => {
+            if !script::run(view.config(), color.color_schemes(), &script) {
+                process::exit(1);
+            }
+        }
+        Command::Info { view } => info(&view),
+        Command::Tune { runs, output } => tune(runs, output),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render(
+    view: &ViewArgs,
+    color: &ColorArgs,
+    output: &Path,
+    raw: bool,
+    stream: bool,
+    dump: Option<&Path>,
+    quiet: bool,
+    verbose: bool,
+) {
     let t0 = SystemTime::now();
 
-    let args: Vec<_> = env::args().collect();
+    let cfg = view.config();
+    let color_schemes = color.color_schemes();
+    log::debug!("{cfg:?}");
 
-    let cfg: MandelConfig;
-    let fname: &str;
+    let t1 = t0.elapsed().unwrap().as_millis();
+    log::info!("arg parsing took {t1} ms");
 
-    if args.len() == 1 {
-        println!("Using default values.");
-        cfg = MandelConfig::new();
-	fname = "fractal.png";
-    } else if args.len() != 9 {
-        eprintln!("Error: invalid number of arguments.");
-        help();
-        process::exit(1);
-    } else {
-        let x0 = arg_parse::<f64>(&args[1], "x0");
-        let x1 = arg_parse::<f64>(&args[2], "x1");
-        let y0 = arg_parse::<f64>(&args[3], "y0");
-        let y1 = arg_parse::<f64>(&args[4], "y1");
-        let max_iters = arg_parse::<usize>(&args[5], "max_iters");
-        let resx = arg_parse::<usize>(&args[6], "resx");
-        let resy = arg_parse::<usize>(&args[7], "resy");
-	fname = &args[8];
-
-        cfg = MandelConfig {
-            xdomain: Domain { start: x0, end: x1 },
-            ydomain: Domain { start: y0, end: y1 },
-            resolution: Resolution { x: resx, y: resy },
-            threshold: 4.0,
-            max_iters: max_iters,
-        };
+    if stream {
+        if raw || color.equalize || color.relief.is_some() || dump.is_some() {
+            // they need the whole image
+            eprintln!("Error: --stream does not support --raw, --equalize, --relief or --dump-iters");
+            process::exit(1);
+        }
+        let metadata = ImageMetadata::new(&cfg, &color_schemes);
+        let complete = render_streaming(&metadata, &color_schemes, output, quiet).unwrap_or_else(|e| {
+            eprintln!("Error saving \"{}\" - {e}", output.display());
+            process::exit(1);
+        });
+        let t2 = t0.elapsed().unwrap().as_millis();
+        log::info!("overall took {t2} ms");
+        if !complete {
+            println!("==> interrupted, partial image saved to \"{}\"", output.display());
+            process::exit(interrupt::EXIT_INTERRUPTED);
+        }
+        return;
     }
-    println!("{:?}", cfg);
 
-    let t1 = t0.elapsed().unwrap().as_millis();
-    println!("==> arg parsing took {} ms", t1);
+    let (iters, stats, missing) = match mandel_with_progress(cfg, quiet) {
+        Ok(rendered) => rendered,
+        Err(e) => {
+            eprintln!("Error rendering \"{}\" - {e}", output.display());
+            process::exit(1);
+        }
+    };
 
-    let iters = mandel(cfg);
+    if !missing.is_empty() {
+        if let Err(e) = save_partial(&iters, &cfg, &color_schemes, &missing, raw, output) {
+            eprintln!("Error saving \"{}\" - {e}", output.display());
+            process::exit(1);
+        }
+        println!("==> interrupted, partial image saved to \"{}\"", output.display());
+        process::exit(interrupt::EXIT_INTERRUPTED);
+    }
 
     let t2 = t0.elapsed().unwrap().as_millis() - t1;
-    println!("==> `mandel()` took {} ms", t2);
+    log::info!("`mandel()` took {t2} ms");
+    if verbose {
+        let ms = |d: Duration| d.as_secs_f64() * 1e3;
+        log::info!(
+            "setting up the domains took {:.1} ms, the iteration {:.1} ms, {:.1} M iterations/s",
+            ms(stats.setup),
+            ms(stats.iterate),
+            stats.iterations_per_second() / 1e6
+        );
+    }
 
-    let mut color_schemes = ColorSchemes::new();
-    color_schemes.next().next().next().next().next().next().next();
-    get_image_buf(&iters, cfg.max_iters, color_schemes)
-	.save(fname).unwrap();
+    let saved = if raw {
+        save_raw(&iters, cfg.max_iters, output)
+    } else {
+        let metadata = ImageMetadata::new(&cfg, &color_schemes);
+        save_image_with_metadata(&iters, &metadata, &color_schemes, output)
+    };
+    if let Err(e) = saved {
+        eprintln!("Error saving \"{}\" - {e}", output.display());
+        process::exit(1);
+    }
 
     let t3 = t0.elapsed().unwrap().as_millis() - t2 - t1;
-    println!("==> `save_image()` took {} ms", t3);
+    log::info!("`save_image()` took {t3} ms");
+
+    if let Some(path) = dump {
+        if let Err(e) = dump_iters(&iters, cfg.max_iters, path) {
+            eprintln!("Error saving \"{}\" - {e}", path.display());
+            process::exit(1);
+        }
+        println!("==> escape data saved to \"{}\"", path.display());
+    }
 
     let t4 = t0.elapsed().unwrap().as_millis();
-    println!("==> Overall took {} ms", t4);
+    log::info!("overall took {t4} ms");
+}
+
+/// Tile size of the renders with progress, small enough for a smooth
+/// progress bar but large enough to keep the threads busy
+const PROGRESS_TILE_SIZE: usize = 256;
+
+/// Same as `mandel()`, rendering in tiles in the background to draw the
+/// progress on stderr, unless `quiet`. Also return the time of the
+/// stages of the render, and the tiles not rendered if it was
+/// interrupted, whose pixels are zero.
+fn mandel_with_progress(
+    cfg: MandelConfig,
+    quiet: bool,
+) -> Result<(IterationGrid, RenderStats, Vec<Tile>), MandelError> {
+    let job = RenderJob::start(cfg, PROGRESS_TILE_SIZE)?;
+    let progress = Progress::new("tiles", job.progress().1, quiet);
+    let mut completed = Vec::new();
+    while !job.is_finished() {
+        if interrupt::requested() && !job.is_cancelled() {
+            // the tiles in progress still complete
+            job.cancel();
+        }
+        completed.extend(job.completed_tiles());
+        progress.set(job.progress().0);
+        thread::sleep(Duration::from_millis(50));
+    }
+    completed.extend(job.completed_tiles());
+    progress.set(job.progress().0);
+    progress.finish();
+    let stats = job.stats();
+    if job.is_cancelled() {
+        let missing = tiles(cfg.resolution, PROGRESS_TILE_SIZE)
+            .into_iter()
+            .filter(|tile| !completed.contains(tile))
+            .collect();
+        return Ok((job.snapshot(), stats, missing));
+    }
+    Ok((job.wait().expect("the job is not cancelled"), stats, Vec::new()))
+}
+
+/// Save the image of an interrupted render, with the pixels of the
+/// `missing` tiles black
+fn save_partial(
+    iters: &IterationGrid,
+    cfg: &MandelConfig,
+    color_schemes: &ColorSchemes,
+    missing: &[Tile],
+    raw: bool,
+    output: &Path,
+) -> Result<(), MandelError> {
+    if raw {
+        // the missing pixels are zero, ie, black
+        return save_raw(iters, cfg.max_iters, output);
+    }
+    let mut rgb = get_rgb_buf(iters, cfg.max_iters, color_schemes)?;
+    let (width, height) = iters.image_size();
+    for tile in missing {
+        // the image goes from the top, the tiles from `ydomain.start`
+        for y in height - tile.y - tile.height..height - tile.y {
+            let start = 3 * (y * width + tile.x);
+            rgb[start..start + 3 * tile.width].fill(0);
+        }
+    }
+    image::save_buffer(output, &rgb, width as u32, height as u32, image::ColorType::Rgb8)?;
+    Ok(())
+}
+
+/// Render `cfg` in bands of rows, written to the PNG file `output` as
+/// they complete, see `mandel_streaming()`. Return `false` if it was
+/// interrupted, with the rows left black.
+fn render_streaming(
+    metadata: &ImageMetadata,
+    color_schemes: &ColorSchemes,
+    output: &Path,
+    quiet: bool,
+) -> Result<bool, Box<dyn Error>> {
+    if !output.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")) {
+        return Err("--stream only saves PNG files".into());
+    }
+    let cfg = metadata.cfg;
+    let file = BufWriter::new(File::create(output)?);
+    let (width, height) = (cfg.resolution.x as u32, cfg.resolution.y as u32);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    metadata.add_to_png(&mut encoder)?;
+    let mut writer = encoder.write_header()?.into_stream_writer()?;
+
+    let band_height = 64;
+    let progress = Progress::new("bands", cfg.resolution.y.div_ceil(band_height), quiet);
+    let mut written: Result<(), Box<dyn Error>> = Ok(());
+    let mut rows = 0;
+    let rendered = mandel_streaming(cfg, band_height, |_, band| {
+        if written.is_ok() {
+            written = get_rgb_buf(band, cfg.max_iters, color_schemes)
+                .map_err(Box::from)
+                .and_then(|buf| Ok(writer.write_all(&buf)?));
+        }
+        rows += band.image_size().1;
+        progress.inc(1);
+        interrupt::check()
+    });
+    progress.finish();
+    rendered?;
+    written?;
+    // the rows not rendered, if interrupted, are black
+    let black = vec![0; 3 * cfg.resolution.x];
+    for _ in rows..cfg.resolution.y {
+        writer.write_all(&black)?;
+    }
+    writer.finish()?;
+    Ok(rows == cfg.resolution.y)
+}
+
+/// Return the path of the tile at `row` and `col` of the image `output`
+fn tile_path(output: &Path, row: usize, col: usize) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = output.extension().unwrap_or_default().to_string_lossy();
+    output.with_file_name(format!("{stem}_tile_{row:03}_{col:03}.{ext}"))
+}
+
+/// Render the image tile by tile, each saved to its own file, see
+/// `render_tiles()`, and optionally stitch them into `output`. The tiles
+/// done are recorded in the checkpoint, if any, and skipped on resume.
+fn render_poster(
+    view: &ViewArgs,
+    color: &ColorArgs,
+    output: &Path,
+    tile_size: usize,
+    stitch: bool,
+    checkpoint_args: &CheckpointArgs,
+    quiet: bool,
+) {
+    let cfg = view.config();
+    let color_schemes = color.color_schemes();
+    log::debug!("{cfg:?}");
+
+    let t0 = SystemTime::now();
+    let all_tiles = tiles(cfg.resolution, tile_size);
+    let cols = all_tiles.iter().filter(|tile| tile.y == 0).count();
+    // in the order they are rendered, from the top
+    let all_tiles: Vec<Tile> = all_tiles.chunks(cols).rev().flatten().copied().collect();
+    // the threads do not change the tiles
+    let key = format!("tiles of {tile_size} {:?} {color:?}", MandelConfig { threads: None, ..cfg });
+    let mut checkpoint = checkpoint_args.start(Checkpoint::new(key, all_tiles.len()));
+    let progress = Progress::new("tiles", all_tiles.len(), quiet);
+    let resumed = checkpoint.units_done();
+    progress.inc(resumed);
+    let skip = checkpoint.done().to_vec();
+    let rendered = render_tiles_skipping(cfg, tile_size, &skip, |row, col, iters| {
+        let path = tile_path(output, row, col);
+        if let Err(e) = save_image(iters, cfg.max_iters, &color_schemes, &path) {
+            eprintln!("\nError saving \"{}\" - {e}", path.display());
+            process::exit(1);
+        }
+        checkpoint.mark_done(row * cols + col);
+        checkpoint_args.save(&checkpoint);
+        progress.inc(1);
+        interrupt::check()
+    });
+    progress.finish();
+    if let Err(e) = rendered {
+        eprintln!("Error rendering \"{}\" - {e}", output.display());
+        process::exit(1);
+    }
+    log::info!("{} tiles took {} ms", checkpoint.units_done() - resumed, t0.elapsed().unwrap().as_millis());
+
+    if stitch {
+        let t0 = SystemTime::now();
+        let metadata = ImageMetadata::new(&cfg, &color_schemes);
+        if let Err(e) = stitch_tiles(output, &all_tiles, cols, checkpoint.done(), &metadata) {
+            eprintln!("Error stitching \"{}\" - {e}", output.display());
+            process::exit(1);
+        }
+        log::info!("stitching took {} ms", t0.elapsed().unwrap().as_millis());
+    }
+    if checkpoint.units_done() < checkpoint.units() {
+        println!(
+            "==> interrupted, {} of {} tiles saved",
+            checkpoint.units_done(),
+            checkpoint.units()
+        );
+        process::exit(interrupt::EXIT_INTERRUPTED);
+    }
+    checkpoint_args.finish();
+}
+
+/// Stitch the tiles of `render_poster()`, `cols` per row from the top,
+/// into the PNG file `output`, one row of tiles at a time. The tiles not
+/// `done` are black.
+fn stitch_tiles(
+    output: &Path,
+    tiles: &[Tile],
+    cols: usize,
+    done: &[bool],
+    metadata: &ImageMetadata,
+) -> Result<(), Box<dyn Error>> {
+    let cfg = &metadata.cfg;
+    if !output.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")) {
+        return Err("--stitch only saves PNG files".into());
+    }
+    let file = BufWriter::new(File::create(output)?);
+    let (width, height) = (cfg.resolution.x as u32, cfg.resolution.y as u32);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    metadata.add_to_png(&mut encoder)?;
+    let mut writer = encoder.write_header()?.into_stream_writer()?;
+
+    for row in 0..tiles.len() / cols {
+        let tiles = (0..cols)
+            .map(|col| {
+                let i = row * cols + col;
+                if !done[i] {
+                    let Tile { width, height, .. } = tiles[i];
+                    return Ok(image::RgbImage::new(width as u32, height as u32));
+                }
+                Ok(image::open(tile_path(output, row, col))?.to_rgb8())
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        for y in 0..tiles[0].height() {
+            for tile in &tiles {
+                let stride = 3 * tile.width() as usize;
+                let start = y as usize * stride;
+                writer.write_all(&tile.as_raw()[start..start + stride])?;
+            }
+        }
+    }
+    writer.finish()?;
+    println!("==> tiles stitched into \"{}\"", output.display());
+    Ok(())
+}
+
+fn zoom_sequence(sequence: &ZoomSequence, color: &ColorArgs, output_dir: &Path, quiet: bool) {
+    let color_schemes = color.color_schemes();
+    if let Err(e) = std::fs::create_dir_all(output_dir) {
+        eprintln!("Error creating \"{}\" - {e}", output_dir.display());
+        process::exit(1);
+    }
+
+    // Frames are rendered in parallel, so they complete in any order
+    let progress = Progress::new("frames", sequence.frames, quiet);
+    let rendered = sequence.render(|i, iters| {
+        let fname = output_dir.join(format!("frame_{i:05}.png"));
+        // `max_iters` changes along the zoom with `auto_iters`
+        let metadata = ImageMetadata::new(&sequence.frame(i), &color_schemes);
+        let saved = save_image_with_metadata(&iters, &metadata, &color_schemes, &fname);
+        if let Err(e) = saved {
+            eprintln!("\nError saving \"{}\" - {e}", fname.display());
+            process::exit(1);
+        }
+        progress.inc(1);
+        interrupt::check()
+    });
+    progress.finish();
+    if let Err(e) = rendered {
+        eprintln!("Error rendering the zoom sequence - {e}");
+        process::exit(1);
+    }
+    if interrupt::requested() {
+        println!("==> interrupted, frames done saved to \"{}\"", output_dir.display());
+        process::exit(interrupt::EXIT_INTERRUPTED);
+    }
+    println!("==> {} frames saved to \"{}\"", sequence.frames, output_dir.display());
+}
+
+/// Save an animation of `frames` frames cycling the palette over the
+/// render of `cfg`, see `save_palette_cycle()`
+fn animate_palette(
+    cfg: MandelConfig,
+    color: &ColorArgs,
+    frames: usize,
+    options: AnimationOptions,
+    output: &Path,
+    quiet: bool,
+) {
+    let t = Instant::now();
+    let iters = match mandel_with_progress(cfg, quiet) {
+        Ok((iters, ..)) => iters,
+        Err(e) => {
+            eprintln!("Error rendering \"{}\" - {e}", output.display());
+            process::exit(1);
+        }
+    };
+    let mut color_schemes = color.color_schemes();
+    if let Err(e) = save_palette_cycle(&iters, cfg.max_iters, &mut color_schemes, frames, options, output) {
+        eprintln!("Error saving \"{}\" - {e}", output.display());
+        process::exit(1);
+    }
+    println!("==> {frames} frames saved to \"{}\" in {} ms", output.display(), t.elapsed().as_millis());
+}
+
+/// Save the frames of `sequence` as an animation, see `AnimationWriter`
+fn animate_zoom(sequence: &ZoomSequence, color: &ColorArgs, options: AnimationOptions, output: &Path, quiet: bool) {
+    let t = Instant::now();
+    let Resolution { x, y } = sequence.start.resolution;
+    let mut writer = AnimationWriter::create(output, x, y, sequence.frames, options).unwrap_or_else(|e| {
+        eprintln!("Error saving \"{}\" - {e}", output.display());
+        process::exit(1);
+    });
+    render_zoom_in_order(sequence, color, output, quiet, |rgb| Ok(writer.add_frame(rgb)?));
+    if let Err(e) = writer.finish() {
+        eprintln!("Error saving \"{}\" - {e}", output.display());
+        process::exit(1);
+    }
+    println!(
+        "==> {} frames saved to \"{}\" in {} ms",
+        sequence.frames,
+        output.display(),
+        t.elapsed().as_millis()
+    );
+}
+
+/// Encode the frames of `sequence` to the video file `output` with
+/// ffmpeg, see `VideoWriter`
+fn zoom_video(sequence: &ZoomSequence, color: &ColorArgs, output: &Path, fps: f64, crf: u8, quiet: bool) {
+    let t = Instant::now();
+    let Resolution { x, y } = sequence.start.resolution;
+    let mut writer = VideoWriter::create(output, x, y, fps, crf).unwrap_or_else(|e| {
+        eprintln!("Error saving \"{}\" - {e}", output.display());
+        process::exit(1);
+    });
+    let added = render_zoom_in_order(sequence, color, output, quiet, |rgb| Ok(writer.add_frame(rgb)?));
+    if let Err(e) = writer.finish() {
+        eprintln!("Error saving \"{}\" - {e}", output.display());
+        process::exit(1);
+    }
+    if added < sequence.frames {
+        println!("==> interrupted, {added} frames saved to \"{}\"", output.display());
+        process::exit(interrupt::EXIT_INTERRUPTED);
+    }
+    println!("==> video saved to \"{}\" in {} ms", output.display(), t.elapsed().as_millis());
+}
+
+/// Render `frames` frames in parallel with `render`, eg,
+/// `ZoomSequence::render()`, and call `add` with the colors of each one,
+/// given by `color`, in order, as soon as the ones before it are done.
+/// Return the number of frames added, fewer if interrupted. An error of
+/// `add` saving to `output` exits.
+fn render_frames_in_order<R, C, F>(frames: usize, render: R, color: C, output: &Path, quiet: bool, add: F) -> usize
+where
+    R: FnOnce(&(dyn Fn(usize, IterationGrid) -> ControlFlow<()> + Sync)) -> Result<(), MandelError>,
+    C: Fn(usize, &IterationGrid) -> Result<Vec<u8>, MandelError> + Sync,
+    F: FnMut(&[u8]) -> Result<(), Box<dyn Error>> + Send,
+{
+    // `add`, the index of the next frame to add, and the frames
+    // completed after it
+    let state = Mutex::new((add, 0, BTreeMap::new()));
+    let progress = Progress::new("frames", frames, quiet);
+    let rendered = render(&|i, iters| {
+        let added = color(i, &iters).map_err(Box::<dyn Error>::from).and_then(|rgb| {
+            let (add, next, pending) = &mut *state.lock().unwrap();
+            pending.insert(i, rgb);
+            while let Some(rgb) = pending.remove(next) {
+                add(&rgb)?;
+                *next += 1;
+            }
+            Ok(())
+        });
+        if let Err(e) = added {
+            eprintln!("\nError saving \"{}\" - {e}", output.display());
+            process::exit(1);
+        }
+        progress.inc(1);
+        interrupt::check()
+    });
+    progress.finish();
+    if let Err(e) = rendered {
+        eprintln!("Error rendering \"{}\" - {e}", output.display());
+        process::exit(1);
+    }
+    let (_, added, _) = state.into_inner().unwrap();
+    added
+}
+
+/// Render the frames of `sequence` in order, see
+/// `render_frames_in_order()`
+fn render_zoom_in_order<F>(sequence: &ZoomSequence, color: &ColorArgs, output: &Path, quiet: bool, add: F) -> usize
+where
+    F: FnMut(&[u8]) -> Result<(), Box<dyn Error>> + Send,
+{
+    let color_schemes = color.color_schemes();
+    render_frames_in_order(
+        sequence.frames,
+        |callback| sequence.render(callback),
+        // `max_iters` changes along the zoom with `auto_iters`
+        |i, iters| get_rgb_buf(iters, sequence.frame(i).max_iters, &color_schemes),
+        output,
+        quiet,
+        add,
+    )
+}
+
+/// Color schemes with the palette of a keyframe
+fn keyframe_colors(palette: &PaletteSettings) -> ColorSchemes {
+    let mut color_schemes = ColorSchemes::new();
+    palette.apply(&mut color_schemes);
+    color_schemes
+}
+
+/// Save the frames of `script` to `output_dir`, like `zoom_sequence()`
+fn keyframes(script: &AnimationScript, output_dir: &Path, quiet: bool) {
+    if let Err(e) = std::fs::create_dir_all(output_dir) {
+        eprintln!("Error creating \"{}\" - {e}", output_dir.display());
+        process::exit(1);
+    }
+
+    let frames = script.frames();
+    let progress = Progress::new("frames", frames, quiet);
+    let rendered = script.render(|i, iters| {
+        let fname = output_dir.join(format!("frame_{i:05}.png"));
+        let (cfg, palette) = script.frame(i);
+        let color_schemes = keyframe_colors(&palette);
+        let metadata = ImageMetadata::new(&cfg, &color_schemes);
+        let saved = save_image_with_metadata(&iters, &metadata, &color_schemes, &fname);
+        if let Err(e) = saved {
+            eprintln!("\nError saving \"{}\" - {e}", fname.display());
+            process::exit(1);
+        }
+        progress.inc(1);
+        interrupt::check()
+    });
+    progress.finish();
+    if let Err(e) = rendered {
+        eprintln!("Error rendering the keyframes - {e}");
+        process::exit(1);
+    }
+    if interrupt::requested() {
+        println!("==> interrupted, frames done saved to \"{}\"", output_dir.display());
+        process::exit(interrupt::EXIT_INTERRUPTED);
+    }
+    println!("==> {frames} frames saved to \"{}\"", output_dir.display());
+}
+
+/// Encode the frames of `script` to the video file `output`, like
+/// `zoom_video()`
+fn keyframes_video(script: &AnimationScript, output: &Path, fps: f64, crf: u8, quiet: bool) {
+    let t = Instant::now();
+    let Resolution { x, y } = script.resolution;
+    let mut writer = VideoWriter::create(output, x, y, fps, crf).unwrap_or_else(|e| {
+        eprintln!("Error saving \"{}\" - {e}", output.display());
+        process::exit(1);
+    });
+    let frames = script.frames();
+    let added = render_frames_in_order(
+        frames,
+        |callback| script.render(callback),
+        |i, iters| {
+            let (cfg, palette) = script.frame(i);
+            get_rgb_buf(iters, cfg.max_iters, &keyframe_colors(&palette))
+        },
+        output,
+        quiet,
+        |rgb| Ok(writer.add_frame(rgb)?),
+    );
+    if let Err(e) = writer.finish() {
+        eprintln!("Error saving \"{}\" - {e}", output.display());
+        process::exit(1);
+    }
+    if added < frames {
+        println!("==> interrupted, {added} frames saved to \"{}\"", output.display());
+        process::exit(interrupt::EXIT_INTERRUPTED);
+    }
+    println!("==> video saved to \"{}\" in {} ms", output.display(), t.elapsed().as_millis());
+}
+
+/// Standard locations of `bench`: name, centre, width of the x domain
+/// and `max_iters`
+const BENCH_LOCATIONS: [(&str, (f64, f64), f64, usize); 5] = [
+    ("full", (-0.75, 0.0), 3.5, 128),
+    ("seahorse-valley", (-0.743643887, 0.131825904), 0.01, 1000),
+    ("elephant-valley", (0.2925, 0.0143), 0.01, 1000),
+    ("spiral", (-0.7453, 0.1127), 6.5e-4, 2000),
+    ("mini-mandelbrot", (-1.7687788, 0.0017389), 2e-5, 4000),
+];
+
+/// Timings of one stage over all the runs
+#[derive(Serialize)]
+struct BenchStats {
+    mean_ms: f64,
+    median_ms: f64,
+    stddev_ms: f64,
+    runs_ms: Vec<f64>,
+}
+impl BenchStats {
+    fn new(runs_ms: Vec<f64>) -> Self {
+        let n = runs_ms.len() as f64;
+        let mean_ms = runs_ms.iter().sum::<f64>() / n;
+        let mut sorted = runs_ms.clone();
+        sorted.sort_by(f64::total_cmp);
+        let mid = sorted.len() / 2;
+        let median_ms = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        };
+        // sample standard deviation
+        let var = runs_ms.iter().map(|t| (t - mean_ms).powi(2)).sum::<f64>() / (n - 1.0).max(1.0);
+        Self {
+            mean_ms,
+            median_ms,
+            stddev_ms: var.sqrt(),
+            runs_ms,
+        }
+    }
+}
+
+/// Timings of one location, per stage: setup of the color schemes,
+/// iteration, coloring and PNG encoding
+#[derive(Serialize)]
+struct BenchLocation {
+    name: String,
+    config: MandelConfig,
+    setup: BenchStats,
+    iteration: BenchStats,
+    coloring: BenchStats,
+    encoding: BenchStats,
+}
+
+#[derive(Serialize)]
+struct BenchReport {
+    runs: usize,
+    threads: usize,
+    locations: Vec<BenchLocation>,
+}
+
+fn bench(view: &ViewArgs, runs: usize, view_only: bool, json: Option<&Path>) {
+    let runs = runs.max(1);
+    let base = view.config();
+    let locations: Vec<(String, MandelConfig)> = if view_only {
+        vec![("view".to_string(), base)]
+    } else {
+        BENCH_LOCATIONS
+            .iter()
+            .map(|&(name, (cx, cy), width, max_iters)| {
+                let mut cfg = base;
+                cfg.domains()
+                    .set_center(0.0, 0.0, width)
+                    .center_on(cx.into(), cy.into());
+                cfg.max_iters = max_iters;
+                (name.to_string(), cfg)
+            })
+            .collect()
+    };
+
+    let mut report = BenchReport {
+        runs,
+        threads: num_threads(&base),
+        locations: Vec::new(),
+    };
+    println!("==> {runs} runs, {} threads, times in ms", report.threads);
+    let color_schemes = ColorSchemes::new();
+    println!("{:<18} {:<10} {:>10} {:>10} {:>10}", "location", "stage", "mean", "median", "stddev");
+    for (name, cfg) in locations {
+        let mut times: [Vec<f64>; 4] = Default::default();
+        for _ in 0..runs {
+            match bench_run(cfg, report.threads, &color_schemes) {
+                Ok(run) => times.iter_mut().zip(run).for_each(|(stage, t)| stage.push(t)),
+                Err(e) => {
+                    eprintln!("Error rendering {name} - {e}");
+                    process::exit(1);
+                }
+            }
+        }
+        let [setup, iteration, coloring, encoding] = times.map(BenchStats::new);
+        for (stage, stats) in [
+            ("setup", &setup),
+            ("iteration", &iteration),
+            ("coloring", &coloring),
+            ("encoding", &encoding),
+        ] {
+            println!(
+                "{name:<18} {stage:<10} {:>10.2} {:>10.2} {:>10.2}",
+                stats.mean_ms, stats.median_ms, stats.stddev_ms
+            );
+        }
+        report.locations.push(BenchLocation {
+            name,
+            config: cfg,
+            setup,
+            iteration,
+            coloring,
+            encoding,
+        });
+    }
+
+    if let Some(path) = json {
+        let saved = serde_json::to_string_pretty(&report)
+            .map_err(|e| e.to_string())
+            .and_then(|s| std::fs::write(path, s).map_err(|e| e.to_string()));
+        match saved {
+            Ok(()) => println!("==> results saved to \"{}\"", path.display()),
+            Err(e) => {
+                eprintln!("Error saving \"{}\" - {e}", path.display());
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Render `cfg` once, in `threads` threads, return the time in ms of
+/// each stage of `bench`
+fn bench_run(
+    cfg: MandelConfig,
+    threads: usize,
+    color_schemes: &ColorSchemes,
+) -> Result<[f64; 4], MandelError> {
+    let ms = |t: Instant| t.elapsed().as_secs_f64() * 1e3;
+    // a new renderer, so that the setup of the domains is not cached
+    // from the last run
+    let (iters, stats) = Renderer::with_threads(threads).render_with_stats(cfg)?;
+    let setup = stats.setup.as_secs_f64() * 1e3;
+    let iteration = stats.iterate.as_secs_f64() * 1e3;
+
+    let t = Instant::now();
+    let img = get_image_buf(&iters, cfg.max_iters, color_schemes)?;
+    let coloring = ms(t);
+
+    let t = Instant::now();
+    let mut png = Cursor::new(Vec::new());
+    img.write_to(&mut png, image::ImageFormat::Png)
+        .expect("encoding to memory does not fail");
+    Ok([setup, iteration, coloring, ms(t)])
+}
+
+fn render_buddhabrot(
+    buddhabrot: &Buddhabrot,
+    exposure: f64,
+    gamma: f64,
+    output: &Path,
+    checkpoint_args: &CheckpointArgs,
+    quiet: bool,
+) {
+    let t = Instant::now();
+    let mut checkpoint = checkpoint_args.start(buddhabrot.checkpoint());
+    let progress = Progress::new("samples", buddhabrot.samples, quiet);
+    progress.inc(buddhabrot.samples_done(&checkpoint));
+    let mut saved = Instant::now();
+    let density = buddhabrot.render_resumable(
+        &mut checkpoint,
+        |samples| progress.inc(samples),
+        |checkpoint| {
+            if interrupt::requested() || saved.elapsed() >= CHECKPOINT_INTERVAL {
+                checkpoint_args.save(checkpoint);
+                saved = Instant::now();
+            }
+            interrupt::check()
+        },
+    );
+    progress.finish();
+    let density = density.unwrap_or_else(|e| {
+        eprintln!("Error rendering the Buddhabrot - {e}");
+        process::exit(1);
+    });
+    let rgb = density.to_rgb(exposure, gamma);
+    let (width, height) = (density.width as u32, density.height as u32);
+    if let Err(e) = image::save_buffer(output, &rgb, width, height, image::ColorType::Rgb8) {
+        eprintln!("Error saving \"{}\" - {e}", output.display());
+        process::exit(1);
+    }
+    if buddhabrot.samples_done(&checkpoint) < buddhabrot.samples {
+        println!(
+            "==> interrupted, Buddhabrot of {} samples saved to \"{}\"",
+            buddhabrot.samples_done(&checkpoint),
+            output.display()
+        );
+        process::exit(interrupt::EXIT_INTERRUPTED);
+    }
+    checkpoint_args.finish();
+    println!("==> Buddhabrot saved to \"{}\" in {} ms", output.display(), t.elapsed().as_millis());
+}
+
+fn render_lyapunov(lyapunov: &Lyapunov, output: &Path) {
+    let t = Instant::now();
+    let exponents = lyapunov.render().unwrap_or_else(|e| {
+        eprintln!("Error rendering the Lyapunov fractal - {e}");
+        process::exit(1);
+    });
+    let rgb = exponents.to_rgb();
+    let (width, height) = (exponents.width as u32, exponents.height as u32);
+    if let Err(e) = image::save_buffer(output, &rgb, width, height, image::ColorType::Rgb8) {
+        eprintln!("Error saving \"{}\" - {e}", output.display());
+        process::exit(1);
+    }
+    println!("==> Lyapunov fractal saved to \"{}\" in {} ms", output.display(), t.elapsed().as_millis());
+}
+
+fn render_julia_sheet(sheet: &ContactSheet, color: &ColorArgs, output: &Path, quiet: bool) {
+    let t = Instant::now();
+    let progress = Progress::new("cells", sheet.columns * sheet.rows, quiet);
+    let rgb = sheet.render(&color.color_schemes(), || progress.inc(1));
+    progress.finish();
+    let rgb = rgb.unwrap_or_else(|e| {
+        eprintln!("Error rendering the contact sheet - {e}");
+        process::exit(1);
+    });
+    let (width, height) = sheet.size();
+    if let Err(e) = image::save_buffer(output, &rgb, width as u32, height as u32, image::ColorType::Rgb8) {
+        eprintln!("Error saving \"{}\" - {e}", output.display());
+        process::exit(1);
+    }
+    println!("==> contact sheet saved to \"{}\" in {} ms", output.display(), t.elapsed().as_millis());
+}
+
+fn tune(runs: usize, output: Option<PathBuf>) {
+    let Some(path) = output.or_else(Tuning::path) else {
+        eprintln!("Error: no cache directory to save the tuning to, use --output");
+        process::exit(1);
+    };
+    println!("==> {runs} runs of each setting, throughput in M iterations/s");
+    println!("{:>8} {:>10} {:>12}", "threads", "block rows", "throughput");
+    let best = Tuning::autotune(runs, |tuning| {
+        println!(
+            "{:>8} {:>10} {:>12.1}",
+            tuning.threads,
+            tuning.block_rows,
+            tuning.iterations_per_second / 1e6
+        );
+    });
+    if let Err(e) = best.save(&path) {
+        eprintln!("Error saving \"{}\" - {e}", path.display());
+        process::exit(1);
+    }
+    println!(
+        "==> {} threads, {} rows per block saved to \"{}\"",
+        best.threads,
+        best.block_rows,
+        path.display()
+    );
+}
+
+fn info(view: &ViewArgs) {
+    let cfg = view.config();
+    // as TOML, with the info in comments, so that the output can be
+    // loaded with `--config`
+    println!("{}", cfg.to_toml());
+    println!("# Pixel size: {:e}", pixel_size(&cfg));
+    let center_zoom = cfg.view();
+    let (x, y) = cfg.center();
+    println!("# Center: {x} {y}");
+    println!("# Zoom: {:e}x", center_zoom.scale);
+    println!("# Rotation: {} degrees", center_zoom.rotation.to_degrees());
+    let scheme = view.link.as_ref().map_or(DEFAULT_COLOR_SCHEME, |link| &link.scheme);
+    println!("# Link: {}", ViewLink::new(&cfg, scheme));
+    match select_precision(&cfg) {
+        Precision::F32 => println!("# Precision: f32"),
+        Precision::F64 => println!("# Precision: f64"),
+        _ => {
+            let bits = required_precision(&cfg).unwrap_or(f64::MANTISSA_DIGITS as usize + 32);
+            println!("# Precision: {bits} bits, arbitrary precision");
+        }
+    }
+    let backends = if cfg!(feature = "gpu") {
+        "scalar, simd, gpu"
+    } else {
+        "scalar, simd"
+    };
+    println!("# Compute backends: {backends}");
+    println!("# Threads: {}", num_threads(&cfg));
 }
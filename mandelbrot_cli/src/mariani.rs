@@ -0,0 +1,169 @@
+// Mariani-Silver ////////////////////////////////////////////////////
+//                ///////////////////////////////////////////////////
+// Rectangle subdivision: only the border of a rectangle is iterated,
+// and if all of its pixels took the same number of iterations, the
+// inside is filled without iterating it. Otherwise the rectangle is cut
+// in two halves, which share the line of pixels along the cut, and so
+// on down to rectangles too small to be worth it.
+//
+// Since the Mandelbrot set is connected, a rectangle bordered by points
+// of the set is inside the set, which is where the time goes in most
+// views, so frames with large areas of the set render many times
+// faster. The same holds, mostly, for the bands of points escaping
+// after the same number of iterations, whose smooth iteration count is
+// interpolated from the border. Rectangles enclosing a whole band, eg,
+// the full set at low `max_iters`, are not detected, and filled wrong.
+//
+// The image is split in tiles that are subdivided in parallel.
+use rayon::prelude::*;
+
+use crate::render_job::tiles;
+use crate::scalar::{BigFloat, Scalar};
+use crate::{
+    linspace, required_precision, rotated_row, scalar_worker, select_precision, Escape,
+    IterationGrid, MandelConfig, Precision, RowMut, Worker,
+};
+
+/// Size of the tiles subdivided in parallel
+const TILE_SIZE: usize = 64;
+
+/// Rectangles with fewer pixels than this between their borders, along
+/// x or y, are iterated pixel by pixel
+const MIN_INSIDE: usize = 2;
+
+/// Rectangle between the pixels `(x0, y0)` and `(x1, y1)`, included
+#[derive(Clone, Copy)]
+struct Rect {
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+}
+
+/// Calculate the set with rectangle subdivision, iterating the pixels
+/// with the scalar worker of the precision of `cfg`
+pub(crate) fn mandel_mariani_silver(cfg: MandelConfig) -> IterationGrid {
+    let (formula, power) = (cfg.formula, cfg.power);
+    let bits = |t: u32| t as usize;
+    let tile = |tile_cfg: MandelConfig| match select_precision(&tile_cfg) {
+        Precision::F32 => {
+            let worker = scalar_worker::<f32>(formula, power);
+            subdivide(&tile_cfg, bits(f32::MANTISSA_DIGITS), worker)
+        }
+        Precision::F64 => {
+            let worker = scalar_worker::<f64>(formula, power);
+            subdivide(&tile_cfg, bits(f64::MANTISSA_DIGITS), worker)
+        }
+        _ => {
+            let precision =
+                required_precision(&tile_cfg).unwrap_or(bits(f64::MANTISSA_DIGITS) + 32);
+            subdivide(
+                &tile_cfg,
+                precision,
+                scalar_worker::<BigFloat>(formula, power),
+            )
+        }
+    };
+    let rendered: Vec<_> = tiles(cfg.resolution, TILE_SIZE)
+        .into_par_iter()
+        .map(|t| (t, tile(t.config(&cfg))))
+        .collect();
+    let mut grid = IterationGrid::for_config(&cfg);
+    for (t, tile_grid) in rendered {
+        grid.paste(t.x, t.y, &tile_grid);
+    }
+    grid
+}
+
+/// Calculate `cfg`, eg, a tile, with rectangle subdivision
+fn subdivide<T: Scalar>(cfg: &MandelConfig, precision: usize, worker: Worker<T>) -> IterationGrid {
+    let (width, height) = (cfg.resolution.x, cfg.resolution.y);
+    let xdomain = linspace::<T>(cfg.origin.x, cfg.xdomain, width, precision);
+    let ydomain = linspace::<T>(cfg.origin.y, cfg.ydomain, height, precision);
+    let mut grid = IterationGrid::for_config(cfg);
+    let mut rows = grid.rows_mut();
+    let mut done = vec![false; width * height];
+
+    let mut iterate = |rows: &mut [RowMut], x: usize, y: usize| {
+        if std::mem::replace(&mut done[y * width + x], true) {
+            return;
+        }
+        let (row, x0) = (rows[y].pixel_mut(x), std::slice::from_ref(&xdomain[x]));
+        match cfg.rotation {
+            0.0 => worker(row, &ydomain[y], x0, cfg, precision),
+            _ => rotated_row(worker, row, &ydomain[y], x0, cfg, precision),
+        }
+    };
+
+    let mut stack = vec![Rect {
+        x0: 0,
+        y0: 0,
+        x1: width - 1,
+        y1: height - 1,
+    }];
+    while let Some(r) = stack.pop() {
+        let (inside_x, inside_y) = (
+            (r.x1 - r.x0).saturating_sub(1),
+            (r.y1 - r.y0).saturating_sub(1),
+        );
+        if inside_x < MIN_INSIDE || inside_y < MIN_INSIDE {
+            for y in r.y0..=r.y1 {
+                for x in r.x0..=r.x1 {
+                    iterate(&mut rows, x, y);
+                }
+            }
+            continue;
+        }
+        let border = (r.x0..=r.x1)
+            .flat_map(|x| [(x, r.y0), (x, r.y1)])
+            .chain((r.y0 + 1..r.y1).flat_map(|y| [(r.x0, y), (r.x1, y)]));
+        for (x, y) in border.clone() {
+            iterate(&mut rows, x, y);
+        }
+        let iters = rows[r.y0].iters[r.x0];
+        if border.clone().all(|(x, y)| rows[y].iters[x] == iters) {
+            // the inside of `r` is not part of any other rectangle
+            fill(&mut rows, r, iters, cfg.max_iters);
+            continue;
+        }
+        // cut across the longer side, the halves share the middle line
+        if r.x1 - r.x0 >= r.y1 - r.y0 {
+            let xm = (r.x0 + r.x1) / 2;
+            stack.push(Rect { x1: xm, ..r });
+            stack.push(Rect { x0: xm, ..r });
+        } else {
+            let ym = (r.y0 + r.y1) / 2;
+            stack.push(Rect { y1: ym, ..r });
+            stack.push(Rect { y0: ym, ..r });
+        }
+    }
+    drop(rows);
+    grid
+}
+
+/// Fill the inside of `r`, whose border pixels all took `iters`
+/// iterations. The smooth iteration count of escaping points is the
+/// mean of its linear interpolations along x and along y.
+fn fill(rows: &mut [RowMut], r: Rect, iters: u32, max_iters: usize) {
+    let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+    for y in r.y0 + 1..r.y1 {
+        let ty = (y - r.y0) as f64 / (r.y1 - r.y0) as f64;
+        for x in r.x0 + 1..r.x1 {
+            let smooth = if iters as usize >= max_iters {
+                max_iters as f64
+            } else {
+                let tx = (x - r.x0) as f64 / (r.x1 - r.x0) as f64;
+                let along_x = lerp(rows[y].smooth[r.x0], rows[y].smooth[r.x1], tx);
+                let along_y = lerp(rows[r.y0].smooth[x], rows[r.y1].smooth[x], ty);
+                (along_x + along_y) / 2.0
+            };
+            rows[y].set(
+                x,
+                Escape {
+                    iters: iters as usize,
+                    smooth,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// Image metadata ////////////////////////////////////////////////////
+//                ///////////////////////////////////////////////////
+// The view of a saved image, ie, its `MandelConfig`, the name of the
+// color scheme and the version of the crate, written to the text chunks
+// of PNG files, see `save_image_with_metadata()`, or to a JSON file next
+// to the image. Reading it back reconstructs the view, so that a saved
+// image can be opened to continue exploring from it.
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::color_schemes::ColorSchemes;
+use crate::MandelConfig;
+
+/// Keyword of the text chunk with the config in TOML, see
+/// `MandelConfig::to_toml()`. The other chunks are only for people.
+const CONFIG_KEYWORD: &str = "MandelConfig";
+const SCHEME_KEYWORD: &str = "Color scheme";
+const SOFTWARE_KEYWORD: &str = "Software";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImageMetadata {
+    /// Version of `mandelbrot_cli` that saved the image
+    pub version: String,
+    /// Name of the color scheme, see `ColorSchemes::set_by_name()`
+    pub color_scheme: String,
+    pub cfg: MandelConfig,
+}
+
+impl ImageMetadata {
+    /// Metadata of an image of `cfg` colored with the current scheme of
+    /// `color_schemes`, saved by this version of the crate
+    pub fn new(cfg: &MandelConfig, color_schemes: &ColorSchemes) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            color_scheme: color_schemes.name().to_string(),
+            cfg: *cfg,
+        }
+    }
+
+    /// Return the keywords and texts of the PNG text chunks
+    fn text_chunks(&self) -> Vec<(&'static str, String)> {
+        let cfg = &self.cfg;
+        let (xdomain, ydomain) = cfg.absolute_domains();
+        vec![
+            (
+                SOFTWARE_KEYWORD,
+                format!("{} {}", env!("CARGO_PKG_NAME"), self.version),
+            ),
+            (
+                "Domain",
+                format!(
+                    "x ({}, {}), y ({}, {})",
+                    xdomain.start, xdomain.end, ydomain.start, ydomain.end
+                ),
+            ),
+            ("Max iters", cfg.max_iters.to_string()),
+            (SCHEME_KEYWORD, self.color_scheme.clone()),
+            (CONFIG_KEYWORD, cfg.to_toml()),
+        ]
+    }
+
+    /// Add the metadata to the text chunks of the PNG `encoder`, before
+    /// its header is written
+    pub fn add_to_png<W: Write>(&self, encoder: &mut png::Encoder<W>) -> io::Result<()> {
+        for (keyword, text) in self.text_chunks() {
+            encoder.add_text_chunk(keyword.to_string(), text)?;
+        }
+        Ok(())
+    }
+
+    /// Read the metadata of the PNG image at `path`, or of a `.json`
+    /// file saved by `save_json()`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+        {
+            return Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?);
+        }
+        let reader = png::Decoder::new(BufReader::new(File::open(path)?)).read_info()?;
+        let text = |keyword: &str| {
+            let chunks = &reader.info().uncompressed_latin1_text;
+            chunks
+                .iter()
+                .find(|chunk| chunk.keyword == keyword)
+                .map(|chunk| chunk.text.clone())
+        };
+        let Some(cfg) = text(CONFIG_KEYWORD) else {
+            return Err(format!("no view saved in {}", path.display()).into());
+        };
+        let software = text(SOFTWARE_KEYWORD).unwrap_or_default();
+        Ok(Self {
+            version: software.rsplit(' ').next().unwrap_or_default().to_string(),
+            color_scheme: text(SCHEME_KEYWORD).unwrap_or_default(),
+            cfg: MandelConfig::from_toml(&cfg)?,
+        })
+    }
+
+    /// Save the metadata to the JSON file `path`, eg, next to an image in
+    /// a format without text chunks
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).expect("the metadata is valid JSON");
+        std::fs::write(path, json + "\n")
+    }
+}
@@ -0,0 +1,214 @@
+// High-precision origin /////////////////////////////////////////////
+//                       ////////////////////////////////////////////
+// The domains are `f64`, which tell two points apart down to about 1e-16
+// of their magnitude, so the domains of deeper views would have no width
+// left and all their pixels the same point. The domains of deep views
+// are instead relative to an `Origin`, kept in high precision: the pixel
+// at `(x, y)` of the domains is at `origin + (x, y)`, rotated, and the
+// domains only hold the small offsets from the origin, which `f64`
+// resolves at any depth.
+//
+// Each `Coordinate` of the origin is the sum of `PARTS` `f64`, each one
+// below the rounding error of the ones before, ie, about 200 bits, for
+// views down to about 1e-60 wide. It stays `Copy`, as `MandelConfig`,
+// and is saved as a decimal string.
+//
+// `DomainManager` moves the centre of deep views to the origin as they
+// zoom in, and back to the domains as they zoom out, so that the origin
+// of views `f64` resolves is zero.
+use std::fmt;
+use std::str::FromStr;
+
+use dashu_float::round::mode::{HalfAway, Zero};
+use dashu_float::DBig;
+use serde::{Deserialize, Serialize};
+
+use crate::scalar::{BigFloat, Scalar};
+
+/// Number of `f64` of a `Coordinate`
+const PARTS: usize = 4;
+/// Bits of the arithmetic on coordinates, beyond those of the parts
+const BITS: usize = 64 * PARTS;
+/// Views with pixels smaller than this fraction of the magnitude of the
+/// centre are relative to their origin, see `DomainManager`
+pub(crate) const DEEP_STEP: f64 = 1e-10;
+
+/// Coordinate of the plane in high precision, the sum of its parts,
+/// largest first
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Coordinate([f64; PARTS]);
+impl Coordinate {
+    /// Return the coordinate nearest to `value`
+    pub fn from_big(value: &BigFloat) -> Self {
+        let mut parts = [0.0; PARTS];
+        let mut rest = value.clone();
+        for part in &mut parts {
+            *part = Scalar::to_f64(&rest);
+            if *part == 0.0 {
+                break;
+            }
+            rest -= BigFloat::from_f64(*part, BITS);
+        }
+        Self(parts)
+    }
+
+    /// Return the coordinate in `T`, with `precision` bits for
+    /// `BigFloat`
+    pub fn to_scalar<T: Scalar>(&self, precision: usize) -> T {
+        // the smallest parts first, so that `f64` rounds the sum once
+        self.0
+            .iter()
+            .rev()
+            .fold(T::from_f64(0.0, precision), |sum, &part| {
+                sum + T::from_f64(part, precision)
+            })
+    }
+
+    /// Return the coordinate rounded to `f64`
+    pub fn to_f64(&self) -> f64 {
+        self.to_scalar(f64::MANTISSA_DIGITS as usize)
+    }
+
+    /// Return the coordinate in a `BigFloat` of the precision of the parts
+    pub fn to_big(&self) -> BigFloat {
+        self.to_scalar(BITS)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0[0] == 0.0
+    }
+}
+impl From<f64> for Coordinate {
+    fn from(value: f64) -> Self {
+        let mut parts = [0.0; PARTS];
+        parts[0] = value;
+        Self(parts)
+    }
+}
+impl fmt::Display for Coordinate {
+    /// Format the coordinate in decimal, with the digits its parts
+    /// resolve, eg, `-0.743643887037158704752191506114774`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let last = self.0.iter().rposition(|&p| p != 0.0).unwrap_or(0);
+        if last == 0 {
+            return write!(f, "{}", self.0[0]);
+        }
+        let bits = self.0[0].abs().log2() - self.0[last].abs().log2() + f64::MANTISSA_DIGITS as f64;
+        // the last part is rounded, so one digit less
+        let digits = (bits * std::f64::consts::LOG10_2).floor() as usize - 1;
+        let decimal = self
+            .to_big()
+            .with_rounding::<HalfAway>()
+            .with_base_and_precision::<10>(digits);
+        let decimal = decimal.value().to_string();
+        match decimal.contains('.') && !decimal.contains('e') {
+            true => write!(f, "{}", decimal.trim_end_matches('0')),
+            false => write!(f, "{decimal}"),
+        }
+    }
+}
+impl FromStr for Coordinate {
+    type Err = String;
+    /// Parse a decimal number, with as many digits as the parts hold.
+    /// The shortest decimal of a `f64`, as `Display` writes it, is that
+    /// `f64`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let decimal =
+            DBig::from_str(s.trim()).map_err(|_| format!("expected a number, got \"{s}\""))?;
+        if let Ok(value) = s.trim().parse::<f64>() {
+            if DBig::from_str(&value.to_string()).is_ok_and(|shortest| shortest == decimal) {
+                return Ok(value.into());
+            }
+        }
+        let value = decimal.with_base_and_precision::<2>(BITS).value();
+        // rounded to the nearest, so that those beyond `f64::MAX` are
+        // infinite
+        if !value.to_f64().value().is_finite() {
+            return Err(format!("\"{s}\" is beyond the range of f64"));
+        }
+        Ok(Self::from_big(&value.with_rounding::<Zero>()))
+    }
+}
+impl From<Coordinate> for String {
+    fn from(c: Coordinate) -> Self {
+        c.to_string()
+    }
+}
+impl TryFrom<String> for Coordinate {
+    type Error = String;
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Point of the domains, in high precision, their `(0, 0)` is at, see
+/// `origin` in `MandelConfig`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Origin {
+    pub x: Coordinate,
+    pub y: Coordinate,
+}
+impl Origin {
+    pub fn is_zero(&self) -> bool {
+        self.x.is_zero() && self.y.is_zero()
+    }
+
+    /// Return the origin rounded to `f64`
+    pub fn to_f64(&self) -> (f64, f64) {
+        (self.x.to_f64(), self.y.to_f64())
+    }
+
+    /// Return the origin moved by `(dx, dy)`
+    pub fn shifted(&self, dx: f64, dy: f64) -> Self {
+        let shift = |c: &Coordinate, d: f64| {
+            Coordinate::from_big(&(c.to_big() + BigFloat::from_f64(d, BITS)))
+        };
+        Self {
+            x: shift(&self.x, dx),
+            y: shift(&self.y, dy),
+        }
+    }
+
+    /// Return the difference from `other` to the origin, in `f64`
+    pub fn offset_from(&self, other: &Origin) -> (f64, f64) {
+        let diff = |a: &Coordinate, b: &Coordinate| Scalar::to_f64(&(a.to_big() - b.to_big()));
+        (diff(&self.x, &other.x), diff(&self.y, &other.y))
+    }
+
+    /// Return the point of the plane at `(x, y)` of the domains, rotated
+    /// by `rotation`, see `MandelConfig::to_plane()`
+    pub fn to_plane(&self, (x, y): (f64, f64), rotation: f64) -> (Coordinate, Coordinate) {
+        let big = |v: f64| BigFloat::from_f64(v, BITS);
+        let (x, y) = (self.x.to_big() + big(x), self.y.to_big() + big(y));
+        if rotation == 0.0 {
+            return (Coordinate::from_big(&x), Coordinate::from_big(&y));
+        }
+        let (sin, cos) = rotation.sin_cos();
+        let (sin, cos) = (big(sin), big(cos));
+        (
+            Coordinate::from_big(&(x.clone() * cos.clone() - y.clone() * sin.clone())),
+            Coordinate::from_big(&(x * sin + y * cos)),
+        )
+    }
+
+    /// Return the origin at the point `(x, y)` of the plane, in domains
+    /// rotated by `rotation`, the inverse of `to_plane()`
+    pub fn from_plane(x: &Coordinate, y: &Coordinate, rotation: f64) -> Self {
+        if rotation == 0.0 {
+            return Self { x: *x, y: *y };
+        }
+        // the inverse of the rotation by the rounded sine and cosine of
+        // `to_plane()`, so that the point goes back exactly
+        let big = |v: f64| BigFloat::from_f64(v, BITS);
+        let (sin, cos) = rotation.sin_cos();
+        let (sin, cos) = (big(sin), big(cos));
+        let norm = sin.clone() * sin.clone() + cos.clone() * cos.clone();
+        let (sin, cos) = (sin / norm.clone(), cos / norm);
+        let (x, y) = (x.to_big(), y.to_big());
+        Self {
+            x: Coordinate::from_big(&(x.clone() * cos.clone() + y.clone() * sin.clone())),
+            y: Coordinate::from_big(&(y * cos - x * sin)),
+        }
+    }
+}
@@ -0,0 +1,253 @@
+// Perturbation //////////////////////////////////////////////////////
+//              /////////////////////////////////////////////////////
+// For deep zooms, iterating every pixel in arbitrary precision is
+// very slow. Instead, one reference orbit `Z` is iterated in high
+// precision and, for each pixel, only its difference `d` to the
+// reference, which is small enough to be iterated in `f64`:
+//
+//    z = Z + d
+//    d' = 2 Z d + d^2 + dc
+//
+// The series approximation skips the first iterations of `d`
+// altogether, while the cubic term remains negligible:
+//
+//    d = A dc + B dc^2 + C dc^3
+//
+// Pixels where `z` gets much smaller than `Z` lose precision (glitch,
+// Pauldelbrot's criterion), so they are iterated again against a new
+// reference picked among them.
+//
+// The reference is seeded from the origin of the domains, in high
+// precision, so that deep views keep their pixels apart, and only the
+// offsets of the pixels from it are `f64`.
+use rayon::prelude::*;
+use std::ops::{Add, Mul};
+
+use crate::origin::{Coordinate, Origin};
+use crate::scalar::{BigFloat, Scalar};
+use crate::{required_precision, Escape, FractalKind, IterationGrid, MandelConfig};
+
+/// Maximum number of reference orbits per frame. Pixels still
+/// glitched after that keep their approximated value.
+const MAX_REFERENCES: usize = 32;
+/// A pixel is glitched when `|z|^2 < GLITCH_TOLERANCE * |Z|^2`
+const GLITCH_TOLERANCE: f64 = 1e-6;
+/// The series approximation is valid while the cubic term is below
+/// this fraction of the linear term
+const SERIES_TOLERANCE: f64 = 1e-8;
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+    fn norm_sqr(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+    fn abs(self) -> f64 {
+        self.norm_sqr().sqrt()
+    }
+}
+impl Add for Complex {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+impl Add<f64> for Complex {
+    type Output = Self;
+    fn add(self, rhs: f64) -> Self {
+        Self::new(self.re + rhs, self.im)
+    }
+}
+impl Mul for Complex {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+impl Mul<f64> for Complex {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self {
+        Self::new(self.re * rhs, self.im * rhs)
+    }
+}
+
+/// Maps pixel indices to the complex plane: `x = origin.x + x0 + dx * i`,
+/// the origin in high precision, see `Origin`, and the offsets from it in
+/// `f64`
+struct Grid {
+    origin: Origin,
+    x0: f64,
+    y0: f64,
+    dx: f64,
+    dy: f64,
+}
+
+/// Reference orbit and its series approximation coefficients
+struct Reference {
+    // Pixel coordinates of the reference point. The difference of any
+    // pixel to the reference is then exact in `f64`.
+    px: f64,
+    py: f64,
+    // `Z_n` for `n` in `0..=max_iters`, or until the reference escapes
+    orbit: Vec<Complex>,
+    // Number of iterations skipped by the series approximation, and
+    // the coefficients `A`, `B`, `C` at that iteration
+    skip: usize,
+    series: [Complex; 3],
+}
+impl Reference {
+    /// Iterate the orbit of pixel `(px, py)` in `precision` bits. If
+    /// `radius` is given, also compute the series approximation valid
+    /// for all pixels within `radius` of the reference.
+    fn new(
+        cfg: &MandelConfig,
+        grid: &Grid,
+        (px, py): (f64, f64),
+        precision: usize,
+        radius: Option<f64>,
+    ) -> Self {
+        let big = |v: f64| BigFloat::from_f64(v, precision);
+        let origin = |c: Coordinate| c.to_scalar::<BigFloat>(precision);
+        let rx = origin(grid.origin.x) + big(grid.x0) + big(grid.dx) * big(px);
+        let ry = origin(grid.origin.y) + big(grid.y0) + big(grid.dy) * big(py);
+        let (mut zx, mut zy, cx, cy) = match cfg.kind {
+            FractalKind::Mandelbrot => (big(0.0), big(0.0), rx, ry),
+            FractalKind::Julia { cx, cy } => (rx, ry, big(cx), big(cy)),
+        };
+        let two = big(2.0);
+
+        let mut orbit = Vec::with_capacity(cfg.max_iters + 1);
+        loop {
+            let (xx, yy) = (zx.clone() * zx.clone(), zy.clone() * zy.clone());
+            orbit.push(Complex::new(Scalar::to_f64(&zx), Scalar::to_f64(&zy)));
+            if Scalar::to_f64(&(xx.clone() + yy.clone())) > cfg.threshold
+                || orbit.len() > cfg.max_iters
+            {
+                break;
+            }
+            let xtmp = xx - yy + cx.clone();
+            zy = two.clone() * zx * zy + cy.clone();
+            zx = xtmp;
+        }
+
+        let mut skip = 0;
+        let mut series = [Complex::default(); 3];
+        if let Some(r) = radius {
+            let bailout = cfg.threshold.sqrt();
+            for n in 0..orbit.len() - 1 {
+                let [a, b, c] = series;
+                let z2 = orbit[n] * 2.0;
+                let a1 = z2 * a + 1.0;
+                let b1 = z2 * b + a * a;
+                let c1 = z2 * c + a * b * 2.0;
+                // Stop when the cubic term is no longer negligible, or
+                // when pixels could escape within the skipped iterations
+                if c1.abs() * r * r > SERIES_TOLERANCE * a1.abs()
+                    || orbit[n + 1].abs() + a1.abs() * r > bailout
+                {
+                    break;
+                }
+                series = [a1, b1, c1];
+                skip = n + 1;
+            }
+        }
+
+        Self {
+            px,
+            py,
+            orbit,
+            skip,
+            series,
+        }
+    }
+
+    /// Iterate pixel `(i, j)`. Return its escape data and whether it is
+    /// glitched, in which case the escape data is only approximated.
+    fn iterate(&self, cfg: &MandelConfig, grid: &Grid, (i, j): (usize, usize)) -> (Escape, bool) {
+        let d0 = Complex::new(
+            grid.dx * (i as f64 - self.px),
+            grid.dy * (j as f64 - self.py),
+        );
+        let (mut dz, dc, mut n) = match cfg.kind {
+            FractalKind::Mandelbrot => {
+                let [a, b, c] = self.series;
+                (a * d0 + b * d0 * d0 + c * d0 * d0 * d0, d0, self.skip)
+            }
+            FractalKind::Julia { .. } => (d0, Complex::default(), 0),
+        };
+        loop {
+            let zn = self.orbit[n];
+            let z = zn + dz;
+            let norm = z.norm_sqr();
+            if norm > cfg.threshold || n >= cfg.max_iters {
+                return (Escape::new(n, norm, cfg.max_iters, cfg.threshold), false);
+            }
+            // Glitched, or the reference escaped before this pixel
+            if norm < GLITCH_TOLERANCE * zn.norm_sqr() || n + 1 >= self.orbit.len() {
+                return (Escape::new(n, norm, cfg.max_iters, cfg.threshold), true);
+            }
+            dz = zn * dz * 2.0 + dz * dz + dc;
+            n += 1;
+        }
+    }
+}
+
+/// Calculate the set with perturbation. Same output as `mandel()`.
+pub(crate) fn mandel_perturbation(cfg: MandelConfig) -> IterationGrid {
+    let (resx, resy) = (cfg.resolution.x, cfg.resolution.y);
+    let grid = Grid {
+        origin: cfg.origin,
+        x0: cfg.xdomain.start,
+        y0: cfg.ydomain.start,
+        dx: (cfg.xdomain.end - cfg.xdomain.start) / (resx - 1) as f64,
+        dy: (cfg.ydomain.end - cfg.ydomain.start) / (resy - 1) as f64,
+    };
+    let precision = required_precision(&cfg).unwrap_or(f64::MANTISSA_DIGITS as usize);
+
+    let mut iters = IterationGrid::new(resx, resy);
+    let mut pending: Vec<(usize, usize)> = (0..resy)
+        .flat_map(|j| (0..resx).map(move |i| (i, j)))
+        .collect();
+
+    // The first reference is the centre of the image, and the series
+    // approximation must hold up to its corners
+    let mut pref = ((resx - 1) as f64 / 2.0, (resy - 1) as f64 / 2.0);
+    let mut radius = match cfg.kind {
+        FractalKind::Mandelbrot => Some((grid.dx * pref.0).hypot(grid.dy * pref.1).abs()),
+        FractalKind::Julia { .. } => None,
+    };
+    for _ in 0..MAX_REFERENCES {
+        let reference = Reference::new(&cfg, &grid, pref, precision, radius);
+        let results: Vec<_> = pending
+            .par_iter()
+            .map(|&ij| (ij, reference.iterate(&cfg, &grid, ij)))
+            .collect();
+        pending.clear();
+        for ((i, j), (escape, glitched)) in results {
+            iters.set(i, j, escape);
+            if glitched {
+                pending.push((i, j));
+            }
+        }
+        if pending.is_empty() {
+            break;
+        }
+        // Next reference is the glitched pixel that iterated the most,
+        // since its orbit is valid for more of the other glitched pixels.
+        // The reference pixel itself is never glitched, so every pass
+        // makes progress.
+        let &(i, j) = pending.iter().max_by_key(|&&(i, j)| iters.get(i, j).iters).unwrap();
+        pref = (i as f64, j as f64);
+        radius = None;
+    }
+    iters
+}
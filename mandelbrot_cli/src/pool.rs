@@ -0,0 +1,127 @@
+// Thread pools //////////////////////////////////////////////////////
+//              /////////////////////////////////////////////////////
+// The renders run in the global rayon pool, with one thread per core,
+// unless `MandelConfig::threads`, the `MANDELBROT_THREADS` environment
+// variable or the tuning of the machine, see `crate::tune`, ask for
+// another number of threads. The pool of each number
+// of threads is built on first use and kept for the following renders,
+// so the interactive GUI does not pay for spawning threads every frame.
+//
+// Within a render, the rows are handed out to the threads in small
+// blocks from a shared counter, see `for_each_block()`. The rows through
+// the set take `max_iters` iterations per pixel and the ones outside a
+// few, so splitting the image in one large share per thread leaves most
+// threads idle while the one with the set finishes. The number of rows
+// per block can be tuned too.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::MandelConfig;
+
+/// Environment variable with the number of threads of the renders whose
+/// config does not set `threads`
+pub const THREADS_ENV: &str = "MANDELBROT_THREADS";
+
+static POOLS: Mutex<Vec<(usize, Arc<ThreadPool>)>> = Mutex::new(Vec::new());
+
+/// Number of threads of the tuning, 0 for none, see `set_tuned()`
+static TUNED_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Rows per block of `for_each_block()` for the renders, see
+/// `block_rows()`
+static BLOCK_ROWS: AtomicUsize = AtomicUsize::new(DEFAULT_BLOCK_ROWS);
+
+/// Default rows per block, without tuning
+pub(crate) const DEFAULT_BLOCK_ROWS: usize = 4;
+
+/// Return the number of threads asked for by `cfg`, the environment or
+/// the tuning, `None` for the default of one per core
+pub(crate) fn requested_threads(cfg: &MandelConfig) -> Option<usize> {
+    // 0 in the config or the environment counts as not set
+    cfg.threads
+        .filter(|&n| n > 0)
+        .or_else(|| std::env::var(THREADS_ENV).ok()?.trim().parse().ok())
+        .filter(|&n| n > 0)
+        .or_else(|| Some(TUNED_THREADS.load(Ordering::Relaxed)).filter(|&n| n > 0))
+}
+
+/// Render with `threads` threads, unless a config or the environment
+/// ask for others, and blocks of `block_rows` rows
+pub(crate) fn set_tuned(threads: usize, block_rows: usize) {
+    TUNED_THREADS.store(threads, Ordering::Relaxed);
+    set_block_rows(block_rows);
+}
+
+pub(crate) fn set_block_rows(block_rows: usize) {
+    BLOCK_ROWS.store(block_rows.max(1), Ordering::Relaxed);
+}
+
+/// Return the number of rows per block of the renders
+pub(crate) fn block_rows() -> usize {
+    BLOCK_ROWS.load(Ordering::Relaxed)
+}
+
+/// Return the number of threads the renders of `cfg` run on
+pub fn num_threads(cfg: &MandelConfig) -> usize {
+    requested_threads(cfg).unwrap_or_else(rayon::current_num_threads)
+}
+
+/// Run `f` in the thread pool of `cfg`, building it on first use
+pub(crate) fn install<R, F>(cfg: &MandelConfig, f: F) -> R
+where
+    R: Send,
+    F: FnOnce() -> R + Send,
+{
+    match requested_threads(cfg) {
+        Some(threads) => get(threads).install(f),
+        None => f(),
+    }
+}
+
+/// Return the pool of `threads` threads, building it on first use
+pub(crate) fn get(threads: usize) -> Arc<ThreadPool> {
+    let mut pools = POOLS.lock().unwrap();
+    if let Some((_, pool)) = pools.iter().find(|(n, _)| *n == threads) {
+        return Arc::clone(pool);
+    }
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|i| format!("mandelbrot-{i}"))
+        .build()
+        .expect("failed to spawn the render threads");
+    log::debug!("pool of {threads} render threads built");
+    let pool = Arc::new(pool);
+    pools.push((threads, Arc::clone(&pool)));
+    pool
+}
+
+/// Call `f` with every item of `items`, in parallel in the current pool.
+/// Each thread takes the next block of `block` items from a shared
+/// counter until there are none left, so the threads finish together
+/// whatever the cost of each item.
+pub(crate) fn for_each_block<I, F>(items: Vec<I>, block: usize, f: F)
+where
+    I: Send,
+    F: Fn(I) + Sync,
+{
+    let mut items = items.into_iter();
+    let blocks: Vec<Mutex<Vec<I>>> = std::iter::from_fn(|| {
+        let items: Vec<I> = items.by_ref().take(block.max(1)).collect();
+        (!items.is_empty()).then(|| Mutex::new(items))
+    })
+    .collect();
+    let next = AtomicUsize::new(0);
+    // one job per thread, the jobs not picked up by the other threads,
+    // eg, busy with another render, end immediately on the caller's
+    (0..rayon::current_num_threads().min(blocks.len()))
+        .into_par_iter()
+        .for_each(|_| {
+            while let Some(block) = blocks.get(next.fetch_add(1, Ordering::Relaxed)) {
+                let items = std::mem::take(&mut *block.lock().unwrap());
+                items.into_iter().for_each(&f);
+            }
+        });
+}
@@ -0,0 +1,102 @@
+// Progress //////////////////////////////////////////////////////////
+//          /////////////////////////////////////////////////////////
+// Progress bar of the CLI, drawn on one line of stderr, so that it
+// does not mix with the output, with the estimated time remaining from
+// the average time per step so far. Steps are tiles, bands of rows or
+// frames, and may complete in any thread.
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Time between redraws of the bar
+const REFRESH: Duration = Duration::from_millis(100);
+/// Width of the bar in characters
+const BAR_WIDTH: usize = 30;
+
+pub struct Progress {
+    label: &'static str,
+    total: usize,
+    done: AtomicUsize,
+    start: Instant,
+    quiet: bool,
+    // time the bar was last drawn, the lock also keeps the threads from
+    // drawing at the same time
+    last_draw: Mutex<Option<Instant>>,
+}
+impl Progress {
+    /// Progress of `total` steps, eg, "tiles". Nothing is drawn if
+    /// `quiet`.
+    pub fn new(label: &'static str, total: usize, quiet: bool) -> Self {
+        Self {
+            label,
+            total: total.max(1),
+            done: AtomicUsize::new(0),
+            start: Instant::now(),
+            quiet,
+            last_draw: Mutex::new(None),
+        }
+    }
+
+    /// Add `n` steps done
+    pub fn inc(&self, n: usize) {
+        let done = self.done.fetch_add(n, Ordering::Relaxed) + n;
+        self.set(done);
+    }
+
+    /// Set the number of steps done
+    pub fn set(&self, done: usize) {
+        self.done.store(done, Ordering::Relaxed);
+        if self.quiet {
+            return;
+        }
+        let mut last_draw = self.last_draw.lock().unwrap();
+        if last_draw.is_some_and(|t| t.elapsed() < REFRESH) && done < self.total {
+            return;
+        }
+        *last_draw = Some(Instant::now());
+        self.draw(done);
+    }
+
+    /// End the line of the bar
+    pub fn finish(&self) {
+        if !self.quiet {
+            self.draw(self.done.load(Ordering::Relaxed));
+            eprintln!();
+        }
+    }
+
+    fn draw(&self, done: usize) {
+        let done = done.min(self.total);
+        let filled = BAR_WIDTH * done / self.total;
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let eta = match done {
+            0 => "?".to_string(),
+            _ => format_secs(elapsed * (self.total - done) as f64 / done as f64),
+        };
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(
+            stderr,
+            "\r[{}{}] {:3}% {done}/{} {}, ETA {eta}   ",
+            "#".repeat(filled),
+            " ".repeat(BAR_WIDTH - filled),
+            100 * done / self.total,
+            self.total,
+            self.label,
+        );
+        let _ = stderr.flush();
+    }
+}
+
+/// Format `secs` as eg `1h02m03s`, `2m03s` or `3s`
+fn format_secs(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}h{m:02}m{s:02}s")
+    } else if m > 0 {
+        format!("{m}m{s:02}s")
+    } else {
+        format!("{s}s")
+    }
+}
@@ -0,0 +1,268 @@
+// Render jobs ///////////////////////////////////////////////////////
+//             //////////////////////////////////////////////////////
+// A `RenderJob` renders the image in the background, split in tiles
+// that are processed in parallel. The job can be cancelled, in which
+// case the tiles not yet started are skipped, and it reports the
+// tiles as they complete, and the time of the render, see `RenderStats`.
+use rayon::prelude::*;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+use crate::{
+    mandel_unchecked, pool, render_timed, Domain, IterationGrid, MandelConfig, MandelError,
+    RenderStats, Resolution,
+};
+
+/// Rectangle of pixels of the image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+impl Tile {
+    /// Return the config to render only this tile of `cfg`
+    pub fn config(&self, cfg: &MandelConfig) -> MandelConfig {
+        let stepx = (cfg.xdomain.end - cfg.xdomain.start) / (cfg.resolution.x - 1) as f64;
+        let stepy = (cfg.ydomain.end - cfg.ydomain.start) / (cfg.resolution.y - 1) as f64;
+        let mut tile_cfg = *cfg;
+        tile_cfg.xdomain = Domain {
+            start: cfg.xdomain.start + stepx * self.x as f64,
+            end: cfg.xdomain.start + stepx * (self.x + self.width - 1) as f64,
+        };
+        tile_cfg.ydomain = Domain {
+            start: cfg.ydomain.start + stepy * self.y as f64,
+            end: cfg.ydomain.start + stepy * (self.y + self.height - 1) as f64,
+        };
+        tile_cfg.resolution = Resolution {
+            x: self.width,
+            y: self.height,
+        };
+        tile_cfg
+    }
+}
+
+/// Split `n` pixels in ranges `(start, length)` of `size` pixels. A
+/// range needs at least 2 pixels to define a domain, so a last range of
+/// 1 pixel is merged into the previous one.
+pub(crate) fn split(n: usize, size: usize) -> Vec<(usize, usize)> {
+    let size = size.max(2);
+    let mut ranges: Vec<(usize, usize)> = (0..n)
+        .step_by(size)
+        .map(|start| (start, size.min(n - start)))
+        .collect();
+    if ranges.len() > 1 && ranges[ranges.len() - 1].1 < 2 {
+        let (_, len) = ranges.pop().unwrap();
+        ranges.last_mut().unwrap().1 += len;
+    }
+    ranges
+}
+
+/// Split the image in tiles of up to `tile_size` by `tile_size` pixels
+pub fn tiles(resolution: Resolution, tile_size: usize) -> Vec<Tile> {
+    let xs = split(resolution.x, tile_size);
+    split(resolution.y, tile_size)
+        .into_iter()
+        .flat_map(|(y, height)| {
+            xs.iter().map(move |&(x, width)| Tile {
+                x,
+                y,
+                width,
+                height,
+            })
+        })
+        .collect()
+}
+
+/// Render `cfg` one tile of up to `tile_size` by `tile_size` pixels at a
+/// time, calling `callback` with the row and column of each tile and its
+/// grid. Only one tile is held in memory at a time, for images too large
+/// for `mandel()`, eg, posters. The tiles go row by row from the top-left
+/// corner of the image, ie, row 0 is at `ydomain.end`.
+pub fn render_tiles<F>(cfg: MandelConfig, tile_size: usize, mut callback: F) -> Result<(), MandelError>
+where
+    F: FnMut(usize, usize, &IterationGrid),
+{
+    render_tiles_skipping(cfg, tile_size, &[], |row, col, grid| {
+        callback(row, col, grid);
+        ControlFlow::Continue(())
+    })
+}
+
+/// Same as `render_tiles()`, skipping the tiles whose index, in the
+/// order they are rendered, is `true` in `skip`, eg, the tiles done
+/// before an interruption, see `Checkpoint`. The tiles left are not
+/// rendered once `callback` breaks, eg, on Ctrl-C.
+pub fn render_tiles_skipping<F>(
+    cfg: MandelConfig,
+    tile_size: usize,
+    skip: &[bool],
+    mut callback: F,
+) -> Result<(), MandelError>
+where
+    F: FnMut(usize, usize, &IterationGrid) -> ControlFlow<()>,
+{
+    cfg.validate()?;
+    let xs = split(cfg.resolution.x, tile_size);
+    let ys = split(cfg.resolution.y, tile_size);
+    for (row, &(y, height)) in ys.iter().rev().enumerate() {
+        for (col, &(x, width)) in xs.iter().enumerate() {
+            if skip.get(row * xs.len() + col).copied().unwrap_or(false) {
+                continue;
+            }
+            let tile = Tile {
+                x,
+                y,
+                width,
+                height,
+            };
+            if callback(row, col, &mandel_unchecked(tile.config(&cfg))).is_break() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Render running in a background thread. Dropping the job cancels it.
+pub struct RenderJob {
+    cancelled: Arc<AtomicBool>,
+    tiles_done: Arc<AtomicUsize>,
+    tiles_total: usize,
+    grid: Arc<Mutex<IterationGrid>>,
+    stats: Arc<Mutex<RenderStats>>,
+    completed: mpsc::Receiver<Tile>,
+    handle: Option<JoinHandle<()>>,
+}
+impl RenderJob {
+    /// Start rendering `cfg` in tiles of `tile_size` by `tile_size` pixels
+    pub fn start(cfg: MandelConfig, tile_size: usize) -> Result<Self, MandelError> {
+        let tiles = tiles(cfg.resolution, tile_size);
+        Self::start_tiles(cfg, tiles, IterationGrid::for_config(&cfg))
+    }
+
+    /// Start rendering only `tiles` of `cfg` into `grid`, eg, the tiles
+    /// missing from a `TileCache`, see `TileCache::lookup`
+    pub fn start_tiles(cfg: MandelConfig, tiles: Vec<Tile>, grid: IterationGrid) -> Result<Self, MandelError> {
+        cfg.validate()?;
+        grid.validate()?;
+        if grid.image_size() != (cfg.resolution.x, cfg.resolution.y) {
+            return Err(MandelError::InvalidGrid("size does not match the config"));
+        }
+        let tiles_total = tiles.len();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let tiles_done = Arc::new(AtomicUsize::new(0));
+        let grid = Arc::new(Mutex::new(grid));
+        let stats = Arc::new(Mutex::new(RenderStats::default()));
+        let (tx, completed) = mpsc::channel();
+
+        let handle = {
+            let (cancelled, tiles_done, grid, stats) = (
+                Arc::clone(&cancelled),
+                Arc::clone(&tiles_done),
+                Arc::clone(&grid),
+                Arc::clone(&stats),
+            );
+            thread::spawn(move || {
+                let started = Instant::now();
+                pool::install(&cfg, || {
+                    tiles.par_iter().for_each_with(tx, |tx, tile| {
+                        if cancelled.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        let (tile_grid, tile_stats) = render_timed(tile.config(&cfg), None);
+                        {
+                            let mut stats = stats.lock().unwrap();
+                            stats.setup += tile_stats.setup;
+                            stats.iterations += tile_stats.iterations;
+                        }
+                        grid.lock().unwrap().paste(tile.x, tile.y, &tile_grid);
+                        tiles_done.fetch_add(1, Ordering::Relaxed);
+                        // the receiver may be gone if the job was dropped
+                        let _ = tx.send(*tile);
+                    });
+                });
+                let mut stats = stats.lock().unwrap();
+                stats.iterate = started.elapsed().saturating_sub(stats.setup);
+                if !cancelled.load(Ordering::Relaxed) {
+                    log::debug!(
+                        "{tiles_total} tiles rendered - setup {:?}, iteration {:?}, {:.1} Mit/s",
+                        stats.setup,
+                        stats.iterate,
+                        stats.iterations_per_second() / 1e6
+                    );
+                }
+            })
+        };
+
+        Ok(Self {
+            cancelled,
+            tiles_done,
+            tiles_total,
+            grid,
+            stats,
+            completed,
+            handle: Some(handle),
+        })
+    }
+
+    /// Stop rendering. Tiles already in progress still complete.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Return `true` once all tiles are done, or the job was cancelled
+    /// and the tiles in progress are done.
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().is_none_or(|h| h.is_finished())
+    }
+
+    /// Return the number of tiles done and the total number of tiles
+    pub fn progress(&self) -> (usize, usize) {
+        (self.tiles_done.load(Ordering::Relaxed), self.tiles_total)
+    }
+
+    /// Return the tiles completed since the last call
+    pub fn completed_tiles(&self) -> Vec<Tile> {
+        self.completed.try_iter().collect()
+    }
+
+    /// Return the time of the setup of the domains and of the iteration
+    /// of the tiles, and their iterations, once the job is finished
+    pub fn stats(&self) -> RenderStats {
+        *self.stats.lock().unwrap()
+    }
+
+    /// Return a copy of the grid as rendered so far. Pixels of tiles
+    /// not yet done are zero.
+    pub fn snapshot(&self) -> IterationGrid {
+        self.grid.lock().unwrap().clone()
+    }
+
+    /// Block until the job is finished. Return the grid, or `None` if
+    /// the job was cancelled before all tiles were done.
+    pub fn wait(mut self) -> Option<IterationGrid> {
+        if let Some(handle) = self.handle.take() {
+            handle.join().expect("render thread panicked");
+        }
+        let (done, total) = self.progress();
+        if done < total {
+            None
+        } else {
+            Some(std::mem::take(&mut *self.grid.lock().unwrap()))
+        }
+    }
+}
+impl Drop for RenderJob {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
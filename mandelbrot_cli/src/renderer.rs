@@ -0,0 +1,165 @@
+// Renderer //////////////////////////////////////////////////////////
+//          /////////////////////////////////////////////////////////
+// Long-lived render context for interactive use, eg, the GUI, which
+// renders many views of the same size one after the other. It holds on
+// to its thread pool and to the points of the last domains, which are
+// reused by the views that keep the x or the y domain, eg, after a pan
+// along one axis or a change of `max_iters`.
+//
+// `mandel()` renders the same as `Renderer::render()`, only without the
+// cache.
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+use rayon::ThreadPool;
+
+use crate::origin::Coordinate;
+use crate::scalar::Scalar;
+use crate::{
+    linspace, pool, preview_config, render_preview, render_timed, Domain, IterationGrid,
+    MandelConfig, MandelError, RenderStats, Resolution,
+};
+
+/// Number of axes kept by `AxisCache`, the x and y axes of the last two
+/// views
+const AXES: usize = 4;
+
+/// Points of one axis of the domains, see `linspace()`
+struct Axis {
+    origin: Coordinate,
+    domain: Domain,
+    n: usize,
+    precision: usize,
+    // `Vec<T>` of the `Scalar` the axis was computed in
+    points: Arc<dyn Any + Send + Sync>,
+}
+
+/// Axes of the domains of the last renders, most recent last
+#[derive(Default)]
+pub(crate) struct AxisCache {
+    axes: Mutex<Vec<Axis>>,
+}
+impl AxisCache {
+    /// Return the `n` points of `domain` from `origin` in `T`, from the
+    /// cache if they were computed before
+    pub(crate) fn get<T: Scalar + 'static>(
+        &self,
+        origin: Coordinate,
+        domain: Domain,
+        n: usize,
+        precision: usize,
+    ) -> Arc<Vec<T>> {
+        let mut axes = self.axes.lock().unwrap();
+        let hit = axes.iter().position(|a| {
+            (a.origin, a.domain, a.n, a.precision) == (origin, domain, n, precision)
+                && a.points.is::<Vec<T>>()
+        });
+        let axis = match hit {
+            Some(i) => axes.remove(i),
+            None => Axis {
+                origin,
+                domain,
+                n,
+                precision,
+                points: Arc::new(linspace::<T>(origin, domain, n, precision)),
+            },
+        };
+        let points = Arc::clone(&axis.points);
+        axes.push(axis);
+        if axes.len() > AXES {
+            axes.remove(0);
+        }
+        points.downcast().expect("the axis is a `Vec<T>`")
+    }
+}
+
+/// Renders views in its own thread pool, reusing the axes of the
+/// domains of the previous views
+pub struct Renderer {
+    // `None` for the global rayon pool
+    threads: Option<usize>,
+    pool: Option<Arc<ThreadPool>>,
+    axes: AxisCache,
+}
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Renderer {
+    /// Renderer with the number of threads of the `MANDELBROT_THREADS`
+    /// environment variable, or one per core
+    pub fn new() -> Self {
+        let threads = pool::requested_threads(&MandelConfig::default());
+        Self {
+            threads,
+            pool: threads.map(pool::get),
+            axes: AxisCache::default(),
+        }
+    }
+
+    /// Renderer with `threads` threads
+    pub fn with_threads(threads: usize) -> Self {
+        let threads = threads.max(1);
+        Self {
+            threads: Some(threads),
+            pool: Some(pool::get(threads)),
+            axes: AxisCache::default(),
+        }
+    }
+
+    /// Return the number of threads of the renderer
+    pub fn threads(&self) -> usize {
+        match &self.pool {
+            Some(pool) => pool.current_num_threads(),
+            None => rayon::current_num_threads(),
+        }
+    }
+
+    /// Run `f` in the thread pool of the renderer, eg, another function
+    /// of the library
+    pub fn install<R, F>(&self, f: F) -> R
+    where
+        R: Send,
+        F: FnOnce() -> R + Send,
+    {
+        match &self.pool {
+            Some(pool) => pool.install(f),
+            None => f(),
+        }
+    }
+
+    /// Same as `mandel()`, in the threads of the renderer whatever
+    /// `cfg.threads` asks for
+    pub fn render(&self, cfg: MandelConfig) -> Result<IterationGrid, MandelError> {
+        self.render_with_stats(cfg).map(|(grid, _)| grid)
+    }
+
+    /// Same as `render()`, also returning the time of the setup of the
+    /// domains and of the iteration
+    pub fn render_with_stats(
+        &self,
+        cfg: MandelConfig,
+    ) -> Result<(IterationGrid, RenderStats), MandelError> {
+        cfg.validate()?;
+        let cfg = MandelConfig {
+            threads: self.threads,
+            ..cfg
+        };
+        Ok(self.install(|| render_timed(cfg, Some(&self.axes))))
+    }
+
+    /// Same as `mandel_preview()`, see `render()`
+    pub fn preview(
+        &self,
+        cfg: MandelConfig,
+        resolution: Resolution,
+    ) -> Result<IterationGrid, MandelError> {
+        let preview = MandelConfig {
+            threads: self.threads,
+            ..preview_config(cfg, resolution)
+        };
+        preview.validate()?;
+        Ok(self.install(|| render_preview(preview, Some(&self.axes))))
+    }
+}
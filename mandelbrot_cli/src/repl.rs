@@ -0,0 +1,249 @@
+// REPL //////////////////////////////////////////////////////////////
+//      /////////////////////////////////////////////////////////////
+// Headless exploration, eg, over SSH or from a script piped in: the
+// commands are read from stdin, one per line, and adjust a view kept
+// between them, which `render` saves to an image, eg:
+//
+//     center -0.75 0.1
+//     zoom 2
+//     iters 512
+//     render out.png
+//
+// The last render is kept, so that rendering the same view again, eg,
+// with another color scheme, only colors it, and that more iterations
+// only iterate the pixels at the cap, see `mandel_deeper()`. Lines
+// starting with `#` are comments.
+//
+// The commands are also functions of the scripts of `script`.
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use mandelbrot_cli::{
+    color_schemes::ColorSchemes, link::ViewLink, mandel, mandel_deeper, metadata::ImageMetadata,
+    save_image_with_metadata, IterationGrid, MandelConfig, Resolution,
+};
+
+const HELP: &str = "\
+zoom F        zoom in by F about the centre, out if F is below 1
+center X Y    centre the view on X + iY
+rotate DEG    rotate the view by DEG degrees, counter-clockwise
+iters N       set max_iters
+size W H      set the resolution, keeping the centre and the zoom
+scheme NAME   set the color scheme, by name or index
+link URL      go to the view of a mandel:// link
+show          print the view, as a link
+render FILE   render the view, the format given by the extension
+help          print this help
+quit          exit, as does the end of the input";
+
+/// Names of the commands, see `HELP`
+pub(crate) const COMMANDS: [&str; 11] = [
+    "zoom", "center", "rotate", "iters", "size", "scheme", "link", "show", "render", "help", "quit",
+];
+
+pub(crate) enum Command {
+    Zoom(f64),
+    Center(f64, f64),
+    Rotate(f64),
+    Iters(usize),
+    Size(Resolution),
+    Scheme(String),
+    Link(ViewLink),
+    Show,
+    Render(PathBuf),
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// Parse a line, `None` if it is blank or a comment
+    fn parse(line: &str) -> Result<Option<Self>, String> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next().filter(|name| !name.starts_with('#')) else {
+            return Ok(None);
+        };
+        let args: Vec<_> = words.collect();
+        Self::new(name, &args).map(Some)
+    }
+
+    /// Return the command `name` with the words `args`
+    pub(crate) fn new(name: &str, args: &[&str]) -> Result<Self, String> {
+        let number = |arg: &str| match arg.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(value),
+            _ => Err(format!("'{arg}' is not a number")),
+        };
+        let at_least = |arg: &str, min: usize| match arg.parse::<usize>() {
+            Ok(n) if n >= min => Ok(n),
+            _ => Err(format!("'{arg}' is not an integer from {min}")),
+        };
+        let command = match (name, args) {
+            ("zoom", [factor]) => match number(factor)? {
+                factor if factor > 0.0 => Self::Zoom(factor),
+                _ => return Err("the zoom factor is not positive".to_string()),
+            },
+            ("center", [x, y]) => Self::Center(number(x)?, number(y)?),
+            ("rotate", [degrees]) => Self::Rotate(number(degrees)?),
+            ("iters", [n]) => Self::Iters(at_least(n, 1)?),
+            ("size", [x, y]) => Self::Size(Resolution {
+                x: at_least(x, 2)?,
+                y: at_least(y, 2)?,
+            }),
+            ("scheme", [scheme]) => Self::Scheme(scheme.to_string()),
+            ("link", [link]) => Self::Link(link.parse().map_err(|e| format!("{e}"))?),
+            ("show", []) => Self::Show,
+            ("render", [path]) => Self::Render(PathBuf::from(path)),
+            ("help", []) => Self::Help,
+            ("quit" | "exit", []) => Self::Quit,
+            _ if COMMANDS.contains(&name) => {
+                return Err(format!("wrong number of arguments of '{name}', see 'help'"))
+            }
+            _ => return Err(format!("unknown command '{name}', see 'help'")),
+        };
+        Ok(command)
+    }
+}
+
+/// View and colors adjusted by the commands
+pub(crate) struct Session {
+    pub(crate) cfg: MandelConfig,
+    color_schemes: ColorSchemes,
+    last_render: Option<(MandelConfig, IterationGrid)>,
+}
+
+impl Session {
+    pub(crate) fn new(cfg: MandelConfig, color_schemes: ColorSchemes) -> Self {
+        Self {
+            cfg,
+            color_schemes,
+            last_render: None,
+        }
+    }
+
+    /// Run `command`, return `false` to quit
+    pub(crate) fn run(&mut self, command: Command) -> Result<bool, String> {
+        let cfg = &mut self.cfg;
+        match command {
+            Command::Zoom(factor) => {
+                let mut view = cfg.view();
+                view.scale *= factor;
+                cfg.domains().set_view(&view);
+                cfg.update_max_iters();
+            }
+            Command::Center(x, y) => {
+                let mut view = cfg.view();
+                (view.center_x, view.center_y) = (x, y);
+                cfg.domains().set_view(&view);
+            }
+            Command::Rotate(degrees) => {
+                cfg.domains().rotate(degrees.to_radians());
+            }
+            Command::Iters(n) => {
+                cfg.max_iters = n;
+                cfg.auto_iters = None;
+            }
+            Command::Size(resolution) => {
+                let view = cfg.view();
+                cfg.resolution = resolution;
+                cfg.domains().set_view(&view);
+            }
+            Command::Scheme(name) => {
+                let schemes = &mut self.color_schemes;
+                let found = match name.parse::<usize>() {
+                    Ok(index) if index < schemes.len() => {
+                        schemes.set(index);
+                        true
+                    }
+                    Ok(_) => false,
+                    Err(_) => schemes.set_by_name(&name).is_some(),
+                };
+                if !found {
+                    let names: Vec<_> = schemes.names().collect();
+                    return Err(format!(
+                        "no color scheme '{name}', there are {}",
+                        names.join(", ")
+                    ));
+                }
+            }
+            Command::Link(link) => {
+                link.apply(cfg);
+                link.apply_scheme(&mut self.color_schemes);
+            }
+            Command::Show => {
+                let (x, y) = (cfg.resolution.x, cfg.resolution.y);
+                println!("{} {x}x{y}", ViewLink::new(cfg, self.color_schemes.name()));
+            }
+            Command::Render(path) => self.save(&path)?,
+            Command::Help => println!("{HELP}"),
+            Command::Quit => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Render the view, from the last render if possible, and keep it
+    pub(crate) fn render(&mut self) -> Result<(), String> {
+        let grid = match self.last_render.take() {
+            Some((prev_cfg, prev)) if prev_cfg == self.cfg => Ok(prev),
+            Some((prev_cfg, prev)) => mandel_deeper(&prev, &prev_cfg, self.cfg),
+            None => mandel(self.cfg),
+        }
+        .map_err(|e| e.to_string())?;
+        self.last_render = Some((self.cfg, grid));
+        Ok(())
+    }
+
+    /// Render the view to `path`, see `render()`
+    fn save(&mut self, path: &Path) -> Result<(), String> {
+        let started = Instant::now();
+        let metadata = ImageMetadata::new(&self.cfg, &self.color_schemes);
+        self.render()?;
+        let (_, grid) = self.last_render.as_ref().expect("rendered");
+        save_image_with_metadata(grid, &metadata, &self.color_schemes, path)
+            .map_err(|e| format!("saving \"{}\" - {e}", path.display()))?;
+        println!(
+            "==> rendered in {:.2} s, saved to \"{}\"",
+            started.elapsed().as_secs_f64(),
+            path.display()
+        );
+        Ok(())
+    }
+}
+
+/// Run the commands of stdin on the view of `cfg`, with a prompt if it
+/// is a terminal, until the end of the input or `quit`. Return `false`
+/// if any command failed.
+pub fn run(cfg: MandelConfig, color_schemes: ColorSchemes) -> bool {
+    let mut session = Session::new(cfg, color_schemes);
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    if interactive {
+        println!("Type 'help' for the commands");
+    }
+    let (mut lines, mut ok) = (stdin.lock().lines(), true);
+    loop {
+        if interactive {
+            print!("> ");
+            let _ = io::stdout().flush();
+        }
+        let line = match lines.next() {
+            None => break,
+            Some(Ok(line)) => line,
+            Some(Err(e)) => {
+                eprintln!("Error reading stdin - {e}");
+                return false;
+            }
+        };
+        match Command::parse(&line).and_then(|command| match command {
+            Some(command) => session.run(command),
+            None => Ok(true),
+        }) {
+            Ok(true) => (),
+            Ok(false) => break,
+            Err(e) => {
+                eprintln!("Error in \"{}\" - {e}", line.trim());
+                ok = false;
+            }
+        }
+    }
+    ok
+}
@@ -0,0 +1,78 @@
+// Scalars //////////////////////////////////////////////////////////
+//         /////////////////////////////////////////////////////////
+// The iteration `z = z^2 + c` is generic over the `Scalar` trait so
+// that the same code runs in `f32` for shallow zooms, in `f64` for normal
+// zoom levels and in an arbitrary-precision float for deep zooms, where
+// the distance between two pixels gets below the resolution of `f64`.
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use dashu_float::FBig;
+
+pub trait Scalar:
+    Clone
+    + Send
+    + Sync
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    /// Convert from `f64`, with `precision` bits in the mantissa.
+    /// Fixed-size types ignore `precision`.
+    fn from_f64(val: f64, precision: usize) -> Self;
+    fn to_f64(&self) -> f64;
+    fn abs(self) -> Self;
+}
+
+impl Scalar for f32 {
+    #[inline]
+    fn from_f64(val: f64, _precision: usize) -> Self {
+        val as f32
+    }
+    #[inline]
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+    #[inline]
+    fn abs(self) -> Self {
+        f32::abs(self)
+    }
+}
+
+impl Scalar for f64 {
+    #[inline]
+    fn from_f64(val: f64, _precision: usize) -> Self {
+        val
+    }
+    #[inline]
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+    #[inline]
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+}
+
+/// Arbitrary-precision binary float
+pub type BigFloat = FBig;
+
+impl Scalar for BigFloat {
+    fn from_f64(val: f64, precision: usize) -> Self {
+        BigFloat::try_from(val)
+            .expect("domain values must be finite")
+            .with_precision(precision)
+            .value()
+    }
+    fn to_f64(&self) -> f64 {
+        self.to_f64().value()
+    }
+    fn abs(self) -> Self {
+        if self < BigFloat::ZERO {
+            -self
+        } else {
+            self
+        }
+    }
+}
@@ -0,0 +1,833 @@
+// Scripts ///////////////////////////////////////////////////////////
+//         //////////////////////////////////////////////////////////
+// Batch renders, eg, sweeps of parameters, written in a small language
+// close to rhai, run with `script FILE` without recompiling:
+//
+//     // the Julia sets of a grid of c
+//     for cx in range(-0.8, 0.01, 0.4) {
+//         for cy in [0.2, 0.6] {
+//             julia(cx, cy);
+//             save("julia_" + cx + "_" + cy + ".png");
+//         }
+//     }
+//
+// The values are numbers, strings, booleans and arrays. There are `let`
+// variables, `=`, `+=`, `-=`, `*=` and `/=`, `if` / `else`, `while`,
+// `for x in array`, `break` and `continue`, the operators of rhai, and
+// `a..b`, the integers from `a` up to `b`. Statements end with `;`, and
+// `//` starts a comment.
+//
+// The functions are those of `FUNCTIONS`, and the commands of `repl`,
+// eg, `zoom(2)` or `iters(512)`, on the view of the flags. `render()`
+// renders the view, and `save(path)` saves it, rendering it if it
+// changed. As in `repl`, rendering with more iterations only iterates
+// the pixels at the cap, see `mandel_deeper()`.
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use mandelbrot_cli::{
+    color_schemes::ColorSchemes, domain::View, origin::Coordinate, FractalKind, MandelConfig,
+};
+
+use crate::repl::{self, Command, Session};
+
+/// Functions of the scripts, besides the commands of `repl`
+const FUNCTIONS: [&str; 18] = [
+    "print",
+    "range",
+    "len",
+    "sqrt",
+    "abs",
+    "floor",
+    "round",
+    "sin",
+    "cos",
+    "exp",
+    "ln",
+    "pow",
+    "set_view",
+    "julia",
+    "mandelbrot",
+    "render",
+    "save",
+    "to_string",
+];
+/// Commands of `repl` which are not functions of the scripts
+const NOT_FUNCTIONS: [&str; 3] = ["render", "help", "quit"];
+/// Operators, the longest first
+const PUNCTS: [&str; 28] = [
+    "..", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=", "/=", "+", "-", "*", "/", "%", "<",
+    ">", "=", "!", "(", ")", "{", "}", "[", "]", ",", ";",
+];
+/// Binary operators, by increasing precedence
+const LEVELS: [&[&str]; 7] = [
+    &["||"],
+    &["&&"],
+    &["==", "!="],
+    &["<", "<=", ">", ">="],
+    &[".."],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+/// Most items of a range, so that a typo does not fill the memory
+const MAX_RANGE: f64 = 1e7;
+
+/// Error of a script, at `line`
+struct ScriptError {
+    line: usize,
+    message: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    Punct(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Unit,
+    Bool(bool),
+    Number(f64),
+    Str(String),
+    Array(Vec<Value>),
+}
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::Unit => "unit",
+            Self::Bool(_) => "bool",
+            Self::Number(_) => "number",
+            Self::Str(_) => "string",
+            Self::Array(_) => "array",
+        }
+    }
+}
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unit => write!(f, "()"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Number(x) => write!(f, "{x}"),
+            Self::Str(s) => write!(f, "{s}"),
+            Self::Array(items) => {
+                let items: Vec<_> = items.iter().map(ToString::to_string).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+        }
+    }
+}
+
+enum Expr {
+    Value(Value),
+    Array(Vec<Expr>),
+    Var(String),
+    Call(String, Vec<Expr>),
+    Index(Box<Expr>, Box<Expr>),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+}
+
+enum Kind {
+    Let(String, Expr),
+    /// Assignment, with the operator of `+=`, etc, if any
+    Assign(String, Option<&'static str>, Expr),
+    Expr(Expr),
+    If(Expr, Vec<Statement>, Vec<Statement>),
+    While(Expr, Vec<Statement>),
+    For(String, Expr, Vec<Statement>),
+    Break,
+    Continue,
+}
+
+struct Statement {
+    kind: Kind,
+    line: usize,
+}
+
+/// Split `source` into tokens, with their lines
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, ScriptError> {
+    let chars: Vec<char> = source.chars().collect();
+    let at = |i: usize| chars.get(i).copied().unwrap_or('\0');
+    let (mut tokens, mut i, mut line) = (Vec::new(), 0, 1);
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && at(i + 1) == '/' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c.is_ascii_digit() || (c == '.' && at(i + 1).is_ascii_digit()) {
+            while at(i).is_ascii_digit() {
+                i += 1;
+            }
+            // not the `..` of a range
+            if at(i) == '.' && at(i + 1).is_ascii_digit() {
+                i += 1;
+                while at(i).is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let sign = matches!(at(i + 1), '+' | '-') as usize;
+            if matches!(at(i), 'e' | 'E') && at(i + 1 + sign).is_ascii_digit() {
+                i += 1 + sign;
+                while at(i).is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text.parse().map_err(|_| ScriptError {
+                line,
+                message: format!("'{text}' is not a number"),
+            })?;
+            tokens.push((Token::Number(value), line));
+        } else if c.is_alphabetic() || c == '_' {
+            while at(i).is_alphanumeric() || at(i) == '_' {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), line));
+        } else if c == '"' {
+            let (mut text, first) = (String::new(), line);
+            i += 1;
+            loop {
+                match at(i) {
+                    '"' => break,
+                    '\0' if i >= chars.len() => {
+                        return Err(ScriptError {
+                            line: first,
+                            message: "the string does not end".to_string(),
+                        })
+                    }
+                    '\\' => {
+                        i += 1;
+                        text.push(match at(i) {
+                            'n' => '\n',
+                            't' => '\t',
+                            other => other,
+                        });
+                    }
+                    '\n' => {
+                        line += 1;
+                        text.push('\n');
+                    }
+                    other => text.push(other),
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push((Token::Str(text), first));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let Some(punct) = PUNCTS.iter().find(|p| rest.starts_with(**p)) else {
+                return Err(ScriptError {
+                    line,
+                    message: format!("unexpected '{c}'"),
+                });
+            };
+            i += punct.len();
+            tokens.push((Token::Punct(punct), line));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    /// Number of loops around the statement, for `break` and `continue`
+    loops: usize,
+}
+
+impl Parser {
+    fn peek(&self, ahead: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + ahead).map(|(token, _)| token)
+    }
+
+    /// Line of the next token, or of the last one at the end
+    fn line(&self) -> usize {
+        let last = self.tokens.len().saturating_sub(1);
+        self.tokens
+            .get(self.pos.min(last))
+            .map_or(1, |&(_, line)| line)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ScriptError {
+        ScriptError {
+            line: self.line(),
+            message: message.into(),
+        }
+    }
+
+    fn is(&self, punct: &str) -> bool {
+        matches!(self.peek(0), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(0), Some(Token::Ident(name)) if name == keyword)
+    }
+
+    /// Skip `punct` or the keyword, if it is next
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = self.is(punct) || self.is_keyword(punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), ScriptError> {
+        match self.eat(punct) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected '{punct}'"))),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, ScriptError> {
+        match self.peek(0) {
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("expected a name")),
+        }
+    }
+
+    fn statements(&mut self) -> Result<Vec<Statement>, ScriptError> {
+        let mut statements = Vec::new();
+        while self.pos < self.tokens.len() {
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn block(&mut self) -> Result<Vec<Statement>, ScriptError> {
+        self.expect("{")?;
+        let mut statements = Vec::new();
+        while !self.eat("}") {
+            if self.pos >= self.tokens.len() {
+                return Err(self.error("expected '}'"));
+            }
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    /// Skip the `;` ending a statement, optional before `}`
+    fn end(&mut self) -> Result<(), ScriptError> {
+        match self.eat(";") || self.is("}") || self.pos >= self.tokens.len() {
+            true => Ok(()),
+            false => Err(self.error("expected ';'")),
+        }
+    }
+
+    fn looped(&mut self) -> Result<Vec<Statement>, ScriptError> {
+        self.loops += 1;
+        let body = self.block();
+        self.loops -= 1;
+        body
+    }
+
+    fn statement(&mut self) -> Result<Statement, ScriptError> {
+        let line = self.line();
+        let kind = if self.eat("let") {
+            let name = self.ident()?;
+            self.expect("=")?;
+            let value = self.expr()?;
+            self.end()?;
+            Kind::Let(name, value)
+        } else if self.is_keyword("if") {
+            self.if_else()?
+        } else if self.eat("while") {
+            let condition = self.expr()?;
+            Kind::While(condition, self.looped()?)
+        } else if self.eat("for") {
+            let name = self.ident()?;
+            self.expect("in")?;
+            let items = self.expr()?;
+            Kind::For(name, items, self.looped()?)
+        } else if self.is_keyword("break") || self.is_keyword("continue") {
+            if self.loops == 0 {
+                return Err(self.error("break or continue outside of a loop"));
+            }
+            let kind = match self.eat("break") {
+                true => Kind::Break,
+                false => {
+                    self.pos += 1;
+                    Kind::Continue
+                }
+            };
+            self.end()?;
+            kind
+        } else if let (Some(Token::Ident(name)), Some(Token::Punct(op))) =
+            (self.peek(0), self.peek(1))
+        {
+            let (name, op) = (name.clone(), *op);
+            match op {
+                "=" | "+=" | "-=" | "*=" | "/=" => {
+                    self.pos += 2;
+                    let value = self.expr()?;
+                    self.end()?;
+                    Kind::Assign(
+                        name,
+                        op.strip_suffix('=').filter(|op| !op.is_empty()),
+                        value,
+                    )
+                }
+                _ => self.expr_statement()?,
+            }
+        } else {
+            self.expr_statement()?
+        };
+        Ok(Statement { kind, line })
+    }
+
+    fn expr_statement(&mut self) -> Result<Kind, ScriptError> {
+        let expr = self.expr()?;
+        self.end()?;
+        Ok(Kind::Expr(expr))
+    }
+
+    /// `if`, and its `else if` and `else`, if any
+    fn if_else(&mut self) -> Result<Kind, ScriptError> {
+        self.expect("if")?;
+        let condition = self.expr()?;
+        let then = self.block()?;
+        let otherwise = if !self.eat("else") {
+            Vec::new()
+        } else if self.is_keyword("if") {
+            let line = self.line();
+            vec![Statement {
+                kind: self.if_else()?,
+                line,
+            }]
+        } else {
+            self.block()?
+        };
+        Ok(Kind::If(condition, then, otherwise))
+    }
+
+    fn expr(&mut self) -> Result<Expr, ScriptError> {
+        self.binary(0)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, ScriptError> {
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(&op) = LEVELS[level].iter().find(|op| self.is(op)) {
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ScriptError> {
+        for op in ["-", "!"] {
+            if self.eat(op) {
+                return Ok(Expr::Unary(op, Box::new(self.unary()?)));
+            }
+        }
+        let mut expr = self.primary()?;
+        while self.eat("[") {
+            let index = self.expr()?;
+            self.expect("]")?;
+            expr = Expr::Index(Box::new(expr), Box::new(index));
+        }
+        Ok(expr)
+    }
+
+    /// Items up to `close`, separated by commas
+    fn list(&mut self, close: &str) -> Result<Vec<Expr>, ScriptError> {
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            items.push(self.expr()?);
+            if !self.eat(",") {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    fn primary(&mut self) -> Result<Expr, ScriptError> {
+        let Some(token) = self.peek(0).cloned() else {
+            return Err(self.error("unexpected end of the script"));
+        };
+        self.pos += 1;
+        Ok(match token {
+            Token::Number(value) => Expr::Value(Value::Number(value)),
+            Token::Str(text) => Expr::Value(Value::Str(text)),
+            Token::Ident(name) if name == "true" || name == "false" => {
+                Expr::Value(Value::Bool(name == "true"))
+            }
+            Token::Ident(name) if self.eat("(") => Expr::Call(name, self.list(")")?),
+            Token::Ident(name) => Expr::Var(name),
+            Token::Punct("(") => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                expr
+            }
+            Token::Punct("[") => Expr::Array(self.list("]")?),
+            Token::Punct(punct) => {
+                self.pos -= 1;
+                return Err(self.error(format!("unexpected '{punct}'")));
+            }
+        })
+    }
+}
+
+enum Flow {
+    Next,
+    Break,
+    Continue,
+}
+
+struct Interpreter<'a> {
+    session: &'a mut Session,
+    /// Variables, the innermost block last
+    scopes: Vec<HashMap<String, Value>>,
+}
+
+impl Interpreter<'_> {
+    fn block(&mut self, statements: &[Statement]) -> Result<Flow, ScriptError> {
+        self.scopes.push(HashMap::new());
+        let flow = self.statements(statements);
+        self.scopes.pop();
+        flow
+    }
+
+    fn statements(&mut self, statements: &[Statement]) -> Result<Flow, ScriptError> {
+        for statement in statements {
+            match self.execute(statement)? {
+                Flow::Next => (),
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn execute(&mut self, statement: &Statement) -> Result<Flow, ScriptError> {
+        let at = |message| ScriptError {
+            line: statement.line,
+            message,
+        };
+        match &statement.kind {
+            Kind::Let(name, value) => {
+                let value = self.eval(value).map_err(at)?;
+                let scope = self.scopes.last_mut().expect("the global scope");
+                scope.insert(name.clone(), value);
+            }
+            Kind::Assign(name, op, value) => {
+                let value = self.eval(value).map_err(at)?;
+                let value = match op {
+                    Some(op) => binary(op, self.var(name).map_err(at)?.clone(), value),
+                    None => Ok(value),
+                }
+                .map_err(at)?;
+                *self.var(name).map_err(at)? = value;
+            }
+            Kind::Expr(expr) => {
+                self.eval(expr).map_err(at)?;
+            }
+            Kind::If(condition, then, otherwise) => {
+                let condition = self.eval(condition).and_then(truth).map_err(at)?;
+                return self.block(if condition { then } else { otherwise });
+            }
+            Kind::While(condition, body) => {
+                while self.eval(condition).and_then(truth).map_err(at)? {
+                    if let Flow::Break = self.block(body)? {
+                        break;
+                    }
+                }
+            }
+            Kind::For(name, items, body) => {
+                let items = match self.eval(items).map_err(at)? {
+                    Value::Array(items) => items,
+                    other => {
+                        return Err(at(format!("cannot loop over a {}", other.type_name())));
+                    }
+                };
+                for item in items {
+                    self.scopes.push(HashMap::from([(name.clone(), item)]));
+                    let flow = self.block(body);
+                    self.scopes.pop();
+                    if let Flow::Break = flow? {
+                        break;
+                    }
+                }
+            }
+            Kind::Break => return Ok(Flow::Break),
+            Kind::Continue => return Ok(Flow::Continue),
+        }
+        Ok(Flow::Next)
+    }
+
+    /// Return the variable `name`, of the innermost block that has one
+    fn var(&mut self, name: &str) -> Result<&mut Value, String> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+            .ok_or(format!("no variable '{name}', declare it with 'let'"))
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Value, String> {
+        match expr {
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Array(items) => items
+                .iter()
+                .map(|item| self.eval(item))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            Expr::Var(name) => self.var(name).cloned(),
+            Expr::Index(items, index) => {
+                let (items, index) = (self.eval(items)?, self.eval(index)?);
+                let Value::Array(items) = items else {
+                    return Err(format!("cannot index a {}", items.type_name()));
+                };
+                let i = number(&index)?;
+                match items.get(i as usize) {
+                    Some(item) if i >= 0.0 && i.fract() == 0.0 => Ok(item.clone()),
+                    _ => Err(format!("no item {index} in an array of {}", items.len())),
+                }
+            }
+            Expr::Unary(op, value) => match (*op, self.eval(value)?) {
+                ("-", Value::Number(x)) => Ok(Value::Number(-x)),
+                ("!", Value::Bool(b)) => Ok(Value::Bool(!b)),
+                (op, value) => Err(format!("cannot apply '{op}' to a {}", value.type_name())),
+            },
+            // only as far as needed
+            Expr::Binary("&&", a, b) => {
+                Ok(Value::Bool(truth(self.eval(a)?)? && truth(self.eval(b)?)?))
+            }
+            Expr::Binary("||", a, b) => {
+                Ok(Value::Bool(truth(self.eval(a)?)? || truth(self.eval(b)?)?))
+            }
+            Expr::Binary(op, a, b) => {
+                let a = self.eval(a)?;
+                binary(op, a, self.eval(b)?)
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(name, &args)
+            }
+        }
+    }
+
+    fn call(&mut self, name: &str, args: &[Value]) -> Result<Value, String> {
+        let cfg = &mut self.session.cfg;
+        match (name, args) {
+            ("print", _) => {
+                let words: Vec<_> = args.iter().map(ToString::to_string).collect();
+                println!("{}", words.join(" "));
+            }
+            ("to_string", [value]) => return Ok(Value::Str(value.to_string())),
+            ("range", [start, end]) => return range(number(start)?, number(end)?, 1.0),
+            ("range", [start, end, step]) => {
+                return range(number(start)?, number(end)?, number(step)?)
+            }
+            ("len", [Value::Array(items)]) => return Ok(Value::Number(items.len() as f64)),
+            ("len", [Value::Str(text)]) => return Ok(Value::Number(text.chars().count() as f64)),
+            ("sqrt" | "abs" | "floor" | "round" | "sin" | "cos" | "exp" | "ln", [x]) => {
+                let x = number(x)?;
+                return Ok(Value::Number(match name {
+                    "sqrt" => x.sqrt(),
+                    "abs" => x.abs(),
+                    "floor" => x.floor(),
+                    "round" => x.round(),
+                    "sin" => x.sin(),
+                    "cos" => x.cos(),
+                    "exp" => x.exp(),
+                    _ => x.ln(),
+                }));
+            }
+            ("pow", [x, y]) => return Ok(Value::Number(number(x)?.powf(number(y)?))),
+            ("set_view", [x, y, zoom, rotation @ ..]) if rotation.len() <= 1 => {
+                let (x, y) = (coordinate(x)?, coordinate(y)?);
+                let zoom = number(zoom)?;
+                if zoom <= 0.0 {
+                    return Err("the zoom is not positive".to_string());
+                }
+                let degrees = rotation.first().map(number).transpose()?.unwrap_or(0.0);
+                let view = View {
+                    center_x: x.to_f64(),
+                    center_y: y.to_f64(),
+                    scale: zoom,
+                    rotation: degrees.to_radians(),
+                };
+                cfg.domains().set_view(&view).center_on(x, y);
+                cfg.update_max_iters();
+            }
+            ("julia", [cx, cy]) => set_kind(
+                cfg,
+                FractalKind::Julia {
+                    cx: number(cx)?,
+                    cy: number(cy)?,
+                },
+            ),
+            ("mandelbrot", []) => set_kind(cfg, FractalKind::Mandelbrot),
+            ("render", []) => self.session.render()?,
+            ("save", [path]) => {
+                let path = PathBuf::from(path.to_string());
+                self.session.run(Command::Render(path))?;
+            }
+            _ if repl::COMMANDS.contains(&name) && !NOT_FUNCTIONS.contains(&name) => {
+                let words: Vec<_> = args.iter().map(ToString::to_string).collect();
+                let words: Vec<_> = words.iter().map(String::as_str).collect();
+                self.session.run(Command::new(name, &words)?)?;
+            }
+            _ if FUNCTIONS.contains(&name) => {
+                return Err(format!("wrong arguments of '{name}'"));
+            }
+            _ => return Err(format!("unknown function '{name}'")),
+        }
+        Ok(Value::Unit)
+    }
+}
+
+/// Return the value of the binary operator `op`
+fn binary(op: &str, a: Value, b: Value) -> Result<Value, String> {
+    use Value::{Array, Bool, Number, Str};
+    Ok(match (op, a, b) {
+        ("+", Number(a), Number(b)) => Number(a + b),
+        ("+", Array(mut a), Array(b)) => {
+            a.extend(b);
+            Array(a)
+        }
+        ("+", a @ Str(_), b) | ("+", a, b @ Str(_)) => Str(format!("{a}{b}")),
+        ("-", Number(a), Number(b)) => Number(a - b),
+        ("*", Number(a), Number(b)) => Number(a * b),
+        ("/", Number(a), Number(b)) => Number(a / b),
+        ("%", Number(a), Number(b)) => Number(a % b),
+        ("==", a, b) => Bool(a == b),
+        ("!=", a, b) => Bool(a != b),
+        ("<" | "<=" | ">" | ">=", a, b) => {
+            let ordering = match (&a, &b) {
+                (Number(a), Number(b)) => a.partial_cmp(b),
+                (Str(a), Str(b)) => Some(a.cmp(b)),
+                _ => None,
+            };
+            let Some(ordering) = ordering else {
+                return Err(format!(
+                    "cannot compare {a} and {b}, a {} and a {}",
+                    a.type_name(),
+                    b.type_name()
+                ));
+            };
+            Bool(match op {
+                "<" => ordering.is_lt(),
+                "<=" => ordering.is_le(),
+                ">" => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            })
+        }
+        ("..", Number(a), Number(b)) => return range(a, b, 1.0),
+        (op, a, b) => {
+            return Err(format!(
+                "cannot apply '{op}' to a {} and a {}",
+                a.type_name(),
+                b.type_name()
+            ))
+        }
+    })
+}
+
+/// Return the numbers from `start` by `step` up to `end`, excluded
+fn range(start: f64, end: f64, step: f64) -> Result<Value, String> {
+    let n = ((end - start) / step).ceil().max(0.0);
+    if step == 0.0 || !n.is_finite() || n > MAX_RANGE {
+        return Err(format!("range({start}, {end}, {step}) is not a range"));
+    }
+    let items = (0..n as usize).map(|i| Value::Number(start + i as f64 * step));
+    Ok(Value::Array(items.collect()))
+}
+
+fn truth(value: Value) -> Result<bool, String> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        value => Err(format!("expected a bool, got a {}", value.type_name())),
+    }
+}
+
+fn number(value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Number(x) if x.is_finite() => Ok(*x),
+        value => Err(format!("expected a number, got {value}")),
+    }
+}
+
+/// Return the coordinate of a number, or of a string with more digits
+/// than `f64` holds, eg, of a deep zoom
+fn coordinate(value: &Value) -> Result<Coordinate, String> {
+    match value {
+        Value::Str(text) => text.parse(),
+        value => number(value).map(Coordinate::from),
+    }
+}
+
+/// Show the fractal `kind`, from its whole view unless it is of the same
+/// fractal, eg, the Julia set of another `c`
+fn set_kind(cfg: &mut MandelConfig, kind: FractalKind) {
+    if std::mem::discriminant(&kind) != std::mem::discriminant(&cfg.kind) {
+        let (xdomain, ydomain) = kind.default_domains();
+        cfg.rotation = 0.0;
+        cfg.domains().set_domains(xdomain, ydomain).fit_to_aspect();
+    }
+    cfg.kind = kind;
+}
+
+/// Run the script of the file `path` on the view of `cfg`, until its end
+/// or the first error. Return `false` if there is one.
+pub fn run(cfg: MandelConfig, color_schemes: ColorSchemes, path: &Path) -> bool {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("Error loading \"{}\" - {e}", path.display());
+            return false;
+        }
+    };
+    let mut session = Session::new(cfg, color_schemes);
+    let mut interpreter = Interpreter {
+        session: &mut session,
+        scopes: vec![HashMap::from([(
+            "PI".to_string(),
+            Value::Number(std::f64::consts::PI),
+        )])],
+    };
+    let ran = tokenize(&source)
+        .and_then(|tokens| {
+            Parser {
+                tokens,
+                pos: 0,
+                loops: 0,
+            }
+            .statements()
+        })
+        .and_then(|statements| interpreter.statements(&statements));
+    if let Err(e) = ran {
+        eprintln!(
+            "Error in \"{}\" line {} - {}",
+            path.display(),
+            e.line,
+            e.message
+        );
+        return false;
+    }
+    true
+}
@@ -0,0 +1,184 @@
+// SIMD //////////////////////////////////////////////////////////////
+//      /////////////////////////////////////////////////////////////
+// Vectorised version of `mandel_worker`, iterating `Lanes::LANES`
+// pixels of a row at once, 4 in `f64` or 8 in `f32`. Lanes that already
+// escaped are masked out and keep their values, and the loop ends when
+// all lanes escaped.
+use std::ops::{Add, AddAssign, BitAnd, BitAndAssign, Mul, Not, Sub};
+
+use wide::{f32x8, f64x4};
+
+use crate::formula::{self, Step};
+use crate::scalar::Scalar;
+use crate::{
+    checks_main_bulbs, in_main_bulbs, periodicity_epsilon, Escape, Formula, FractalKind,
+    MandelConfig, RowMut, Worker,
+};
+
+/// Vector of floats the SIMD worker iterates, `f64x4` or `f32x8`
+pub(crate) trait Lanes:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + BitAnd<Output = Self>
+    + Not<Output = Self>
+    + AddAssign
+    + BitAndAssign
+{
+    /// Type of one lane
+    type Scalar: Scalar + Copy;
+    const LANES: usize;
+    fn splat(val: f64) -> Self;
+    /// Load `xs`, padding a short slice with its last value
+    fn load(xs: &[Self::Scalar]) -> Self;
+    /// Return a mask with all the bits set in the lanes where `f` is
+    /// `true`
+    fn mask(f: impl Fn(usize) -> bool) -> Self;
+    fn lane(self, i: usize) -> f64;
+    fn any(self) -> bool;
+    fn abs(self) -> Self;
+    fn simd_gt(self, rhs: Self) -> Self;
+    fn simd_lt(self, rhs: Self) -> Self;
+    /// Take the lanes of `if_one` where the mask `self` is set, and the
+    /// ones of `if_zero` elsewhere
+    fn bitselect(self, if_one: Self, if_zero: Self) -> Self;
+}
+
+macro_rules! impl_lanes {
+    ($vector:ident, $scalar:ident, $bits:ident, $lanes:literal) => {
+        impl Lanes for $vector {
+            type Scalar = $scalar;
+            const LANES: usize = $lanes;
+            #[inline]
+            fn splat(val: f64) -> Self {
+                $vector::splat(val as $scalar)
+            }
+            #[inline]
+            fn load(xs: &[$scalar]) -> Self {
+                let mut lanes = [xs[xs.len() - 1]; $lanes];
+                lanes[..xs.len()].copy_from_slice(xs);
+                $vector::new(lanes)
+            }
+            #[inline]
+            fn mask(f: impl Fn(usize) -> bool) -> Self {
+                let set = $scalar::from_bits($bits::MAX);
+                $vector::new(std::array::from_fn(|i| if f(i) { set } else { 0.0 }))
+            }
+            #[inline]
+            fn lane(self, i: usize) -> f64 {
+                self.to_array()[i] as f64
+            }
+            #[inline]
+            fn any(self) -> bool {
+                $vector::any(self)
+            }
+            #[inline]
+            fn abs(self) -> Self {
+                $vector::abs(self)
+            }
+            #[inline]
+            fn simd_gt(self, rhs: Self) -> Self {
+                $vector::simd_gt(self, rhs)
+            }
+            #[inline]
+            fn simd_lt(self, rhs: Self) -> Self {
+                $vector::simd_lt(self, rhs)
+            }
+            #[inline]
+            fn bitselect(self, if_one: Self, if_zero: Self) -> Self {
+                $vector::bitselect(self, if_one, if_zero)
+            }
+        }
+    };
+}
+impl_lanes!(f64x4, f64, u64, 4);
+impl_lanes!(f32x8, f32, u32, 8);
+
+/// Return `mandel_worker_simd` for `formula`, in `f64` with `V = f64x4`
+/// or in `f32` with `V = f32x8`
+pub(crate) fn simd_worker<V: Lanes>(formula: Formula) -> Worker<V::Scalar> {
+    match formula {
+        Formula::Mandelbrot => mandel_worker_simd::<V, formula::Mandelbrot>,
+        Formula::BurningShip => mandel_worker_simd::<V, formula::BurningShip>,
+        Formula::Tricorn => mandel_worker_simd::<V, formula::Tricorn>,
+        Formula::Celtic => mandel_worker_simd::<V, formula::Celtic>,
+    }
+}
+
+/// Process one horizontal row of the domain, see `mandel_worker`
+fn mandel_worker_simd<V: Lanes, F: Step>(
+    mut row: RowMut,
+    y0: &V::Scalar,
+    xdomain: &[V::Scalar],
+    cfg: &MandelConfig,
+    _precision: usize,
+) {
+    let max_iters = cfg.max_iters;
+    let threshold = V::splat(cfg.threshold);
+    let (one, zero) = (V::splat(1.0), V::splat(0.0));
+    let main_bulbs = checks_main_bulbs(cfg);
+    let epsilon = V::splat(periodicity_epsilon(cfg));
+    let inside_count = V::splat(max_iters as f64);
+
+    debug_assert_eq!(xdomain.len(), row.len());
+    for (chunk, xs) in xdomain.chunks(V::LANES).enumerate() {
+        // The last chunk may be short, so it is padded with its last value
+        let x0 = V::load(xs);
+        let y0 = V::splat(y0.to_f64());
+
+        // `(x1, y1)` is `z` and `(cx, cy)` is `c` in `z = z^2 + c`
+        let (mut x1, mut y1, cx, cy) = match cfg.kind {
+            FractalKind::Mandelbrot => (zero, zero, x0, y0),
+            FractalKind::Julia { cx, cy } => (x0, y0, V::splat(cx), V::splat(cy)),
+        };
+        // `active` lanes have not escaped yet. Escaped lanes keep the
+        // number of iterations and `|z|^2` at the time they escaped.
+        let mut active = V::mask(|_| true);
+        let mut count = zero;
+        if main_bulbs {
+            // lanes in the main bulbs are inside from the start
+            let inside = V::mask(|lane| in_main_bulbs(x0.lane(lane), y0.lane(lane)));
+            count = inside.bitselect(inside_count, count);
+            active &= !inside;
+        }
+        let mut norm = zero;
+        let mut c = 0;
+        // periodicity check, see `mandel_worker`
+        let (mut saved_x, mut saved_y) = (V::splat(f64::NAN), V::splat(f64::NAN));
+        let mut checkpoint = 1;
+        loop {
+            let (xx, yy) = (x1 * x1, y1 * y1);
+            let nrm = xx + yy;
+            norm = active.bitselect(nrm, norm);
+            active &= !nrm.simd_gt(threshold);
+            if !active.any() || c >= max_iters {
+                break;
+            }
+            count += active & one;
+            (x1, y1) = F::step_simd(x1, y1, xx, yy, cx, cy);
+            c += 1;
+            if cfg.interior_check {
+                let periodic = active
+                    & (x1 - saved_x).abs().simd_lt(epsilon)
+                    & (y1 - saved_y).abs().simd_lt(epsilon);
+                count = periodic.bitselect(inside_count, count);
+                active &= !periodic;
+                if c == checkpoint {
+                    (saved_x, saved_y) = (x1, y1);
+                    checkpoint *= 2;
+                }
+            }
+        }
+
+        for lane in 0..xs.len() {
+            let escape = Escape::new(
+                count.lane(lane) as usize,
+                norm.lane(lane),
+                max_iters,
+                cfg.threshold,
+            );
+            row.set(chunk * V::LANES + lane, escape);
+        }
+    }
+}
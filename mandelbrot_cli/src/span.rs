@@ -0,0 +1,76 @@
+// Spans /////////////////////////////////////////////////////////////
+//       ////////////////////////////////////////////////////////////
+// Scoped timing of the stages of a render, eg, the setup of the
+// domains, the workers and the encoding of the image, logged when they
+// end, as the spans of `tracing` would be, on top of `log`:
+//
+//     TRACE mandelbrot_cli: render 1920x1080 > domains took 85.2µs
+//     TRACE mandelbrot_cli: render 1920x1080 > workers took 412.6ms
+//     TRACE mandelbrot_cli: render 1920x1080 took 413.1ms
+//
+// Each record is prefixed with the spans it is nested in, on the same
+// thread, and filtered by level and module as the other records, eg,
+// with `MANDELBROT_LOG=mandelbrot_cli=trace`. Nothing is timed nor
+// formatted unless the record is enabled.
+use std::cell::RefCell;
+use std::time::Instant;
+
+use log::Level;
+
+thread_local! {
+    /// Labels of the spans entered on this thread, the innermost last
+    static STACK: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Time of a stage, logged when it is dropped, see `span!`
+pub(crate) struct Span {
+    level: Level,
+    target: &'static str,
+    /// Start, if the record is enabled
+    started: Option<Instant>,
+}
+
+impl Span {
+    /// Enter the span `label()` of `target`, logged at `level`
+    pub(crate) fn enter(
+        level: Level,
+        target: &'static str,
+        label: impl FnOnce() -> String,
+    ) -> Self {
+        let enabled = log::log_enabled!(target: target, level);
+        if enabled {
+            STACK.with(|stack| stack.borrow_mut().push(label()));
+        }
+        Self {
+            level,
+            target,
+            started: enabled.then(Instant::now),
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(started) = self.started else {
+            return;
+        };
+        let elapsed = started.elapsed();
+        let path = STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let path = stack.join(" > ");
+            stack.pop();
+            path
+        });
+        log::log!(target: self.target, self.level, "{path} took {elapsed:?}");
+    }
+}
+
+/// Enter a span of the current module, eg, `span!(Level::Debug,
+/// "encode \"{}\"", path.display())`, until the end of the scope of the
+/// value returned, which must be bound, eg, to `_span`
+macro_rules! span {
+    ($level:expr, $($label:tt)+) => {
+        $crate::span::Span::enter($level, module_path!(), || format!($($label)+))
+    };
+}
+pub(crate) use span;
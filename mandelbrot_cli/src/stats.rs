@@ -0,0 +1,65 @@
+// Render statistics /////////////////////////////////////////////////
+//                   ////////////////////////////////////////////////
+// Time spent in each stage of a render, returned alongside the grid by
+// `Renderer::render_with_stats()` and `RenderJob::stats()`. The library
+// times the stages it runs, the setup of the domains and the iteration,
+// and the apps fill in the coloring and the upload of the image, eg, to
+// a texture of the GPU, which they run themselves.
+//
+// The iterations of the pixels give the throughput of a render, in
+// iterations per second, to compare the settings of the threads, see
+// `crate::tune`, and the machines.
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderStats {
+    /// Computing the points of the domains, see `linspace()`, summed
+    /// over the tiles rendered in parallel
+    pub setup: Duration,
+    /// Iterating the pixels, the rest of the render
+    pub iterate: Duration,
+    /// Coloring the iterations, see `get_rgb_buf()`
+    pub color: Duration,
+    /// Uploading the image, eg, to a texture
+    pub upload: Duration,
+    /// Iterations of the pixels, the sum of their counts, including the
+    /// pixels copied or filled in without iterating, eg, mirrored rows
+    pub iterations: u64,
+}
+
+impl RenderStats {
+    pub fn total(&self) -> Duration {
+        self.setup + self.iterate + self.color + self.upload
+    }
+    /// Return the iterations per second of the render, its setup and
+    /// iteration, 0 if it took no time
+    pub fn iterations_per_second(&self) -> f64 {
+        let secs = (self.setup + self.iterate).as_secs_f64();
+        if secs > 0.0 {
+            self.iterations as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for RenderStats {
+    /// Times in ms and throughput, if the iterations are known, eg,
+    /// `setup 0.2, iterate 41.3, color 5.1, upload 1.0 ms, 812.5 Mit/s`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = |d: Duration| d.as_secs_f64() * 1e3;
+        write!(
+            f,
+            "setup {:.1}, iterate {:.1}, color {:.1}, upload {:.1} ms",
+            ms(self.setup),
+            ms(self.iterate),
+            ms(self.color),
+            ms(self.upload)
+        )?;
+        if self.iterations > 0 {
+            write!(f, ", {:.1} Mit/s", self.iterations_per_second() / 1e6)?;
+        }
+        Ok(())
+    }
+}
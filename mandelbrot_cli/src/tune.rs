@@ -0,0 +1,164 @@
+// Tuning ////////////////////////////////////////////////////////////
+//        ///////////////////////////////////////////////////////////
+// Pick the number of threads and of rows per block of the renders, see
+// `crate::pool`, with the highest throughput on this machine, in
+// iterations per second, by timing a standard view with each of them.
+// The best depends on the cores and their caches, eg, fewer threads
+// than cores can be faster on CPUs with efficiency cores, so it is
+// measured rather than guessed.
+//
+// The tuning is saved to a cache file, see `Tuning::path()`, by
+// `mandelbrot_cli tune`, and the apps apply it on start with
+// `Tuning::load_cached()`. `MandelConfig::threads` and the
+// `MANDELBROT_THREADS` environment variable still take precedence over
+// its threads.
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{pool, render_timed, MandelConfig, MandelError, Resolution};
+
+/// Environment variable with the path of the cache file, see
+/// `Tuning::path()`
+pub const TUNING_ENV: &str = "MANDELBROT_TUNING";
+
+/// Rows per block tried by `Tuning::autotune()`
+const BLOCK_ROWS: [usize; 6] = [1, 2, 4, 8, 16, 32];
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tuning {
+    /// Number of threads of the renders
+    pub threads: usize,
+    /// Rows each thread takes at a time
+    pub block_rows: usize,
+    /// Throughput measured with them
+    pub iterations_per_second: f64,
+}
+
+impl Tuning {
+    /// View timed, the whole Mandelbrot set, with rows of all costs
+    fn view() -> MandelConfig {
+        let mut cfg = MandelConfig {
+            max_iters: 512,
+            ..Default::default()
+        };
+        cfg.domains().resize(Resolution { x: 640, y: 360 });
+        cfg
+    }
+
+    /// Time the renders with `threads` threads and blocks of
+    /// `block_rows` rows, the fastest of `runs` after a warm-up.
+    ///
+    /// The renders of other threads meanwhile take blocks of
+    /// `block_rows` rows too.
+    pub fn measure(threads: usize, block_rows: usize, runs: usize) -> Tuning {
+        let cfg = MandelConfig {
+            threads: Some(threads.max(1)),
+            ..Self::view()
+        };
+        let previous = pool::block_rows();
+        pool::set_block_rows(block_rows);
+        let render = || pool::install(&cfg, || render_timed(cfg, None)).1;
+        render();
+        let iterations_per_second = (0..runs.max(1))
+            .map(|_| render().iterations_per_second())
+            .fold(0.0, f64::max);
+        pool::set_block_rows(previous);
+        Tuning {
+            threads: threads.max(1),
+            block_rows: block_rows.max(1),
+            iterations_per_second,
+        }
+    }
+
+    /// Return the tuning with the highest throughput, timing each
+    /// setting `runs` times, see `measure()`, and calling `measured` with
+    /// it. The threads, up to one per core, are tuned first, then the
+    /// rows per block with the best of them.
+    pub fn autotune(runs: usize, mut measured: impl FnMut(&Tuning)) -> Tuning {
+        let cores = rayon::current_num_threads();
+        let mut threads: Vec<usize> = std::iter::successors(Some(1), |n| Some(n * 2))
+            .take_while(|&n| n < cores)
+            .collect();
+        threads.push(cores);
+        let mut measure = |threads, block_rows| {
+            let tuning = Self::measure(threads, block_rows, runs);
+            measured(&tuning);
+            tuning
+        };
+        let best = |a: Tuning, b: Tuning| {
+            if b.iterations_per_second > a.iterations_per_second {
+                b
+            } else {
+                a
+            }
+        };
+        let by_threads = threads
+            .into_iter()
+            .map(|threads| measure(threads, pool::DEFAULT_BLOCK_ROWS))
+            .reduce(best)
+            .expect("there is at least one core");
+        BLOCK_ROWS
+            .into_iter()
+            .filter(|&rows| rows != pool::DEFAULT_BLOCK_ROWS)
+            .map(|rows| measure(by_threads.threads, rows))
+            .fold(by_threads, best)
+    }
+
+    /// Render with these threads, unless a config or the environment ask
+    /// for others, and blocks of rows
+    pub fn apply(&self) {
+        pool::set_tuned(self.threads, self.block_rows);
+    }
+
+    /// Return the path of the cache file, `MANDELBROT_TUNING` if set, or
+    /// else `mandelbrot/tuning.json` in the cache directory of the user,
+    /// `None` if there's none
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(TUNING_ENV) {
+            return Some(PathBuf::from(path));
+        }
+        let cache = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+        Some(cache.join("mandelbrot").join("tuning.json"))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MandelError> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json).map_err(std::io::Error::from)?)
+    }
+
+    /// Save the tuning as JSON, creating the directory of `path`
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MandelError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        Ok(std::fs::write(path, json)?)
+    }
+
+    /// Load the tuning of the cache file, if any, and apply it
+    pub fn load_cached() -> Option<Self> {
+        let path = Self::path()?;
+        match Self::load(&path) {
+            Ok(tuning) => {
+                log::debug!(
+                    "tuning of \"{}\" applied, {} threads, {} rows per block",
+                    path.display(),
+                    tuning.threads,
+                    tuning.block_rows
+                );
+                tuning.apply();
+                Some(tuning)
+            }
+            Err(MandelError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                log::warn!("tuning of \"{}\" ignored - {e}", path.display());
+                None
+            }
+        }
+    }
+}
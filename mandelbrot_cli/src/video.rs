@@ -0,0 +1,83 @@
+// Video /////////////////////////////////////////////////////////////
+//       ////////////////////////////////////////////////////////////
+// Video files encoded by an external ffmpeg process, which reads the
+// frames as raw RGB from a pipe, so that a zoom sequence is saved as a
+// ready to share video instead of a directory of PNG files. The codec
+// is the default one of ffmpeg for the container, given by the
+// extension of the file, eg, H.264 for `.mp4` or VP9 for `.webm`, with
+// `crf` setting its quality, lower is better.
+//
+// ffmpeg is looked up in the `PATH`, or given by the `MANDELBROT_FFMPEG`
+// environment variable.
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// Environment variable with the path of ffmpeg
+pub const FFMPEG_ENV: &str = "MANDELBROT_FFMPEG";
+
+pub struct VideoWriter {
+    ffmpeg: Child,
+    stdin: ChildStdin,
+    frame_size: usize,
+}
+
+impl VideoWriter {
+    /// Start encoding a video of `width` by `height` pixels at `fps`
+    /// frames per second to `path`, replacing it
+    pub fn create(path: &Path, width: usize, height: usize, fps: f64, crf: u8) -> io::Result<Self> {
+        let program = std::env::var_os(FFMPEG_ENV).unwrap_or_else(|| "ffmpeg".into());
+        let mut ffmpeg = Command::new(&program)
+            .args(["-hide_banner", "-loglevel", "error", "-y"])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgb24"])
+            .args(["-video_size", &format!("{width}x{height}")])
+            .args(["-framerate", &fps.to_string(), "-i", "-"])
+            // the chroma of yuv420p, which the players expect, needs an
+            // even size
+            .args([
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+                "-pix_fmt",
+                "yuv420p",
+            ])
+            .args(["-crf", &crf.to_string()])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                let program = program.to_string_lossy();
+                io::Error::new(
+                    e.kind(),
+                    format!("{program} not started, set {FFMPEG_ENV} to its path - {e}"),
+                )
+            })?;
+        let stdin = ffmpeg.stdin.take().expect("stdin is piped");
+        Ok(Self {
+            ffmpeg,
+            stdin,
+            frame_size: 3 * width * height,
+        })
+    }
+
+    /// Add a frame, its RGB bytes row-major from the top-left corner,
+    /// eg, of `get_rgb_buf()`
+    pub fn add_frame(&mut self, rgb: &[u8]) -> io::Result<()> {
+        if rgb.len() != self.frame_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frame does not match the video size",
+            ));
+        }
+        self.stdin.write_all(rgb)
+    }
+
+    /// Close the pipe and wait for ffmpeg to finish the file
+    pub fn finish(mut self) -> io::Result<()> {
+        drop(self.stdin);
+        let status = self.ffmpeg.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("ffmpeg failed, {status}")));
+        }
+        Ok(())
+    }
+}
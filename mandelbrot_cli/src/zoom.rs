@@ -0,0 +1,147 @@
+// Zoom sequences ////////////////////////////////////////////////////
+//                ///////////////////////////////////////////////////
+// Frames of an animation zooming from a view into a point, eg, to be
+// put together into a video with ffmpeg.
+//
+// The width of the domain is interpolated geometrically, so that the
+// zoom speed is constant with `Easing::Linear`. The centre moves to the
+// target point in proportion to the width, ie, the target point stays
+// still on the screen while zooming in. With `MandelConfig::auto_iters`,
+// `max_iters` grows with the zoom.
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::origin::{Coordinate, Origin};
+use crate::{mandel_unchecked, pool, Domain, IterationGrid, MandelConfig, MandelError};
+
+/// Speed profile of the zoom, see `Easing::apply`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Easing {
+    /// Constant zoom speed
+    #[default]
+    Linear,
+    /// Starts slow
+    EaseIn,
+    /// Ends slow
+    EaseOut,
+    /// Starts and ends slow
+    EaseInOut,
+}
+impl Easing {
+    /// Map the progress `t` in `[0, 1]` to the eased progress
+    pub fn apply(&self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+impl std::str::FromStr for Easing {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Easing::Linear),
+            "ease-in" => Ok(Easing::EaseIn),
+            "ease-out" => Ok(Easing::EaseOut),
+            "ease-in-out" => Ok(Easing::EaseInOut),
+            _ => Err(format!("unknown easing \"{s}\"")),
+        }
+    }
+}
+
+/// Zoom from the view of `start` into `center`, a point of the complex
+/// plane in high precision, until the x domain is `end_width` wide
+#[derive(Clone, Copy, Debug)]
+pub struct ZoomSequence {
+    pub start: MandelConfig,
+    pub center: (Coordinate, Coordinate),
+    pub end_width: f64,
+    pub frames: usize,
+    pub easing: Easing,
+}
+impl ZoomSequence {
+    /// Return the config of frame `i`, in `0..frames`
+    pub fn frame(&self, i: usize) -> MandelConfig {
+        let cfg = &self.start;
+        let t = self.easing.apply(i as f64 / (self.frames - 1).max(1) as f64);
+        let (w0, h0) = (
+            cfg.xdomain.end - cfg.xdomain.start,
+            cfg.ydomain.end - cfg.ydomain.start,
+        );
+        let (x0, y0) = (
+            (cfg.xdomain.start + cfg.xdomain.end) / 2.0,
+            (cfg.ydomain.start + cfg.ydomain.end) / 2.0,
+        );
+        let scale = (self.end_width / w0).powf(t);
+        // 1.0 at the start, 0.0 at the end, proportional to the width
+        let offset = if w0 == self.end_width {
+            1.0 - t
+        } else {
+            (w0 * scale - self.end_width) / (w0 - self.end_width)
+        };
+        // the frames are around their centre, moved to the origin, so
+        // that the deep ones keep their width, see `DomainManager`
+        let center = Origin::from_plane(&self.center.0, &self.center.1, cfg.rotation);
+        let (cx, cy) = center.offset_from(&cfg.origin);
+
+        let mut frame = *cfg;
+        frame.origin = center.shifted((x0 - cx) * offset, (y0 - cy) * offset);
+        frame.xdomain = Domain {
+            start: -w0 * scale / 2.0,
+            end: w0 * scale / 2.0,
+        };
+        frame.ydomain = Domain {
+            start: -h0 * scale / 2.0,
+            end: h0 * scale / 2.0,
+        };
+        frame.domains().recenter();
+        frame.update_max_iters();
+        frame
+    }
+
+    /// Return the configs of all frames
+    pub fn configs(&self) -> Vec<MandelConfig> {
+        (0..self.frames).map(|i| self.frame(i)).collect()
+    }
+
+    /// Render the frames in parallel, calling `callback` with the index
+    /// and the grid of each frame as it completes, in any order. Return
+    /// an error, before rendering, if the config of any frame is invalid.
+    /// The frames not started are skipped once `callback` breaks, eg, on
+    /// Ctrl-C, and the ones in progress complete.
+    pub fn render<F>(&self, callback: F) -> Result<(), MandelError>
+    where
+        F: Fn(usize, IterationGrid) -> ControlFlow<()> + Sync,
+    {
+        render_frames(self.configs(), callback)
+    }
+}
+
+/// Render the frames `configs` in parallel, on the threads of the first
+/// one, see `ZoomSequence::render()`
+pub(crate) fn render_frames<F>(configs: Vec<MandelConfig>, callback: F) -> Result<(), MandelError>
+where
+    F: Fn(usize, IterationGrid) -> ControlFlow<()> + Sync,
+{
+    configs.iter().try_for_each(MandelConfig::validate)?;
+    let Some(first) = configs.first().copied() else {
+        return Ok(());
+    };
+    let stopped = AtomicBool::new(false);
+    pool::install(&first, || {
+        configs.into_par_iter().enumerate().for_each(|(i, cfg)| {
+            if stopped.load(Ordering::Relaxed) {
+                return;
+            }
+            if callback(i, mandel_unchecked(cfg)).is_break() {
+                stopped.store(true, Ordering::Relaxed);
+            }
+        })
+    });
+    Ok(())
+}
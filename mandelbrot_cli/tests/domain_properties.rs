@@ -0,0 +1,212 @@
+// Properties of the domain math over many random views, from the whole
+// set down to deep zooms, at any resolution and scale factor: the
+// pixels map into the domains, zooming keeps the anchor under the mouse,
+// panning keeps the size of the view, and the validation rejects the
+// domains that can not be rendered. Views deeper than `f64` resolves
+// keep their centre in their origin. The views come from a seeded
+// generator, so a failure is reproducible, and its message gives the
+// view.
+use mandelbrot_cli::{origin::Coordinate, Domain, MandelConfig, Resolution};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+const CASES: usize = 2000;
+
+fn rng() -> SmallRng {
+    SmallRng::seed_from_u64(0x6d616e64656c)
+}
+
+/// Random view with square pixels, centred within the default domains,
+/// from 3 to 1e-12 wide
+fn random_view(rng: &mut SmallRng) -> MandelConfig {
+    let mut cfg = MandelConfig {
+        resolution: Resolution {
+            x: rng.gen_range(2..4000),
+            y: rng.gen_range(2..4000),
+        },
+        ..Default::default()
+    };
+    let width = 10_f64.powf(rng.gen_range(-12.0..0.5));
+    cfg.domains()
+        .set_center(rng.gen_range(-2.0..1.0), rng.gen_range(-1.2..1.2), width);
+    cfg
+}
+
+/// Random point of the domains of `cfg`
+fn random_point(rng: &mut SmallRng, cfg: &MandelConfig) -> (f64, f64) {
+    let along = |d: Domain, t: f64| d.start + t * (d.end - d.start);
+    (
+        along(cfg.xdomain, rng.gen_range(0.0..=1.0)),
+        along(cfg.ydomain, rng.gen_range(0.0..=1.0)),
+    )
+}
+
+/// Rounding error of the arithmetic on the coordinates of `cfg`, a few
+/// ulps of the largest
+fn rounding(cfg: &MandelConfig) -> f64 {
+    let largest = [cfg.xdomain, cfg.ydomain]
+        .iter()
+        .flat_map(|d| [d.start.abs(), d.end.abs()])
+        .fold(0.0, f64::max);
+    8.0 * f64::EPSILON * largest
+}
+
+#[test]
+fn every_pixel_maps_into_the_domains() {
+    let mut rng = rng();
+    for _ in 0..CASES {
+        let cfg = random_view(&mut rng);
+        let scale_factor = rng.gen_range(1.0..3.0);
+        let map = cfg.screen_map(scale_factor);
+        let (rx, ry) = (cfg.resolution.x, cfg.resolution.y);
+        let pixels = [
+            (0, 0),
+            (rx - 1, ry - 1),
+            (0, ry - 1),
+            (rx - 1, 0),
+            (rng.gen_range(0..rx), rng.gen_range(0..ry)),
+        ];
+        for (i, j) in pixels {
+            // the centre of the pixel, in points from the centre of the
+            // window
+            let position = [
+                (i as f64 + 0.5 - rx as f64 / 2.0) / scale_factor,
+                (j as f64 + 0.5 - ry as f64 / 2.0) / scale_factor,
+            ];
+            let (x, y) = map.to_domains(position);
+            let tolerance = rounding(&cfg);
+            let inside = |v: f64, d: Domain| v >= d.start - tolerance && v <= d.end + tolerance;
+            assert!(
+                inside(x, cfg.xdomain) && inside(y, cfg.ydomain),
+                "pixel ({i}, {j}) at ({x}, {y}) outside of {cfg:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn validation_accepts_the_views() {
+    let mut rng = rng();
+    for _ in 0..CASES {
+        let cfg = random_view(&mut rng);
+        assert!(cfg.validate().is_ok(), "{cfg:?}");
+    }
+}
+
+#[test]
+fn zoom_about_point_keeps_the_anchor() {
+    let mut rng = rng();
+    for _ in 0..CASES {
+        let mut cfg = random_view(&mut rng);
+        let scale_factor = rng.gen_range(1.0..3.0);
+        let (x, y) = random_point(&mut rng, &cfg);
+        let before = cfg.screen_map(scale_factor).to_window((x, y));
+        let factor = 10_f64.powf(rng.gen_range(-1.0..1.0));
+        cfg.domains().zoom_about_point(x, y, factor);
+        let after = cfg.screen_map(scale_factor).to_window((x, y));
+        // the rounding of the coordinates, in points, at the pixels of
+        // the smaller of the two views
+        let step = (cfg.xdomain.end - cfg.xdomain.start) / (cfg.resolution.x - 1) as f64;
+        let tolerance = rounding(&cfg) / step.min(step / factor) / scale_factor + 1e-9;
+        assert!(
+            (after[0] - before[0]).abs() <= tolerance && (after[1] - before[1]).abs() <= tolerance,
+            "({x}, {y}) moved from {before:?} to {after:?} zooming {factor} into {cfg:?}"
+        );
+    }
+}
+
+#[test]
+fn pan_keeps_the_size() {
+    let mut rng = rng();
+    for _ in 0..CASES {
+        let mut cfg = random_view(&mut rng);
+        let (width, height) = (
+            cfg.xdomain.end - cfg.xdomain.start,
+            cfg.ydomain.end - cfg.ydomain.start,
+        );
+        let (dx, dy) = (
+            width * rng.gen_range(-2.0..2.0),
+            height * rng.gen_range(-2.0..2.0),
+        );
+        let before = cfg;
+        cfg.domains().pan(dx, dy);
+        let tolerance = rounding(&before).max(rounding(&cfg));
+        assert!(
+            (cfg.xdomain.end - cfg.xdomain.start - width).abs() <= tolerance
+                && (cfg.ydomain.end - cfg.ydomain.start - height).abs() <= tolerance,
+            "panning ({dx}, {dy}) resized {before:?} to {cfg:?}"
+        );
+        assert_eq!(cfg.resolution, before.resolution);
+    }
+}
+
+#[test]
+fn validation_rejects_nan_infinite_empty_and_inverted_domains() {
+    let mut rng = rng();
+    for _ in 0..CASES {
+        let cfg = random_view(&mut rng);
+        let Domain { start, end } = cfg.xdomain;
+        let bad = [
+            Domain {
+                start: f64::NAN,
+                end,
+            },
+            Domain {
+                start,
+                end: f64::NAN,
+            },
+            Domain {
+                start,
+                end: f64::INFINITY,
+            },
+            Domain {
+                start: f64::NEG_INFINITY,
+                end,
+            },
+            Domain { start, end: start },
+            Domain {
+                start: end,
+                end: start,
+            },
+        ];
+        for domain in bad {
+            let x = MandelConfig {
+                xdomain: domain,
+                ..cfg
+            };
+            assert!(x.validate().is_err(), "x domain {domain:?} is valid");
+            let y = MandelConfig {
+                ydomain: domain,
+                ..cfg
+            };
+            assert!(y.validate().is_err(), "y domain {domain:?} is valid");
+        }
+    }
+}
+
+#[test]
+fn deep_views_keep_their_centre() {
+    let (x, y): (Coordinate, Coordinate) = (
+        "-0.743643887037158704752191506114774".parse().unwrap(),
+        "0.131825904205311970493132056385139".parse().unwrap(),
+    );
+    for exponent in 13..=50 {
+        let width = 10_f64.powi(-exponent);
+        let mut cfg = MandelConfig {
+            resolution: Resolution { x: 64, y: 48 },
+            ..Default::default()
+        };
+        cfg.domains().set_center(0.0, 0.0, width).center_on(x, y);
+        assert!(cfg.validate().is_ok(), "1e-{exponent} wide {cfg:?}");
+        assert_eq!(cfg.center(), (x, y), "1e-{exponent} wide");
+        let view = cfg.xdomain.end - cfg.xdomain.start;
+        assert!(
+            (view / width - 1.0).abs() < 1e-9,
+            "1e-{exponent} wide {cfg:?}"
+        );
+        // out of the deep zoom, the origin is folded back into the domains
+        cfg.domains()
+            .zoom_about_point(0.0, 0.0, 10_f64.powi(exponent))
+            .recenter();
+        assert!(cfg.origin.is_zero(), "1e-{exponent} zoomed out {cfg:?}");
+    }
+}
@@ -0,0 +1,210 @@
+// Golden images: a handful of fixed views rendered at small resolutions
+// and compared with the data checked in `tests/golden`, so that changes
+// to the render paths, eg, the SIMD, GPU or perturbation backends, can
+// be validated against the scalar one. The scalar renders, on any number
+// of threads, and the SIMD ones in `f64` must match the iteration counts
+// exactly, and the colors of every built-in color scheme, hashed, must
+// match too. The other engines may differ by an iteration here and
+// there, eg, at the edges of the set, on a small fraction of the
+// pixels.
+//
+// After a deliberate change of the output, regenerate the data with
+// `GOLDEN_UPDATE=1 cargo test --test golden` and review the diff.
+use std::path::PathBuf;
+
+use mandelbrot_cli::{
+    color_schemes::ColorSchemes, get_rgb_buf, landmarks::Landmark, mandel, mandel_single,
+    ComputeBackend, Domain, Engine, Formula, FractalKind, IterationGrid, MandelConfig, Precision,
+    Resolution,
+};
+
+const RESOLUTION: Resolution = Resolution { x: 64, y: 48 };
+
+/// Largest fraction of pixels of the other engines that may differ from
+/// the golden data
+const TOLERANCE: f64 = 0.01;
+
+fn domain(start: f64, end: f64) -> Domain {
+    Domain { start, end }
+}
+
+/// The fixed views, by the name of their golden data
+fn views() -> Vec<(&'static str, MandelConfig)> {
+    let base = MandelConfig {
+        resolution: RESOLUTION,
+        xdomain: domain(-2.2, 0.8),
+        ydomain: domain(-1.125, 1.125),
+        max_iters: 256,
+        ..Default::default()
+    };
+    let mut seahorse = base;
+    "seahorse"
+        .parse::<Landmark>()
+        .expect("seahorse is a landmark")
+        .apply(&mut seahorse);
+    seahorse.max_iters = 512;
+    vec![
+        ("mandelbrot", base),
+        ("seahorse", seahorse),
+        (
+            "julia",
+            MandelConfig {
+                kind: FractalKind::Julia {
+                    cx: -0.8,
+                    cy: 0.156,
+                },
+                xdomain: domain(-1.6, 1.6),
+                ydomain: domain(-1.2, 1.2),
+                ..base
+            },
+        ),
+        (
+            "burning-ship",
+            MandelConfig {
+                formula: Formula::BurningShip,
+                xdomain: domain(-2.4, 1.4),
+                ydomain: domain(-2.0, 0.85),
+                ..base
+            },
+        ),
+        (
+            "tricorn",
+            MandelConfig {
+                formula: Formula::Tricorn,
+                xdomain: domain(-2.0, 2.0),
+                ydomain: domain(-1.5, 1.5),
+                ..base
+            },
+        ),
+    ]
+}
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+/// Regenerate the golden data instead of comparing with it
+fn updating() -> bool {
+    std::env::var_os("GOLDEN_UPDATE").is_some()
+}
+
+/// Iteration counts, a line per row of the grid
+fn to_text(grid: &IterationGrid) -> String {
+    grid.data
+        .chunks(grid.width)
+        .map(|row| {
+            let row: Vec<_> = row.iter().map(u32::to_string).collect();
+            row.join(" ") + "\n"
+        })
+        .collect()
+}
+
+fn from_text(text: &str) -> Vec<u32> {
+    text.split_whitespace()
+        .map(|count| count.parse().expect("golden iteration counts"))
+        .collect()
+}
+
+/// Read the golden data `file`
+fn read_golden(file: &str) -> String {
+    let path = golden_dir().join(file);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "{} - {e}, run with GOLDEN_UPDATE=1 to create it",
+            path.display()
+        )
+    })
+}
+
+/// Golden iteration counts of the view `name`, rendered in `grid` with
+/// the scalar backend
+fn golden_iters(name: &str, grid: &IterationGrid) -> Vec<u32> {
+    let file = format!("{name}.txt");
+    if updating() {
+        std::fs::create_dir_all(golden_dir()).unwrap();
+        std::fs::write(golden_dir().join(&file), to_text(grid)).unwrap();
+    }
+    from_text(&read_golden(&file))
+}
+
+/// FNV-1a, stable across platforms and releases, unlike the hasher of
+/// the standard library
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[test]
+fn scalar_renders_match_golden_iterations() {
+    for (name, cfg) in views() {
+        let grid = mandel(cfg).unwrap();
+        assert_eq!(grid.data, golden_iters(name, &grid), "{name}");
+    }
+}
+
+#[test]
+fn exact_render_paths_match_golden_iterations() {
+    for (name, cfg) in views() {
+        let golden = from_text(&read_golden(&format!("{name}.txt")));
+        let single = mandel_single(cfg).unwrap();
+        assert_eq!(single.data, golden, "{name}: single thread");
+        let threads = mandel(MandelConfig {
+            threads: Some(3),
+            ..cfg
+        })
+        .unwrap();
+        assert_eq!(threads.data, golden, "{name}: 3 threads");
+        // in `f64`, `Auto` picks `f32` for SIMD where it's enough
+        let simd = mandel(MandelConfig {
+            backend: ComputeBackend::Simd,
+            precision: Precision::F64,
+            ..cfg
+        })
+        .unwrap();
+        assert_eq!(simd.data, golden, "{name}: simd");
+    }
+}
+
+#[test]
+fn other_engines_are_close_to_golden_iterations() {
+    for (name, cfg) in views() {
+        let golden = from_text(&read_golden(&format!("{name}.txt")));
+        for engine in [Engine::MarianiSilver, Engine::Perturbation] {
+            let grid = mandel(MandelConfig { engine, ..cfg }).unwrap();
+            let differing = grid
+                .data
+                .iter()
+                .zip(&golden)
+                .filter(|(a, b)| a != b)
+                .count();
+            assert!(
+                differing as f64 <= TOLERANCE * golden.len() as f64,
+                "{name}: {engine:?} differs on {differing} pixels"
+            );
+        }
+    }
+}
+
+#[test]
+fn colors_match_golden_hashes() {
+    let mut color_schemes = ColorSchemes::new();
+    let mut hashes = String::new();
+    for (name, cfg) in views() {
+        let grid = mandel(cfg).unwrap();
+        for index in 0..color_schemes.len() {
+            color_schemes.set(index);
+            let rgb = get_rgb_buf(&grid, cfg.max_iters, &color_schemes).unwrap();
+            hashes += &format!("{name} {} {:016x}\n", color_schemes.name(), fnv1a(&rgb));
+        }
+    }
+    if updating() {
+        std::fs::create_dir_all(golden_dir()).unwrap();
+        std::fs::write(golden_dir().join("colors.txt"), &hashes).unwrap();
+    }
+    let golden = read_golden("colors.txt");
+    for (line, golden) in hashes.lines().zip(golden.lines()) {
+        assert_eq!(line, golden);
+    }
+    assert_eq!(hashes.lines().count(), golden.lines().count());
+}
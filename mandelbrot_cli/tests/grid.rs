@@ -0,0 +1,438 @@
+// Every pixel of the grid is calculated, up to the last column and row,
+// for any resolution and render path. The grid starts with zeros and
+// no pixel of these views escapes before the first iteration, so a
+// pixel left out has 0 iterations. Perturbation tells apart the pixels
+// of views deeper than the `f64` domains resolve.
+use std::ops::ControlFlow;
+
+use mandelbrot_cli::{
+    color_schemes::ColorSchemes, contact_sheet::ContactSheet, get_rgb_buf, mandel, mandel_deeper,
+    mandel_single, mandel_streaming, AaMode, ComputeBackend, Domain, Engine, Formula, FractalKind,
+    IterationGrid, MandelConfig, Precision, Renderer, Resolution,
+};
+
+/// Resolutions around the SIMD width, 4, and odd ones
+const SIZES: [(usize, usize); 9] = [
+    (2, 2),
+    (3, 2),
+    (2, 3),
+    (5, 7),
+    (8, 3),
+    (13, 11),
+    (31, 4),
+    (33, 17),
+    (257, 3),
+];
+
+fn domain(start: f64, end: f64) -> Domain {
+    Domain { start, end }
+}
+
+/// Views covering the render paths, all within `|z|^2 < 4` at the first
+/// iteration
+fn views() -> Vec<(&'static str, MandelConfig)> {
+    let base = MandelConfig {
+        xdomain: domain(-1.9, 0.6),
+        ydomain: domain(-1.2, 1.2),
+        max_iters: 64,
+        ..Default::default()
+    };
+    let julia = MandelConfig {
+        kind: FractalKind::Julia {
+            cx: -0.8,
+            cy: 0.156,
+        },
+        xdomain: domain(-1.0, 1.0),
+        ydomain: domain(-1.0, 1.0),
+        ..base
+    };
+    vec![
+        ("scalar", base),
+        (
+            "simd",
+            MandelConfig {
+                backend: ComputeBackend::Simd,
+                ..base
+            },
+        ),
+        (
+            "f32",
+            MandelConfig {
+                precision: Precision::F32,
+                ..base
+            },
+        ),
+        (
+            "simd-f32",
+            MandelConfig {
+                backend: ComputeBackend::Simd,
+                precision: Precision::F32,
+                ..base
+            },
+        ),
+        (
+            "arbitrary",
+            MandelConfig {
+                precision: Precision::Arbitrary,
+                ..base
+            },
+        ),
+        (
+            "perturbation",
+            MandelConfig {
+                engine: Engine::Perturbation,
+                ..base
+            },
+        ),
+        (
+            "mariani-silver",
+            MandelConfig {
+                engine: Engine::MarianiSilver,
+                ..base
+            },
+        ),
+        (
+            "interior-check",
+            MandelConfig {
+                interior_check: true,
+                ..base
+            },
+        ),
+        (
+            "simd-interior-check",
+            MandelConfig {
+                backend: ComputeBackend::Simd,
+                interior_check: true,
+                ..base
+            },
+        ),
+        (
+            "trap",
+            MandelConfig {
+                trap: "point".parse().ok(),
+                ..base
+            },
+        ),
+        (
+            "distance",
+            MandelConfig {
+                distance: true,
+                ..base
+            },
+        ),
+        (
+            "final-z",
+            MandelConfig {
+                final_z: true,
+                ..base
+            },
+        ),
+        (
+            "stripes",
+            MandelConfig {
+                stripes: Some(5.0),
+                ..base
+            },
+        ),
+        ("power-3", MandelConfig { power: 3.0, ..base }),
+        (
+            "burning-ship",
+            MandelConfig {
+                formula: Formula::BurningShip,
+                ..base
+            },
+        ),
+        ("julia", julia),
+        (
+            "simd-julia",
+            MandelConfig {
+                backend: ComputeBackend::Simd,
+                ..julia
+            },
+        ),
+        (
+            "rotated",
+            MandelConfig {
+                rotation: 0.5,
+                ..base
+            },
+        ),
+        (
+            "simd-rotated",
+            MandelConfig {
+                backend: ComputeBackend::Simd,
+                rotation: 0.5,
+                ..base
+            },
+        ),
+        (
+            "threads",
+            MandelConfig {
+                threads: Some(3),
+                ..base
+            },
+        ),
+        (
+            "aa-full",
+            MandelConfig {
+                aa: AaMode::Full { samples: 2 },
+                ..base
+            },
+        ),
+        (
+            "aa-adaptive",
+            MandelConfig {
+                aa: AaMode::Adaptive {
+                    tolerance: 1.0,
+                    max_samples: 3,
+                },
+                ..base
+            },
+        ),
+    ]
+}
+
+fn with_size(cfg: MandelConfig, (x, y): (usize, usize)) -> MandelConfig {
+    MandelConfig {
+        resolution: Resolution { x, y },
+        ..cfg
+    }
+}
+
+#[test]
+fn every_pixel_is_calculated() {
+    for (name, view) in views() {
+        for size in SIZES {
+            let cfg = with_size(view, size);
+            let grid = mandel(cfg).unwrap();
+            let n = cfg.aa.samples();
+            assert_eq!(
+                (grid.width, grid.height),
+                (size.0 * n, size.1 * n),
+                "{name} {size:?}"
+            );
+            assert_eq!(grid.image_size(), size, "{name} {size:?}");
+            grid.validate().unwrap();
+            for (i, &iters) in grid.data.iter().enumerate() {
+                let (x, y) = (i % grid.width, i / grid.width);
+                assert!(
+                    iters >= 1,
+                    "{name} {size:?}: pixel ({x}, {y}) not calculated"
+                );
+            }
+            if cfg.trap.is_some() {
+                assert!(grid.trap.iter().all(|t| t.is_finite()), "{name} {size:?}");
+            }
+            if cfg.final_z {
+                assert_eq!(grid.z_abs.len(), grid.data.len(), "{name} {size:?}");
+                assert!(grid.z_abs.iter().all(|z| z.is_finite()), "{name} {size:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn single_threaded_matches_parallel() {
+    for (name, view) in views() {
+        // they only differ in the paths `mandel_single()` does not take
+        if view.engine != Engine::Direct || matches!(view.aa, AaMode::Adaptive { .. }) {
+            continue;
+        }
+        for size in SIZES {
+            let cfg = with_size(view, size);
+            let (single, parallel) = (mandel_single(cfg).unwrap(), mandel(cfg).unwrap());
+            assert_eq!(single.data, parallel.data, "{name} {size:?}");
+            assert_eq!(single.smooth, parallel.smooth, "{name} {size:?}");
+        }
+    }
+}
+
+#[test]
+fn final_z_is_conjugate_across_the_real_axis() {
+    // the base view is centred on the real axis
+    let view = MandelConfig {
+        final_z: true,
+        ..views()[0].1
+    };
+    for size in SIZES {
+        let grid = mandel(with_size(view, size)).unwrap();
+        for y in 0..grid.height / 2 {
+            let mirror = grid.height - 1 - y;
+            for x in 0..grid.width {
+                let (a, b) = (grid.get_pixel(x, y), grid.get_pixel(x, mirror));
+                assert_eq!(a.escape.iters, b.escape.iters, "{size:?} ({x}, {y})");
+                assert_eq!(a.z_abs, b.z_abs, "{size:?} ({x}, {y})");
+                assert_eq!(a.z_arg, -b.z_arg, "{size:?} ({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn renderer_matches_mandel() {
+    let renderer = Renderer::with_threads(2);
+    for (name, view) in views() {
+        for size in SIZES {
+            let cfg = with_size(view, size);
+            let grid = mandel(cfg).unwrap();
+            // the second render takes the axes from the cache
+            for _ in 0..2 {
+                let rendered = renderer.render(cfg).unwrap();
+                assert_eq!(rendered.data, grid.data, "{name} {size:?}");
+                assert_eq!(rendered.smooth, grid.smooth, "{name} {size:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn deeper_matches_mandel() {
+    for (name, view) in views() {
+        // the samples are iterated again directly
+        if view.engine != Engine::Direct {
+            continue;
+        }
+        // from the start, or else from the last `z`
+        for resumable in [false, true] {
+            for size in SIZES {
+                let prev_cfg = MandelConfig {
+                    resumable,
+                    ..with_size(view, size)
+                };
+                let cfg = MandelConfig {
+                    max_iters: 4 * prev_cfg.max_iters,
+                    ..prev_cfg
+                };
+                let prev = mandel(prev_cfg).unwrap();
+                let (deeper, grid) = (
+                    mandel_deeper(&prev, &prev_cfg, cfg).unwrap(),
+                    mandel(cfg).unwrap(),
+                );
+                let name = format!("{name} resumable={resumable} {size:?}");
+                assert_eq!(deeper.data, grid.data, "{name}");
+                assert_eq!(deeper.smooth, grid.smooth, "{name}");
+                assert_eq!(deeper.trap, grid.trap, "{name}");
+                // the `z` of the points found periodic is that of
+                // the iteration they were found at
+                let escaped = |grid: &IterationGrid| -> Vec<_> {
+                    (0..grid.zx.len())
+                        .filter(|&i| grid.data[i] < cfg.max_iters as u32)
+                        .map(|i| (grid.zx[i], grid.zy[i]))
+                        .collect()
+                };
+                assert_eq!(escaped(&deeper), escaped(&grid), "{name}");
+            }
+        }
+    }
+}
+
+#[test]
+fn deeper_than_an_invalid_config_renders_again() {
+    let cfg = with_size(views()[0].1, (13, 11));
+    let prev = mandel(cfg).unwrap();
+    let prev_cfg = MandelConfig {
+        max_iters: 0,
+        ..cfg
+    };
+    let deeper = mandel_deeper(&prev, &prev_cfg, cfg).unwrap();
+    assert_eq!(deeper.data, mandel(cfg).unwrap().data);
+}
+
+#[test]
+fn deep_perturbation_tells_the_pixels_apart() {
+    // 1e-30 wide, far below what the `f64` domains resolve
+    let mut cfg = MandelConfig {
+        engine: Engine::Perturbation,
+        max_iters: 40000,
+        ..with_size(views()[0].1, (8, 6))
+    };
+    cfg.domains().set_center(0.0, 0.0, 1e-30).center_on(
+        "-0.743643887037158704752191506114774".parse().unwrap(),
+        "0.131825904205311970493132056385139".parse().unwrap(),
+    );
+    let grid = mandel(cfg).unwrap();
+    let mut iters = grid.data.clone();
+    iters.sort_unstable();
+    iters.dedup();
+    assert!(iters.len() > grid.data.len() / 2, "{:?}", grid.data);
+    assert!(
+        !grid.data.contains(&(cfg.max_iters as u32)),
+        "{:?}",
+        grid.data
+    );
+}
+
+#[test]
+fn streaming_bands_cover_the_grid() {
+    for size in SIZES {
+        let cfg = with_size(views()[0].1, size);
+        let grid = mandel(cfg).unwrap();
+        let mut rows_seen = vec![0; size.1];
+        mandel_streaming(cfg, 3, |y, band| {
+            assert_eq!(band.width, size.0);
+            for row in 0..band.height {
+                rows_seen[y + row] += 1;
+                let (start, end) = ((y + row) * size.0, (y + row + 1) * size.0);
+                assert_eq!(
+                    &band.data[row * size.0..(row + 1) * size.0],
+                    &grid.data[start..end]
+                );
+            }
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert!(rows_seen.iter().all(|&n| n == 1), "{size:?}: {rows_seen:?}");
+    }
+}
+
+#[test]
+fn contact_sheet_cells_are_the_julia_sets() {
+    let mut sheet = ContactSheet::new(domain(-0.8, 0.2), domain(0.0, 0.6), 3, 2);
+    sheet.cell = with_size(sheet.cell, (13, 11));
+    sheet.labels = false;
+    assert_eq!(sheet.c(0, 0), (-0.8, 0.6));
+    assert_eq!(sheet.c(2, 1), (0.2, 0.0));
+    let color_schemes = ColorSchemes::new();
+    let mut cells = 0;
+    let rgb = sheet.render(&color_schemes, || cells += 1).unwrap();
+    assert_eq!(cells, 6);
+    let (width, height) = sheet.size();
+    assert_eq!(rgb.len(), 3 * width * height);
+    // the cell at column 1 and row 1, after the gaps of 2 pixels
+    let cfg = sheet.config(1, 1);
+    let cell = get_rgb_buf(&mandel(cfg).unwrap(), cfg.max_iters, &color_schemes).unwrap();
+    let (left, top) = (2 + 15, 2 + 13);
+    for (y, line) in cell.chunks_exact(3 * 13).enumerate() {
+        let start = 3 * ((top + y) * width + left);
+        assert_eq!(&rgb[start..start + line.len()], line, "row {y}");
+    }
+}
+
+#[test]
+fn invalid_configs_are_errors() {
+    let base = views()[0].1;
+    for size in [(0, 0), (1, 5), (5, 1)] {
+        assert!(mandel(with_size(base, size)).is_err(), "{size:?}");
+        assert!(mandel_single(with_size(base, size)).is_err(), "{size:?}");
+    }
+    assert!(mandel(MandelConfig {
+        xdomain: domain(1.0, 1.0),
+        ..base
+    })
+    .is_err());
+    assert!(mandel(MandelConfig {
+        ydomain: domain(0.0, f64::NAN),
+        ..base
+    })
+    .is_err());
+    assert!(mandel(MandelConfig {
+        threshold: 0.0,
+        ..base
+    })
+    .is_err());
+    assert!(mandel(MandelConfig {
+        max_iters: 0,
+        ..base
+    })
+    .is_err());
+}
@@ -0,0 +1,48 @@
+// A link of a view opens the same view, from the whole set down to deep
+// zooms, whose centre has more digits than `f64` holds.
+use mandelbrot_cli::{link::ViewLink, origin::Coordinate, MandelConfig, Resolution};
+
+fn view(width: f64, rotation: f64) -> MandelConfig {
+    let mut cfg = MandelConfig {
+        resolution: Resolution { x: 64, y: 48 },
+        max_iters: 5000,
+        rotation: rotation.to_radians(),
+        ..Default::default()
+    };
+    cfg.domains().set_center(0.0, 0.0, width).center_on(
+        "-0.743643887037158704752191506114774".parse().unwrap(),
+        "0.131825904205311970493132056385139".parse().unwrap(),
+    );
+    cfg
+}
+
+#[test]
+fn links_round_trip() {
+    for (width, rotation) in [(3.0, 0.0), (1e-6, 30.0), (1e-30, 0.0), (1e-30, -45.0)] {
+        let cfg = view(width, rotation);
+        let text = ViewLink::new(&cfg, "wiky").to_string();
+        let link: ViewLink = text.parse().unwrap();
+        assert_eq!(link.to_string(), text);
+
+        let mut opened = MandelConfig {
+            resolution: cfg.resolution,
+            ..Default::default()
+        };
+        link.apply(&mut opened);
+        assert_eq!(opened.max_iters, cfg.max_iters, "{text}");
+        // within a thousandth of a pixel of the centre
+        let step = (cfg.xdomain.end - cfg.xdomain.start) / (cfg.resolution.x - 1) as f64;
+        let distance =
+            |a: Coordinate, b: Coordinate| (a.to_big() - b.to_big()).to_f64().value().abs() / step;
+        let (center, at) = (cfg.center(), opened.center());
+        assert!(
+            distance(center.0, at.0) < 1e-3 && distance(center.1, at.1) < 1e-3,
+            "{text} opens at {at:?}"
+        );
+        let width = opened.xdomain.end - opened.xdomain.start;
+        assert!(
+            (width / (cfg.xdomain.end - cfg.xdomain.start) - 1.0).abs() < 1e-9,
+            "{text} opens {width} wide"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+// The map between a window and the domains of the view is the same at
+// any scale factor of the display, so that the point under the mouse
+// stays under it when zooming, eg, on HiDPI displays.
+use mandelbrot_cli::{domain::ScreenMap, Domain, MandelConfig, Resolution};
+
+const SCALE_FACTORS: [f64; 4] = [1.0, 1.25, 2.0, 3.0];
+
+/// View of a window `w` by `h` points with `scale_factor` pixels per point
+fn view(w: f64, h: f64, scale_factor: f64) -> MandelConfig {
+    let mut cfg = MandelConfig::default();
+    cfg.domains().resize(Resolution {
+        x: (w * scale_factor) as usize,
+        y: (h * scale_factor) as usize,
+    });
+    cfg
+}
+
+fn assert_close(a: (f64, f64), b: (f64, f64), tolerance: f64) {
+    assert!(
+        (a.0 - b.0).abs() <= tolerance && (a.1 - b.1).abs() <= tolerance,
+        "{a:?} != {b:?}"
+    );
+}
+
+#[test]
+fn centre_of_the_window_is_centre_of_the_domains() {
+    for scale_factor in SCALE_FACTORS {
+        let mut cfg = view(800.0, 450.0, scale_factor);
+        let center = cfg.domains().center();
+        assert_close(
+            cfg.screen_map(scale_factor).to_domains([0.0, 0.0]),
+            center,
+            1e-12,
+        );
+    }
+}
+
+#[test]
+fn image_fills_the_window() {
+    for scale_factor in SCALE_FACTORS {
+        let cfg = view(800.0, 450.0, scale_factor);
+        let map = cfg.screen_map(scale_factor);
+        // up to a pixel less, the window is not a whole number of pixels
+        let (w, h) = map.image_size();
+        let pixel = 1.0 / scale_factor;
+        assert!(w <= 800.0 && w > 800.0 - pixel, "{w}");
+        assert!(h <= 450.0 && h > 450.0 - pixel, "{h}");
+        // the domains go from the centre of the first pixel to the
+        // centre of the last one
+        let first = map.to_window((cfg.xdomain.start, cfg.ydomain.start));
+        let last = map.to_window((cfg.xdomain.end, cfg.ydomain.end));
+        let (x, y) = ((w - pixel) / 2.0, (h - pixel) / 2.0);
+        assert_close((first[0], first[1]), (-x, -y), 1e-9);
+        assert_close((last[0], last[1]), (x, y), 1e-9);
+    }
+}
+
+#[test]
+fn to_window_is_the_inverse_of_to_domains() {
+    let map = ScreenMap {
+        xdomain: Domain {
+            start: -0.75,
+            end: -0.74,
+        },
+        ydomain: Domain {
+            start: 0.1,
+            end: 0.105,
+        },
+        resolution: Resolution { x: 1600, y: 900 },
+        scale_factor: 2.0,
+    };
+    for position in [
+        [0.0, 0.0],
+        [-400.0, 225.0],
+        [123.5, -67.25],
+        [399.75, -224.75],
+    ] {
+        let [x, y] = map.to_window(map.to_domains(position));
+        assert_close((x, y), (position[0], position[1]), 1e-6);
+    }
+}
+
+#[test]
+fn zoom_keeps_the_point_under_the_mouse() {
+    for scale_factor in SCALE_FACTORS {
+        let mut cfg = view(800.0, 450.0, scale_factor);
+        let mouse = [250.0, -100.0];
+        for factor in [0.9, 0.5, 1.0 / 0.9] {
+            let (x, y) = cfg.screen_map(scale_factor).to_domains(mouse);
+            cfg.domains().zoom_about_point(x, y, factor);
+            let (zx, zy) = cfg.screen_map(scale_factor).to_domains(mouse);
+            let step = (cfg.xdomain.end - cfg.xdomain.start) / cfg.resolution.x as f64;
+            assert_close((zx, zy), (x, y), 1e-6 * step);
+        }
+    }
+}
@@ -0,0 +1,294 @@
+// Command line //////////////////////////////////////////////////////
+//              /////////////////////////////////////////////////////
+// Flags of the GUI, to start at a saved view, eg, `--config
+// mandel.png` or `--bookmark 3`, or at a location, eg, `--center
+// -0.743643 0.131825 --zoom 1e6`, and to set the window, the HUD and
+// the images saved. They are the same as those of the CLI, where both
+// have them.
+use std::path::PathBuf;
+
+use clap::Parser;
+use mandelbrot_cli::{
+    color_schemes::{ColorSchemes, Palette},
+    link::ViewLink,
+    metadata::ImageMetadata,
+    origin::Coordinate,
+    ComputeBackend, MandelConfig, Resolution,
+};
+
+use crate::bookmarks::Bookmarks;
+use crate::history;
+use crate::keymap::{Keymap, MouseButtons, KEYMAP_FILE};
+use crate::output::DEFAULT_TEMPLATE;
+use crate::transition;
+
+#[derive(Parser)]
+#[command(version, about = "Explore the Mandelbrot set interactively")]
+pub struct Args {
+    /// Start at the view of a TOML file, or of a PNG or JSON file saved
+    /// with it, overridden by the other flags
+    #[arg(long, conflicts_with = "bookmark")]
+    pub config: Option<PathBuf>,
+    /// Start at the bookmark N of `bookmarks.json`, from 1, see the K
+    /// key
+    #[arg(long, value_name = "N")]
+    pub bookmark: Option<usize>,
+    /// Start of the x domain [default: depends on the fractal]
+    #[arg(long, allow_hyphen_values = true)]
+    pub x0: Option<f64>,
+    /// End of the x domain
+    #[arg(long, allow_hyphen_values = true)]
+    pub x1: Option<f64>,
+    /// Start of the y domain
+    #[arg(long, allow_hyphen_values = true)]
+    pub y0: Option<f64>,
+    /// End of the y domain
+    #[arg(long, allow_hyphen_values = true)]
+    pub y1: Option<f64>,
+    /// Centre of the view, instead of the domains, with as many digits
+    /// as deep zooms need [default: centre of the domains]
+    #[arg(long, num_args = 2, value_names = ["X", "Y"], allow_negative_numbers = true,
+          conflicts_with_all = ["x0", "x1", "y0", "y1"])]
+    pub center: Option<Vec<Coordinate>>,
+    /// Zoom factor from the default domains, instead of the domains
+    /// [default: zoom of the domains]
+    #[arg(long, conflicts_with_all = ["x0", "x1", "y0", "y1"])]
+    pub zoom: Option<f64>,
+    /// Start at the link of a view, `mandel://x/y/zoom/iters/scheme`,
+    /// with its `max_iters` and color scheme, see the HUD
+    #[arg(long, value_name = "URL", conflicts_with_all = ["x0", "x1", "y0", "y1", "center", "zoom"])]
+    pub link: Option<ViewLink>,
+    /// Maximum number of iterations for divergence [default: 128]
+    #[arg(short, long)]
+    pub max_iters: Option<usize>,
+    /// Compute backend: scalar, simd or gpu [default: scalar]
+    #[arg(long)]
+    pub backend: Option<ComputeBackend>,
+    /// Keep the last `z` of each pixel, so that more iterations, eg,
+    /// with the . key, continue those of the pixels inside the set, at
+    /// 16 more bytes per pixel
+    #[arg(long)]
+    pub resumable: bool,
+    /// Built-in color scheme, by name or index, see the C key
+    #[arg(long, value_name = "NAME")]
+    pub color_scheme: Option<String>,
+    /// Custom palette, TOML or gradient image, as the initial color
+    /// scheme
+    #[arg(long)]
+    pub palette: Option<PathBuf>,
+    /// Width of the window, in points
+    #[arg(long, default_value_t = 800)]
+    pub width: u32,
+    /// Height of the window, in points
+    #[arg(long, default_value_t = 450)]
+    pub height: u32,
+    /// Start in borderless fullscreen, see F11
+    #[arg(long)]
+    pub fullscreen: bool,
+    /// Keep the domains when the window is resized, stretching the view,
+    /// instead of keeping the pixels square
+    #[arg(long)]
+    pub stretch: bool,
+    /// Items of the HUD, comma separated, eg, `zoom,time` [default: all]
+    #[arg(long, value_name = "ITEMS")]
+    pub hud: Option<String>,
+    /// File name of the images saved with the F key, where `{time}` and
+    /// `{zoom}` are replaced, its format given by the extension
+    #[arg(long, value_name = "TEMPLATE", default_value = DEFAULT_TEMPLATE)]
+    pub output: String,
+    /// Directory of the images saved
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub output_dir: PathBuf,
+    /// Resolution of the exports of Shift+F
+    #[arg(long, value_name = "WxH", value_parser = parse_size, default_value = "3840x2160")]
+    pub export_size: Resolution,
+    /// Anti-aliasing of the exports of Shift+F, n x n samples per pixel
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    pub export_aa: usize,
+    /// Number of views Backspace goes back to
+    #[arg(long, value_name = "N", default_value_t = history::DEFAULT_DEPTH)]
+    pub history: usize,
+    /// Scale of the zoom of the wheel, touchpads and pinching, eg, 0.5
+    /// for half as fast
+    #[arg(long, value_name = "S", value_parser = parse_positive, default_value_t = 1.0)]
+    pub zoom_sensitivity: f64,
+    /// Time of the glide to the view zoomed to with the wheel or a
+    /// rectangle, in ms, 0 to jump to it
+    #[arg(long, value_name = "MS", default_value_t = transition::DEFAULT_MILLIS)]
+    pub zoom_transition: u64,
+    /// Key bindings, a JSON file of the keys of the actions to rebind,
+    /// eg, `{"zoom-in": ["Equals"]}` [default: `keymap.json`, if any]
+    #[arg(long, value_name = "FILE")]
+    pub keymap: Option<PathBuf>,
+    /// Actions of the mouse buttons, comma separated, pan, rect, rotate
+    /// or julia, eg, `left=rect,right=pan` to drag a rectangle to zoom
+    /// without a key held
+    #[arg(
+        long,
+        value_name = "BUTTONS",
+        default_value = "left=pan,middle=pan,right=julia"
+    )]
+    pub mouse: MouseButtons,
+}
+
+impl Args {
+    /// Return the initial view, at the `resolution` of the window, and
+    /// set the color scheme it was saved with, if any, or the one of the
+    /// flags
+    pub fn view(
+        &self,
+        resolution: Resolution,
+        bookmarks: &mut Bookmarks,
+        color_schemes: &mut ColorSchemes,
+    ) -> MandelConfig {
+        let mut cfg = if let Some(path) = &self.config {
+            let loaded = if path.extension().is_some_and(|e| e == "toml") {
+                std::fs::read_to_string(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|s| MandelConfig::from_toml(&s).map_err(|e| e.to_string()))
+            } else {
+                ImageMetadata::from_file(path)
+                    .map(|metadata| {
+                        color_schemes.set_by_name(&metadata.color_scheme);
+                        metadata.cfg
+                    })
+                    .map_err(|e| e.to_string())
+            };
+            loaded.unwrap_or_else(|e| {
+                eprintln!("Error loading config \"{}\" - {e}", path.display());
+                std::process::exit(1);
+            })
+        } else if let Some(n) = self.bookmark {
+            let Some(bookmark) = n.checked_sub(1).and_then(|i| bookmarks.go_to(i)) else {
+                eprintln!(
+                    "Error in --bookmark \"{n}\" - there are {} bookmarks in '{}'",
+                    bookmarks.len(),
+                    bookmarks.path().display()
+                );
+                std::process::exit(1);
+            };
+            if bookmark.color_scheme < color_schemes.len() {
+                color_schemes.set(bookmark.color_scheme);
+            }
+            bookmark.cfg
+        } else {
+            MandelConfig::default()
+        };
+        // the resolution is the one of the window
+        cfg.resolution = resolution;
+        if [self.x0, self.x1, self.y0, self.y1]
+            .iter()
+            .any(Option::is_some)
+        {
+            // the domains given are points of the plane
+            let (xdomain, ydomain) = cfg.absolute_domains();
+            cfg.domains().set_domains(xdomain, ydomain);
+        }
+        let set = |field: &mut f64, value: Option<f64>| {
+            if let Some(v) = value {
+                *field = v;
+            }
+        };
+        set(&mut cfg.xdomain.start, self.x0);
+        set(&mut cfg.xdomain.end, self.x1);
+        set(&mut cfg.ydomain.start, self.y0);
+        set(&mut cfg.ydomain.end, self.y1);
+        cfg.domains().fit_to_aspect();
+        if self.center.is_some() || self.zoom.is_some() {
+            // the centre in high precision, for deep zooms
+            let (x, y) = match &self.center {
+                Some(center) => (center[0], center[1]),
+                None => cfg.center(),
+            };
+            let mut view = cfg.view();
+            view.scale = self.zoom.unwrap_or(view.scale);
+            cfg.domains().set_view(&view).center_on(x, y);
+        }
+        if let Some(link) = &self.link {
+            link.apply(&mut cfg);
+            link.apply_scheme(color_schemes);
+        }
+        if let Some(max_iters) = self.max_iters {
+            cfg.max_iters = max_iters;
+            // overrides the `auto_iters` of `--config`
+            cfg.auto_iters = None;
+        }
+        cfg.backend = self.backend.unwrap_or(cfg.backend);
+        cfg.resumable |= self.resumable;
+        if let Err(e) = cfg.validate() {
+            eprintln!("Error in the initial view - {e}");
+            std::process::exit(1);
+        }
+        self.set_color_scheme(color_schemes);
+        cfg
+    }
+
+    /// Return the key bindings of `--keymap`, or of `KEYMAP_FILE` if it
+    /// exists, or else the default ones
+    pub fn keymap(&self) -> Keymap {
+        let path = self
+            .keymap
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(KEYMAP_FILE));
+        if self.keymap.is_none() && !path.exists() {
+            return Keymap::default();
+        }
+        Keymap::load(&path).unwrap_or_else(|e| {
+            eprintln!("Error loading keymap \"{}\" - {e}", path.display());
+            std::process::exit(1);
+        })
+    }
+
+    /// Set the color scheme of `--palette` or `--color-scheme`, if given
+    fn set_color_scheme(&self, color_schemes: &mut ColorSchemes) {
+        if let Some(fname) = &self.palette {
+            match Palette::from_file(fname) {
+                Ok(palette) => {
+                    let index = color_schemes.register(Box::new(palette));
+                    color_schemes.set(index);
+                }
+                Err(e) => {
+                    eprintln!("Error loading palette \"{}\" - {e}", fname.display());
+                    std::process::exit(1);
+                }
+            }
+        }
+        let Some(name) = &self.color_scheme else {
+            return;
+        };
+        let found = match name.parse::<usize>() {
+            Ok(index) if index < color_schemes.len() => {
+                color_schemes.set(index);
+                true
+            }
+            Ok(_) => false,
+            Err(_) => color_schemes.set_by_name(name).is_some(),
+        };
+        if !found {
+            let names: Vec<_> = color_schemes.names().collect();
+            eprintln!(
+                "Error: no color scheme \"{name}\", there are {}",
+                names.join(", ")
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parse `WIDTHxHEIGHT`, eg, `7680x4320`
+fn parse_size(s: &str) -> Result<Resolution, String> {
+    let parsed = s
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+    match parsed {
+        Some((x, y)) if x >= 2 && y >= 2 => Ok(Resolution { x, y }),
+        _ => Err("expected WIDTHxHEIGHT, eg, 7680x4320".to_string()),
+    }
+}
+
+fn parse_positive(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(x) if x > 0.0 && x.is_finite() => Ok(x),
+        _ => Err("expected a positive number".to_string()),
+    }
+}
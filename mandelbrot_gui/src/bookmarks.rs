@@ -0,0 +1,85 @@
+// Bookmarks /////////////////////////////////////////////////////////
+//           ////////////////////////////////////////////////////////
+// Views stored while exploring, to come back to them later, eg, for a
+// high resolution render with the CLI. They are saved to a JSON file
+// every time one is added, and loaded again at startup.
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use mandelbrot_cli::MandelConfig;
+
+/// File the bookmarks are persisted to
+const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Domain, iterations, fractal... The resolution is ignored when
+    /// restoring, since it is the one of the window.
+    pub cfg: MandelConfig,
+    /// Index of the color scheme
+    pub color_scheme: usize,
+}
+
+pub struct Bookmarks {
+    bookmarks: Vec<Bookmark>,
+    // index of the bookmark shown last, the next one is shown next
+    index_current: Option<usize>,
+    path: PathBuf,
+}
+impl Bookmarks {
+    /// Load the bookmarks saved in the previous sessions, if any
+    pub fn load() -> Self {
+        let path = PathBuf::from(BOOKMARKS_FILE);
+        let bookmarks = match std::fs::read_to_string(&path) {
+            Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
+                eprintln!("Error reading '{}' - {e}", path.display());
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self {
+            bookmarks,
+            index_current: None,
+            path,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.bookmarks.len()
+    }
+
+    /// Add a bookmark and save all of them to file
+    pub fn add(&mut self, bookmark: Bookmark) -> std::io::Result<()> {
+        self.bookmarks.push(bookmark);
+        self.index_current = Some(self.bookmarks.len() - 1);
+        let json = serde_json::to_string_pretty(&self.bookmarks)?;
+        std::fs::write(&self.path, json)
+    }
+
+    /// Return the bookmark `index`, from 0, the next one being the one
+    /// after it, or `None` if there is no such bookmark
+    pub fn go_to(&mut self, index: usize) -> Option<Bookmark> {
+        let bookmark = *self.bookmarks.get(index)?;
+        self.index_current = Some(index);
+        Some(bookmark)
+    }
+
+    /// Return the bookmark after the one returned last, cycling back to
+    /// the first after the last one, or `None` if there are none
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Bookmark> {
+        if self.bookmarks.is_empty() {
+            return None;
+        }
+        let index = match self.index_current {
+            Some(i) => (i + 1) % self.bookmarks.len(),
+            None => 0,
+        };
+        self.index_current = Some(index);
+        Some(self.bookmarks[index])
+    }
+}
@@ -0,0 +1,236 @@
+// Clipboard /////////////////////////////////////////////////////////
+//           ////////////////////////////////////////////////////////
+// Locations copied to and pasted from the system clipboard, to share
+// them, eg, with the CLI or other fractal programs. The text is that of
+// the flags of the command lines, eg, `--center -0.743643 0.131825
+// --zoom 1e6`, or `--x0 -2.5 --x1 1 --y0 -1 --y1 1` for the domains.
+// Pasted text may also be a link, see `ViewLink`, or only numbers, the
+// centre and, optionally, the zoom, eg, `-0.743643, 0.131825, 1e6`.
+// The centre keeps the digits of deep zooms, see `Origin`.
+//
+// The clipboard is reached through the commands of the platform:
+// pbcopy and pbpaste on macOS, clip and PowerShell on Windows, and
+// wl-copy and wl-paste on Wayland or xclip on X11 elsewhere.
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use mandelbrot_cli::{
+    color_schemes::ColorSchemes, domain::View, link::ViewLink, origin::Coordinate, Domain,
+    MandelConfig,
+};
+
+/// Location of a view, see `Location::parse()`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Location {
+    /// Centre, zoom, if any, and rotation in degrees
+    View {
+        center: (Coordinate, Coordinate),
+        zoom: Option<f64>,
+        rotation: f64,
+    },
+    Domains {
+        x: Domain,
+        y: Domain,
+    },
+    /// View, `max_iters` and color scheme
+    Link(ViewLink),
+}
+
+impl Location {
+    /// Return the location of `cfg` as the flags of its centre and zoom,
+    /// or else of its domains
+    pub fn text(cfg: &MandelConfig, domains: bool) -> String {
+        if domains {
+            let (x, y) = cfg.absolute_domains();
+            return format!(
+                "--x0 {} --x1 {} --y0 {} --y1 {}",
+                x.start, x.end, y.start, y.end
+            );
+        }
+        let view = cfg.view();
+        let (x, y) = cfg.center();
+        let mut text = format!("--center {x} {y} --zoom {:e}", view.scale);
+        if view.rotation != 0.0 {
+            text += &format!(" --rotation {}", view.rotation.to_degrees());
+        }
+        text
+    }
+
+    /// Parse the flags of `text()`, a link, or the numbers of the centre
+    /// and the zoom, if any, separated by spaces or commas
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.trim_start().starts_with(mandelbrot_cli::link::PREFIX) {
+            return text.parse().map(Self::Link).map_err(|e| e.to_string());
+        }
+        let mut tokens = text
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|t| !t.is_empty());
+        if !text.trim_start().starts_with("--") {
+            let center = (coordinate(&mut tokens, "x")?, coordinate(&mut tokens, "y")?);
+            let mut tokens = tokens.peekable();
+            let zoom = match tokens.peek() {
+                Some(_) => Some(number(&mut tokens, "zoom")?),
+                None => None,
+            };
+            if let Some(token) = tokens.next() {
+                return Err(format!("unexpected '{token}' after the zoom"));
+            }
+            return Self::view(center, zoom, 0.0);
+        }
+        let (mut center, mut zoom, mut rotation) = (None, None, 0.0);
+        let mut domains = [None; 4];
+        while let Some(flag) = tokens.next() {
+            let mut value = || number(&mut tokens, flag);
+            match flag {
+                "--center" => {
+                    center = Some((
+                        coordinate(&mut tokens, flag)?,
+                        coordinate(&mut tokens, flag)?,
+                    ))
+                }
+                "--zoom" => zoom = Some(value()?),
+                "--rotation" => rotation = value()?,
+                "--x0" => domains[0] = Some(value()?),
+                "--x1" => domains[1] = Some(value()?),
+                "--y0" => domains[2] = Some(value()?),
+                "--y1" => domains[3] = Some(value()?),
+                _ => return Err(format!("unknown flag '{flag}'")),
+            }
+        }
+        match (center, domains) {
+            (Some(center), _) => Self::view(center, zoom, rotation),
+            (None, [Some(x0), Some(x1), Some(y0), Some(y1)]) => {
+                if x0 >= x1 || y0 >= y1 {
+                    return Err("the domains are empty".to_string());
+                }
+                let x = Domain { start: x0, end: x1 };
+                let y = Domain { start: y0, end: y1 };
+                Ok(Self::Domains { x, y })
+            }
+            _ => Err("expected --center or --x0, --x1, --y0 and --y1".to_string()),
+        }
+    }
+
+    fn view(
+        center: (Coordinate, Coordinate),
+        zoom: Option<f64>,
+        rotation: f64,
+    ) -> Result<Self, String> {
+        if zoom.is_some_and(|zoom| zoom <= 0.0) {
+            return Err("the zoom is not positive".to_string());
+        }
+        Ok(Self::View {
+            center,
+            zoom,
+            rotation,
+        })
+    }
+
+    /// Go to the location, at the resolution of `cfg`, keeping its zoom
+    /// if none is given. A link also sets the color scheme, if there is
+    /// one of its name.
+    pub fn apply(&self, cfg: &mut MandelConfig, color_schemes: &mut ColorSchemes) {
+        match *self {
+            Self::View {
+                center,
+                zoom,
+                rotation,
+            } => {
+                let view = View {
+                    center_x: center.0.to_f64(),
+                    center_y: center.1.to_f64(),
+                    scale: zoom.unwrap_or(cfg.view().scale),
+                    rotation: rotation.to_radians(),
+                };
+                cfg.domains().set_view(&view).center_on(center.0, center.1);
+            }
+            Self::Domains { x, y } => {
+                cfg.rotation = 0.0;
+                cfg.domains().set_domains(x, y).fit_to_aspect();
+            }
+            Self::Link(ref link) => {
+                link.apply(cfg);
+                link.apply_scheme(color_schemes);
+            }
+        }
+    }
+}
+
+/// Parse the next token, the value of `name`, as a finite number
+fn number<'a>(tokens: &mut impl Iterator<Item = &'a str>, name: &str) -> Result<f64, String> {
+    let token = tokens.next().ok_or(format!("{name} has no value"))?;
+    match token.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => Err(format!("'{token}' is not a number")),
+    }
+}
+
+/// Parse the next token, the value of `name`, as a coordinate in high
+/// precision
+fn coordinate<'a>(
+    tokens: &mut impl Iterator<Item = &'a str>,
+    name: &str,
+) -> Result<Coordinate, String> {
+    let token = tokens.next().ok_or(format!("{name} has no value"))?;
+    token
+        .parse()
+        .map_err(|_| format!("'{token}' is not a number"))
+}
+
+/// Copy `text` to the clipboard
+pub fn copy(text: &str) -> io::Result<()> {
+    let (program, args) = if cfg!(target_os = "macos") {
+        ("pbcopy", &[][..])
+    } else if cfg!(windows) {
+        ("clip", &[][..])
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        ("wl-copy", &[][..])
+    } else {
+        ("xclip", &["-selection", "clipboard"][..])
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| not_started(program, e))?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(text.as_bytes())?;
+    check(program, child.wait()?)
+}
+
+/// Return the text of the clipboard
+pub fn paste() -> io::Result<String> {
+    let (program, args) = if cfg!(target_os = "macos") {
+        ("pbpaste", &[][..])
+    } else if cfg!(windows) {
+        (
+            "powershell",
+            &["-NoProfile", "-Command", "Get-Clipboard"][..],
+        )
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        ("wl-paste", &["--no-newline"][..])
+    } else {
+        ("xclip", &["-selection", "clipboard", "-o"][..])
+    };
+    let output = Command::new(program)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| not_started(program, e))?;
+    check(program, output.status)?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn not_started(program: &str, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{program} not started - {e}"))
+}
+
+fn check(program: &str, status: std::process::ExitStatus) -> io::Result<()> {
+    if !status.success() {
+        return Err(io::Error::other(format!("{program} failed, {status}")));
+    }
+    Ok(())
+}
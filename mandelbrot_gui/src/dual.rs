@@ -0,0 +1,212 @@
+// Dual view /////////////////////////////////////////////////////////
+//           ////////////////////////////////////////////////////////
+// Split screen, toggled with Shift+J: the Mandelbrot set on the left
+// half and, on the right, the Julia set of the `c` picked by
+// right-clicking on it. The mouse acts on the half it is over, each
+// with its own pan and zoom, while the keys act on the Mandelbrot view.
+// The Julia view follows the settings of the Mandelbrot one, eg, the
+// formula and `max_iters`, and is colored with the same color scheme.
+//
+// The Julia set is rendered in the background, see `RenderJob`, as the
+// Mandelbrot view is, and the last render is kept to color it again.
+use nannou::draw::Draw;
+use nannou::geom::{Rect, Vec2};
+use nannou::image;
+use nannou::prelude::{App, MouseButton, WHITE};
+use nannou::wgpu;
+
+use mandelbrot_cli::{
+    color_schemes::ColorSchemes, get_rgb_buf, render_job::RenderJob, FractalKind, IterationGrid,
+    MandelConfig, Resolution,
+};
+
+/// Tile size of the renders of the Julia set, small to keep the threads
+/// busy on half of the window
+const TILE_SIZE: usize = 64;
+/// Size of the label of `c`, in points
+const LABEL_SIZE: (f32, f32) = (220.0, 16.0);
+
+pub struct DualView {
+    /// View of the Julia set, of which only `c`, the domains and the
+    /// rotation are its own, see `sync()`
+    cfg: MandelConfig,
+    /// Render in progress, replaced when the view changes
+    job: Option<(MandelConfig, RenderJob)>,
+    /// Last render and its image
+    rendered: Option<(MandelConfig, IterationGrid)>,
+    texture: Option<wgpu::Texture>,
+    /// Button panning and the positions of the mouse at the start of the
+    /// pan and now
+    pan: Option<(MouseButton, Vec2, Vec2)>,
+}
+
+impl DualView {
+    /// Julia set of `c`, whole, in a view of `resolution`
+    pub fn new(c: [f64; 2], resolution: Resolution) -> Self {
+        let mut cfg = MandelConfig {
+            kind: FractalKind::Julia { cx: c[0], cy: c[1] },
+            resolution,
+            ..MandelConfig::default()
+        };
+        (cfg.xdomain, cfg.ydomain) = cfg.kind.default_domains();
+        cfg.domains().fit_to_aspect();
+        Self {
+            cfg,
+            job: None,
+            rendered: None,
+            texture: None,
+            pan: None,
+        }
+    }
+
+    /// Show the Julia set of `c`, in the same view
+    pub fn set_c(&mut self, c: [f64; 2]) {
+        self.cfg.kind = FractalKind::Julia { cx: c[0], cy: c[1] };
+    }
+
+    /// Change the resolution, keeping the centre and the x domain, eg,
+    /// when the window is resized
+    pub fn resize(&mut self, resolution: Resolution) {
+        self.cfg.domains().resize(resolution);
+    }
+
+    /// Return `true` while the Julia set is rendering
+    pub fn is_rendering(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Take the settings of the Mandelbrot view `main`, start rendering
+    /// the view if it changed, and color the render once it is done, or
+    /// again if `recolor`. Return the error of the render, if any.
+    pub fn update(
+        &mut self,
+        app: &App,
+        main: &MandelConfig,
+        color_schemes: &ColorSchemes,
+        recolor: bool,
+    ) -> Result<(), String> {
+        self.sync(main);
+        let rendered = self.rendered.as_ref().map(|(cfg, _)| cfg);
+        let started = self.job.as_ref().map(|(cfg, _)| cfg);
+        if rendered != Some(&self.cfg) && started != Some(&self.cfg) {
+            // the job replaced is cancelled when dropped
+            let job = RenderJob::start(self.cfg, TILE_SIZE).map_err(|e| e.to_string())?;
+            self.job = Some((self.cfg, job));
+        }
+        let mut recolor = recolor;
+        if self.job.as_ref().is_some_and(|(_, job)| job.is_finished()) {
+            let (cfg, job) = self.job.take().unwrap();
+            if let Some(iters) = job.wait() {
+                self.rendered = Some((cfg, iters));
+                recolor = true;
+            }
+        }
+        if let Some((cfg, iters)) = self.rendered.as_ref().filter(|_| recolor) {
+            let buf =
+                get_rgb_buf(iters, cfg.max_iters, color_schemes).map_err(|e| e.to_string())?;
+            let (w, h) = iters.image_size();
+            let imgbuf = image::ImageBuffer::from_raw(w as u32, h as u32, buf)
+                .expect("buffer matches the grid size");
+            let texture = wgpu::Texture::from_image(app, &image::DynamicImage::ImageRgb8(imgbuf));
+            self.texture = Some(texture);
+        }
+        Ok(())
+    }
+
+    /// Take the settings of `main` other than the kind, the domains, the
+    /// rotation and the resolution
+    fn sync(&mut self, main: &MandelConfig) {
+        let cfg = &self.cfg;
+        self.cfg = MandelConfig {
+            kind: cfg.kind,
+            xdomain: cfg.xdomain,
+            ydomain: cfg.ydomain,
+            origin: cfg.origin,
+            rotation: cfg.rotation,
+            resolution: cfg.resolution,
+            ..*main
+        };
+        self.cfg.update_max_iters();
+    }
+
+    /// Draw the Julia set in `rect`, shifted while panning, with its `c`
+    /// at the top, `precision` decimals
+    pub fn draw(&self, draw: &Draw, rect: Rect, scale_factor: f32, precision: usize) {
+        if let Some(texture) = &self.texture {
+            let [w, h] = texture.size();
+            let offset = self.pan.map_or(Vec2::ZERO, |(_, start, end)| end - start);
+            draw.scissor(rect)
+                .texture(texture)
+                .w_h(w as f32 / scale_factor, h as f32 / scale_factor)
+                .xy(rect.xy() + offset);
+        }
+        draw.line()
+            .start(rect.bottom_left())
+            .end(rect.top_left())
+            .weight(2.0)
+            .color(WHITE);
+        if let FractalKind::Julia { cx, cy } = self.cfg.kind {
+            let p = precision;
+            // in the top-left corner, clear of the palette strip
+            let (w, h) = LABEL_SIZE;
+            draw.text(&format!("Julia c = {cx:.p$}, {cy:.p$}"))
+                .xy(rect.top_left() + Vec2::new(w / 2.0 + 10.0, -h / 2.0 - 10.0))
+                .w_h(w, h)
+                .left_justify()
+                .color(WHITE);
+        }
+    }
+
+    /// Return the point of the domains of the Julia set at `position`,
+    /// in the window, of the view drawn in `rect`
+    fn to_domains(&self, rect: Rect, scale_factor: f32, position: Vec2) -> (f64, f64) {
+        let [px, py] = (position - rect.xy()).to_array();
+        self.cfg
+            .screen_map(scale_factor as f64)
+            .to_domains([px as f64, py as f64])
+    }
+
+    /// Zoom by `factor` about `position`, in the window, of the view drawn
+    /// in `rect`
+    pub fn zoom(&mut self, rect: Rect, scale_factor: f32, position: Vec2, factor: f64) {
+        let (x, y) = self.to_domains(rect, scale_factor, position);
+        self.cfg.domains().zoom_about_point(x, y, factor);
+    }
+
+    /// Start panning from `position`, in the window, while `button` is
+    /// held
+    pub fn start_pan(&mut self, position: Vec2, button: MouseButton) {
+        self.pan = Some((button, position, position));
+    }
+
+    /// Return `true` while panning
+    pub fn is_panning(&self) -> bool {
+        self.pan.is_some()
+    }
+
+    /// Move the pan in progress, if any, to `position`. Return `true` if
+    /// it is panning.
+    pub fn move_pan(&mut self, position: Vec2) -> bool {
+        if let Some((_, _, end)) = &mut self.pan {
+            *end = position;
+        }
+        self.pan.is_some()
+    }
+
+    /// End the pan in progress, if any, of the view drawn in `rect`, when
+    /// its `button` is released, and move the domains. Return `true` if
+    /// it was panning with `button`.
+    pub fn end_pan(&mut self, rect: Rect, scale_factor: f32, button: MouseButton) -> bool {
+        let Some((start, end)) = self
+            .pan
+            .take_if(|(b, _, _)| *b == button)
+            .map(|(_, start, end)| (start, end))
+        else {
+            return false;
+        };
+        let (x0, y0) = self.to_domains(rect, scale_factor, start);
+        let (x1, y1) = self.to_domains(rect, scale_factor, end);
+        self.cfg.domains().pan(x0 - x1, y0 - y1);
+        true
+    }
+}
@@ -0,0 +1,104 @@
+// Iteration histogram ///////////////////////////////////////////////
+//                     //////////////////////////////////////////////
+// Distribution of the iteration counts of the last render, plotted in a
+// panel shown with Shift+H, to judge `max_iters`. A tail of pixels still
+// escaping just below `max_iters` means that it is too low, and many
+// more would escape with more iterations, while counts far below it
+// mean that it could be lowered for faster renders. The pixels reaching
+// `max_iters`, ie, taken as inside the set, are drawn apart, in red.
+use nannou::draw::Draw;
+use nannou::geom::{Rect, Vec2};
+use nannou::prelude::{GREY, RED, WHITE};
+
+use mandelbrot_cli::IterationGrid;
+
+/// Number of bins of iterations, fewer if `max_iters` is lower
+const BINS: usize = 128;
+
+pub struct IterHistogram {
+    /// Number of pixels escaping in each bin of iterations, over
+    /// `0..max_iters`
+    bins: Vec<usize>,
+    /// Number of pixels reaching `max_iters`
+    capped: usize,
+    /// Number of pixels, with the subpixels of anti-aliasing
+    total: usize,
+    max_iters: usize,
+}
+
+impl IterHistogram {
+    /// Count the iterations of `iters`, rendered with `max_iters`
+    pub fn new(iters: &IterationGrid, max_iters: usize) -> Self {
+        let n_bins = BINS.min(max_iters).max(1);
+        let mut bins = vec![0; n_bins];
+        let mut capped = 0;
+        for &count in &iters.data {
+            let count = count as usize;
+            if count >= max_iters {
+                capped += 1;
+            } else {
+                bins[count * n_bins / max_iters] += 1;
+            }
+        }
+        Self {
+            bins,
+            capped,
+            total: iters.data.len(),
+            max_iters,
+        }
+    }
+
+    /// Draw the bars of the bins, on a log scale, with the pixels
+    /// reaching `max_iters` as a red bar on the right, past a marker, in
+    /// `rect`
+    pub fn draw(&self, draw: &Draw, rect: Rect) {
+        draw.rect()
+            .xy(rect.xy())
+            .wh(rect.wh())
+            .rgba(0.0, 0.0, 0.0, 0.7);
+        let plot = rect.pad(6.0).pad_top(16.0);
+        // one more bar for the capped pixels, after a gap
+        let bar_w = plot.w() / (self.bins.len() + 3) as f32;
+        let highest = self
+            .bins
+            .iter()
+            .copied()
+            .chain([self.capped])
+            .max()
+            .unwrap_or(0);
+        let height = |count: usize| {
+            if count == 0 {
+                0.0
+            } else {
+                plot.h() * (1.0 + count as f32).ln() / (1.0 + highest as f32).ln()
+            }
+        };
+        for (i, &count) in self.bins.iter().enumerate() {
+            let h = height(count);
+            let x = plot.left() + (i as f32 + 0.5) * bar_w;
+            draw.rect()
+                .xy(Vec2::new(x, plot.bottom() + h / 2.0))
+                .w_h(bar_w, h)
+                .color(WHITE);
+        }
+        let marker_x = plot.left() + (self.bins.len() as f32 + 1.0) * bar_w;
+        draw.line()
+            .start(Vec2::new(marker_x, plot.bottom()))
+            .end(Vec2::new(marker_x, plot.top()))
+            .weight(1.0)
+            .color(GREY);
+        let h = height(self.capped);
+        draw.rect()
+            .xy(Vec2::new(plot.right() - bar_w, plot.bottom() + h / 2.0))
+            .w_h(bar_w * 2.0, h)
+            .color(RED);
+        let capped = 100.0 * self.capped as f64 / self.total.max(1) as f64;
+        draw.text(&format!(
+            "max_iters {}, {capped:.1}% reach it",
+            self.max_iters
+        ))
+        .xy(Vec2::new(rect.x(), rect.top() - 10.0))
+        .w(rect.w())
+        .color(WHITE);
+    }
+}
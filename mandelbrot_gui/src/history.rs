@@ -0,0 +1,74 @@
+// History ///////////////////////////////////////////////////////////
+//         //////////////////////////////////////////////////////////
+// Views visited while exploring, to go back to them with Backspace and
+// forward again with Shift+Backspace, like the history of a browser.
+// Only the views count, ie, the fractal, domains and rotation, so that
+// going back keeps the other settings, eg, `max_iters` or the backend.
+use std::collections::VecDeque;
+
+use mandelbrot_cli::MandelConfig;
+
+/// Default number of views kept, see `--history`
+pub const DEFAULT_DEPTH: usize = 100;
+
+pub struct History {
+    back: VecDeque<MandelConfig>,
+    forward: Vec<MandelConfig>,
+    /// Maximum number of views to go back to, the oldest are dropped
+    depth: usize,
+}
+impl History {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            back: VecDeque::new(),
+            forward: Vec::new(),
+            depth,
+        }
+    }
+
+    /// Record `prev`, the view left for a new one, unless it is the same
+    /// view. The views undone are dropped.
+    pub fn push(&mut self, prev: MandelConfig) {
+        if self.depth == 0 || self.back.back().is_some_and(|last| same_view(last, &prev)) {
+            return;
+        }
+        if self.back.len() == self.depth {
+            self.back.pop_front();
+        }
+        self.back.push_back(prev);
+        self.forward.clear();
+    }
+
+    /// Return `current` with the view before it, if any, which it can be
+    /// redone from
+    pub fn undo(&mut self, current: MandelConfig) -> Option<MandelConfig> {
+        let prev = self.back.pop_back()?;
+        self.forward.push(current);
+        Some(with_view(current, &prev))
+    }
+
+    /// Return `current` with the view undone last, if any
+    pub fn redo(&mut self, current: MandelConfig) -> Option<MandelConfig> {
+        let next = self.forward.pop()?;
+        self.back.push_back(current);
+        Some(with_view(current, &next))
+    }
+}
+
+/// Return `true` if `a` and `b` show the same view, whatever their other
+/// settings
+pub fn same_view(a: &MandelConfig, b: &MandelConfig) -> bool {
+    with_view(*a, b) == *a
+}
+
+/// Return `cfg` showing the fractal, domains and rotation of `view`
+fn with_view(cfg: MandelConfig, view: &MandelConfig) -> MandelConfig {
+    MandelConfig {
+        kind: view.kind,
+        xdomain: view.xdomain,
+        ydomain: view.ydomain,
+        origin: view.origin,
+        rotation: view.rotation,
+        ..cfg
+    }
+}
@@ -0,0 +1,153 @@
+// HUD ///////////////////////////////////////////////////////////////
+//     //////////////////////////////////////////////////////////////
+// Text drawn over the fractal with the state of the view. The H key
+// hides it, and `--hud items` picks the items shown, eg, `--hud
+// zoom,time` for only the zoom factor and the render time.
+use mandelbrot_cli::{link::ViewLink, FractalKind, MandelConfig, RenderStats};
+
+/// Names of the items of `--hud`, in the order they are drawn
+const ITEMS: [&str; 8] = ["domain", "mouse", "iters", "zoom", "time", "scheme", "backend", "link"];
+
+pub struct Hud {
+    pub visible: bool,
+    /// x and y domains, and `c` of the Julia set
+    pub domain: bool,
+    /// Position of the mouse in the domain
+    pub mouse: bool,
+    pub max_iters: bool,
+    /// Zoom factor from the default domains
+    pub zoom: bool,
+    /// Time of the last render and of its stages, see `RenderStats`
+    pub render_time: bool,
+    pub color_scheme: bool,
+    pub backend: bool,
+    /// Link of the view, to share it, see `ViewLink`
+    pub link: bool,
+}
+impl Default for Hud {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            domain: true,
+            mouse: true,
+            max_iters: true,
+            zoom: true,
+            render_time: true,
+            color_scheme: true,
+            backend: true,
+            link: true,
+        }
+    }
+}
+
+/// State of the view shown in the HUD
+pub struct HudInfo<'a> {
+    pub cfg: &'a MandelConfig,
+    pub mouse: [f64; 2],
+    /// Number of decimals of the coordinates
+    pub precision: usize,
+    /// Time of each stage of the last render
+    pub render_stats: &'a RenderStats,
+    pub color_scheme: &'a str,
+    /// Error of the last render, always shown
+    pub error: Option<&'a str>,
+}
+
+impl Hud {
+    /// Show only `items`, comma separated, see `ITEMS`
+    pub fn from_items(items: &str) -> Result<Self, String> {
+        let mut hud = Self {
+            visible: true,
+            domain: false,
+            mouse: false,
+            max_iters: false,
+            zoom: false,
+            render_time: false,
+            color_scheme: false,
+            backend: false,
+            link: false,
+        };
+        for item in items.split(',').map(str::trim) {
+            match item {
+                "domain" => hud.domain = true,
+                "mouse" => hud.mouse = true,
+                "iters" => hud.max_iters = true,
+                "zoom" => hud.zoom = true,
+                "time" => hud.render_time = true,
+                "scheme" => hud.color_scheme = true,
+                "backend" => hud.backend = true,
+                "link" => hud.link = true,
+                _ => return Err(format!("unknown item \"{item}\", expected one of {ITEMS:?}")),
+            }
+        }
+        Ok(hud)
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Return the text of the HUD, one line per item, empty if hidden
+    pub fn text(&self, info: &HudInfo) -> String {
+        if !self.visible {
+            return String::new();
+        }
+        let cfg = info.cfg;
+        let p = info.precision;
+        let mut lines = Vec::new();
+        if self.domain {
+            let (x, y) = cfg.absolute_domains();
+            lines.push(format!(
+                "x ({:.p$}, {:.p$}), y ({:.p$}, {:.p$})",
+                x.start, x.end, y.start, y.end,
+            ));
+            if !cfg.origin.is_zero() {
+                // deeper than `f64` resolves the domains, see `Origin`
+                let (x, y) = cfg.center();
+                lines.push(format!(
+                    "Center {x}, {y}, rotation {:.1} deg",
+                    cfg.rotation.to_degrees()
+                ));
+            } else if cfg.rotation != 0.0 {
+                // the domains are in the rotated frame, see `MandelConfig`
+                let view = cfg.view();
+                lines.push(format!(
+                    "Center {:.p$}, {:.p$}, rotation {:.1} deg",
+                    view.center_x,
+                    view.center_y,
+                    view.rotation.to_degrees()
+                ));
+            }
+            if let FractalKind::Julia { cx, cy } = cfg.kind {
+                lines.push(format!("Julia c = {cx:.p$}, {cy:.p$}"));
+            }
+        }
+        if self.mouse {
+            lines.push(format!("Mouse @ {:.p$}, {:.p$}", info.mouse[0], info.mouse[1]));
+        }
+        if self.max_iters {
+            let auto = if cfg.auto_iters.is_some() { " (auto)" } else { "" };
+            lines.push(format!("Max iters: {}{auto}", cfg.max_iters));
+        }
+        if self.zoom {
+            lines.push(format!("Zoom: {:.3e}x", cfg.view().scale));
+        }
+        if self.render_time {
+            let stats = info.render_stats;
+            lines.push(format!("Render: {:.1} ms ({stats})", stats.total().as_secs_f64() * 1e3));
+        }
+        if self.color_scheme {
+            lines.push(format!("Color scheme: {}", info.color_scheme));
+        }
+        if self.backend {
+            lines.push(format!("Backend: {:?}", cfg.backend));
+        }
+        if self.link {
+            lines.push(format!("Link: {}", ViewLink::new(cfg, info.color_scheme)));
+        }
+        if let Some(error) = info.error {
+            lines.push(format!("Error: {error}"));
+        }
+        lines.join("\n")
+    }
+}
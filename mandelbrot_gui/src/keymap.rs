@@ -0,0 +1,464 @@
+// Key bindings //////////////////////////////////////////////////////
+//              /////////////////////////////////////////////////////
+// Keys of the actions of the GUI, eg, `Plus` to zoom in or `Shift+C`
+// for the previous color scheme. The defaults are in `ACTIONS`, and
+// `keymap.json`, or the file of `--keymap`, rebinds some of the actions,
+// eg, for other keyboard layouts:
+//
+//     {"zoom-in": ["Equals", "NumpadAdd"], "fewer-iters": ["Semicolon"]}
+//
+// A key with modifiers held runs the action bound to it with the most of
+// those modifiers, eg, Ctrl+Shift+C the one of `Ctrl+Shift+C` before
+// those of `Ctrl+C` and `C`. The held actions, eg, rotating while Alt is
+// held, last until their key is released, whatever the modifiers.
+//
+// The mouse buttons drag to pan, to zoom to a rectangle or to rotate, or
+// click to pick the `c` of the Julia set, as set with `--mouse`, eg,
+// `left=rect,right=pan`, see `MouseButtons`. Whatever the button, the
+// held actions of the keys drag as they do.
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use nannou::event::{Key, ModifiersState, MouseButton};
+
+/// File of the key bindings, if any, see `Keymap::load`
+pub const KEYMAP_FILE: &str = "keymap.json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    // held
+    SelectRect,
+    DragRotate,
+    ShowOrbit,
+    // view
+    RotateLeft,
+    RotateRight,
+    ZoomIn,
+    ZoomOut,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    Reset,
+    Back,
+    Forward,
+    // iterations
+    MoreIters,
+    FewerIters,
+    AutoIters,
+    Threshold,
+    Formula,
+    PowerUp,
+    PowerDown,
+    Backend,
+    Julia,
+    DualView,
+    // colors
+    NextScheme,
+    PrevScheme,
+    Equalize,
+    Relief,
+    OrbitTrap,
+    Distance,
+    Stripes,
+    PaletteOffsetUp,
+    PaletteOffsetDown,
+    StretchPalette,
+    SqueezePalette,
+    CyclePalette,
+    SaveCycle,
+    // locations
+    CopyView,
+    CopyDomains,
+    Paste,
+    SaveView,
+    LoadView,
+    Landmark,
+    Bookmark,
+    NextBookmark,
+    Keyframe,
+    ClearKeyframes,
+    // window
+    Hud,
+    Histogram,
+    Minimap,
+    JuliaPreview,
+    Fullscreen,
+    Screenshot,
+    Save,
+    Export,
+    Help,
+}
+
+/// Name of each action, in the keymap file, its description, in the help
+/// overlay, and its default keys
+#[rustfmt::skip]
+const ACTIONS: [(Action, &str, &str, &[&str]); 56] = [
+    (Action::SelectRect, "select-rect", "hold and drag to zoom to a rectangle", &["LControl", "LShift"]),
+    (Action::DragRotate, "drag-rotate", "hold and drag to rotate", &["LAlt"]),
+    (Action::ShowOrbit, "show-orbit", "hold to draw the orbit under the mouse", &["RShift"]),
+    (Action::RotateLeft, "rotate-left", "rotate counter-clockwise", &["Q"]),
+    (Action::RotateRight, "rotate-right", "rotate clockwise", &["W"]),
+    (Action::ZoomIn, "zoom-in", "zoom in", &["Plus", "Equals", "NumpadAdd"]),
+    (Action::ZoomOut, "zoom-out", "zoom out", &["Minus", "NumpadSubtract"]),
+    (Action::PanUp, "pan-up", "pan up", &["Up"]),
+    (Action::PanDown, "pan-down", "pan down", &["Down"]),
+    (Action::PanLeft, "pan-left", "pan left", &["Left"]),
+    (Action::PanRight, "pan-right", "pan right", &["Right"]),
+    (Action::Reset, "reset", "reset the view", &["R"]),
+    (Action::Back, "back", "previous view", &["Backspace", "U"]),
+    (Action::Forward, "forward", "next view", &["Shift+Backspace"]),
+    (Action::MoreIters, "more-iters", "double max_iters", &["Period"]),
+    (Action::FewerIters, "fewer-iters", "halve max_iters", &["Comma"]),
+    (Action::AutoIters, "auto-iters", "max_iters from the zoom", &["A"]),
+    (Action::Threshold, "threshold", "toggle the large bailout", &["T"]),
+    (Action::Formula, "formula", "next formula", &["G"]),
+    (Action::PowerUp, "power-up", "increase the power", &["Ctrl+RBracket"]),
+    (Action::PowerDown, "power-down", "decrease the power", &["Ctrl+LBracket"]),
+    (Action::Backend, "backend", "next compute backend", &["B"]),
+    (Action::Julia, "julia", "toggle the Julia set", &["J"]),
+    (Action::DualView, "dual-view", "Mandelbrot and Julia side by side", &["Shift+J"]),
+    (Action::NextScheme, "next-scheme", "next color scheme", &["C"]),
+    (Action::PrevScheme, "prev-scheme", "previous color scheme", &["Shift+C"]),
+    (Action::Equalize, "equalize", "toggle histogram equalisation", &["E"]),
+    (Action::Relief, "relief", "toggle relief shading", &["I"]),
+    (Action::OrbitTrap, "orbit-trap", "next orbit trap", &["O"]),
+    (Action::Distance, "distance", "toggle distance estimation", &["D"]),
+    (Action::Stripes, "stripes", "toggle stripe coloring", &["V"]),
+    (Action::PaletteOffsetUp, "palette-offset-up", "shift the palette forward", &["RBracket"]),
+    (Action::PaletteOffsetDown, "palette-offset-down", "shift the palette back", &["LBracket"]),
+    (Action::StretchPalette, "stretch-palette", "stretch the palette", &["Shift+RBracket"]),
+    (Action::SqueezePalette, "squeeze-palette", "squeeze the palette", &["Shift+LBracket"]),
+    (Action::CyclePalette, "cycle-palette", "start/stop cycling the palette", &["Y"]),
+    (Action::SaveCycle, "save-cycle", "save a palette cycle animation", &["Shift+Y"]),
+    (Action::CopyView, "copy-view", "copy the centre and zoom", &["Ctrl+C"]),
+    (Action::CopyDomains, "copy-domains", "copy the domains", &["Ctrl+Shift+C"]),
+    (Action::Paste, "paste", "go to the location copied", &["Ctrl+V"]),
+    (Action::SaveView, "save-view", "save the view", &["S"]),
+    (Action::LoadView, "load-view", "load the saved view", &["L"]),
+    (Action::Landmark, "landmark", "next landmark", &["M"]),
+    (Action::Bookmark, "bookmark", "bookmark the view", &["K"]),
+    (Action::NextBookmark, "next-bookmark", "next bookmark", &["N"]),
+    (Action::Keyframe, "keyframe", "record a keyframe", &["X"]),
+    (Action::ClearKeyframes, "clear-keyframes", "clear the keyframes", &["Shift+X"]),
+    (Action::Hud, "hud", "show/hide the HUD", &["H"]),
+    (Action::Histogram, "histogram", "show/hide the histogram", &["Shift+H"]),
+    (Action::Minimap, "minimap", "show/hide the minimap", &["Shift+M"]),
+    (Action::JuliaPreview, "julia-preview", "show/hide the Julia preview", &["P"]),
+    (Action::Fullscreen, "fullscreen", "toggle fullscreen", &["F11", "Alt+Enter"]),
+    (Action::Screenshot, "screenshot", "save a screenshot", &["F12"]),
+    (Action::Save, "save", "save the image", &["F"]),
+    (Action::Export, "export", "export at --export-size", &["Shift+F"]),
+    (Action::Help, "help", "show/hide this help", &["F1"]),
+];
+
+/// Names of the keys
+#[rustfmt::skip]
+const KEYS: [(&str, Key); 87] = [
+    ("A", Key::A), ("B", Key::B), ("C", Key::C), ("D", Key::D), ("E", Key::E),
+    ("F", Key::F), ("G", Key::G), ("H", Key::H), ("I", Key::I), ("J", Key::J),
+    ("K", Key::K), ("L", Key::L), ("M", Key::M), ("N", Key::N), ("O", Key::O),
+    ("P", Key::P), ("Q", Key::Q), ("R", Key::R), ("S", Key::S), ("T", Key::T),
+    ("U", Key::U), ("V", Key::V), ("W", Key::W), ("X", Key::X), ("Y", Key::Y),
+    ("Z", Key::Z),
+    ("0", Key::Key0), ("1", Key::Key1), ("2", Key::Key2), ("3", Key::Key3), ("4", Key::Key4),
+    ("5", Key::Key5), ("6", Key::Key6), ("7", Key::Key7), ("8", Key::Key8), ("9", Key::Key9),
+    ("F1", Key::F1), ("F2", Key::F2), ("F3", Key::F3), ("F4", Key::F4), ("F5", Key::F5),
+    ("F6", Key::F6), ("F7", Key::F7), ("F8", Key::F8), ("F9", Key::F9), ("F10", Key::F10),
+    ("F11", Key::F11), ("F12", Key::F12),
+    ("Up", Key::Up), ("Down", Key::Down), ("Left", Key::Left), ("Right", Key::Right),
+    ("Home", Key::Home), ("End", Key::End), ("PageUp", Key::PageUp), ("PageDown", Key::PageDown),
+    ("Insert", Key::Insert), ("Delete", Key::Delete),
+    ("Backspace", Key::Back), ("Enter", Key::Return), ("Escape", Key::Escape),
+    ("Tab", Key::Tab), ("Space", Key::Space),
+    ("Plus", Key::Plus), ("Minus", Key::Minus), ("Equals", Key::Equals),
+    ("Comma", Key::Comma), ("Period", Key::Period), ("Slash", Key::Slash),
+    ("Backslash", Key::Backslash), ("Semicolon", Key::Semicolon), ("Colon", Key::Colon),
+    ("Apostrophe", Key::Apostrophe), ("Grave", Key::Grave),
+    ("LBracket", Key::LBracket), ("RBracket", Key::RBracket),
+    ("NumpadAdd", Key::NumpadAdd), ("NumpadSubtract", Key::NumpadSubtract),
+    ("NumpadMultiply", Key::NumpadMultiply), ("NumpadDivide", Key::NumpadDivide),
+    ("NumpadEnter", Key::NumpadEnter),
+    ("LShift", Key::LShift), ("RShift", Key::RShift), ("LControl", Key::LControl),
+    ("RControl", Key::RControl), ("LAlt", Key::LAlt), ("RAlt", Key::RAlt),
+];
+
+impl Action {
+    /// Return `true` for the actions lasting while their key is held
+    pub fn is_held(self) -> bool {
+        matches!(
+            self,
+            Action::SelectRect | Action::DragRotate | Action::ShowOrbit
+        )
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        ACTIONS
+            .iter()
+            .find(|(_, n, _, _)| *n == name)
+            .map(|(action, _, _, _)| *action)
+    }
+}
+
+/// Key with the modifiers held, eg, `Ctrl+Shift+C`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Binding {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Binding {
+    /// Return the number of modifiers of the binding, if they are all
+    /// held in `mods`
+    fn modifiers_held(&self, mods: ModifiersState) -> Option<usize> {
+        let held = [
+            (self.ctrl, mods.ctrl()),
+            (self.shift, mods.shift()),
+            (self.alt, mods.alt()),
+        ];
+        held.iter()
+            .all(|&(needed, held)| !needed || held)
+            .then(|| held.iter().filter(|(needed, _)| *needed).count())
+    }
+}
+
+impl std::str::FromStr for Binding {
+    type Err = String;
+    /// Parse the modifiers and the key, joined by `+`, eg, `Ctrl+Shift+C`
+    /// or `Plus`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // the last `+` is that of the key in, eg, `Shift++`
+        let (mods, name) = match s.rsplit_once('+') {
+            Some((mods, "")) => (mods.strip_suffix('+').unwrap_or(mods), "Plus"),
+            Some((mods, name)) => (mods, name),
+            None => ("", s),
+        };
+        let key = KEYS
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, key)| *key)
+            .ok_or_else(|| format!("unknown key '{name}'"))?;
+        let mut binding = Binding {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        };
+        for modifier in mods.split('+').filter(|m| !m.is_empty()) {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => binding.ctrl = true,
+                "shift" => binding.shift = true,
+                "alt" => binding.alt = true,
+                _ => return Err(format!("unknown modifier '{modifier}' in '{s}'")),
+            }
+        }
+        Ok(binding)
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = KEYS
+            .iter()
+            .find(|(_, key)| *key == self.key)
+            .map_or("?", |(name, _)| name);
+        for (held, modifier) in [
+            (self.ctrl, "Ctrl+"),
+            (self.shift, "Shift+"),
+            (self.alt, "Alt+"),
+        ] {
+            if held {
+                f.write_str(modifier)?;
+            }
+        }
+        f.write_str(name)
+    }
+}
+
+/// Actions of the keys
+pub struct Keymap {
+    bindings: Vec<(Binding, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = ACTIONS
+            .iter()
+            .flat_map(|(action, _, _, keys)| {
+                keys.iter()
+                    .map(|key| (key.parse().expect("default keys are valid"), *action))
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Load the default bindings, with those of the actions in the JSON
+    /// file `path` instead, a map of the names of the actions to their
+    /// keys, see `ACTIONS`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let rebound: HashMap<String, Vec<String>> =
+            serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        let mut keymap = Self::default();
+        for (name, keys) in rebound {
+            let action =
+                Action::from_name(&name).ok_or_else(|| format!("unknown action '{name}'"))?;
+            keymap.bindings.retain(|(_, a)| *a != action);
+            for key in keys {
+                keymap.bindings.push((key.parse()?, action));
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// Return the action of `key` pressed with the modifiers `mods`, the
+    /// one of the binding with the most of them
+    pub fn action(&self, key: Key, mods: ModifiersState) -> Option<Action> {
+        self.bindings
+            .iter()
+            .filter(|(binding, _)| binding.key == key)
+            .filter_map(|(binding, action)| Some((binding.modifiers_held(mods)?, *action)))
+            .max_by_key(|(held, _)| *held)
+            .map(|(_, action)| action)
+    }
+
+    /// Return the keys bound to each action, joined by commas, and its
+    /// description, in the order of `ACTIONS`, without the actions not
+    /// bound to any key
+    pub fn help(&self) -> Vec<(String, &'static str)> {
+        ACTIONS
+            .iter()
+            .filter_map(|(action, _, description, _)| {
+                let keys: Vec<_> = self
+                    .bindings
+                    .iter()
+                    .filter(|(_, a)| a == action)
+                    .map(|(binding, _)| binding.to_string())
+                    .collect();
+                (!keys.is_empty()).then(|| (keys.join(", "), *description))
+            })
+            .collect()
+    }
+
+    /// Return the held action ended by releasing `key`, if any
+    pub fn held(&self, key: Key) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(binding, action)| binding.key == key && action.is_held())
+            .map(|(_, action)| *action)
+    }
+}
+
+/// What dragging or clicking a mouse button does
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseAction {
+    Pan,
+    ZoomRect,
+    Rotate,
+    /// Pick the `c` of the Julia set, on the Mandelbrot set, or else pan
+    PickJulia,
+}
+
+/// Name of each mouse action, in `--mouse`, and its description, in the
+/// help overlay
+const MOUSE_ACTIONS: [(MouseAction, &str, &str); 4] = [
+    (MouseAction::Pan, "pan", "drag to pan"),
+    (MouseAction::ZoomRect, "rect", "drag to zoom to a rectangle"),
+    (MouseAction::Rotate, "rotate", "drag to rotate"),
+    (
+        MouseAction::PickJulia,
+        "julia",
+        "click to pick c of the Julia set",
+    ),
+];
+
+/// Actions of the left, middle and right mouse buttons
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MouseButtons {
+    pub left: MouseAction,
+    pub middle: MouseAction,
+    pub right: MouseAction,
+}
+
+impl Default for MouseButtons {
+    fn default() -> Self {
+        Self {
+            left: MouseAction::Pan,
+            middle: MouseAction::Pan,
+            right: MouseAction::PickJulia,
+        }
+    }
+}
+
+impl MouseButtons {
+    /// Return the action of `button`, none for the buttons other than
+    /// the left, middle and right ones
+    pub fn action(&self, button: MouseButton) -> Option<MouseAction> {
+        match button {
+            MouseButton::Left => Some(self.left),
+            MouseButton::Middle => Some(self.middle),
+            MouseButton::Right => Some(self.right),
+            _ => None,
+        }
+    }
+
+    /// Return the name of each button and the description of its action,
+    /// as `Keymap::help()`
+    pub fn help(&self) -> Vec<(String, &'static str)> {
+        [
+            ("Left mouse", self.left),
+            ("Middle mouse", self.middle),
+            ("Right mouse", self.right),
+        ]
+        .into_iter()
+        .map(|(button, action)| {
+            let (_, _, description) = MOUSE_ACTIONS
+                .iter()
+                .find(|(a, _, _)| *a == action)
+                .expect("all the mouse actions are named");
+            (button.to_string(), *description)
+        })
+        .collect()
+    }
+}
+
+impl std::str::FromStr for MouseButtons {
+    type Err = String;
+    /// Parse the actions of some buttons, comma separated, eg,
+    /// `left=rect,right=pan`, the others keeping their default ones
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut buttons = Self::default();
+        for item in s.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let (button, name) = item
+                .split_once('=')
+                .ok_or_else(|| format!("expected BUTTON=ACTION, eg, left=rect, not '{item}'"))?;
+            let action = MOUSE_ACTIONS
+                .iter()
+                .find(|(_, n, _)| n.eq_ignore_ascii_case(name.trim()))
+                .map(|(action, _, _)| *action)
+                .ok_or_else(|| {
+                    let names: Vec<_> = MOUSE_ACTIONS.iter().map(|(_, n, _)| *n).collect();
+                    format!(
+                        "unknown mouse action '{name}', there are {}",
+                        names.join(", ")
+                    )
+                })?;
+            match button.trim().to_ascii_lowercase().as_str() {
+                "left" => buttons.left = action,
+                "middle" => buttons.middle = action,
+                "right" => buttons.right = action,
+                _ => {
+                    return Err(format!(
+                        "unknown mouse button '{button}', there are left, middle, right"
+                    ))
+                }
+            }
+        }
+        Ok(buttons)
+    }
+}
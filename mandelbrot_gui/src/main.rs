@@ -1,15 +1,53 @@
 use nannou::prelude::{
     geom, wgpu, App, Frame, LoopMode, 
-    Key, KeyPressed, KeyReleased,
-    MouseMoved, MousePressed, MouseReleased,
-    MouseScrollDelta::LineDelta, MouseScrollDelta::PixelDelta, MouseWheel, Resized, Update, Vec2,
-    WindowEvent, WindowId, BLACK, RED,
+    KeyPressed, KeyReleased,
+    MouseButton, MouseMoved, MousePressed, MouseReleased,
+    DroppedFile, MouseScrollDelta::LineDelta, MouseScrollDelta::PixelDelta, MouseWheel, Resized, Update, Vec2,
+    WindowEvent, WindowId, BLACK, DARKGREY, RED, WHITE,
 };
+use clap::Parser;
 use nannou::image;
 use nannou::winit::dpi::PhysicalPosition;
-use mandelbrot_cli::{mandel, MandelConfig, color_schemes};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use mandelbrot_cli::{
+    get_rgb_buf, is_deeper, mandel_deeper, mandel_incremental, orbit, orbit_from, pixel_shift, save_image_with_metadata, AaMode, AutoIters, ComputeBackend, Domain, Formula, FractalKind, IterationGrid, MandelConfig, MandelError,
+    Renderer, RenderStats, Resolution,
+    OrbitTrap, cache::TileCache, domain::{ScreenMap, View}, color_schemes::{self, MandelRGB}, landmarks::LANDMARKS, metadata::ImageMetadata, origin::Origin, render_job::{RenderJob, Tile}, tune::Tuning,
+    animation::{save_palette_cycle, AnimationOptions}, keyframes::{AnimationScript, Keyframe, PaletteSettings}, zoom::Easing,
+};
+
+mod args;
+use args::Args;
+mod bookmarks;
+use bookmarks::{Bookmark, Bookmarks};
+mod clipboard;
+use clipboard::Location;
+mod dual;
+use dual::DualView;
+mod histogram;
+use histogram::IterHistogram;
+mod keymap;
+use keymap::{Action, Keymap, MouseAction, MouseButtons};
+mod minimap;
+use minimap::Minimap;
+mod history;
+use history::History;
+mod hud;
+use hud::{Hud, HudInfo};
+mod output;
+use output::Output;
+mod transition;
+use transition::ZoomTransition;
+
+/// Command line flags, parsed before the window is opened, eg, to print
+/// `--help` without one
+static ARGS: OnceLock<Args> = OnceLock::new();
 
 fn main() {
+    ARGS.get_or_init(Args::parse);
+    Tuning::load_cached();
     nannou::app(model)
         // Vulkan works-ish in WSL. Setting this is not required in native Linux or Windows
         //.backends(wgpu::Backends::VULKAN) 
@@ -18,6 +56,57 @@ fn main() {
         .run();
 }
 
+/// File the view is saved to and restored from, see `MandelConfig::to_toml`
+const VIEW_FILE: &str = "view.toml";
+/// Rotation of the Q and W keys, in degrees
+const ROTATION_STEP: f64 = 5.0;
+/// The Julia preview is this many times smaller than the window
+const PREVIEW_DIVISOR: usize = 4;
+/// Highest `max_iters` of the Julia preview, to keep it responsive
+const PREVIEW_MAX_ITERS: usize = 1000;
+/// Shortest time between the previews of the view while panning
+const PAN_PREVIEW_INTERVAL: Duration = Duration::from_millis(50);
+/// Bailout of the T key, large for accurate smooth coloring
+const SMOOTH_THRESHOLD: f64 = 65536.0;
+/// Stripe density of the V key, see `MandelConfig::stripes`
+const STRIPE_DENSITY: f64 = 5.0;
+/// Scale of the width of the view per line of the mouse wheel, zooming in
+const ZOOM_STEP: f64 = 0.9;
+/// Pixels of the precise scroll of touchpads per line of the wheel
+const PIXELS_PER_LINE: f64 = 40.0;
+/// Shift of the palette of the [ and ] keys, a fraction of `max_iters`
+const PALETTE_OFFSET_STEP: f64 = 0.05;
+/// Factor of the palette scale of the { and } keys
+const PALETTE_SCALE_STEP: f64 = 1.25;
+/// Frames per second of the palette cycling of the Y key
+const CYCLE_FPS: f64 = 30.0;
+/// Speed of the palette cycling, in cycles per second
+const CYCLE_SPEED: f64 = 0.1;
+/// Frames of the palette cycling animations of Shift+Y, a cycle at
+/// `CYCLE_FPS`
+const ANIMATION_FRAMES: usize = 60;
+/// File the keyframes of the X key are saved to, see `AnimationScript`
+const KEYFRAMES_FILE: &str = "keyframes.toml";
+/// Frames from a keyframe of the X key to the next, 2 s at 30 fps
+const KEYFRAME_FRAMES: usize = 60;
+/// Tile size of the exports, see `RenderJob`
+const EXPORT_TILE_SIZE: usize = 256;
+/// Time a message, eg, the path of a saved image, is shown
+const MESSAGE_TIME: Duration = Duration::from_secs(3);
+/// Width of the texture of the palette strip, in colors
+const STRIP_COLORS: u32 = 256;
+/// Size of the palette strip in the window
+const STRIP_SIZE: (f32, f32) = (200.0, 12.0);
+/// Size of the iteration histogram panel in the window
+const HISTOGRAM_SIZE: (f32, f32) = (260.0, 110.0);
+/// Width of the keys and of the descriptions of the help overlay, and
+/// the height of its lines
+const HELP_COLUMNS: (f32, f32) = (140.0, 240.0);
+const HELP_LINE_HEIGHT: f32 = 13.0;
+/// Font size of the help overlay, small enough for two columns of all
+/// the actions in the default window
+const HELP_FONT_SIZE: u32 = 11;
+
 struct Model {
     // Store the window ID so we can refer to this specific window later if needed.
     window: WindowId,
@@ -25,17 +114,123 @@ struct Model {
     cfg: MandelConfig,
     pan_mode: SelectMode,
     rect_mode: SelectMode,
+    rotate_mode: SelectMode,
+    // Draw the orbit of the point under the mouse, while right Shift is
+    // held
+    show_orbit: bool,
     color_schemes: color_schemes::ColorSchemes,
     float_format_precision: usize,
     flag_update: bool,
+    // Only the colors changed, so the last render is colored again
+    // without recalculating it
+    flag_recolor: bool,
+    // Time the colors were last shifted, while cycling the palette
+    cycling: Option<Instant>,
+    // Constant `c` used in the Julia set, picked by right-clicking the
+    // Mandelbrot view
+    julia_c: [f64; 2],
+    // Show the Julia set of the point under the mouse in an inset
+    julia_preview: bool,
+    // `c` of the Julia preview and its image
+    preview: Option<([f64; 2], wgpu::Texture)>,
+    // Julia set of `julia_c` on the right half of the window, with
+    // Shift+J, the view on the left half
+    dual: Option<DualView>,
+    // Low resolution image of the view being panned to, drawn under the
+    // shifted image, with the offset of the pan and the time it was
+    // rendered for
+    pan_preview: Option<(Vec2, Instant, wgpu::Texture)>,
+    // Colors of the current scheme over `0..max_iters`, drawn with the
+    // color scheme in the HUD, and the scheme, offset, scale and
+    // `max_iters` they were made with
+    palette_strip: Option<(StripKey, wgpu::Texture)>,
+    // Show the histogram of the iterations of the last render, with
+    // Shift+H, and the view it was counted for
+    show_histogram: bool,
+    histogram: Option<(MandelConfig, IterHistogram)>,
+    // Show the overview of the set with the outline of the view, with
+    // Shift+M
+    show_minimap: bool,
+    // Show the keys and their actions over the view, with F1
+    show_help: bool,
+    minimap: Minimap,
+    // x and y domains and origin of the view not being shown (Mandelbrot
+    // or Julia), restored when toggling between them
+    other_domains: (Domain, Domain, Origin),
+    // Directory and file name template of the images saved with the F
+    // key
+    output: Output,
+    // View being exported at `export_size` in the background, see
+    // `start_export`
+    export: Option<(MandelConfig, RenderJob)>,
+    // Resolution and anti-aliasing samples of the exports
+    export_size: Resolution,
+    export_samples: usize,
+    // Message shown along the bottom since the time given, eg, the path
+    // of a saved image
+    message: Option<(String, Instant)>,
+    // Path of the screenshot of F12 and the number of frames drawn when
+    // it was taken, it is written when the next one is drawn
+    screenshot: Option<(PathBuf, u64)>,
+    bookmarks: Bookmarks,
+    // Views recorded with the X key, rendered by the CLI at the export
+    // resolution
+    keyframes: AnimationScript,
+    // Scale of the zoom of the wheel and pinching, see `--zoom-sensitivity`
+    zoom_sensitivity: f64,
+    // Glide to the view zoomed to with the wheel or a rectangle, and its
+    // time, see `--zoom-transition`
+    transition: Option<ZoomTransition>,
+    zoom_transition: Duration,
+    // Actions of the keys, see `--keymap`
+    keymap: Keymap,
+    // Actions of the mouse buttons, see `--mouse`
+    mouse_buttons: MouseButtons,
+    // Keep the domains on `Resized` instead of the pixels square, see
+    // `--stretch`
+    stretch: bool,
+    // Views left, gone back to with Backspace, and the view rendered
+    // last, which is recorded when left
+    history: History,
+    shown: MandelConfig,
+    // index of the landmark shown last, see `LANDMARKS`
+    landmark: Option<usize>,
+    // Thread pool and domain axes of the renders done in this thread
+    renderer: Renderer,
+    // Config and grid of the last render, reused when panning
+    last_render: Option<(MandelConfig, IterationGrid)>,
+    // Tiles of the views rendered recently, reused when coming back
+    tile_cache: TileCache,
+    hud: Hud,
+    // Time of each stage of the last render and of its coloring, shown
+    // in the HUD
+    render_stats: RenderStats,
+    // Render in progress in the background, if any
+    pending: Option<PendingRender>,
+    // Error of the last render, shown in the HUD until the next one
+    error: Option<String>,
 }
 
-/// Track keys and mouse moves to pan or zoom with a rectangle
+/// Tiles of a view missing from the tile cache, rendering in the
+/// background so that the window keeps responding
+struct PendingRender {
+    cfg: MandelConfig,
+    job: RenderJob,
+    tiles: Vec<Tile>,
+}
+
+/// Index, offset and scale of the color scheme, and `max_iters`, of the
+/// palette strip
+type StripKey = (usize, f64, f64, usize);
+
+/// Track keys and mouse moves to pan, zoom with a rectangle or rotate
 struct SelectMode {
     is_active: bool,
     start: Vec2,
     end: Vec2,
     draw: Vec2,
+    // Mouse button dragging, the drag ends when it is released
+    button: Option<MouseButton>,
 }
 impl Default for SelectMode {
     fn default() -> Self {
@@ -44,6 +239,7 @@ impl Default for SelectMode {
             start: Vec2::new(0.0, 0.0),
             end: Vec2::new(0.0, 0.0),
             draw: Vec2::new(0.0, 0.0),
+            button: None,
         }
     }
 }
@@ -51,7 +247,8 @@ impl Default for SelectMode {
 // //////////////////////////////////////////////////////////////////
 
 fn model(app: &App) -> Model {
-    let (w, h) = (800, 450);
+    let args = ARGS.get().expect("parsed in main");
+    let (w, h) = (args.width, args.height);
 
     let window = app
         .new_window()
@@ -59,49 +256,556 @@ fn model(app: &App) -> Model {
         .title("Mandelbrot Set")
         .view(view)
         .event(event)
+        .raw_event(raw_event)
         .build()
         .unwrap();
+    if args.fullscreen {
+        app.window(window).unwrap().set_fullscreen(true);
+    }
 
     let texture = wgpu::TextureBuilder::new()
         .size([w, h])
         .format(wgpu::TextureFormat::Rgba8Unorm)
         .build(app.window(window).unwrap().device());
 
+    // the resolution follows the window, see `Resized`
+    let mut bookmarks = Bookmarks::load();
+    let mut color_schemes = color_schemes::ColorSchemes::new();
+    let resolution = Resolution {
+        x: w as usize,
+        y: h as usize,
+    };
+    let cfg = args.view(resolution, &mut bookmarks, &mut color_schemes);
+    let julia_c = match cfg.kind {
+        FractalKind::Julia { cx, cy } => [cx, cy],
+        FractalKind::Mandelbrot => [-0.8, 0.156],
+    };
+    let hud = match &args.hud {
+        Some(items) => Hud::from_items(items).unwrap_or_else(|e| {
+            eprintln!("Error in --hud \"{items}\" - {e}");
+            std::process::exit(1);
+        }),
+        None => Hud::default(),
+    };
+
     Model {
         window,
         texture,
-        cfg: MandelConfig::default(),
+        cfg,
         pan_mode: SelectMode::default(),
         rect_mode: SelectMode::default(),
-        color_schemes: color_schemes::ColorSchemes::new(),
+        rotate_mode: SelectMode::default(),
+        show_orbit: false,
+        color_schemes,
         float_format_precision: 3,
         flag_update: false,
+        flag_recolor: false,
+        cycling: None,
+        julia_c,
+        julia_preview: false,
+        preview: None,
+        dual: None,
+        pan_preview: None,
+        palette_strip: None,
+        show_histogram: false,
+        histogram: None,
+        show_minimap: false,
+        show_help: false,
+        minimap: Minimap::new(app),
+        other_domains: {
+            let (xdomain, ydomain) = FractalKind::Julia { cx: 0.0, cy: 0.0 }.default_domains();
+            (xdomain, ydomain, Origin::default())
+        },
+        output: Output {
+            dir: args.output_dir.clone(),
+            template: args.output.clone(),
+        },
+        message: None,
+        screenshot: None,
+        export: None,
+        export_size: args.export_size,
+        export_samples: args.export_aa,
+        bookmarks,
+        keyframes: AnimationScript {
+            resolution: args.export_size,
+            keyframes: Vec::new(),
+        },
+        zoom_sensitivity: args.zoom_sensitivity,
+        transition: None,
+        zoom_transition: Duration::from_millis(args.zoom_transition),
+        keymap: args.keymap(),
+        mouse_buttons: args.mouse,
+        stretch: args.stretch,
+        history: History::new(args.history),
+        shown: cfg,
+        landmark: None,
+        renderer: Renderer::new(),
+        last_render: None,
+        tile_cache: TileCache::new(256, 160),
+        hud,
+        render_stats: RenderStats::default(),
+        pending: None,
+        error: None,
     }
 }
 
 fn update(app: &App, model: &mut Model, _update: Update) {
     //println!("{_update:?}");
-    update_mandel(app, model)
+    update_cycling(model);
+    update_export(model);
+    // the Julia set of the dual view is colored again with the view
+    let recolor = model.flag_recolor;
+    update_transition(model);
+    update_mandel(app, model);
+    update_dual(app, model, recolor);
+    update_julia_preview(app, model);
+    update_pan_preview(app, model);
+    update_palette_strip(app, model);
+    update_histogram(model);
+    update_screenshot(app, model);
 }
 
-/// Update image after changes in `model.cfg`
+/// Render the view at the end of the glide of a zoom transition, and end
+/// the transition once it is rendered, or when the view changes otherwise
+fn update_transition(model: &mut Model) {
+    let Some(transition) = &mut model.transition else {
+        return;
+    };
+    if transition.is_gliding() {
+        if model.flag_update {
+            // another change of the view, eg, with a key, renders it now
+            model.transition = None;
+        } else if transition.end_glide() {
+            model.flag_update = true;
+        }
+    } else if model.pending.is_none() && !model.flag_update {
+        model.transition = None;
+    }
+}
+
+/// Shift the colors while cycling the palette, at most `CYCLE_FPS`
+/// times per second
+fn update_cycling(model: &mut Model) {
+    let Some(last) = model.cycling else {
+        return;
+    };
+    let elapsed = last.elapsed().as_secs_f64();
+    if elapsed < 1.0 / CYCLE_FPS {
+        return;
+    }
+    let offset = model.color_schemes.offset() + CYCLE_SPEED * elapsed;
+    model.color_schemes.set_offset(offset);
+    model.cycling = Some(Instant::now());
+    model.flag_recolor = true;
+}
+
+/// Render the Julia set of the dual view if it changed, eg, its `c` or
+/// the settings of the view, or color it again if `recolor`
+fn update_dual(app: &App, model: &mut Model, recolor: bool) {
+    let Some(dual) = &mut model.dual else {
+        return;
+    };
+    if let Err(e) = dual.update(app, &model.cfg, &model.color_schemes, recolor) {
+        eprintln!("Error rendering the Julia set - {e}");
+        model.error = Some(e);
+    }
+    // keep updating until it is rendered
+    if dual.is_rendering() && app.loop_mode() != LoopMode::RefreshSync {
+        app.set_loop_mode(LoopMode::RefreshSync);
+    }
+}
+
+/// Render the Julia set of the point under the mouse, if the preview is
+/// shown and the mouse moved
+fn update_julia_preview(app: &App, model: &mut Model) {
+    if !model.julia_preview || model.cfg.kind != FractalKind::Mandelbrot || model.dual.is_some() {
+        return;
+    }
+    let c = mouse2plane(app, model, model.pan_mode.end);
+    if model.preview.as_ref().is_some_and(|(prev, _)| *prev == c) {
+        return;
+    }
+    let max_iters = model.cfg.max_iters.min(PREVIEW_MAX_ITERS);
+    let mut cfg = MandelConfig {
+        kind: FractalKind::Julia { cx: c[0], cy: c[1] },
+        resolution: Resolution {
+            x: (model.cfg.resolution.x / PREVIEW_DIVISOR).max(2),
+            y: (model.cfg.resolution.y / PREVIEW_DIVISOR).max(2),
+        },
+        rotation: 0.0,
+        max_iters,
+        auto_iters: None,
+        ..model.cfg
+    };
+    let (xdomain, ydomain) = cfg.kind.default_domains();
+    cfg.domains().set_domains(xdomain, ydomain).fit_to_aspect();
+    let buf = model.renderer.preview(cfg, cfg.resolution)
+        .and_then(|iters| get_rgb_buf(&iters, max_iters, &model.color_schemes));
+    match buf {
+        Ok(buf) => {
+            let (w, h) = (cfg.resolution.x as u32, cfg.resolution.y as u32);
+            let imgbuf = image::ImageBuffer::from_raw(w, h, buf).expect("buffer matches the size");
+            let texture = wgpu::Texture::from_image(app, &image::DynamicImage::ImageRgb8(imgbuf));
+            model.preview = Some((c, texture));
+        }
+        Err(e) => eprintln!("Error rendering the Julia preview - {e}"),
+    }
+}
+
+/// Render the view being panned to at a low resolution, at most every
+/// `PAN_PREVIEW_INTERVAL`, so that the borders uncovered by the shifted
+/// image are not black until the mouse is released
+fn update_pan_preview(app: &App, model: &mut Model) {
+    let offset = model.pan_mode.draw;
+    if !model.pan_mode.is_active || offset == Vec2::ZERO {
+        return;
+    }
+    if let Some((prev, rendered, _)) = &model.pan_preview {
+        if *prev == offset || rendered.elapsed() < PAN_PREVIEW_INTERVAL {
+            return;
+        }
+    }
+    let [x0, y0] = mouse2domain(app, model, model.pan_mode.start);
+    let [x1, y1] = mouse2domain(app, model, model.pan_mode.end);
+    let mut cfg = MandelConfig {
+        max_iters: model.cfg.max_iters.min(PREVIEW_MAX_ITERS),
+        ..model.cfg
+    };
+    cfg.domains().pan(x0 - x1, y0 - y1);
+    let resolution = Resolution {
+        x: (cfg.resolution.x / PREVIEW_DIVISOR).max(2),
+        y: (cfg.resolution.y / PREVIEW_DIVISOR).max(2),
+    };
+    let buf = model.renderer.preview(cfg, resolution)
+        .and_then(|iters| get_rgb_buf(&iters, cfg.max_iters, &model.color_schemes));
+    match buf {
+        Ok(buf) => {
+            let (w, h) = (resolution.x as u32, resolution.y as u32);
+            let imgbuf = image::ImageBuffer::from_raw(w, h, buf).expect("buffer matches the size");
+            let texture = wgpu::Texture::from_image(app, &image::DynamicImage::ImageRgb8(imgbuf));
+            model.pan_preview = Some((offset, Instant::now(), texture));
+        }
+        Err(e) => eprintln!("Error rendering the pan preview - {e}"),
+    }
+}
+
+/// Count the iterations of the last render again for the histogram, if
+/// it is shown and the render changed
+fn update_histogram(model: &mut Model) {
+    if !model.show_histogram {
+        return;
+    }
+    let Some((cfg, iters)) = &model.last_render else {
+        return;
+    };
+    if model.histogram.as_ref().is_some_and(|(counted, _)| counted == cfg) {
+        return;
+    }
+    model.histogram = Some((*cfg, IterHistogram::new(iters, cfg.max_iters)));
+}
+
+/// Color the palette strip again if the color scheme or `max_iters`
+/// changed, eg, with the C key, before the view is colored again
+fn update_palette_strip(app: &App, model: &mut Model) {
+    let schemes = &model.color_schemes;
+    let max_iters = model.cfg.max_iters;
+    let key = (schemes.index(), schemes.offset(), schemes.scale(), max_iters);
+    if model.palette_strip.as_ref().is_some_and(|(prev, _)| *prev == key) {
+        return;
+    }
+    let scheme = color_schemes::Shifted {
+        scheme: schemes.get(),
+        offset: schemes.offset(),
+        scale: schemes.scale(),
+    };
+    let buf = (0..STRIP_COLORS)
+        .flat_map(|i| {
+            let (r, g, b) = scheme.rgb(i as f64 * max_iters as f64 / STRIP_COLORS as f64, max_iters);
+            [r, g, b]
+        })
+        .collect();
+    let imgbuf = image::ImageBuffer::from_raw(STRIP_COLORS, 1, buf).expect("buffer matches the size");
+    let texture = wgpu::Texture::from_image(app, &image::DynamicImage::ImageRgb8(imgbuf));
+    model.palette_strip = Some((key, texture));
+}
+
+/// Update image after changes in `model.cfg`, or only its colors
 fn update_mandel(app: &App, model: &mut Model) {
     if model.flag_update {
-        let iters = mandel(model.cfg);
-        let imgbuf = get_image_buf(&iters, model);
-        let image = image::DynamicImage::ImageRgb8(imgbuf);
-        let texture = wgpu::Texture::from_image(app, &image);
-        model.float_format_precision = get_ffmt_precision(model);
-        model.texture = texture;
+        // a new render replaces the one in progress, which is cancelled
+        // when dropped
+        model.pending = None;
+        model.cfg.update_max_iters();
+        // record the view left, unless the window was only resized
+        if model.cfg.resolution == model.shown.resolution && !history::same_view(&model.shown, &model.cfg) {
+            model.history.push(model.shown);
+        }
+        model.shown = model.cfg;
+        if let Err(e) = start_render(model) {
+            eprintln!("Error rendering - {e}");
+            model.error = Some(e.to_string());
+        }
         model.flag_update = false;
     }
+    if model.pending.as_ref().is_some_and(|p| p.job.is_finished()) {
+        let pending = model.pending.take().unwrap();
+        let stats = pending.job.stats();
+        if let Some(iters) = pending.job.wait() {
+            model.tile_cache.insert_tiles(&pending.cfg, &iters, &pending.tiles);
+            set_render(model, pending.cfg, iters, stats);
+        }
+    }
+    if model.message.as_ref().is_some_and(|(_, shown)| shown.elapsed() > MESSAGE_TIME) {
+        model.message = None;
+    }
+    // keep updating while rendering or exporting in the background, to
+    // check the jobs and draw their progress, while panning, to render
+    // the last pan preview, while gliding to a zoom, until a screenshot
+    // is drawn, while cycling the palette, or until the message is hidden
+    let rendering = model.pending.is_some()
+        || model.dual.as_ref().is_some_and(DualView::is_rendering)
+        || model.export.is_some()
+        || model.pan_mode.is_active
+        || model.transition.is_some()
+        || model.screenshot.is_some();
+    let loop_mode = match (rendering, model.cycling) {
+        (true, _) => LoopMode::RefreshSync,
+        (false, Some(_)) => LoopMode::rate_fps(CYCLE_FPS),
+        (false, None) if model.message.is_some() => LoopMode::RefreshSync,
+        (false, None) => LoopMode::Wait,
+    };
+    if app.loop_mode() != loop_mode {
+        app.set_loop_mode(loop_mode);
+    }
+    if model.flag_recolor {
+        if let Some((_, iters)) = &model.last_render {
+            let t = Instant::now();
+            match get_image_buf(iters, model) {
+                Ok(imgbuf) => {
+                    model.render_stats.color = t.elapsed();
+                    let t = Instant::now();
+                    let image = image::DynamicImage::ImageRgb8(imgbuf);
+                    model.texture = wgpu::Texture::from_image(app, &image);
+                    model.render_stats.upload = t.elapsed();
+                }
+                Err(e) => {
+                    eprintln!("Error coloring - {e}");
+                    model.error = Some(e.to_string());
+                }
+            }
+        }
+        model.flag_recolor = false;
+    }
+}
+
+/// Render `model.cfg`, or start rendering it in the background
+fn start_render(model: &mut Model) -> Result<(), MandelError> {
+    let started = Instant::now();
+    match &model.last_render {
+        // only the pixels exposed by a pan are calculated, which is
+        // quick enough to wait for
+        Some((prev_cfg, prev)) if pixel_shift(prev_cfg, &model.cfg).is_some() => {
+            let iters = model.renderer.install(|| mandel_incremental(prev, prev_cfg, model.cfg))?;
+            set_render(model, model.cfg, iters, iterated_since(started));
+        }
+        // more iterations, eg, the . key, only iterate again the pixels
+        // which reached the last max_iters
+        Some((prev_cfg, prev)) if is_deeper(prev_cfg, &model.cfg) => {
+            let iters = model.renderer.install(|| mandel_deeper(prev, prev_cfg, model.cfg))?;
+            set_render(model, model.cfg, iters, iterated_since(started));
+        }
+        // other views are looked up in the tile cache first, and the
+        // missing tiles are rendered in the background
+        _ => {
+            let (grid, tiles) = model.tile_cache.lookup(&model.cfg)?;
+            if tiles.is_empty() {
+                set_render(model, model.cfg, grid, iterated_since(started));
+            } else {
+                model.pending = Some(PendingRender {
+                    cfg: model.cfg,
+                    job: RenderJob::start_tiles(model.cfg, tiles.clone(), grid)?,
+                    tiles,
+                });
+            }
+        }
+    }
+    Ok(())
 }
 
-fn image2file(model: &Model) {
-    let iters = mandel(model.cfg);
-    let imgbuf = get_image_buf(&iters, model);
-    imgbuf.save("fractal.png").unwrap();
-    println!("Image saved to 'fractal.png'");
+/// Stats of a render which took the time since `started`, without
+/// setting up the domains, eg, one copying from the last render
+fn iterated_since(started: Instant) -> RenderStats {
+    RenderStats {
+        iterate: started.elapsed(),
+        ..Default::default()
+    }
+}
+
+/// Make `iters`, rendered for `cfg` in the time of `stats`, the last
+/// render, colored next
+fn set_render(model: &mut Model, cfg: MandelConfig, iters: IterationGrid, stats: RenderStats) {
+    model.error = None;
+    model.render_stats = stats;
+    model.float_format_precision = get_ffmt_precision(model);
+    model.last_render = Some((cfg, iters));
+    model.flag_recolor = true;
+}
+
+fn image2file(model: &mut Model) {
+    // the last render is the current view, unless it is still pending
+    let rendered;
+    let iters = match &model.last_render {
+        Some((cfg, iters)) if *cfg == model.cfg => iters,
+        _ => match model.renderer.render(model.cfg) {
+            Ok(iters) => {
+                rendered = iters;
+                &rendered
+            }
+            Err(e) => {
+                eprintln!("Error rendering - {e}");
+                return;
+            }
+        },
+    };
+    let message = save_grid(model, &model.cfg, iters);
+    model.message = Some((message, Instant::now()));
+}
+
+/// Save the keyframes to `KEYFRAMES_FILE` and show `message`
+fn save_keyframes(model: &mut Model, message: String) {
+    model.keyframes.resolution = model.export_size;
+    let message = match model.keyframes.save(KEYFRAMES_FILE) {
+        Ok(()) => format!("{message} in '{KEYFRAMES_FILE}'"),
+        Err(e) => format!("Error saving '{KEYFRAMES_FILE}' - {e}"),
+    };
+    println!("{message}");
+    model.message = Some((message, Instant::now()));
+}
+
+/// Save an animated PNG of the colors of the view cycling once through
+/// the palette, see `save_palette_cycle()`
+fn save_cycle(model: &mut Model) {
+    let rendered;
+    let iters = match &model.last_render {
+        Some((cfg, iters)) if *cfg == model.cfg => iters,
+        _ => match model.renderer.render(model.cfg) {
+            Ok(iters) => {
+                rendered = iters;
+                &rendered
+            }
+            Err(e) => {
+                eprintln!("Error rendering - {e}");
+                return;
+            }
+        },
+    };
+    let path = model.output.animation_path(&model.cfg, std::time::SystemTime::now());
+    let options = AnimationOptions {
+        delay: Duration::from_secs_f64(1.0 / CYCLE_FPS),
+        loops: 0,
+    };
+    let saved = std::fs::create_dir_all(&model.output.dir)
+        .map_err(MandelError::from)
+        .and_then(|()| {
+            let (max_iters, frames) = (model.cfg.max_iters, ANIMATION_FRAMES);
+            save_palette_cycle(iters, max_iters, &mut model.color_schemes, frames, options, &path)
+        });
+    let message = match saved {
+        Ok(()) => {
+            let message = format!("Animation saved to '{}'", path.display());
+            println!("{message}");
+            message
+        }
+        Err(e) => {
+            let message = format!("Error saving '{}' - {e}", path.display());
+            eprintln!("{message}");
+            message
+        }
+    };
+    model.message = Some((message, Instant::now()));
+}
+
+/// Capture the next frame drawn, as on screen, to a new file next to the
+/// images saved, see `update_screenshot`
+fn take_screenshot(app: &App, model: &mut Model) {
+    let path = model.output.screenshot_path(&model.cfg, std::time::SystemTime::now());
+    if let Err(e) = std::fs::create_dir_all(&model.output.dir) {
+        let message = format!("Error saving '{}' - {e}", path.display());
+        eprintln!("{message}");
+        model.message = Some((message, Instant::now()));
+        return;
+    }
+    let window = app.window(model.window).unwrap();
+    window.capture_frame(&path);
+    model.screenshot = Some((path, window.elapsed_frames()));
+}
+
+/// Tell where the screenshot was saved, once its frame is drawn, not to
+/// capture the message itself
+fn update_screenshot(app: &App, model: &mut Model) {
+    let frames = app.window(model.window).unwrap().elapsed_frames();
+    if model.screenshot.as_ref().is_some_and(|(_, taken)| frames > *taken) {
+        let (path, _) = model.screenshot.take().unwrap();
+        let message = format!("Screenshot saved to '{}'", path.display());
+        println!("{message}");
+        model.message = Some((message, Instant::now()));
+    }
+}
+
+/// Start rendering the view at the export resolution in the background,
+/// or cancel the export in progress
+fn start_export(model: &mut Model) {
+    if model.export.take().is_some() {
+        model.message = Some(("Export cancelled".to_string(), Instant::now()));
+        return;
+    }
+    let mut cfg = MandelConfig {
+        aa: match model.export_samples {
+            0 | 1 => AaMode::Off,
+            samples => AaMode::Full { samples },
+        },
+        ..model.cfg
+    };
+    cfg.domains().resize(model.export_size);
+    match RenderJob::start(cfg, EXPORT_TILE_SIZE) {
+        Ok(job) => model.export = Some((cfg, job)),
+        Err(e) => model.message = Some((format!("Error exporting - {e}"), Instant::now())),
+    }
+}
+
+/// Save the export once it is rendered
+fn update_export(model: &mut Model) {
+    if !model.export.as_ref().is_some_and(|(_, job)| job.is_finished()) {
+        return;
+    }
+    let (cfg, job) = model.export.take().unwrap();
+    if let Some(iters) = job.wait() {
+        let message = save_grid(model, &cfg, &iters);
+        model.message = Some((message, Instant::now()));
+    }
+}
+
+/// Save `iters`, rendered for `cfg`, with the colors of the view to a
+/// new file, see `Output`. Return the message with its path, or the
+/// error.
+fn save_grid(model: &Model, cfg: &MandelConfig, iters: &IterationGrid) -> String {
+    let path = model.output.path(cfg, std::time::SystemTime::now());
+    let metadata = ImageMetadata::new(cfg, &model.color_schemes);
+    let saved = std::fs::create_dir_all(&model.output.dir)
+        .map_err(MandelError::from)
+        .and_then(|()| save_image_with_metadata(iters, &metadata, &model.color_schemes, &path));
+    match saved {
+        Ok(()) => {
+            let message = format!("Image saved to '{}'", path.display());
+            println!("{message}");
+            message
+        }
+        Err(e) => {
+            let message = format!("Error saving '{}' - {e}", path.display());
+            eprintln!("{message}");
+            message
+        }
+    }
 }
 
 // Draw the state of your `Model` into the given `Frame` here.
@@ -109,8 +813,43 @@ fn view(app: &App, model: &Model, frame: Frame) {
     frame.clear(BLACK);
     let draw = app.draw();
 
-    // Draw the image
-    draw.texture(&model.texture).xy(model.pan_mode.draw);
+    // Draw the image, rotated while dragging with Alt. Its pixels are
+    // those of the screen, see `ScreenMap`.
+    let scale_factor = app.window(model.window).unwrap().scale_factor();
+    let [w, h] = model.texture.size();
+    let (w, h) = (w as f32 / scale_factor, h as f32 / scale_factor);
+    let rect = view_rect(app, model);
+    let image = draw.scissor(rect);
+    // the preview of the pan fills in the borders uncovered by the image,
+    // where it was rendered for the offset of the pan then
+    if let Some((offset, _, texture)) = &model.pan_preview {
+        if model.pan_mode.is_active {
+            image.texture(texture).w_h(w, h).xy(rect.xy() + model.pan_mode.draw - *offset);
+        }
+    }
+    // scaled to the view shown during a zoom transition
+    let (offset, scale) = model
+        .transition
+        .as_ref()
+        .zip(model.last_render.as_ref())
+        .and_then(|(transition, (image, _))| {
+            transition::placement(image, &transition.view(&model.cfg), scale_factor)
+        })
+        .unwrap_or((Vec2::ZERO, Vec2::ONE));
+    image.texture(&model.texture)
+        .w_h(w * scale.x, h * scale.y)
+        .xy(rect.xy() + model.pan_mode.draw + offset)
+        .rotate(drag_angle(&model.rotate_mode));
+
+    // Draw the Julia set of the dual view on the right half
+    if let Some(dual) = &model.dual {
+        dual.draw(&draw, julia_rect(app), scale_factor, model.float_format_precision);
+    }
+
+    // Draw the orbit of the point under the mouse
+    if model.show_orbit {
+        draw_orbit(&draw, app, model);
+    }
 
     // Draw the selection rectangle
     if model.rect_mode.is_active && model.rect_mode.draw != Vec2::ZERO {
@@ -129,19 +868,90 @@ fn view(app: &App, model: &Model, frame: Frame) {
             .points_closed(points);
     }
 
-    // Write some text
-    let [x, y] = mouse2domain(app, model, model.pan_mode.end);
-    let p = model.float_format_precision;
-    let text = format!(
-        "x ({:.p$}, {:.p$}), y ({:.p$}, {:.p$}) \nMouse @ {:.p$}, {:.p$}\nMax iters: {}",
-        model.cfg.xdomain.start,
-        model.cfg.xdomain.end,
-        model.cfg.ydomain.start,
-        model.cfg.ydomain.end,
-        x,
-        y,
-        model.cfg.max_iters,
-    );
+    // Draw the Julia preview in the bottom-right corner
+    if let Some((_, texture)) = &model.preview {
+        if model.julia_preview && model.cfg.kind == FractalKind::Mandelbrot && model.dual.is_none() {
+            let win = app.window_rect();
+            let (w, h) = (win.w() / PREVIEW_DIVISOR as f32, win.h() / PREVIEW_DIVISOR as f32);
+            let xy = Vec2::new(win.right() - w / 2.0 - 10.0, win.bottom() + h / 2.0 + 30.0);
+            draw.rect().xy(xy).w_h(w + 2.0, h + 2.0).color(WHITE);
+            draw.texture(texture).xy(xy).w_h(w, h);
+        }
+    }
+
+    // Draw the palette strip in the top-right corner, with the color
+    // scheme in the HUD
+    if let Some((_, texture)) = &model.palette_strip {
+        if model.hud.visible && model.hud.color_scheme {
+            let win = app.window_rect();
+            let (w, h) = STRIP_SIZE;
+            let xy = Vec2::new(win.right() - w / 2.0 - 10.0, win.top() - h / 2.0 - 10.0);
+            draw.rect().xy(xy).w_h(w + 2.0, h + 2.0).color(WHITE);
+            draw.texture(texture).xy(xy).w_h(w, h);
+        }
+    }
+
+    // Draw the histogram of the iterations in the bottom-left corner,
+    // above the message
+    if let Some((_, histogram)) = &model.histogram {
+        if model.show_histogram {
+            let win = app.window_rect();
+            let (w, h) = HISTOGRAM_SIZE;
+            let xy = Vec2::new(win.left() + w / 2.0 + 10.0, win.bottom() + h / 2.0 + 70.0);
+            histogram.draw(&draw, geom::Rect::from_xy_wh(xy, Vec2::new(w, h)));
+        }
+    }
+
+    // Draw the minimap in the top-right corner, below the palette strip
+    if model.show_minimap && model.cfg.kind == FractalKind::Mandelbrot {
+        model.minimap.draw(&draw, minimap_rect(app), &model.cfg);
+    }
+
+    // Draw the progress of the render in the background along the bottom
+    if let Some(pending) = &model.pending {
+        let (done, total) = pending.job.progress();
+        let win = app.window_rect();
+        let w = win.w() * done as f32 / total as f32;
+        draw.rect()
+            .x_y(win.left() + win.w() / 2.0, win.bottom() + 3.0)
+            .w_h(win.w(), 6.0)
+            .color(DARKGREY);
+        draw.rect()
+            .x_y(win.left() + w / 2.0, win.bottom() + 3.0)
+            .w_h(w, 6.0)
+            .color(RED);
+        draw.text(&format!("{}%", 100 * done / total))
+            .x_y(win.right() - 30.0, win.bottom() + 16.0)
+            .color(RED);
+    }
+
+    // Write the message along the bottom, above the progress bar, and
+    // the progress of the export above it
+    let win = app.window_rect();
+    if let Some((message, _)) = &model.message {
+        draw.text(message)
+            .x_y(win.x(), win.bottom() + 30.0)
+            .w(win.w() - 40.0)
+            .color(WHITE);
+    }
+    if let Some((cfg, job)) = &model.export {
+        let (done, total) = job.progress();
+        let (w, h) = (cfg.resolution.x, cfg.resolution.y);
+        draw.text(&format!("Exporting {w}x{h}, {}% (Shift+F to cancel)", 100 * done / total))
+            .x_y(win.x(), win.bottom() + 50.0)
+            .w(win.w() - 40.0)
+            .color(WHITE);
+    }
+
+    // Write the HUD
+    let text = model.hud.text(&HudInfo {
+        cfg: &model.cfg,
+        mouse: mouse2plane(app, model, model.pan_mode.end),
+        precision: model.float_format_precision,
+        render_stats: &model.render_stats,
+        color_scheme: model.color_schemes.name(),
+        error: model.error.as_deref(),
+    });
     let winp = app.window_rect().pad(20.0);
     let text_area = geom::Rect::from_wh(winp.wh()).top_left_of(winp);
     draw.text(&text)
@@ -151,133 +961,596 @@ fn view(app: &App, model: &Model, frame: Frame) {
         .left_justify()
         .color(RED);
 
+    // Draw the keys and their actions over everything
+    if model.show_help {
+        draw_help(&draw, app, model);
+    }
+
     // Write to window's frame
     draw.to_frame(app, &frame).unwrap();
 }
 
+/// Handle the events `nannou` does not pass to `event()`, ie, pinching
+/// on touchpads, on macOS only
+fn raw_event(app: &App, model: &mut Model, event: &nannou::winit::event::WindowEvent) {
+    if let nannou::winit::event::WindowEvent::TouchpadMagnify { delta, .. } = *event {
+        // the width scales by `exp(-delta)`, in lines of the wheel
+        mouse_zoom(app, model, delta / -ZOOM_STEP.ln());
+    }
+}
+
 /// Handle events related to the window and update the model if necessary
+#[allow(clippy::collapsible_match)]
 fn event(app: &App, model: &mut Model, event: WindowEvent) {
     //println!("{event:?}");
     match event {
-        // Window resize - update resolution
+        // Window resize - update resolution, keeping the x domain and
+        // the pixels square, or else the domains with `--stretch`. Not
+        // below 2 pixels, eg, when minimised, see `MandelConfig::validate`
         Resized(size) => {
-            if size != Vec2::ZERO {
+            if size.x >= 2.0 && size.y >= 2.0 {
                 let size = size.to_array();
                 let sf = app.window(model.window).unwrap().scale_factor();
-                model.cfg.resolution.x = (sf * size[0]) as usize;
-                model.cfg.resolution.y = (sf * size[1]) as usize;
+                let mut resolution = Resolution {
+                    x: (sf * size[0]) as usize,
+                    y: (sf * size[1]) as usize,
+                };
+                // the halves of the dual view
+                if let Some(dual) = &mut model.dual {
+                    resolution.x = (resolution.x / 2).max(2);
+                    dual.resize(resolution);
+                }
+                if model.stretch {
+                    model.cfg.resolution = resolution;
+                } else {
+                    model.cfg.domains().resize(resolution);
+                }
                 model.flag_update = true;
             }
         }
-        // Mouse press - start pan
-        MousePressed(_button) => {
-            if model.rect_mode.is_active {
+        // A button pressed while another one drags is ignored
+        MousePressed(_) if is_dragging(model) => (),
+        // Left click on the minimap - centre the view there, at the same
+        // zoom
+        MousePressed(MouseButton::Left)
+            if model.show_minimap
+                && model.cfg.kind == FractalKind::Mandelbrot
+                && minimap_rect(app).contains(Vec2::new(app.mouse.x, app.mouse.y)) =>
+        {
+            let (x, y) = minimap::to_plane(minimap_rect(app), Vec2::new(app.mouse.x, app.mouse.y));
+            let view = View {
+                center_x: x,
+                center_y: y,
+                ..model.cfg.view()
+            };
+            model.cfg.domains().set_view(&view);
+            model.flag_update = true;
+        }
+        // The mouse over the Julia set of the dual view pans and zooms it
+        MousePressed(button) if model.dual.is_some() && over_julia(app) => {
+            let mouse = Vec2::new(app.mouse.x, app.mouse.y);
+            model.dual.as_mut().unwrap().start_pan(mouse, button);
+        }
+        MouseWheel(delta, ..) if model.dual.is_some() && over_julia(app) => {
+            let lines = match delta {
+                LineDelta(_x, y) => y as f64,
+                PixelDelta(PhysicalPosition { x: _x, y }) => y / PIXELS_PER_LINE,
+            };
+            let factor = ZOOM_STEP.powf(lines * model.zoom_sensitivity);
+            let scale_factor = app.window(model.window).unwrap().scale_factor();
+            let mouse = Vec2::new(app.mouse.x, app.mouse.y);
+            model.dual.as_mut().unwrap().zoom(julia_rect(app), scale_factor, mouse, factor);
+        }
+        // Right click, by default, on the Mandelbrot view - pick `c` and
+        // show its Julia set, on the right half in the dual view
+        MousePressed(button)
+            if model.mouse_buttons.action(button) == Some(MouseAction::PickJulia)
+                && model.cfg.kind == FractalKind::Mandelbrot =>
+        {
+            model.julia_c = mouse2plane(app, model, Vec2::new(app.mouse.x, app.mouse.y));
+            match &mut model.dual {
+                Some(dual) => dual.set_c(model.julia_c),
+                None => toggle_julia(model),
+            }
+        }
+        // Mouse press - start the drag of the key held, or else of the
+        // button, see `--mouse`
+        MousePressed(button) => {
+            let drag = model.mouse_buttons.action(button);
+            if model.rotate_mode.is_active || drag == Some(MouseAction::Rotate) {
+                // around the centre of the view
+                model.rotate_mode.start = Vec2::new(app.mouse.x, app.mouse.y) - view_rect(app, model).xy();
+                model.rotate_mode.end = model.rotate_mode.start;
+                // for rotate_mode, `draw` is a flag that the mouse is down
+                model.rotate_mode.draw = Vec2::ONE;
+                model.rotate_mode.button = Some(button);
+            } else if model.rect_mode.is_active || drag == Some(MouseAction::ZoomRect) {
+                model.rect_mode.is_active = true;
                 model.rect_mode.start = Vec2::new(app.mouse.x, app.mouse.y);
                 // for rect_mode, `draw` is a flag to activate drawing after 
                 // Ctrl or Shift key is pressed
                 model.rect_mode.draw = Vec2::ONE;
-            } else {
+                model.rect_mode.button = Some(button);
+            } else if drag.is_some() {
                 model.pan_mode.is_active = true;
                 model.pan_mode.start = Vec2::new(app.mouse.x, app.mouse.y);
+                model.pan_mode.button = Some(button);
             }
         }
         // Mouse move - update pan, shift image buffer without calling mandel()
         MouseMoved(position) => {
+            if model.dual.as_mut().is_some_and(|dual| dual.move_pan(position)) {
+                return;
+            }
             model.pan_mode.end = position;
             model.rect_mode.end = position;
+            model.rotate_mode.end = position - view_rect(app, model).xy();
             if model.pan_mode.is_active {
                 // For pan_mode, `draw` is the offset to shift the image buffer
                 model.pan_mode.draw = model.pan_mode.end - model.pan_mode.start;
             } 
         }
         // Mouse release - end pan, update x,y domain, call mandel()
-        MouseReleased(_button) => {
-            if model.pan_mode.is_active {
+        // only by the button which started the drag
+        MouseReleased(button) => {
+            let scale_factor = app.window(model.window).unwrap().scale_factor();
+            if model.dual.as_mut().is_some_and(|dual| dual.end_pan(julia_rect(app), scale_factor, button)) {
+                return;
+            }
+            if model.rotate_mode.button == Some(button) {
+                model.rotate_mode.button = None;
+                mouse_rotate(model);
+                model.rotate_mode.draw = Vec2::ZERO;
+            } else if model.pan_mode.button == Some(button) {
+                model.pan_mode.button = None;
                 model.pan_mode.is_active = false;
                 model.pan_mode.draw = Vec2::ZERO;
+                model.pan_preview = None;
                 mouse_pan(app, model);
-            } else if model.rect_mode.is_active {
+            } else if model.rect_mode.button == Some(button) {
+                model.rect_mode.button = None;
                 model.rect_mode.is_active = false;
                 mouse_zoom_rect(app, model);
             }
         }
         
+        // Zoom with mouse wheel
+        MouseWheel(LineDelta(_x, y), ..) => {
+            mouse_zoom(app, model, y as f64);
+        }
+        // precise deltas of touchpads, zooming smoothly
+        MouseWheel(PixelDelta(PhysicalPosition { x: _x, y }), ..) => {
+            mouse_zoom(app, model, y / PIXELS_PER_LINE);
+        }
+
+        // Dropping an image saved with its view, see `ImageMetadata`, or
+        // a view file onto the window shows that view
+        DroppedFile(path) => {
+            let loaded = if path.extension().is_some_and(|e| e == "toml") {
+                std::fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|s| MandelConfig::from_toml(&s).map_err(|e| e.to_string()))
+            } else {
+                ImageMetadata::from_file(&path).map_err(|e| e.to_string()).map(|metadata| {
+                    // the scheme may be missing, eg, a palette of another session
+                    model.color_schemes.set_by_name(&metadata.color_scheme);
+                    metadata.cfg
+                })
+            };
+            match loaded {
+                Ok(cfg) => {
+                    load_view(model, cfg);
+                    println!("View loaded from '{}'", path.display());
+                }
+                Err(e) => eprintln!("Error loading '{}' - {e}", path.display()),
+            }
+        }
+
+        // Keys run their action, see `Keymap`
+        KeyPressed(key) => {
+            if let Some(action) = model.keymap.action(key, app.keys.mods) {
+                run_action(app, model, action);
+            }
+        }
+        // and end the held ones
+        KeyReleased(key) => match model.keymap.held(key) {
+            Some(Action::SelectRect) => {
+                model.rect_mode.is_active = false;
+                model.rect_mode.draw = Vec2::ZERO;
+                model.rect_mode.button = None;
+            }
+            Some(Action::ShowOrbit) => model.show_orbit = false,
+            Some(Action::DragRotate) => {
+                model.rotate_mode.is_active = false;
+                if model.rotate_mode.draw != Vec2::ZERO {
+                    mouse_rotate(model);
+                    model.rotate_mode.draw = Vec2::ZERO;
+                    model.rotate_mode.button = None;
+                }
+            }
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+/// Run `action`, of a key pressed
+#[allow(clippy::collapsible_match)]
+fn run_action(app: &App, model: &mut Model, action: Action) {
+    match action {
         // Ctrl or Shift keys zoom with rectangle
-        KeyPressed(Key::LControl) | KeyPressed(Key::LShift) => {
-            if ! model.rect_mode.is_active {
+        Action::SelectRect => {
+            if !model.rect_mode.is_active {
                 model.rect_mode.is_active = true;
                 model.rect_mode.draw = Vec2::ZERO;
+            }
         }
+        // Right Shift key shows the orbit of the point under the mouse
+        Action::ShowOrbit => model.show_orbit = true,
+        // Alt key rotates the view dragging the mouse around the centre
+        Action::DragRotate => {
+            if !model.rotate_mode.is_active {
+                model.rotate_mode.is_active = true;
+                model.rotate_mode.draw = Vec2::ZERO;
+            }
         }
-        KeyReleased(Key::LControl) | KeyReleased(Key::LShift) => {
-            model.rect_mode.is_active = false;
-            model.rect_mode.draw = Vec2::ZERO;
-        }
-
-        // Zoom with mouse wheel
-        MouseWheel(LineDelta(_x, y), ..) => {
-            mouse_zoom(app, model, y as f64);
+        // Q/W keys rotate the view counter-clockwise/clockwise
+        Action::RotateLeft => {
+            model.cfg.domains().rotate(ROTATION_STEP.to_radians());
+            model.flag_update = true;
         }
-        MouseWheel(PixelDelta(PhysicalPosition { x: _x, y }), ..) => {
-            mouse_zoom(app, model, y);
+        Action::RotateRight => {
+            model.cfg.domains().rotate(-ROTATION_STEP.to_radians());
+            model.flag_update = true;
         }
 
-        // ,/. keys increase/reduce max_iters
-        KeyPressed(Key::Period) => {
+        // ,/. keys increase/reduce max_iters, which turns off auto_iters
+        Action::MoreIters => {
             if model.cfg.max_iters < 20000 {
                 model.cfg.max_iters *= 2;
+                model.cfg.auto_iters = None;
                 model.flag_update = true;
             }
         }
-        KeyPressed(Key::Comma) => {
+        Action::FewerIters => {
             if model.cfg.max_iters > 32 {
                 model.cfg.max_iters /= 2;
+                model.cfg.auto_iters = None;
                 model.flag_update = true;
             }
         }
+        // A key toggles setting max_iters from the zoom
+        Action::AutoIters => {
+            model.cfg.auto_iters = match model.cfg.auto_iters {
+                Some(_) => None,
+                None => Some(AutoIters::default()),
+            };
+            model.flag_update = true;
+        }
 
         // +/- keys zoom in and out
-        KeyPressed(Key::Plus) | KeyPressed(Key::NumpadAdd) => {
-            keyboard_zoom(model, 0.25);
+        Action::ZoomIn => {
+            keyboard_zoom(model, 0.5);
         }
-        KeyPressed(Key::Minus) | KeyPressed(Key::NumpadSubtract) => {
-            keyboard_zoom(model, -0.25);
+        Action::ZoomOut => {
+            // zooms out by 2, the reverse of zooming in, so that the
+            // tiles of the previous view are in the cache
+            keyboard_zoom(model, 2.0);
         }
 
         // arrows keys pan the domain by half
-        KeyPressed(Key::Up) => {
+        Action::PanUp => {
             keyboard_pan(model, 0.0, -0.25);
         }
-        KeyPressed(Key::Down) => {
+        Action::PanDown => {
             keyboard_pan(model, 0.0, 0.25);
         }
-        KeyPressed(Key::Right) => {
+        Action::PanRight => {
             keyboard_pan(model, -0.25, 0.0);
         }
-        KeyPressed(Key::Left) => {
+        Action::PanLeft => {
             keyboard_pan(model, 0.25, 0.0);
         }
 
+        // Ctrl+C copies the centre and zoom of the view to the clipboard,
+        // Ctrl+Shift+C its domains, and Ctrl+V goes to the location in
+        // the clipboard
+        Action::CopyView | Action::CopyDomains => {
+            let text = Location::text(&model.cfg, action == Action::CopyDomains);
+            let message = match clipboard::copy(&text) {
+                Ok(()) => format!("Copied '{text}'"),
+                Err(e) => format!("Error copying the location - {e}"),
+            };
+            println!("{message}");
+            model.message = Some((message, Instant::now()));
+        }
+        Action::Paste => {
+            let location = clipboard::paste()
+                .map_err(|e| e.to_string())
+                .and_then(|text| Location::parse(&text).map_err(|e| format!("{e} in '{}'", text.trim())));
+            match location {
+                Ok(location) => {
+                    location.apply(&mut model.cfg, &mut model.color_schemes);
+                    model.flag_update = true;
+                }
+                Err(e) => {
+                    let message = format!("Error pasting the location - {e}");
+                    eprintln!("{message}");
+                    model.message = Some((message, Instant::now()));
+                }
+            }
+        }
+
         // Change color scheme
-        KeyPressed(Key::C) => {
+        Action::PrevScheme => {
+            model.color_schemes.prev();
+            model.flag_recolor = true;
+        }
+        Action::NextScheme => {
             model.color_schemes.next();
+            model.flag_recolor = true;
+        }
+
+        // E key toggles histogram equalisation of the colors
+        Action::Equalize => {
+            model.color_schemes.toggle_equalize();
+            model.flag_recolor = true;
+        }
+
+        // Shift+Y saves an animation of a cycle of the palette
+        Action::SaveCycle => {
+            save_cycle(model);
+        }
+        // Y key toggles palette cycling, which animates the colors of
+        // the last render without recalculating it
+        Action::CyclePalette => {
+            model.cycling = match model.cycling {
+                None => Some(Instant::now()),
+                Some(_) => None,
+            };
+        }
+
+        // I key toggles relief shading, lit from the top left
+        Action::Relief => {
+            let relief = match model.color_schemes.get_relief() {
+                None => Some(color_schemes::ReliefShaded::default()),
+                Some(_) => None,
+            };
+            model.color_schemes.set_relief(relief);
+            model.flag_recolor = true;
+        }
+
+        // O key cycles the orbit trap: none, point, line, circle
+        Action::OrbitTrap => {
+            model.cfg.trap = match model.cfg.trap {
+                None => "point".parse().ok(),
+                Some(OrbitTrap::Point { .. }) => "line".parse().ok(),
+                Some(OrbitTrap::Line { .. }) => "circle".parse().ok(),
+                Some(OrbitTrap::Circle { .. }) => None,
+            };
+            println!("Orbit trap: {:?}", model.cfg.trap);
+            model.flag_update = true;
+        }
+
+        // D key toggles distance estimation shading
+        Action::Distance => {
+            model.cfg.distance = !model.cfg.distance;
+            model.flag_update = true;
+        }
+
+        // V key toggles stripe average coloring
+        Action::Stripes => {
+            model.cfg.stripes = match model.cfg.stripes {
+                None => Some(STRIPE_DENSITY),
+                Some(_) => None,
+            };
+            model.flag_update = true;
+        }
+
+        // G key cycles the iteration formula
+        Action::Formula => {
+            model.cfg.formula = match model.cfg.formula {
+                Formula::Mandelbrot => Formula::BurningShip,
+                Formula::BurningShip => Formula::Tricorn,
+                Formula::Tricorn => Formula::Celtic,
+                Formula::Celtic => Formula::Mandelbrot,
+            };
+            println!("Formula: {:?}", model.cfg.formula);
+            model.flag_update = true;
+        }
+
+        // Ctrl+[ and Ctrl+] decrease/increase the power of `z^n + c`
+        Action::PowerDown => {
+            model.cfg.power = (model.cfg.power - 1.0).max(2.0);
+            println!("Power: {}", model.cfg.power);
+            model.flag_update = true;
+        }
+        Action::PowerUp => {
+            model.cfg.power += 1.0;
+            println!("Power: {}", model.cfg.power);
+            model.flag_update = true;
+        }
+        // { and } squeeze/stretch the palette along the iteration counts
+        Action::SqueezePalette => {
+            scale_palette(model, 1.0 / PALETTE_SCALE_STEP);
+        }
+        Action::StretchPalette => {
+            scale_palette(model, PALETTE_SCALE_STEP);
+        }
+        // [ and ] shift the palette along the iteration counts
+        Action::PaletteOffsetDown => shift_palette(model, -PALETTE_OFFSET_STEP),
+        Action::PaletteOffsetUp => shift_palette(model, PALETTE_OFFSET_STEP),
+
+        // T key toggles the bailout between the default and a large one
+        Action::Threshold => {
+            model.cfg.threshold = if model.cfg.threshold == SMOOTH_THRESHOLD {
+                MandelConfig::default().threshold
+            } else {
+                SMOOTH_THRESHOLD
+            };
+            println!("Threshold: {}", model.cfg.threshold);
             model.flag_update = true;
         }
 
+        // Backspace or U goes back to the previous view, Shift+Backspace
+        // forward again
+        Action::Forward => {
+            if let Some(cfg) = model.history.redo(model.cfg) {
+                go_to_history(model, cfg);
+            }
+        }
+        Action::Back => {
+            if let Some(cfg) = model.history.undo(model.cfg) {
+                go_to_history(model, cfg);
+            }
+        }
+
+        // B key cycles the compute backend
+        Action::Backend => {
+            model.cfg.backend = match model.cfg.backend {
+                ComputeBackend::Scalar => ComputeBackend::Simd,
+                ComputeBackend::Simd => ComputeBackend::Gpu,
+                ComputeBackend::Gpu => ComputeBackend::Scalar,
+            };
+            println!("Compute backend: {:?}", model.cfg.backend);
+            model.flag_update = true;
+        }
+
+        // Shift+J shows/hides the Julia set of the point right-clicked on
+        // the right half of the window
+        Action::DualView => {
+            toggle_dual(app, model);
+        }
+        // J key toggles between the Mandelbrot and the Julia set
+        Action::Julia => {
+            toggle_julia(model);
+        }
+
         // R key resets domain to default
-        KeyPressed(Key::R) => {
-            model.cfg.xdomain.start = -2.5;
-            model.cfg.xdomain.end = 1.0;
-            model.cfg.ydomain.start = -1.0;
-            model.cfg.ydomain.end = 1.0;
+        Action::Reset => {
+            let (xdomain, ydomain) = model.cfg.kind.default_domains();
+            model.cfg.rotation = 0.0;
+            model.cfg.domains().set_domains(xdomain, ydomain).fit_to_aspect();
             model.flag_update = true;
         }
 
+        // S key saves the current view, L key restores it
+        Action::SaveView => match std::fs::write(VIEW_FILE, model.cfg.to_toml()) {
+            Ok(()) => println!("View saved to '{VIEW_FILE}'"),
+            Err(e) => eprintln!("Error saving '{VIEW_FILE}' - {e}"),
+        },
+        Action::LoadView => {
+            let loaded = std::fs::read_to_string(VIEW_FILE)
+                .map_err(|e| e.to_string())
+                .and_then(|s| MandelConfig::from_toml(&s).map_err(|e| e.to_string()));
+            match loaded {
+                Ok(cfg) => {
+                    load_view(model, cfg);
+                    println!("View loaded from '{VIEW_FILE}'");
+                }
+                Err(e) => eprintln!("Error loading '{VIEW_FILE}' - {e}"),
+            }
+        }
+
+        // P key shows/hides the Julia preview
+        Action::JuliaPreview => {
+            model.julia_preview = !model.julia_preview;
+            model.preview = None;
+        }
+
+        // Shift+M shows/hides the minimap
+        Action::Minimap => {
+            model.show_minimap = !model.show_minimap;
+        }
+        // M key cycles the built-in landmarks
+        Action::Landmark => {
+            let i = model.landmark.map_or(0, |i| (i + 1) % LANDMARKS.len());
+            model.landmark = Some(i);
+            LANDMARKS[i].apply(&mut model.cfg);
+            model.flag_update = true;
+            println!("Landmark {}: {}", LANDMARKS[i].name, LANDMARKS[i].title);
+        }
+
+        // K key bookmarks the current view, N key cycles the bookmarks
+        Action::Bookmark => {
+            let bookmark = Bookmark {
+                cfg: model.cfg,
+                color_scheme: model.color_schemes.index(),
+            };
+            match model.bookmarks.add(bookmark) {
+                Ok(()) => println!(
+                    "Bookmark {} saved to '{}'",
+                    model.bookmarks.len(),
+                    model.bookmarks.path().display()
+                ),
+                Err(e) => eprintln!("Error saving bookmarks - {e}"),
+            }
+        }
+        Action::NextBookmark => match model.bookmarks.next() {
+            Some(bookmark) => {
+                // the resolution is the one of the window
+                model.cfg = MandelConfig {
+                    resolution: model.cfg.resolution,
+                    ..bookmark.cfg
+                };
+                model.cfg.domains().fit_to_aspect();
+                if let FractalKind::Julia { cx, cy } = bookmark.cfg.kind {
+                    model.julia_c = [cx, cy];
+                }
+                // custom color schemes may be gone since the bookmark
+                if bookmark.color_scheme < model.color_schemes.len() {
+                    model.color_schemes.set(bookmark.color_scheme);
+                }
+                model.flag_update = true;
+            }
+            None => println!("No bookmarks, press K to add one"),
+        },
+
+        // X key records the view as a keyframe, to render the animation
+        // from one to the next with the CLI, Shift+X clears them
+        Action::ClearKeyframes => {
+            model.keyframes.keyframes.clear();
+            save_keyframes(model, "Keyframes cleared".to_string());
+        }
+        Action::Keyframe => {
+            model.keyframes.keyframes.push(Keyframe {
+                cfg: model.cfg,
+                palette: PaletteSettings::new(&model.color_schemes),
+                frames: KEYFRAME_FRAMES,
+                easing: Easing::EaseInOut,
+            });
+            let message = format!("Keyframe {} recorded", model.keyframes.keyframes.len());
+            save_keyframes(model, message);
+        }
+
+        // Shift+H shows/hides the histogram of the iterations
+        Action::Histogram => {
+            model.show_histogram = !model.show_histogram;
+        }
+        // H key toggles the HUD
+        Action::Hud => {
+            model.hud.toggle();
+        }
+        // F1 shows/hides the keys and their actions
+        Action::Help => {
+            model.show_help = !model.show_help;
+        }
+
+        // Shift+F exports the view at `--export-size`, see `start_export`
+        Action::Export => {
+            start_export(model);
+        }
+        // F11 or Alt+Enter toggles borderless fullscreen, the resolution
+        // follows the size of the window, see `Resized`
+        Action::Fullscreen => toggle_fullscreen(app, model),
+
+        // F12 saves a screenshot of the window, with the HUD and overlays
+        Action::Screenshot => take_screenshot(app, model),
+
         // F key saves image to file
-        KeyPressed(Key::F) => {
+        Action::Save => {
             image2file(model);
         }
-        _ => (),
     }
 }
 
@@ -285,12 +1558,91 @@ fn event(app: &App, model: &mut Model, event: WindowEvent) {
 fn get_ffmt_precision(model: &Model) -> usize {
     let delta = (model.cfg.xdomain.end - model.cfg.xdomain.start)
         .min(model.cfg.ydomain.end - model.cfg.ydomain.start);
-    let precision = if delta > f64::MIN_POSITIVE {
+    if delta > f64::MIN_POSITIVE {
         (2 - delta.log10() as i32) as usize
     } else {
         20
+    }
+}
+
+/// Shift the palette by `step`, a fraction of `max_iters`
+fn shift_palette(model: &mut Model, step: f64) {
+    let offset = model.color_schemes.offset() + step;
+    model.color_schemes.set_offset(offset);
+    println!("Palette offset: {:.2}", model.color_schemes.offset());
+    model.flag_recolor = true;
+}
+
+/// Multiply the scale of the palette by `factor`
+fn scale_palette(model: &mut Model, factor: f64) {
+    let scale = model.color_schemes.scale() * factor;
+    model.color_schemes.set_scale(scale);
+    println!("Palette scale: {:.2}", model.color_schemes.scale());
+    model.flag_recolor = true;
+}
+
+/// Show the view `cfg`, eg, loaded from a file, at the resolution of
+/// the window
+fn load_view(model: &mut Model, cfg: MandelConfig) {
+    model.cfg = MandelConfig {
+        resolution: model.cfg.resolution,
+        ..cfg
     };
-    precision
+    model.cfg.domains().fit_to_aspect();
+    if let FractalKind::Julia { cx, cy } = cfg.kind {
+        model.julia_c = [cx, cy];
+    }
+    model.flag_update = true;
+}
+
+/// Show `cfg`, a view of the history, without recording the view left,
+/// which the history already has
+fn go_to_history(model: &mut Model, cfg: MandelConfig) {
+    load_view(model, cfg);
+    model.shown = model.cfg;
+}
+
+fn toggle_fullscreen(app: &App, model: &Model) {
+    let window = app.window(model.window).unwrap();
+    window.set_fullscreen(!window.is_fullscreen());
+}
+
+/// Split the window between the view and the Julia set of
+/// `model.julia_c`, or show the view in the whole window again. The
+/// view is the Mandelbrot set, with its domains, in the split window.
+fn toggle_dual(app: &App, model: &mut Model) {
+    let scale_factor = app.window(model.window).unwrap().scale_factor();
+    let mut resolution = Resolution {
+        x: (scale_factor * app.window_rect().w()) as usize,
+        y: model.cfg.resolution.y,
+    };
+    if model.dual.take().is_none() {
+        if model.cfg.kind != FractalKind::Mandelbrot {
+            toggle_julia(model);
+        }
+        resolution.x = (resolution.x / 2).max(2);
+        model.dual = Some(DualView::new(model.julia_c, resolution));
+    }
+    model.cfg.domains().resize(resolution);
+    model.flag_update = true;
+}
+
+/// Switch between the Mandelbrot and the Julia set of `model.julia_c`.
+/// The domains of the view being left are kept to be restored later.
+fn toggle_julia(model: &mut Model) {
+    model.cfg.kind = match model.cfg.kind {
+        FractalKind::Mandelbrot => FractalKind::Julia {
+            cx: model.julia_c[0],
+            cy: model.julia_c[1],
+        },
+        FractalKind::Julia { .. } => FractalKind::Mandelbrot,
+    };
+    let domains = (model.cfg.xdomain, model.cfg.ydomain, model.cfg.origin);
+    (model.cfg.xdomain, model.cfg.ydomain, model.cfg.origin) = model.other_domains;
+    // the window may have been resized since
+    model.cfg.domains().fit_to_aspect();
+    model.other_domains = domains;
+    model.flag_update = true;
 }
 
 /// Zoom with mouse. Update mandelbrot set x and y domains.
@@ -298,39 +1650,50 @@ fn mouse_zoom(app: &App, model: &mut Model, delta: f64) {
     if delta.abs() < f64::MIN_POSITIVE {
         return;
     }
-    let y = delta / delta.abs();
-    let zoom = 0.10 * y;
-    let (x0, x1) = (model.cfg.xdomain.start, model.cfg.xdomain.end);
-    let (y0, y1) = (model.cfg.ydomain.start, model.cfg.ydomain.end);
-    let (dx, dy) = (x1 - x0, y1 - y0);
-    let (ox, oy) = (dx * zoom, dy * zoom);
+    // the width goes to `ZOOM_STEP` per line zooming in and to its
+    // inverse zooming out, which takes back to the same view, see
+    // `TileCache`, and the zoom is proportional to fractions of lines
+    let factor = ZOOM_STEP.powf(delta * model.zoom_sensitivity);
     let [x, y] = mouse2domain(app, model, model.pan_mode.end);
-    let (fx, fy) = ((x - x0) / (x1 - x), (y - y0) / (y1 - y));
-    let (ox0, oy0) = (ox * fx / (fx + 1.), oy * fy / (fy + 1.));
-    model.cfg.xdomain.start += ox0;
-    model.cfg.xdomain.end += -(ox - ox0);
-    model.cfg.ydomain.start += oy0;
-    model.cfg.ydomain.end += -(oy - oy0);
-    model.flag_update = true;
+    let shown = shown_view(model);
+    model.cfg.domains().zoom_about_point(x, y, factor);
+    start_transition(model, shown);
 }
 
-/// Update mandelbrot set x and y domains after selection with mouse
+/// Update mandelbrot set x and y domains after selection with mouse.
+/// The rectangle is widened or heightened to the aspect of the window.
 fn mouse_zoom_rect(app: &App, model: &mut Model) {
     let [x0, y0] = mouse2domain(app, model, model.rect_mode.start);
     let [x1, y1] = mouse2domain(app, model, model.rect_mode.end);
-    (model.cfg.xdomain.start, model.cfg.xdomain.end) = min_max(x0, x1);
-    (model.cfg.ydomain.start, model.cfg.ydomain.end) = min_max(y0, y1);
-    model.flag_update = true;
+    if x0 == x1 && y0 == y1 {
+        return;
+    }
+    let shown = shown_view(model);
+    model.cfg.domains().zoom_to_rect(x0, y0, x1, y1);
+    start_transition(model, shown);
 }
 
-/// Zoom with keyboard. Update mandelbrot set x and y domains.
-fn keyboard_zoom(model: &mut Model, zoom: f64) {
-    let dx = zoom * (model.cfg.xdomain.end - model.cfg.xdomain.start);
-    let dy = zoom * (model.cfg.ydomain.end - model.cfg.ydomain.start);
-    model.cfg.xdomain.start += dx;
-    model.cfg.xdomain.end -= dx;
-    model.cfg.ydomain.start += dy;
-    model.cfg.ydomain.end -= dy;
+/// Return the view shown, on the way to `model.cfg` during a zoom
+/// transition
+fn shown_view(model: &Model) -> MandelConfig {
+    model.transition.as_ref().map_or(model.cfg, |t| t.view(&model.cfg))
+}
+
+/// Glide from the view `shown` to `model.cfg`, rendered at the end, see
+/// `ZoomTransition`, or render it now without a transition or an image
+/// to scale
+fn start_transition(model: &mut Model, shown: MandelConfig) {
+    if model.zoom_transition.is_zero() || model.last_render.is_none() {
+        model.flag_update = true;
+        return;
+    }
+    model.transition = Some(ZoomTransition::new(&shown, model.zoom_transition));
+}
+
+/// Zoom with keyboard, scaling the domains by `factor` around the centre
+fn keyboard_zoom(model: &mut Model, factor: f64) {
+    let (x, y) = model.cfg.domains().center();
+    model.cfg.domains().zoom_about_point(x, y, factor);
     model.flag_update = true;
 }
 
@@ -338,69 +1701,174 @@ fn keyboard_zoom(model: &mut Model, zoom: f64) {
 fn mouse_pan(app: &App, model: &mut Model) {
     let [x0, y0] = mouse2domain(app, model, model.pan_mode.start);
     let [x1, y1] = mouse2domain(app, model, model.pan_mode.end);
-    let (dx, dy) = (x1 - x0, y1 - y0);
-    model.cfg.xdomain.start -= dx;
-    model.cfg.xdomain.end -= dx;
-    model.cfg.ydomain.start -= dy;
-    model.cfg.ydomain.end -= dy;
+    model.cfg.domains().pan(x0 - x1, y0 - y1);
     model.flag_update = true;
 }
 
-/// Pan with keyboard. Update mandelbrot set x and y domains.
+/// Pan with keyboard by fractions of the domains
 fn keyboard_pan(model: &mut Model, panx: f64, pany: f64) {
-    let xoffset = panx * (model.cfg.xdomain.end - model.cfg.xdomain.start);
-    let yoffset = pany * (model.cfg.ydomain.end - model.cfg.ydomain.start);
-    model.cfg.xdomain.start += xoffset;
-    model.cfg.xdomain.end += xoffset;
-    model.cfg.ydomain.start += yoffset;
-    model.cfg.ydomain.end += yoffset;
+    model.cfg.domains().pan_by_fraction(panx, pany);
     model.flag_update = true;
 }
 
-/// Converts a window-relative `position` into Mandelbrot x,y domain
-fn mouse2domain(app: &App, model: &Model, position: Vec2) -> [f64; 2] {
-    let [px, py] = position.to_array();
-    let (w, h) = app.window(model.window).unwrap().inner_size_points();
+/// Draw the orbit of the point under the mouse as a polyline. Only for
+/// `z^2 + c`, see `mandelbrot_cli::orbit()`.
+fn draw_orbit(draw: &nannou::Draw, app: &App, model: &Model) {
+    let cfg = &model.cfg;
+    if cfg.formula != Formula::Mandelbrot || cfg.power != 2.0 {
+        return;
+    }
+    let [x, y] = mouse2plane(app, model, model.pan_mode.end);
+    let points = match cfg.kind {
+        FractalKind::Mandelbrot => orbit((x, y), cfg.max_iters, cfg.threshold),
+        FractalKind::Julia { cx, cy } => {
+            orbit_from((x, y), (cx, cy), cfg.max_iters, cfg.threshold)
+        }
+    };
+    let points: Vec<_> = points
+        .into_iter()
+        .map(|(x, y)| {
+            let (x, y) = cfg.to_domains(x, y);
+            domain2window(app, model, [x, y])
+        })
+        .collect();
+    draw.polyline()
+        .weight(1.0)
+        .rgb8(255, 255, 0)
+        .points(points.iter().copied());
+    draw.ellipse().xy(points[0]).radius(3.0).rgb8(255, 255, 0);
+}
 
-    // move origin from screen centre to bottom-left corner
-    let px = (px + w / 2.0) as f64;
-    let py = (py + h / 2.0) as f64;
+/// Draw the keys of the actions, from the keymap, and the actions of the
+/// mouse buttons, in as many columns as needed, over the view darkened
+fn draw_help(draw: &nannou::Draw, app: &App, model: &Model) {
+    let win = app.window_rect();
+    draw.rect().xy(win.xy()).wh(win.wh()).rgba(0.0, 0.0, 0.0, 0.8);
+    let area = win.pad(20.0);
+    draw.text("Keys (F1 to close)")
+        .xy(area.mid_top() - Vec2::new(0.0, HELP_LINE_HEIGHT / 2.0))
+        .w(area.w())
+        .color(WHITE);
+    let (key_width, description_width) = HELP_COLUMNS;
+    let rows = ((area.h() / HELP_LINE_HEIGHT) as usize).saturating_sub(2).max(1);
+    let lines = model.keymap.help().into_iter().chain(model.mouse_buttons.help());
+    for (i, (keys, description)) in lines.enumerate() {
+        let (column, row) = (i / rows, i % rows);
+        let left = area.left() + column as f32 * (key_width + description_width);
+        let y = area.top() - (row as f32 + 2.5) * HELP_LINE_HEIGHT;
+        draw.text(&keys)
+            .x_y(left + key_width / 2.0, y)
+            .w_h(key_width - 10.0, HELP_LINE_HEIGHT)
+            .font_size(HELP_FONT_SIZE)
+            .left_justify()
+            .color(RED);
+        draw.text(description)
+            .x_y(left + key_width + description_width / 2.0, y)
+            .w_h(description_width - 10.0, HELP_LINE_HEIGHT)
+            .font_size(HELP_FONT_SIZE)
+            .left_justify()
+            .color(WHITE);
+    }
+}
 
-    // current domain
-    let (x0, x1) = (model.cfg.xdomain.start, model.cfg.xdomain.end);
-    let (y0, y1) = (model.cfg.ydomain.start, model.cfg.ydomain.end);
+/// Return `true` while a mouse button drags, in the view or in the Julia
+/// set of the dual view
+fn is_dragging(model: &Model) -> bool {
+    [&model.pan_mode, &model.rect_mode, &model.rotate_mode]
+        .iter()
+        .any(|mode| mode.button.is_some())
+        || model.dual.as_ref().is_some_and(DualView::is_panning)
+}
 
-    // calc new domain
-    let x_new = x0 + px / w as f64 * (x1 - x0);
-    let y_new = y0 + py / h as f64 * (y1 - y0);
+/// Return the angle in radians, counter-clockwise, the mouse was
+/// dragged around the centre of the window, 0 if not dragging
+fn drag_angle(mode: &SelectMode) -> f32 {
+    if mode.draw == Vec2::ZERO {
+        return 0.0;
+    }
+    let (start, end) = (mode.start, mode.end);
+    end.y.atan2(end.x) - start.y.atan2(start.x)
+}
 
-    [x_new, y_new]
+/// Rotate with mouse. The image turns with the mouse, so the view turns
+/// the other way.
+fn mouse_rotate(model: &mut Model) {
+    let angle = drag_angle(&model.rotate_mode) as f64;
+    if angle != 0.0 {
+        model.cfg.domains().rotate(-angle);
+        model.flag_update = true;
+    }
 }
 
-/// Return a buffer with the image of the mandelbrot set
-fn get_image_buf(
-    iters: &Vec<Vec<usize>>,
-    model: &Model,
-) -> image::ImageBuffer<image::Rgb<u8>, Vec<u8>> {
-    let resy = iters.len() as u32;
-    let resx = iters[0].len() as u32;
+/// Converts a window-relative `position` into the point of the complex
+/// plane, ie, `mouse2domain` rotated, see `MandelConfig::to_plane`
+fn mouse2plane(app: &App, model: &Model, position: Vec2) -> [f64; 2] {
+    let [x, y] = mouse2domain(app, model, position);
+    let (x, y) = model.cfg.to_plane(x, y);
+    [x, y]
+}
 
-    let mut imgbuf = image::ImageBuffer::new(resx, resy);
-    for (x, y, pixel) in imgbuf.enumerate_pixels_mut() {
-        // imgbuf is indexed top-left to bottom-right,
-        // hence the y-index must be reversed:
-        let c = iters[(resy - y - 1) as usize][x as usize];
-        let (r, g, b) = model.color_schemes.get().rgb(c, model.cfg.max_iters);
-        *pixel = image::Rgb([r, g, b]);
-    }
-    imgbuf
+/// Return the rectangle of the minimap in the window, in the top-right
+/// corner below the palette strip
+fn minimap_rect(app: &App) -> geom::Rect {
+    let win = app.window_rect();
+    let (w, h) = minimap::SIZE;
+    let xy = Vec2::new(win.right() - w / 2.0 - 10.0, win.top() - STRIP_SIZE.1 - 20.0 - h / 2.0);
+    geom::Rect::from_xy_wh(xy, Vec2::new(w, h))
 }
 
-/// Return a tuple `(min(a, b), max(a, b))`
-fn min_max(a: f64, b: f64) -> (f64, f64) {
-    if a < b {
-        (a, b)
-    } else {
-        (b, a)
+/// Return the rectangle of the window the view is drawn in, the left half
+/// in the dual view
+fn view_rect(app: &App, model: &Model) -> geom::Rect {
+    let win = app.window_rect();
+    match model.dual {
+        Some(_) => geom::Rect::from_corners(win.bottom_left(), win.mid_top()),
+        None => win,
     }
 }
+
+/// Return the rectangle of the Julia set of the dual view, the right
+/// half of the window
+fn julia_rect(app: &App) -> geom::Rect {
+    let win = app.window_rect();
+    geom::Rect::from_corners(win.mid_bottom(), win.top_right())
+}
+
+/// Return `true` if the mouse is over the right half of the window
+fn over_julia(app: &App) -> bool {
+    julia_rect(app).contains(Vec2::new(app.mouse.x, app.mouse.y))
+}
+
+/// Converts a window-relative `position` into Mandelbrot x,y domain
+fn mouse2domain(app: &App, model: &Model, position: Vec2) -> [f64; 2] {
+    let [px, py] = (position - view_rect(app, model).xy()).to_array();
+    let (x, y) = screen_map(app, model).to_domains([px as f64, py as f64]);
+    [x, y]
+}
+
+/// Converts a point of the Mandelbrot x,y domain into a window-relative
+/// position, the inverse of `mouse2domain`
+fn domain2window(app: &App, model: &Model, [x, y]: [f64; 2]) -> Vec2 {
+    let [px, py] = screen_map(app, model).to_window((x, y));
+    Vec2::new(px as f32, py as f32) + view_rect(app, model).xy()
+}
+
+/// Return the map between the window, scaled on HiDPI displays, and the
+/// domains of the view
+fn screen_map(app: &App, model: &Model) -> ScreenMap {
+    let scale_factor = app.window(model.window).unwrap().scale_factor();
+    model.cfg.screen_map(scale_factor as f64)
+}
+
+/// Return a buffer with the image of the mandelbrot set
+fn get_image_buf(
+    iters: &IterationGrid,
+    model: &Model,
+) -> Result<image::ImageBuffer<image::Rgb<u8>, Vec<u8>>, MandelError> {
+    // `nannou` uses its own version of `image`, so build its image
+    // buffer from the raw bytes
+    let buf = get_rgb_buf(iters, model.cfg.max_iters, &model.color_schemes)?;
+    let (width, height) = iters.image_size();
+    Ok(image::ImageBuffer::from_raw(width as u32, height as u32, buf)
+        .expect("buffer matches the grid size"))
+}
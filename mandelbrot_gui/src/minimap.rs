@@ -0,0 +1,113 @@
+// Minimap ///////////////////////////////////////////////////////////
+//         //////////////////////////////////////////////////////////
+// Overview of the whole Mandelbrot set in a corner of the window, shown
+// with Shift+M, with the outline of the view on it, to keep track of
+// where a deep zoom is. Clicking on it centres the view there, at the
+// same zoom. The overview is rendered once, when the window opens, with
+// the default colors.
+use nannou::draw::Draw;
+use nannou::geom::{Rect, Vec2};
+use nannou::image;
+use nannou::prelude::{App, WHITE};
+use nannou::wgpu;
+
+use mandelbrot_cli::{
+    color_schemes::ColorSchemes, get_rgb_buf, mandel, Domain, MandelConfig, Resolution,
+};
+
+/// Domains of the overview
+const XDOMAIN: Domain = Domain {
+    start: -2.5,
+    end: 1.0,
+};
+const YDOMAIN: Domain = Domain {
+    start: -1.0,
+    end: 1.0,
+};
+/// Size of the overview in the window, in points, of the same aspect as
+/// the domains
+pub const SIZE: (f32, f32) = (175.0, 100.0);
+/// Pixels of the overview per point, sharp on HiDPI displays
+const PIXELS_PER_POINT: f32 = 2.0;
+/// Smallest size of the outline of the view, in points, so that it is
+/// still seen when zoomed in deep
+const MIN_OUTLINE: f32 = 6.0;
+
+pub struct Minimap {
+    texture: wgpu::Texture,
+}
+
+impl Minimap {
+    /// Render the overview
+    pub fn new(app: &App) -> Self {
+        let resolution = Resolution {
+            x: (SIZE.0 * PIXELS_PER_POINT) as usize,
+            y: (SIZE.1 * PIXELS_PER_POINT) as usize,
+        };
+        let cfg = MandelConfig {
+            xdomain: XDOMAIN,
+            ydomain: YDOMAIN,
+            resolution,
+            ..MandelConfig::default()
+        };
+        let color_schemes = ColorSchemes::new();
+        let buf = mandel(cfg)
+            .and_then(|iters| get_rgb_buf(&iters, cfg.max_iters, &color_schemes))
+            .expect("the overview is a valid render");
+        let imgbuf = image::ImageBuffer::from_raw(resolution.x as u32, resolution.y as u32, buf)
+            .expect("buffer matches the size");
+        let texture = wgpu::Texture::from_image(app, &image::DynamicImage::ImageRgb8(imgbuf));
+        Self { texture }
+    }
+
+    /// Draw the overview in `rect`, with the outline of the view of `cfg`
+    pub fn draw(&self, draw: &Draw, rect: Rect, cfg: &MandelConfig) {
+        draw.rect()
+            .xy(rect.xy())
+            .wh(rect.wh() + Vec2::splat(2.0))
+            .color(WHITE);
+        draw.texture(&self.texture).xy(rect.xy()).wh(rect.wh());
+        let (x, y) = (cfg.xdomain, cfg.ydomain);
+        let corners = [
+            (x.start, y.start),
+            (x.end, y.start),
+            (x.end, y.end),
+            (x.start, y.end),
+        ]
+        .map(|(x, y)| to_window(rect, cfg.to_plane(x, y)));
+        let center = corners.iter().sum::<Vec2>() / 4.0;
+        let outline = if corners[0].distance(corners[2]) < MIN_OUTLINE {
+            let half = MIN_OUTLINE / 2.0;
+            [(-half, -half), (half, -half), (half, half), (-half, half)]
+                .map(|(dx, dy)| center + Vec2::new(dx, dy))
+        } else {
+            corners
+        };
+        draw.polyline()
+            .weight(1.5)
+            .rgb8(255, 0, 0)
+            .points_closed(outline.map(|p| p.clamp(rect.bottom_left(), rect.top_right())));
+    }
+}
+
+/// Return the point of the complex plane at `position` of the window,
+/// on the overview drawn in `rect`
+pub fn to_plane(rect: Rect, position: Vec2) -> (f64, f64) {
+    let tx = ((position.x - rect.left()) / rect.w()) as f64;
+    let ty = ((position.y - rect.bottom()) / rect.h()) as f64;
+    (
+        XDOMAIN.start + tx * (XDOMAIN.end - XDOMAIN.start),
+        YDOMAIN.start + ty * (YDOMAIN.end - YDOMAIN.start),
+    )
+}
+
+/// Return the position in the window of the point `(x, y)` of the
+/// complex plane, on the overview drawn in `rect`
+fn to_window(rect: Rect, (x, y): (f64, f64)) -> Vec2 {
+    let tx = (x - XDOMAIN.start) / (XDOMAIN.end - XDOMAIN.start);
+    let ty = (y - YDOMAIN.start) / (YDOMAIN.end - YDOMAIN.start);
+    Vec2::new(
+        rect.left() + tx as f32 * rect.w(),
+        rect.bottom() + ty as f32 * rect.h(),
+    )
+}
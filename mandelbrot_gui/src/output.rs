@@ -0,0 +1,108 @@
+// Output files //////////////////////////////////////////////////////
+//              /////////////////////////////////////////////////////
+// Paths of the images saved with the F key, in `--output-dir`, named
+// after the template of `--output` with the time and zoom of the view,
+// eg, `mandel_{time}_z{zoom}.png` for
+// `mandel_2024-05-01T12-00-00_z1e7.png`, of the screenshots of F12
+// and of the palette cycling animations of Shift+Y. Existing files are never overwritten, a number is added to the
+// name instead.
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use mandelbrot_cli::MandelConfig;
+
+pub const DEFAULT_TEMPLATE: &str = "mandel_{time}_z{zoom}.png";
+/// Name of the screenshots, with the overlays, of the window
+const SCREENSHOT_TEMPLATE: &str = "screenshot_{time}_z{zoom}.png";
+/// Name of the palette cycling animations, animated PNGs
+const ANIMATION_TEMPLATE: &str = "cycle_{time}_z{zoom}.png";
+
+pub struct Output {
+    /// Directory of the images
+    pub dir: PathBuf,
+    /// File name, with `{time}` replaced by the UTC time and `{zoom}` by
+    /// the zoom factor of the view
+    pub template: String,
+}
+impl Default for Output {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("."),
+            template: DEFAULT_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl Output {
+    /// Return the path of an image of `cfg` saved at `time`, which is not
+    /// an existing file
+    pub fn path(&self, cfg: &MandelConfig, time: SystemTime) -> PathBuf {
+        self.path_from(&self.template, cfg, time)
+    }
+
+    /// Return the path of a screenshot of the window showing `cfg`,
+    /// taken at `time`, which is not an existing file
+    pub fn screenshot_path(&self, cfg: &MandelConfig, time: SystemTime) -> PathBuf {
+        self.path_from(SCREENSHOT_TEMPLATE, cfg, time)
+    }
+
+    /// Return the path of an animation of `cfg` saved at `time`, which
+    /// is not an existing file
+    pub fn animation_path(&self, cfg: &MandelConfig, time: SystemTime) -> PathBuf {
+        self.path_from(ANIMATION_TEMPLATE, cfg, time)
+    }
+
+    fn path_from(&self, template: &str, cfg: &MandelConfig, time: SystemTime) -> PathBuf {
+        let name = template
+            .replace("{time}", &timestamp(time))
+            .replace("{zoom}", &format!("{:.0e}", cfg.view().scale));
+        let path = self.dir.join(name);
+        (1..)
+            .map(|n| numbered(&path, n))
+            .find(|p| !p.exists())
+            .expect("some number is free")
+    }
+}
+
+/// Return `path` itself for `n = 1`, or else with `_n` after its stem
+fn numbered(path: &Path, n: usize) -> PathBuf {
+    if n == 1 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(ext) => path.with_file_name(format!("{stem}_{n}.{}", ext.to_string_lossy())),
+        None => path.with_file_name(format!("{stem}_{n}")),
+    }
+}
+
+/// Format `time` as `2024-05-01T12-00-00`, in UTC, with dashes instead
+/// of colons to be valid in file names everywhere
+fn timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    let (y, m, d) = civil_from_days(secs.div_euclid(86400));
+    let s = secs.rem_euclid(86400);
+    format!(
+        "{y:04}-{m:02}-{d:02}T{:02}-{:02}-{:02}",
+        s / 3600,
+        s / 60 % 60,
+        s % 60
+    )
+}
+
+/// Year, month and day of the `days` since 1970-01-01, in the proleptic
+/// Gregorian calendar, after Howard Hinnant's `civil_from_days`
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d)
+}
@@ -0,0 +1,151 @@
+// Zoom transitions //////////////////////////////////////////////////
+//                  /////////////////////////////////////////////////
+// Zooming with the wheel or to a rectangle glides to the new view over
+// `--zoom-transition` ms instead of jumping to it: the image of the last
+// render is drawn scaled to the views in between, and the new view is
+// rendered once it is reached. The image is drawn scaled until its
+// render is done, then the transition ends.
+//
+// The width is interpolated geometrically, as the frames of `zoom`, and
+// the centre moves in proportion to the width, so that the point which
+// stays in place, eg, the one under the mouse, stays still. The glide
+// ends slow, see `Easing::EaseOut`. The views of deep zooms may have
+// different origins, so the domains are moved to the origin of the new
+// view first, see `Origin`.
+use std::time::{Duration, Instant};
+
+use nannou::geom::Vec2;
+
+use mandelbrot_cli::{origin::Origin, zoom::Easing, Domain, MandelConfig};
+
+/// Default time of the glide, in ms, see `--zoom-transition`
+pub const DEFAULT_MILLIS: u64 = 200;
+
+enum Stage {
+    /// Gliding from the x and y domains and the origin `from` since
+    /// `started`
+    Gliding {
+        from: (Domain, Domain, Origin),
+        started: Instant,
+    },
+    /// At the new view, until its render is drawn
+    Rendering,
+}
+
+/// Glide from a view to the one of the config, see `view()`
+pub struct ZoomTransition {
+    stage: Stage,
+    duration: Duration,
+}
+
+impl ZoomTransition {
+    /// Start gliding from the view of `from`, over `duration`
+    pub fn new(from: &MandelConfig, duration: Duration) -> Self {
+        Self {
+            stage: Stage::Gliding {
+                from: (from.xdomain, from.ydomain, from.origin),
+                started: Instant::now(),
+            },
+            duration,
+        }
+    }
+
+    /// Return `true` while gliding, before the new view is rendered
+    pub fn is_gliding(&self) -> bool {
+        matches!(self.stage, Stage::Gliding { .. })
+    }
+
+    /// Stop gliding once the time of the glide is over. Return `true` if
+    /// it stopped, when the new view is to be rendered.
+    pub fn end_glide(&mut self) -> bool {
+        let Stage::Gliding { started, .. } = self.stage else {
+            return false;
+        };
+        let ended = started.elapsed() >= self.duration;
+        if ended {
+            self.stage = Stage::Rendering;
+        }
+        ended
+    }
+
+    /// Return the view shown now, on the way to the view of `to`, with
+    /// the other settings of `to`
+    pub fn view(&self, to: &MandelConfig) -> MandelConfig {
+        let Stage::Gliding {
+            from: (x0, y0, origin),
+            started,
+        } = self.stage
+        else {
+            return *to;
+        };
+        let t = started.elapsed().as_secs_f64() / self.duration.as_secs_f64();
+        if t >= 1.0 {
+            return *to;
+        }
+        let t = Easing::EaseOut.apply(t);
+        let (dx, dy) = origin.offset_from(&to.origin);
+        let (x, y) = (
+            interpolate(moved(x0, dx), to.xdomain, t),
+            interpolate(moved(y0, dy), to.ydomain, t),
+        );
+        MandelConfig {
+            xdomain: x,
+            ydomain: y,
+            ..*to
+        }
+    }
+}
+
+/// Return `domain` moved by `offset`
+fn moved(domain: Domain, offset: f64) -> Domain {
+    Domain {
+        start: domain.start + offset,
+        end: domain.end + offset,
+    }
+}
+
+/// Return the domain at `t` in `[0, 1]` from `a` to `b`, its width
+/// geometric and its centre in proportion to the width
+fn interpolate(a: Domain, b: Domain, t: f64) -> Domain {
+    let (wa, wb) = (a.end - a.start, b.end - b.start);
+    let width = wa * (wb / wa).powf(t);
+    // 1.0 at `a`, 0.0 at `b`, proportional to the width
+    let offset = if wa == wb {
+        1.0 - t
+    } else {
+        (width - wb) / (wa - wb)
+    };
+    let (ca, cb) = ((a.start + a.end) / 2.0, (b.start + b.end) / 2.0);
+    let center = cb + (ca - cb) * offset;
+    Domain {
+        start: center - width / 2.0,
+        end: center + width / 2.0,
+    }
+}
+
+/// Return the offset and the scale to draw the image rendered for
+/// `image` in the window showing `view`, with `scale_factor` physical
+/// pixels per point, or `None` if it cannot be, ie, rotated
+pub fn placement(
+    image: &MandelConfig,
+    view: &MandelConfig,
+    scale_factor: f32,
+) -> Option<(Vec2, Vec2)> {
+    if image.rotation != view.rotation || image.kind != view.kind {
+        return None;
+    }
+    // in the domains of `view`
+    let (dx, dy) = image.origin.offset_from(&view.origin);
+    let center = (
+        (image.xdomain.start + image.xdomain.end) / 2.0 + dx,
+        (image.ydomain.start + image.ydomain.end) / 2.0 + dy,
+    );
+    let [x, y] = view.screen_map(scale_factor as f64).to_window(center);
+    // the widths of the pixels of the image in those of the view
+    let step = |d: Domain, n: usize| (d.end - d.start) / (n - 1) as f64;
+    let scale = Vec2::new(
+        (step(image.xdomain, image.resolution.x) / step(view.xdomain, view.resolution.x)) as f32,
+        (step(image.ydomain, image.resolution.y) / step(view.ydomain, view.resolution.y)) as f32,
+    );
+    Some((Vec2::new(x as f32, y as f32), scale))
+}
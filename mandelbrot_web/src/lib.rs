@@ -0,0 +1,125 @@
+// Web explorer //////////////////////////////////////////////////////
+//              /////////////////////////////////////////////////////
+// Explorer of the Mandelbrot set in the browser, with the pan and zoom
+// of `mandelbrot_gui`. The pixels are rendered here, single-threaded
+// with `mandel_single()`, and `www/index.html` draws them to a canvas
+// and forwards the mouse and keyboard events.
+//
+// Positions are in pixels of the canvas, from its top-left corner.
+use wasm_bindgen::prelude::*;
+
+use mandelbrot_cli::{
+    color_schemes::ColorSchemes, get_rgb_buf, mandel_single, MandelConfig, Resolution,
+};
+
+#[wasm_bindgen]
+pub struct Explorer {
+    cfg: MandelConfig,
+    color_schemes: ColorSchemes,
+}
+
+#[wasm_bindgen]
+impl Explorer {
+    /// Explorer of a canvas of `width` by `height` pixels
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, height: usize) -> Explorer {
+        let mut explorer = Explorer {
+            cfg: MandelConfig::default(),
+            color_schemes: ColorSchemes::new(),
+        };
+        explorer.resize(width, height);
+        explorer
+    }
+
+    /// Change the size of the canvas, keeping the centre and the x domain
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.cfg.domains().resize(Resolution {
+            x: width.max(2),
+            y: height.max(2),
+        });
+    }
+
+    /// Return the RGBA pixels of the image, row-major from the top-left
+    /// corner, as expected by `ImageData`, or throw the error
+    pub fn render(&self) -> Result<Vec<u8>, JsError> {
+        let iters = mandel_single(self.cfg)?;
+        Ok(get_rgb_buf(&iters, self.cfg.max_iters, &self.color_schemes)?
+            .chunks(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect())
+    }
+
+    /// Return the point of the domain at pixel `(x, y)`
+    fn to_domain(&self, x: f64, y: f64) -> (f64, f64) {
+        let cfg = &self.cfg;
+        let (w, h) = ((cfg.resolution.x - 1) as f64, (cfg.resolution.y - 1) as f64);
+        (
+            cfg.xdomain.start + x / w * (cfg.xdomain.end - cfg.xdomain.start),
+            cfg.ydomain.end - y / h * (cfg.ydomain.end - cfg.ydomain.start),
+        )
+    }
+
+    /// Pan by `(dx, dy)` pixels, eg, dragging the image with the mouse
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        let (x0, y0) = self.to_domain(0.0, 0.0);
+        let (x1, y1) = self.to_domain(dx, dy);
+        self.cfg.domains().pan(x0 - x1, y0 - y1);
+    }
+
+    /// Scale the domains by `factor` around pixel `(x, y)`, which stays
+    /// still. Zooms in with `factor < 1`.
+    pub fn zoom(&mut self, x: f64, y: f64, factor: f64) {
+        let (cx, cy) = self.to_domain(x, y);
+        self.cfg.domains().zoom_about_point(cx, cy, factor);
+    }
+
+    /// Zoom into the rectangle between pixels `(x0, y0)` and `(x1, y1)`,
+    /// widened or heightened to the aspect of the canvas
+    pub fn zoom_rect(&mut self, x0: f64, y0: f64, x1: f64, y1: f64) {
+        let (ax, ay) = self.to_domain(x0, y0);
+        let (bx, by) = self.to_domain(x1, y1);
+        if ax == bx && ay == by {
+            return;
+        }
+        self.cfg.domains().zoom_to_rect(ax, ay, bx, by);
+    }
+
+    /// Reset to the default domains
+    pub fn reset(&mut self) {
+        let (xdomain, ydomain) = self.cfg.kind.default_domains();
+        self.cfg
+            .domains()
+            .set_domains(xdomain, ydomain)
+            .fit_to_aspect();
+    }
+
+    pub fn next_color_scheme(&mut self) {
+        self.color_schemes.next();
+    }
+
+    /// Double `max_iters`, or halve it if `more` is `false`, within the
+    /// limits of the GUI
+    pub fn change_iters(&mut self, more: bool) {
+        let cfg = &mut self.cfg;
+        if more && cfg.max_iters < 20000 {
+            cfg.max_iters *= 2;
+        } else if !more && cfg.max_iters > 32 {
+            cfg.max_iters /= 2;
+        }
+    }
+
+    /// Return a line of text about the view
+    pub fn info(&self) -> String {
+        let cfg = &self.cfg;
+        let (x, y) = cfg.absolute_domains();
+        format!(
+            "x: ({:e}, {:e})  y: ({:e}, {:e})  max_iters: {}  color scheme: {}",
+            x.start,
+            x.end,
+            y.start,
+            y.end,
+            cfg.max_iters,
+            self.color_schemes.index()
+        )
+    }
+}